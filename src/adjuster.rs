@@ -1,11 +1,105 @@
+use std::{collections::HashMap, ops::Range};
+
 use crate::{
     analysis_ctx::AnalysisContext,
-    ast::TokenNode as Node,
+    analyzer::{find_ids, for_each_node_mut, sub_nodes},
+    ast::{AssignmentOpType, NodeType, TokenNode as Node},
     checker::BorrowError,
-    data_model::{LineNumber, ReferenceType, UsageType},
+    data_model::{LineNumber, Reference, ReferenceType, Usage, UsageType, VarData},
+    dependence::Effects,
+    lexer::CType,
+    solver::Fix,
 };
 
 impl AnalysisContext {
+    /// Shares a value between its pointers, with a `Cell` if it can be, since it can't panic at
+    /// runtime, otherwise with an `Rc<RefCell>`
+    fn set_ptr_shared(&mut self, value_id: &str) {
+        match self.cell_valid(value_id) {
+            true => self.set_ptr_cell(value_id),
+            false => self.set_ptr_rc(value_id),
+        }
+    }
+
+    /// A value is only shared through the declaration it's wrapped in, which the anonymous value
+    /// behind a heap allocation doesn't have, and its pointers are plain references to it, which
+    /// the slices of an array aren't
+    pub fn shareable(&self, value_id: &str) -> bool {
+        let var_data = self.get_var(value_id);
+        var_data.heap_allocation.is_none() && !var_data.is_array()
+    }
+
+    /// A raw pointer can't be indexed into an array like a slice can, and a raw owner would leak
+    /// its allocation
+    pub fn raw_valid(&self, ptr_id: &str) -> bool {
+        self.get_var(ptr_id).points_to.iter().all(|reference| {
+            let reference = reference.borrow();
+            let pointee = self.get_var(reference.get_reference_to());
+            !reference.get_reference_type().is_owned()
+                && !pointee.is_array()
+                && !pointee.pointed_to.iter().any(|reference| {
+                    reference.borrow().get_reference_type() == ReferenceType::OwnedVec
+                })
+        })
+    }
+
+    /// The pointer a heap allocation belongs to, which can't be made raw since it owns it
+    pub fn heap_owner(&self, value_id: &str) -> Option<&str> {
+        let heap_allocation = self.get_var(value_id).heap_allocation.as_ref()?;
+        Some(heap_allocation.owner.as_str())
+    }
+
+    /// A `Cell` only works for `Copy` values, and its references can't leave the function since
+    /// they're plain borrows
+    pub fn cell_valid(&self, value_id: &str) -> bool {
+        let var_data = self.get_var(value_id);
+        let copy = matches!(
+            var_data.var_type,
+            CType::Int | CType::Char | CType::Float | CType::Double
+        );
+        if !copy
+            || var_data.is_ptr()
+            || var_data.storage.is_some()
+            || var_data.fieldof_struct.is_some()
+        {
+            return false;
+        }
+
+        // NOTE Pointers to the pointers, struct fields holding them and arguments all carry the
+        // reference somewhere else
+        var_data.pointed_to.iter().all(|reference| {
+            let b = reference.borrow();
+            self.current_scope()
+                .variables
+                .get(b.get_borrower())
+                .is_some_and(|ptr_data| {
+                    ptr_data.points_to.len() == 1
+                        && ptr_data.pointed_to.is_empty()
+                        && ptr_data.passed_to.is_empty()
+                        && ptr_data.fieldof_struct.is_none()
+                        && ptr_data.storage.is_none()
+                        && !ptr_data.raw
+                })
+        })
+    }
+
+    fn set_ptr_cell(&mut self, value_id: &str) {
+        let var_data = self.get_var_mut(value_id);
+        var_data.cell = true;
+        // NOTE `Cell::set` only needs a shared reference
+        var_data.is_mut = false;
+        let ptrs = var_data.pointed_to.clone();
+
+        ptrs.iter().for_each(|reference_block| {
+            reference_block.borrow_mut().set_cell();
+
+            let b = reference_block.borrow();
+            self.mut_var(b.get_borrower().to_string(), |ptr_data| {
+                ptr_data.is_mut = false
+            })
+        });
+    }
+
     fn set_ptr_rc(&mut self, value_id: &str) {
         let var_data = self.get_var_mut(value_id);
         var_data.rc = true;
@@ -22,6 +116,7 @@ impl AnalysisContext {
 
             let b = reference_block.borrow();
             let ptr_id = b.get_borrower();
+            self.set_field_ref_type(ptr_id, ReferenceType::RcRefClone);
             self.mut_var(ptr_id.to_string(), |ptr_data| {
                 let has_higher_mut_borrower = ptr_data
                     .pointed_to
@@ -44,6 +139,11 @@ impl AnalysisContext {
         // is an rside value of
         let ptr_data = self.get_var_mut(ptr_id);
         ptr_data.set_raw();
+        if let Some(reference) = ptr_data.points_to.first() {
+            let ref_type = reference.borrow().get_reference_type();
+            self.set_field_ref_type(ptr_id, ref_type);
+        }
+        let ptr_data = self.get_var(ptr_id);
 
         ptr_data
             .usages
@@ -57,307 +157,624 @@ impl AnalysisContext {
             });
     }
 
-    pub fn adjust_ptr_type(&mut self, errors: Vec<BorrowError>, root: &mut Node) {
-        // TODO Figure out correct error hierarchy
-        // errors.sort();
-        // TODO Implement temporary cloned variable solution for empty cases
-        errors.iter().for_each(|error| {
-            match &error {
-                BorrowError::MutMutOverlap {
-                    first_ptr_id: _,
-                    second_ptr_id: _,
-                    value_id,
-                } => self.set_ptr_rc(value_id),
-                BorrowError::MutConstOverlap {
-                    mut_ptr_id,
-                    const_ptr_id,
-                    value_id,
-                } if !line_rearrangement_mut_const_overlap(
-                    mut_ptr_id,
-                    const_ptr_id,
-                    value_id,
-                    root,
-                    self,
-                ) =>
-                {
-                    self.set_ptr_rc(value_id);
-                }
-                BorrowError::MutConstOverlap {
-                    mut_ptr_id: _,
-                    const_ptr_id: _,
-                    value_id: _,
-                } => {}
-                BorrowError::MutMutSameLine {
-                    first_ptr_id,
-                    second_ptr_id,
-                    value_id: _,
-                } => {
-                    self.set_ptr_raw(second_ptr_id);
-                    self.set_ptr_raw(first_ptr_id);
+    /// A pointer held in a struct field has the field's type, which every instance of the struct
+    /// shares, so making the pointer raw or shared changes the field
+    fn set_field_ref_type(&mut self, ptr_id: &str, ref_type: ReferenceType) {
+        let Some(field_info) = self.get_var(ptr_id).fieldof_struct.clone() else {
+            return;
+        };
+        self.mut_struct(field_info.struct_id, |struct_data| {
+            struct_data.mut_field(field_info.field_id, |field| {
+                if let Some(ptr_type) = field.ptr_type.first_mut().filter(|p| p.is_borrow()) {
+                    *ptr_type = ref_type;
                 }
+            })
+        });
+    }
 
-                BorrowError::MutConstSameLine {
-                    mut_ptr_id,
-                    const_ptr_id,
-                    value_id: _,
-                } => {
-                    self.set_ptr_raw(mut_ptr_id);
-                    self.set_ptr_raw(const_ptr_id);
-                }
-                // TODO: if the id is the value, we can clone
-                BorrowError::ValueMutOverlap { ptr_id, value_id }
-                    if !line_rearrangement_value_ptr_overlap(
-                        value_id, ptr_id, root, self, false,
-                    ) =>
-                {
-                    self.set_ptr_rc(value_id);
-                }
-                BorrowError::ValueMutOverlap {
-                    ptr_id: _,
-                    value_id: _,
-                } => {}
-                BorrowError::ValueMutSameLine {
-                    ptr_id,
-                    value_id: _,
-                } => {
-                    self.set_ptr_raw(ptr_id);
-                }
-                BorrowError::ValueConstOverlap { ptr_id, value_id }
-                    if !line_rearrangement_value_ptr_overlap(
-                        value_id, ptr_id, root, self, true,
-                    ) =>
-                {
-                    self.set_ptr_rc(value_id)
-                }
-                BorrowError::ValueConstOverlap {
-                    ptr_id: _,
-                    value_id: _,
-                } => {}
-                BorrowError::ValueConstSameLine {
-                    ptr_id: _,
-                    value_id: _,
-                } => {
-                    // NOTE This must be rside, so it's fine i think (not actually an error)
-                    // TODO Remove this error type and make permanent note
-                    // self.set_ptr_raw(ptr_id);
-                }
-            };
+    /// Declares the variables `mut`, for bindings `rustc` found borrowed mutably without it
+    ///
+    /// Returns whether any of them wasn't already
+    pub fn make_mut(&mut self, ids: &[String]) -> bool {
+        let mut made_mut = false;
+        for id in ids.iter() {
+            if self
+                .current_scope()
+                .variables
+                .get(id)
+                .is_some_and(|var_data| !var_data.is_mut)
+            {
+                self.mut_var(id.clone(), |var_data| var_data.is_mut = true);
+                made_mut = true;
+            }
+        }
+        made_mut
+    }
+
+    /// Downgrades the variables `rustc` still rejects the translation for, a pointer by
+    /// downgrading the values it points to
+    ///
+    /// Returns whether anything was downgraded, if not, the translation can't be fixed
+    pub fn downgrade(&mut self, ids: &[String]) -> bool {
+        let mut value_ids: Vec<String> = ids
+            .iter()
+            .filter_map(|id| {
+                self.current_scope()
+                    .variables
+                    .get(id)
+                    .map(|data| (id, data))
+            })
+            .flat_map(|(id, var_data)| match var_data.is_ptr() {
+                // NOTE A pointer to a pointer borrows the pointer as a value too
+                true => var_data
+                    .points_to
+                    .iter()
+                    .map(|reference| reference.borrow().get_reference_to().to_string())
+                    .chain((!var_data.pointed_to.is_empty()).then(|| id.clone()))
+                    .collect(),
+                false => vec![id.clone()],
+            })
+            .collect();
+        // NOTE A value named by several errors is still only downgraded one step at a time
+        value_ids.sort();
+        value_ids.dedup();
+
+        let mut downgraded = false;
+        for value_id in value_ids.iter() {
+            downgraded |= self.downgrade_value(value_id);
+        }
+        downgraded
+    }
+
+    /// A value is shared between its pointers first, and if that isn't enough, they all become raw
+    ///
+    /// # Important
+    /// A pointer's declaration is a borrow, so pointers to a pointer become raw without sharing it
+    fn downgrade_value(&mut self, value_id: &str) -> bool {
+        let var_data = self.get_var(value_id);
+        let ptrs: Vec<String> = var_data
+            .pointed_to
+            .iter()
+            .map(|reference| reference.borrow().clone())
+            .filter(|reference| {
+                let reference_type = reference.get_reference_type();
+                !reference_type.is_raw() && !reference_type.is_owned()
+            })
+            .map(|reference| reference.get_borrower().to_string())
+            .collect();
+
+        if ptrs.is_empty() {
+            return false;
+        } else if !var_data.rc && !var_data.cell && !var_data.is_ptr() && self.shareable(value_id) {
+            self.set_ptr_shared(value_id);
+            return true;
+        } else if !ptrs.iter().all(|ptr_id| self.raw_valid(ptr_id)) {
+            return false;
+        }
 
-            // TODO: This should actually traverse the pointer chain downwards
+        // NOTE Raw pointers point to the value itself, not to a `Cell` or `RefCell` around it
+        self.mut_var(value_id.to_string(), |var_data| {
+            var_data.rc = false;
+            var_data.cell = false;
+            var_data.is_mut = true;
         });
+        ptrs.iter().for_each(|ptr_id| self.set_ptr_raw(ptr_id));
+        true
+    }
+
+    /// Takes the pointer's borrow through another pointer to the same value, see
+    /// [`reborrow_source`]
+    fn set_ptr_reborrow(&mut self, ptr_id: &str, from_ptr_id: &str, value_id: &str) {
+        self.get_var(ptr_id)
+            .reference_to_var(value_id)
+            .unwrap()
+            .borrow_mut()
+            .set_reborrow(from_ptr_id);
+    }
+
+    /// Returns whether statements were moved, in which case the analysis is out of date and the
+    /// rest of the errors have to be found again
+    pub fn apply_fix(&mut self, fix: &Fix, root: &mut Node) -> bool {
+        match fix {
+            Fix::Rearrange(error) => {
+                let moved = rearrange(error, root, self);
+                if moved {
+                    renumber_lines(root);
+                }
+                return moved;
+            }
+            Fix::Reborrow {
+                ptr_id,
+                from_ptr_id,
+                value_id,
+            } => self.set_ptr_reborrow(ptr_id, from_ptr_id, value_id),
+            Fix::Clone { ptr_id, value_id } => {
+                clone_before_borrow(value_id, ptr_id, root, self);
+            }
+            // NOTE A pointer made raw since the fix was picked rules out the `Cell`
+            Fix::Cell(value_id) => self.set_ptr_shared(value_id),
+            Fix::Rc(value_id) => self.set_ptr_rc(value_id),
+            Fix::Raw(ptr_id) => self.set_ptr_raw(ptr_id),
+        };
+
+        false
     }
 }
 
-fn line_rearrangement_mut_const_overlap(
-    mut_ptr_id: &str,
-    const_ptr_id: &str,
+/// Moves statements so that the borrows in the error no longer overlap
+pub fn rearrange(error: &BorrowError, root: &mut Node, ctx: &AnalysisContext) -> bool {
+    match error {
+        BorrowError::MutMutOverlap {
+            first_ptr_id,
+            second_ptr_id,
+            value_id,
+        } => line_rearrangement_ptr_overlap(first_ptr_id, second_ptr_id, value_id, root, ctx),
+        BorrowError::MutConstOverlap {
+            mut_ptr_id,
+            const_ptr_id,
+            value_id,
+        } => line_rearrangement_ptr_overlap(mut_ptr_id, const_ptr_id, value_id, root, ctx),
+        BorrowError::ValueMutOverlap { ptr_id, value_id } => {
+            line_rearrangement_value_ptr_overlap(value_id, ptr_id, root, ctx, false)
+        }
+        BorrowError::ValueConstOverlap { ptr_id, value_id } => {
+            line_rearrangement_value_ptr_overlap(value_id, ptr_id, root, ctx, true)
+        }
+        _ => false,
+    }
+}
+
+/// Finds which of two overlapping borrows can be taken through the other pointer instead, as
+/// `let g = &*m;`, which needs the earlier pointer to be a mutable borrow that isn't used until
+/// the later one is done
+///
+/// Returns the later pointer and the one it's reborrowed from
+pub fn reborrow_source(
+    first_ptr_id: &str,
+    second_ptr_id: &str,
     value_id: &str,
-    root: &mut Node,
-    ctx: &mut AnalysisContext,
-) -> bool {
-    let mut_ptr = ctx.get_var(mut_ptr_id);
-    let const_ptr = ctx.get_var(const_ptr_id);
+    root: &Node,
+    ctx: &AnalysisContext,
+) -> Option<(String, String)> {
+    let reference = |ptr_id: &str| -> Option<Reference> {
+        let ptr_data = ctx.get_var(ptr_id);
+        // NOTE Pointers that are reassigned, pointed to or passed on carry the borrow elsewhere
+        let plain = ptr_data.points_to.len() == 1
+            && ptr_data.pointed_to.is_empty()
+            && ptr_data.passed_to.is_empty()
+            && ptr_data.fieldof_struct.is_none()
+            && ptr_data.storage.is_none()
+            && !ptr_data.raw;
+        let reference = ptr_data.reference_to_var(value_id)?.borrow().clone();
+        (plain && reference.get_reference_type().is_borrow()).then_some(reference)
+    };
+    let first = reference(first_ptr_id)?;
+    let second = reference(second_ptr_id)?;
+    let (earlier, later) = match first.get_range().start < second.get_range().start {
+        true => (first, second),
+        false => (second, first),
+    };
 
-    let mut_reference = mut_ptr.reference_to_var(value_id).unwrap().clone();
-    let const_reference = const_ptr.reference_to_var(value_id).unwrap().clone();
+    let range = later.get_range();
+    let earlier_used = ctx
+        .get_var(earlier.get_borrower())
+        .usages
+        .iter()
+        .any(|usage| range.start < usage.get_line_number() && usage.get_line_number() <= range.end);
+
+    if earlier.get_reference_type() != ReferenceType::MutBorrowed
+        || earlier.get_range().start == range.start
+        || earlier_used
+        || !declares_borrow(root, later.get_borrower(), value_id, range.start)
+    {
+        return None;
+    }
 
-    let mut mut_ptr_usages = mut_ptr.usages.iter();
-    let mut const_ptr_usages = const_ptr.usages.iter();
+    Some((
+        later.get_borrower().to_string(),
+        earlier.get_borrower().to_string(),
+    ))
+}
 
-    let const_range = const_reference.borrow().get_range();
-    let mut_range = mut_reference.borrow().get_range();
+/// Only `int* g = &n;` is reborrowed, since that's the statement the reborrow replaces
+fn declares_borrow(root: &Node, ptr_id: &str, value_id: &str, line: LineNumber) -> bool {
+    match &root.token {
+        NodeType::PtrDeclaration(id, _, adr) if id == ptr_id && root.line == line => {
+            matches!(&adr.token, NodeType::Adr(adr_id) if adr_id == value_id)
+        }
+        _ => root
+            .children
+            .iter()
+            .flat_map(|children| children.iter())
+            .any(|child| declares_borrow(child, ptr_id, value_id, line)),
+    }
+}
 
-    if const_range.start == const_range.end {
-        rearrange_lines_tree(mut_range.start, const_range.start, root);
-        return true;
-    } else if mut_range.start == mut_range.end {
-        rearrange_lines_tree(const_range.start, mut_range.start, root);
-        return true;
+/// `p++`, `p += n` and `p = p + n` only move `p` along the array, without using what it points to
+fn offsets_ptr(root: &Node, ptr_id: &str, line: LineNumber) -> bool {
+    match &root.token {
+        NodeType::Assignment(op, id) if id == ptr_id && root.line == line => match op {
+            AssignmentOpType::AddEq | AssignmentOpType::SubEq => true,
+            AssignmentOpType::Eq => find_ids(root).iter().any(|rvalue_id| rvalue_id == ptr_id),
+            _ => false,
+        },
+        _ => root
+            .children
+            .iter()
+            .flat_map(|children| children.iter())
+            .any(|child| offsets_ptr(child, ptr_id, line)),
     }
+}
 
-    match const_range.start > mut_range.start {
-        true => {
-            let first_const_usage_in_reference = const_ptr_usages
-                .find(|const_usage| {
-                    const_reference
-                        .borrow()
-                        .contained_within_current_range(const_usage.get_line_number())
-                })
-                .unwrap();
+/// Sequences two overlapping borrows of the same value, so that one of them is done before the
+/// other is taken
+fn line_rearrangement_ptr_overlap(
+    first_ptr_id: &str,
+    second_ptr_id: &str,
+    value_id: &str,
+    root: &mut Node,
+    ctx: &AnalysisContext,
+) -> bool {
+    let first_ptr = ctx.get_var(first_ptr_id);
+    let second_ptr = ctx.get_var(second_ptr_id);
+
+    let first_range = first_ptr
+        .reference_to_var(value_id)
+        .unwrap()
+        .borrow()
+        .get_range();
+    let second_range = second_ptr
+        .reference_to_var(value_id)
+        .unwrap()
+        .borrow()
+        .get_range();
+
+    // NOTE A borrow that's never used can simply be taken before the other one
+    if second_range.start == second_range.end {
+        return move_lines_before(first_range.start, &[second_range.start], root, ctx);
+    } else if first_range.start == first_range.end {
+        return move_lines_before(second_range.start, &[first_range.start], root, ctx);
+    }
 
-            let last_mut_usage_in_reference = mut_ptr_usages
-                .filter(|mut_usage| {
-                    const_reference
-                        .borrow()
-                        .contained_within_current_range(mut_usage.get_line_number())
-                })
-                .next_back()
-                .unwrap();
+    match second_range.start > first_range.start {
+        true => end_borrow_before(
+            first_ptr,
+            &first_range,
+            second_ptr,
+            second_range.start,
+            root,
+            ctx,
+        ),
+        false => end_borrow_before(
+            second_ptr,
+            &second_range,
+            first_ptr,
+            first_range.start,
+            root,
+            ctx,
+        ),
+    }
+}
 
-            if first_const_usage_in_reference.get_line_number()
-                > last_mut_usage_in_reference.get_line_number()
-            {
-                // TODO Iteratively move all overlapped lines
-                rearrange_lines_tree(mut_range.start, const_range.end, root);
-                true
-            } else {
-                false
-            }
-        }
-        false => {
-            let last_const_usage_in_reference = const_ptr_usages
-                .filter(|const_usage| {
-                    mut_reference
-                        .borrow()
-                        .contained_within_current_range(const_usage.get_line_number())
-                })
-                .next_back()
-                .unwrap();
-
-            let first_mut_usage_in_reference = mut_ptr_usages
-                .find(|mut_usage| {
-                    mut_reference
-                        .borrow()
-                        .contained_within_current_range(mut_usage.get_line_number())
-                })
-                .unwrap();
+/// Moves every usage of the earlier pointer after the later pointer's borrow before that borrow,
+/// which works as long as the later pointer isn't used until the earlier one is done
+fn end_borrow_before(
+    earlier_ptr: &VarData,
+    earlier_range: &Range<LineNumber>,
+    later_ptr: &VarData,
+    later_start: LineNumber,
+    root: &mut Node,
+    ctx: &AnalysisContext,
+) -> bool {
+    let mut earlier_lines: Vec<LineNumber> = earlier_ptr
+        .usages
+        .iter()
+        .map(Usage::get_line_number)
+        .filter(|line| later_start < *line && *line <= earlier_range.end)
+        .collect();
+    earlier_lines.dedup();
 
-            if first_mut_usage_in_reference.get_line_number()
-                > last_const_usage_in_reference.get_line_number()
-            {
-                // TODO Iteratively move all overlapped lines
-                rearrange_lines_tree(mut_range.start, const_range.end, root);
-                true
-            } else {
-                false
-            }
+    let first_later_usage = later_ptr
+        .usages
+        .iter()
+        .map(Usage::get_line_number)
+        .find(|line| later_start < *line);
+
+    match (earlier_lines.last(), first_later_usage) {
+        (Some(last_earlier_usage), Some(first_later_usage))
+            if *last_earlier_usage < first_later_usage =>
+        {
+            move_lines_before(later_start, &earlier_lines, root, ctx)
         }
+        _ => false,
     }
 }
 
-/// Checks if a simple rearrangement of lines could fix = the borrow error
+/// Reads the value into a clone before it's borrowed, when it's only read on the last line of the
+/// borrow, so `*g = t + 1` becomes `let t_clone = t; let g = &mut t; *g = t_clone + 1;`
 ///
 /// # Important
-/// A value can be used behind a immutable reference if it's an rvalue that implements copy, which
-/// every non-struct, non-ptr rust analog to a c variable does
-fn line_rearrangement_value_ptr_overlap(
+/// The clone is only the same as the value if the value isn't changed between the borrow and the
+/// line reading it, including through the pointer itself
+pub fn clone_before_borrow(
     value_id: &str,
     ptr_id: &str,
     root: &mut Node,
     ctx: &mut AnalysisContext,
-    const_ptr: bool,
 ) -> bool {
     let var_data = ctx.get_var(value_id);
     let ptr_data = ctx.get_var(ptr_id);
-    let reference = ptr_data.reference_to_var(value_id).unwrap().clone();
-
-    // NOTE Each of these are mutations of var or a usage by a mutable reference
-    let var_mut_usages = var_data
-        .usages
-        .iter()
-        .filter(|usage| *usage.get_usage_type() == UsageType::LValue || !const_ptr);
-
-    let last_var_usage = var_mut_usages.clone().next_back().unwrap();
-    let first_ptr_usage = ptr_data
+    let range = ptr_data
+        .reference_to_var(value_id)
+        .unwrap()
+        .borrow()
+        .get_range();
+
+    let copy = matches!(
+        var_data.var_type,
+        CType::Int | CType::Char | CType::Float | CType::Double
+    );
+    let within = |usage: &&Usage| {
+        range.start < usage.get_line_number() && usage.get_line_number() < range.end
+    };
+    // NOTE Usages of the value within the borrow are overlaps of their own
+    let value_changed = var_data.usages.iter().any(|usage| {
+        within(&usage)
+            || (usage.get_line_number() == range.end
+                && *usage.get_usage_type() != UsageType::RValue)
+    });
+    let written_through_ptr = ptr_data
         .usages
         .iter()
-        .find(|usage| {
-            reference
-                .borrow()
-                .contained_within_current_range(usage.get_line_number())
-        })
-        .expect("Ptr never used within reference (meaning it lasts a single)")
-        .clone();
-
-    if last_var_usage.get_line_number() < first_ptr_usage.get_line_number() {
-        var_mut_usages.for_each(|usage| {
-            rearrange_lines_tree(
-                reference.borrow().get_range().start,
-                usage.get_line_number(),
-                root,
-            )
-        });
+        .filter(within)
+        .any(|usage| *usage.get_usage_type() == UsageType::LValue);
+
+    if !copy
+        || var_data.is_ptr()
+        || var_data.storage.is_some()
+        || value_changed
+        || written_through_ptr
+        || !ptr_data.pointed_to.is_empty()
+    {
+        return false;
+    }
 
-        return true;
+    let mut clone_id = format!("{value_id}_clone");
+    while ctx.current_scope().variables.contains_key(&clone_id) {
+        clone_id.push('_');
+    }
+    let var_type = var_data.var_type.clone();
+    if !insert_clone(root, value_id, &clone_id, &var_type, &range) {
+        return false;
     }
 
-    false
-}
+    let mut clone_data = VarData::new(var_type, true, None, None);
+    clone_data.new_usage(range.end, UsageType::RValue);
+    ctx.declaration(&clone_id, clone_data);
+    ctx.mut_var(value_id.to_string(), |var_data| {
+        var_data
+            .usages
+            .retain(|usage| usage.get_line_number() != range.end)
+    });
 
-#[allow(unused)]
-/// This function assumes that `rearrange_lines_tree` has already been called
-fn rearrange_lines_ctx(pivot: LineNumber, swing: LineNumber, ctx: &mut AnalysisContext) {
-    ctx.current_scope_mut()
-        .variables
-        .iter_mut()
-        .for_each(|(_var_id, var_data)| {
-            var_data
-                .usages
-                .iter_mut()
-                .filter(|usage| usage.get_line_number() >= pivot && usage.get_line_number() < swing)
-                .for_each(|usage| usage.set_line_number(usage.get_line_number() + 1));
-
-            var_data
-                .usages
-                .iter_mut()
-                .filter(|usage| usage.get_line_number() == swing)
-                .for_each(|usage| usage.set_line_number(pivot));
-        })
+    true
 }
 
-// TODO Call analyzer again or manually go through and change ctx line numbers for both
-// usages and nodes
-// Wait, do we even need to do that? Will we ever used LineNumbers again?
-fn rearrange_lines_tree(first_line: LineNumber, second_line: LineNumber, root: &mut Node) {
-    let mut first = false;
-    let mut second = false;
-    if let Some(children) = root.children.as_mut() {
-        for child in children.iter_mut() {
-            if child.line == first_line {
-                first = true;
-            } else if child.line == second_line {
-                second = true;
+/// Declares the clone right before the borrow, and has the statement reading the value read the
+/// clone instead, as long as both are in the same block
+fn insert_clone(
+    root: &mut Node,
+    value_id: &str,
+    clone_id: &str,
+    t: &CType,
+    range: &Range<LineNumber>,
+) -> bool {
+    let Some(children) = root.children.as_mut() else {
+        return false;
+    };
+    let borrow_index = children.iter().position(|child| child.line == range.start);
+    let usage_index = children.iter().position(|child| child.line == range.end);
+
+    match (borrow_index, usage_index) {
+        (Some(borrow_index), Some(usage_index)) => {
+            // NOTE Loops and ifs might read the value again after it changes
+            let mut statement = children[usage_index].clone();
+            let simple_statement = matches!(
+                statement.token,
+                NodeType::DerefAssignment(..)
+                    | NodeType::Assignment(..)
+                    | NodeType::Declaration(..)
+            );
+            if !simple_statement || !rename_id(&mut statement, value_id, clone_id) {
+                return false;
             }
 
-            if first && second {
-                let first_node_index = children
-                    .iter()
-                    .position(|child| child.line == first_line)
-                    .unwrap();
-                let second_node_index = children
-                    .iter()
-                    .position(|child| child.line == second_line)
-                    .unwrap();
+            let value = Node::new(NodeType::Id(value_id.to_string()), None, range.start);
+            let mut clone = Node::new(
+                NodeType::Declaration(clone_id.to_string(), t.clone(), 0),
+                Some(Box::new([value])),
+                range.start,
+            );
+            clone.c_line = children[borrow_index].c_line;
 
-                move_node_before(children, second_node_index, first_node_index);
+            let mut vec = children.to_vec();
+            vec[usage_index] = statement;
+            vec.insert(borrow_index, clone);
+            *children = vec.into_boxed_slice();
 
-                // NOTE Returns from function
-                return;
-            }
+            true
         }
+        _ => children
+            .iter_mut()
+            .any(|child| insert_clone(child, value_id, clone_id, t, range)),
+    }
+}
 
-        // NOTE This makes it a breadth first search sorta
-        for child in children.iter_mut() {
-            rearrange_lines_tree(first_line, second_line, child);
+/// Fails if the value's address is taken, since the clone can't stand in for it
+fn rename_id(root: &mut Node, from: &str, to: &str) -> bool {
+    let renamed = match &mut root.token {
+        NodeType::Id(id) if id == from => {
+            *id = to.to_string();
+            true
         }
-    }
+        NodeType::Adr(id) if id == from => false,
+        NodeType::DerefAssignment(_, node) | NodeType::DeRef(node) => rename_id(node, from, to),
+        NodeType::IndexArray { indices, .. } | NodeType::AdrIndex { indices, .. } => {
+            indices.iter_mut().all(|index| rename_id(index, from, to))
+        }
+        _ => true,
+    };
+
+    renamed
+        && root
+            .children
+            .iter_mut()
+            .flat_map(|children| children.iter_mut())
+            .all(|child| rename_id(child, from, to))
 }
 
-fn move_node_before(children: &mut Box<[Node]>, from_index: usize, to_index: usize) {
-    if from_index == to_index || from_index >= children.len() || to_index >= children.len() {
-        return; // NOTE No need to move if indices are the same or out of bounds.
+/// Checks if a simple rearrangement of lines could fix = the borrow error
+///
+/// # Important
+/// A value can be used behind a immutable reference if it's an rvalue that implements copy, which
+/// every non-struct, non-ptr rust analog to a c variable does
+fn line_rearrangement_value_ptr_overlap(
+    value_id: &str,
+    ptr_id: &str,
+    root: &mut Node,
+    ctx: &AnalysisContext,
+    const_ptr: bool,
+) -> bool {
+    let ptr_data = ctx.get_var(ptr_id);
+    let range = ptr_data
+        .reference_to_var(value_id)
+        .unwrap()
+        .borrow()
+        .get_range();
+    let within = |line: &LineNumber| range.start < *line && *line <= range.end;
+    let var_lines = value_lines_within(value_id, ptr_id, ctx, const_ptr);
+
+    let first_ptr_usage = ptr_data
+        .usages
+        .iter()
+        .map(Usage::get_line_number)
+        .filter(|line| !offsets_ptr(root, ptr_id, *line))
+        .find(within);
+
+    match (var_lines.last(), first_ptr_usage) {
+        (Some(last_var_usage), Some(first_ptr_usage)) if *last_var_usage < first_ptr_usage => {
+            move_lines_before(range.start, &var_lines, root, ctx)
+        }
+        _ => false,
     }
+}
+
+/// The lines the value is used on while it's borrowed by the pointer, which for a const pointer
+/// are only the ones changing it
+///
+/// # Important
+/// Borrowing the value again isn't a usage, since that's an overlap between the pointers
+pub fn value_lines_within(
+    value_id: &str,
+    ptr_id: &str,
+    ctx: &AnalysisContext,
+    const_ptr: bool,
+) -> Vec<LineNumber> {
+    let var_data = ctx.get_var(value_id);
+    let range = ctx
+        .get_var(ptr_id)
+        .reference_to_var(value_id)
+        .unwrap()
+        .borrow()
+        .get_range();
+    let borrow_lines: Vec<LineNumber> = var_data
+        .pointed_to
+        .iter()
+        .map(|reference| reference.borrow().get_range().start)
+        .collect();
 
-    let mut vec = children.to_vec();
-    let node = vec.remove(from_index);
+    let mut lines: Vec<LineNumber> = var_data
+        .usages
+        .iter()
+        .filter(|usage| *usage.get_usage_type() == UsageType::LValue || !const_ptr)
+        .map(Usage::get_line_number)
+        .filter(|line| range.start < *line && *line <= range.end)
+        .filter(|line| !borrow_lines.contains(line))
+        .collect();
+    lines.dedup();
+    lines
+}
 
-    let insert_index = if from_index < to_index {
-        to_index - 1
-    } else {
-        to_index
+/// Moves the statements on `lines` right before the one on `pivot`, keeping their order, as long
+/// as they're in the same block and don't depend on any of the statements they're moved past
+fn move_lines_before(
+    pivot: LineNumber,
+    lines: &[LineNumber],
+    root: &mut Node,
+    ctx: &AnalysisContext,
+) -> bool {
+    let Some(children) = root.children.as_mut() else {
+        return false;
+    };
+    let Some(pivot_index) = children.iter().position(|child| child.line == pivot) else {
+        return children
+            .iter_mut()
+            .any(|child| move_lines_before(pivot, lines, child, ctx));
     };
 
-    vec.insert(insert_index, node);
+    let moved = |child: &Node| lines.contains(&child.line);
+    // NOTE Statements can't be moved out of the loop or if they're nested in
+    let all_in_block = lines.iter().all(|line| {
+        *line != pivot
+            && children[pivot_index..]
+                .iter()
+                .any(|child| child.line == *line)
+    });
+    if lines.is_empty() || !all_in_block {
+        return false;
+    }
+
+    let effects: Vec<Effects> = children[pivot_index..]
+        .iter()
+        .map(|child| Effects::of(child, ctx))
+        .collect();
+    let block = &children[pivot_index..];
+    let independent = (0..block.len()).filter(|i| moved(&block[*i])).all(|i| {
+        (0..i)
+            .filter(|j| !moved(&block[*j]))
+            .all(|j| effects[i].independent(&effects[j]))
+    });
+    if !independent {
+        return false;
+    }
 
+    let (moved_statements, rest): (Vec<Node>, Vec<Node>) =
+        block.iter().cloned().partition(|child| moved(child));
+    let mut vec = children[..pivot_index].to_vec();
+    vec.extend(moved_statements);
+    vec.extend(rest);
     *children = vec.into_boxed_slice();
+
+    true
+}
+
+/// Gives statements increasing line numbers in their new order, since the analysis orders usages
+/// and borrows by line
+pub fn renumber_lines(root: &mut Node) {
+    let mut order: Vec<LineNumber> = vec![];
+    collect_lines(root, &mut order);
+    let mut sorted = order.clone();
+    sorted.sort();
+
+    let lines: HashMap<LineNumber, LineNumber> = order.into_iter().zip(sorted).collect();
+    for_each_node_mut(root, &mut |node| node.line = lines[&node.line]);
+}
+
+fn collect_lines(root: &Node, order: &mut Vec<LineNumber>) {
+    if !order.contains(&root.line) {
+        order.push(root.line);
+    }
+    sub_nodes(root)
+        .into_iter()
+        .for_each(|node| collect_lines(node, order));
 }
@@ -1,9 +1,14 @@
 use crate::{
-    data_model::{LineNumber, Reference, ReferenceType, StructData, UsageType, VarData},
+    ast::StorageClass,
+    data_model::{
+        EnumData, FieldDefinition, GlobalStrategy, HeapAllocation, LineNumber, Place,
+        RecursiveStrategy, Reference, ReferenceType, StringMode, StructData, TaggedUnion,
+        UsageType, VarData,
+    },
     lexer::CType,
     scope::{ScopeContext, ScopeType},
 };
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, ops::RangeInclusive, rc::Rc};
 
 /// The top-level datastructure that stores data about all the variables and referencing
 /// Stores a vector of the instances of addresses being taken, in order
@@ -12,6 +17,7 @@ pub struct AnalysisContext {
     pub scopes: Vec<ScopeContext>,
     current_scope: usize,
     pub structs: HashMap<String, StructData>,
+    pub enums: HashMap<String, EnumData>,
 }
 
 impl Default for AnalysisContext {
@@ -26,6 +32,7 @@ impl AnalysisContext {
             scopes: vec![ScopeContext::new(ScopeType::Top)],
             current_scope: 0,
             structs: HashMap::new(),
+            enums: HashMap::new(),
         }
     }
 
@@ -38,16 +45,26 @@ impl AnalysisContext {
     }
 
     pub fn new_scope(&mut self, scope_type: ScopeType) {
-        self.scopes.push(ScopeContext {
-            scope_type,
-            variables: HashMap::new(),
-        })
+        self.scopes.push(ScopeContext::new(scope_type))
     }
 
-    pub fn declaration(&mut self, id: impl ToString, data: VarData) {
-        self.current_scope_mut()
+    pub fn declaration(&mut self, id: impl ToString, mut data: VarData) {
+        let id = id.to_string();
+        data.place = Place::parse(&id);
+        let scope = self.current_scope_mut();
+        let places = scope.places.entry(data.place.base.clone()).or_default();
+        if !places.contains(&id) {
+            places.push(id.clone());
+        }
+        scope.variables.insert(id, data);
+    }
+
+    /// The variable holding a place, `s` for `s.a.b`, or the id itself if it isn't declared
+    pub fn base_var<'a>(&'a self, id: &'a str) -> &'a str {
+        self.current_scope()
             .variables
-            .insert(id.to_string(), data);
+            .get(id)
+            .map_or(id, |var_data| var_data.place.base.as_str())
     }
 
     pub fn new_usage(&mut self, id: &str, line: LineNumber, t: UsageType) {
@@ -65,10 +82,10 @@ impl AnalysisContext {
             var_data.new_usage(line, UsageType::RValue);
         });
 
-        let l_value_data = self.get_var(assigned_to);
-        if l_value_data.fieldof_struct.is_some() {
-            let struct_var_id = assigned_to.split(".").nth(0).unwrap().to_string();
-            self.mut_var(struct_var_id, |struct_var_data| {
+        // NOTE Writing a field writes every struct holding it
+        let mut place = self.get_var(assigned_to).place.clone();
+        while place.projections.pop().is_some() {
+            self.mut_var(place.to_string(), |struct_var_data| {
                 struct_var_data.new_usage(line, UsageType::LValue);
             })
         }
@@ -82,28 +99,299 @@ impl AnalysisContext {
     pub fn ptr_assignment(&mut self, borrowed: &str, assigned_to: &str, line: LineNumber) {
         // WARNING If rvalue_ids.len() > 1 for ptr assignments then raw ptr
         self.assignment(assigned_to, vec![borrowed.to_string()], line);
+        self.borrow(Reference::new(borrowed, assigned_to, line));
+    }
+
+    /// `q = p + 2` borrows what `p` does through `p`, so it uses `p` rather than the value
+    pub fn derived_ptr_assignment(
+        &mut self,
+        borrowed: &str,
+        assigned_to: &str,
+        from_ptr_id: &str,
+        line: LineNumber,
+    ) {
+        self.assignment(assigned_to, vec![from_ptr_id.to_string()], line);
+        let mut reference = Reference::new(borrowed, assigned_to, line);
+        reference.set_derived(from_ptr_id);
+        self.borrow(reference);
+    }
 
-        let new_reference = Rc::new(RefCell::new(Reference::new(borrowed, assigned_to, line)));
+    fn borrow(&mut self, reference: Reference) {
+        let borrowed = reference.get_reference_to().to_string();
+        let assigned_to = reference.get_borrower().to_string();
+        let new_reference = Rc::new(RefCell::new(reference));
 
         let l_value = self
             .current_scope_mut()
             .variables
-            .get_mut(assigned_to)
+            .get_mut(&assigned_to)
             .expect("Var not in ctx");
         l_value.points_to.push(new_reference.clone());
         l_value.is_mut = false;
 
         self.current_scope_mut()
             .variables
-            .entry(borrowed.to_string())
+            .entry(borrowed.clone())
             .and_modify(|rvalue| rvalue.pointed_to.push(new_reference.clone()));
+
+        // NOTE Globals that are pointed to become a `static mut`, which can't be borrowed safely
+        if self.is_global(&borrowed) {
+            self.get_var_mut(&assigned_to).set_raw();
+        }
+    }
+
+    /// `q = p + 1` is taken through `p`, so writing through `q` needs `p` to be mutable too
+    fn derived_set_mut(&mut self, ptr_id: &str) {
+        let mut reference = self.get_var(ptr_id).current_reference_held();
+        while let Some((from_ptr_id, line)) = reference.and_then(|reference| {
+            let reference = reference.borrow();
+            let from_ptr_id = reference.get_derived_from()?.to_string();
+            Some((from_ptr_id, reference.get_range().start))
+        }) {
+            reference = self.get_var(&from_ptr_id).reference_at_line(line);
+            if let Some(reference) = &reference {
+                reference.borrow_mut().set_mut();
+            }
+        }
+    }
+
+    /// Declares the anonymous heap value created by `malloc`/`calloc` and hands ownership of it
+    /// to the pointer it's assigned to
+    pub fn heap_allocation(
+        &mut self,
+        owner: &str,
+        c_type: CType,
+        reference_type: ReferenceType,
+        line: LineNumber,
+    ) {
+        let heap_id = format!("{owner}@heap{line}");
+        let instanceof_struct = match &c_type {
+            CType::Struct(struct_id) => Some(struct_id.clone()),
+            _ => None,
+        };
+
+        let mut heap_data = VarData::new(c_type, false, instanceof_struct, None);
+        heap_data.heap_allocation = Some(HeapAllocation {
+            owner: owner.to_string(),
+            line,
+            frees: vec![],
+        });
+        self.declaration(heap_id.clone(), heap_data);
+
+        self.ptr_assignment(&heap_id, owner, line);
+        self.get_var(owner)
+            .current_reference_held()
+            .unwrap()
+            .borrow_mut()
+            .set_owned(reference_type);
+    }
+
+    /// Declares the anonymous value behind a string literal, which the pointer borrows immutably
+    pub fn string_literal(&mut self, ptr_id: &str, line: LineNumber) {
+        let literal_id = format!("{ptr_id}@str{line}");
+        let mut literal_data = VarData::new(CType::Array(Box::new(CType::Char)), false, None, None);
+        literal_data.string_literal = Some(StringMode::Bytes);
+        self.declaration(literal_id.clone(), literal_data);
+
+        self.ptr_assignment(&literal_id, ptr_id, line);
+    }
+
+    /// The translation of the string literal a pointer points into, if it points into one
+    pub fn string_literal_of(&self, ptr_id: &str, line: LineNumber) -> Option<StringMode> {
+        self.get_var(&self.string_literal_id(ptr_id, line)?)
+            .string_literal
+    }
+
+    /// Records the characters of a string literal being read directly through a pointer
+    pub fn string_element_read(&mut self, ptr_id: &str, line: LineNumber) {
+        if let Some(literal_id) = self.string_literal_id(ptr_id, line) {
+            self.get_var_mut(&literal_id).string_literal = Some(StringMode::NulTerminated);
+        }
+    }
+
+    fn string_literal_id(&self, ptr_id: &str, line: LineNumber) -> Option<String> {
+        let ptr_data = self.get_var(ptr_id);
+        let reference = ptr_data
+            .reference_at_line(line)
+            .or_else(|| ptr_data.current_reference_held())?;
+        let literal_id = reference.borrow().get_reference_to().to_string();
+        self.get_var(&literal_id)
+            .string_literal
+            .is_some()
+            .then_some(literal_id)
     }
 
-    pub fn array_declaration(&mut self, id: &str, c_type: CType, _count: usize) {
+    /// Records `free(ptr_id)`
+    /// Freeing something that isn't a heap allocation is treated as a normal function call
+    pub fn free(&mut self, ptr_id: &str, line: LineNumber) {
+        self.new_usage(ptr_id, line, UsageType::RValue);
+
+        let Some(reference) = self.get_var(ptr_id).reference_at_line(line) else {
+            return;
+        };
+        let heap_id = reference.borrow().get_reference_to().to_string();
+        if let Some(heap_allocation) = self.get_var_mut(&heap_id).heap_allocation.as_mut() {
+            heap_allocation.frees.push(line);
+        }
+    }
+
+    /// How the recursive struct a pointer of this type points to is translated
+    pub fn recursive_strategy(&self, t: &CType) -> Option<&RecursiveStrategy> {
+        match t {
+            CType::Struct(struct_id) => self.structs.get(struct_id)?.recursive.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Whether a variable is a pointer to a recursive struct, which isn't a borrow
+    pub fn is_node_ptr(&self, id: &str) -> bool {
+        self.current_scope()
+            .variables
+            .get(id)
+            .is_some_and(|var_data| var_data.node_role.is_some())
+    }
+
+    /// Whether a variable is a global, a `static` local or an `extern` declaration
+    /// Also works for names that aren't variables, like `stdout`
+    pub fn is_global(&self, id: &str) -> bool {
+        self.current_scope()
+            .variables
+            .get(id)
+            .is_some_and(|var_data| var_data.storage.is_some())
+    }
+
+    /// How a global is translated, `None` for anything else
+    pub fn global_strategy(&self, id: &str) -> Option<GlobalStrategy> {
+        let var_data = self.get_var(id);
+        let storage = var_data.storage?;
+
+        Some(match var_data.var_type {
+            _ if storage == StorageClass::Extern || !var_data.pointed_to.is_empty() => {
+                GlobalStrategy::StaticMut
+            }
+            CType::Array(_) if !var_data.is_mut => GlobalStrategy::Static,
+            _ if !var_data.is_mut => GlobalStrategy::Const,
+            CType::Int | CType::Char => GlobalStrategy::Atomic,
+            CType::Float | CType::Double | CType::Enum(_) | CType::Array(_) => {
+                GlobalStrategy::ThreadLocal
+            }
+            _ => panic!("Unsupported: Mutable {:?} globals", var_data.var_type),
+        })
+    }
+
+    /// Records a variable being written by inline asm, as one of its output operands
+    /// The asm writes it behind the borrow checker's back, so it's mutable and every pointer to it
+    /// becomes raw
+    pub fn asm_usage(&mut self, id: &str, line: LineNumber) {
+        let var_data = self.get_var_mut(id);
+        assert!(
+            !var_data.is_ptr() && !var_data.is_array(),
+            "Unsupported: Naming a pointer or array in asm"
+        );
+        assert!(
+            var_data.storage.is_none(),
+            "Unsupported: Naming a global in asm"
+        );
+        var_data.is_mut = true;
+        var_data.new_usage(line, UsageType::LValue);
+
+        let ptr_ids: Vec<String> = var_data
+            .pointed_to
+            .iter()
+            .map(|reference| reference.borrow().get_borrower().to_string())
+            .collect();
+        ptr_ids
+            .iter()
+            .for_each(|ptr_id| self.get_var_mut(ptr_id).set_raw());
+    }
+
+    /// Records `ptr += offset` (and `ptr++`) for a pointer into an array
+    /// The pointer keeps borrowing the same array, it just no longer borrows the front of it
+    pub fn ptr_offset(&mut self, ptr_id: &str, line: LineNumber) {
+        assert!(
+            self.is_array_ptr(ptr_id, line),
+            "Unsupported: Pointer arithmetic outside of an array"
+        );
+
+        self.string_element_read(ptr_id, line);
+        self.mut_var(ptr_id.to_string(), |ptr_data| {
+            // NOTE The binding itself is reassigned to the rest of the slice
+            ptr_data.is_mut = true;
+            ptr_data.new_usage(line, UsageType::LValue);
+        });
+    }
+
+    /// Whether the pointer points into an array or a `Vec` heap allocation, making it a slice
+    pub fn is_array_ptr(&self, ptr_id: &str, line: LineNumber) -> bool {
+        let ptr_data = self.get_var(ptr_id);
+        let Some(reference) = ptr_data
+            .reference_at_line(line)
+            .or_else(|| ptr_data.current_reference_held())
+        else {
+            return false;
+        };
+
+        let reference = reference.borrow();
+        if reference.get_reference_type().is_owned() {
+            return false;
+        }
+
+        let pointee = self.get_var(reference.get_reference_to());
+        pointee.is_array()
+            || pointee.pointed_to.iter().any(|reference| {
+                reference.borrow().get_reference_type() == ReferenceType::OwnedVec
+            })
+    }
+
+    /// The ids of a function's arguments, as they're used within the function
+    pub fn function_args(&self, name: &str) -> Option<Vec<String>> {
+        self.scopes
+            .iter()
+            .find_map(|scope| match &scope.scope_type {
+                ScopeType::Function {
+                    name: function_name,
+                    ret: _,
+                    args,
+                } if function_name == name => Some(args),
+                _ => None,
+            })
+            .map(|args| {
+                args.iter()
+                    .map(|arg| arg.strip_suffix(&format!("_{name}")).unwrap().to_string())
+                    .collect()
+            })
+    }
+
+    pub fn is_function_arg(&self, id: &str) -> bool {
+        self.scopes.iter().any(|scope| match &scope.scope_type {
+            ScopeType::Function { name, ret: _, args } => args.contains(&format!("{id}_{name}")),
+            _ => false,
+        })
+    }
+
+    /// Whether an array is passed to a function that mutates it
+    pub fn passed_mutably(&self, id: &str) -> bool {
+        self.get_var(id)
+            .passed_to
+            .iter()
+            .any(|(function_id, i)| self.arg_is_mut(function_id, *i))
+    }
+
+    /// Whether the function mutates its `i`th argument
+    pub fn arg_is_mut(&self, function_id: &str, i: usize) -> bool {
+        self.function_args(function_id)
+            .and_then(|args| args.get(i).cloned())
+            .is_some_and(|arg| self.get_var(&arg).is_mut)
+    }
+
+    pub fn array_declaration(&mut self, id: &str, c_type: CType, dimensions: &[usize]) {
         // TODO Figure out how to represent arrays as pointers to nothing
         // The current solution is to just represent them as variables with a special type that's
         // compatible with pointers
-        let var_data = VarData::new(CType::Array(Box::new(c_type)), false, None, None);
+        let array_type = dimensions
+            .iter()
+            .fold(c_type, |t, _| CType::Array(Box::new(t)));
+        let var_data = VarData::new(array_type, false, None, None);
         self.declaration(id, var_data);
     }
 
@@ -129,22 +417,25 @@ impl AnalysisContext {
                         .for_each(|ptr_type| *ptr_type = ReferenceType::MutBorrowed)
                 });
             });
-            let struct_var_id = top_ptr.split(".").nth(0).unwrap();
-            self.mut_var(struct_var_id.to_string(), |struct_var_data| {
+            let struct_var_id = self.get_var(&top_ptr).place.base.clone();
+            self.mut_var(struct_var_id, |struct_var_data| {
                 struct_var_data.new_usage(line, UsageType::LValue);
             })
         }
 
-        self.mut_var(top_ptr, |ptr_var| {
+        self.mut_var(top_ptr.clone(), |ptr_var| {
             assert!(ptr_var.is_ptr());
 
             ptr_var.new_usage(line, UsageType::LValue);
-            ptr_var
-                .current_reference_held()
-                .unwrap()
-                .borrow_mut()
-                .set_mut();
+            let reference = ptr_var.current_reference_held().unwrap();
+            reference.borrow_mut().set_mut();
+
+            // NOTE Mutating through a Box or Vec requires a mutable binding to the owner
+            if reference.borrow().get_reference_type().is_owned() {
+                ptr_var.is_mut = true;
+            }
         });
+        self.derived_set_mut(&top_ptr);
 
         ptr_chain.for_each(|var_id| {
             let var_data = self.get_var_mut(&var_id);
@@ -152,6 +443,12 @@ impl AnalysisContext {
             if let Some(reference) = var_data.current_reference_held() {
                 reference.borrow_mut().set_mut();
             }
+
+            // NOTE Mutating a field through a pointer needs a mutable binding to the struct
+            if var_data.fieldof_struct.is_some() {
+                let struct_var_id = self.get_var(&var_id).place.base.clone();
+                self.mut_var(struct_var_id, VarData::set_mut);
+            }
         });
 
         // NOTE We don't want to also assign to the sub_var here, because we're checking actual
@@ -173,14 +470,138 @@ impl AnalysisContext {
         self.structs.insert(id.to_string(), data);
     }
 
+    pub fn new_enum(&mut self, id: impl ToString, variants: Vec<(String, i32)>) {
+        self.enums.insert(
+            id.to_string(),
+            EnumData {
+                variants,
+                arithmetic: false,
+            },
+        );
+    }
+
+    pub fn arithmetic_enum(&mut self, id: &str) {
+        if let Some(enum_data) = self.enums.get_mut(id) {
+            enum_data.arithmetic = true;
+        }
+    }
+
     pub fn get_struct(&self, id: &str) -> &StructData {
         self.structs.get(id).expect("Struct not in map")
     }
 
+    /// The lifetime parameters a struct needs, one per borrowed level of each of its fields, plus
+    /// the ones of the structs held in its fields
+    pub fn struct_lifetimes(&self, struct_id: &str) -> usize {
+        self.struct_lifetimes_within(struct_id, &mut vec![])
+    }
+
+    fn struct_lifetimes_within(&self, struct_id: &str, visited: &mut Vec<String>) -> usize {
+        // NOTE Structs that (indirectly) hold themselves get no lifetimes for themselves, and
+        // recursive structs own or share the nodes they link to
+        if visited.iter().any(|id| id == struct_id)
+            || self.get_struct(struct_id).recursive.is_some()
+        {
+            return 0;
+        }
+        visited.push(struct_id.to_string());
+        let lifetimes = self
+            .get_struct(struct_id)
+            .field_definitions
+            .iter()
+            .map(|field| {
+                let borrows = field
+                    .ptr_type
+                    .iter()
+                    .filter(|ptr_type| ptr_type.is_borrow())
+                    .count();
+                match &field.c_type {
+                    CType::Struct(field_struct_id) => {
+                        borrows + self.struct_lifetimes_within(field_struct_id, visited)
+                    }
+                    _ => borrows,
+                }
+            })
+            .sum();
+        visited.pop();
+        lifetimes
+    }
+
+    pub fn type_lifetimes(&self, t: &CType) -> usize {
+        match t {
+            CType::Struct(struct_id) if self.structs.contains_key(struct_id) => {
+                self.struct_lifetimes(struct_id)
+            }
+            _ => 0,
+        }
+    }
+
     pub fn get_struct_mut(&mut self, id: &str) -> &mut StructData {
         self.structs.get_mut(id).expect("Struct not in map")
     }
 
+    /// Keeps the borrows taken before a loop and used in it alive until the loop ends, since the
+    /// next iteration uses them again
+    pub fn loop_borrows(&mut self, loop_lines: RangeInclusive<LineNumber>) {
+        self.current_scope()
+            .variables
+            .values()
+            .flat_map(|var_data| var_data.points_to.iter())
+            .for_each(|reference| reference.borrow_mut().extend_over_loop(loop_lines.clone()));
+    }
+
+    /// Records a usage of every variable overlapping the place, so using a struct uses all of its
+    /// fields and using a field uses the structs holding it, while its sibling fields stay unused
+    pub fn place_usage(&mut self, place: &Place, line: LineNumber, t: UsageType) {
+        let ids: Vec<String> = self.current_scope().overlapping(place).cloned().collect();
+        ids.iter()
+            .for_each(|id| self.get_var_mut(id).new_usage(line, t.clone()));
+    }
+
+    /// The definition of a (possibly nested) field of a struct, `data.i` for `s.data.i`
+    pub fn field_definition(&self, struct_id: &str, field_path: &str) -> Option<&FieldDefinition> {
+        let (field_id, rest) = match field_path.split_once('.') {
+            Some((field_id, rest)) => (field_id, Some(rest)),
+            None => (field_path, None),
+        };
+        let field = self
+            .structs
+            .get(struct_id)?
+            .field_definitions
+            .iter()
+            .find(|field| field.id == field_id)?;
+
+        match (rest, &field.c_type) {
+            (None, _) => Some(field),
+            (Some(rest), CType::Struct(field_struct_id)) => {
+                self.field_definition(field_struct_id, rest)
+            }
+            (Some(_), _) => None,
+        }
+    }
+
+    /// Whether an enum is translated to a Rust enum rather than integer constants
+    pub fn is_rust_enum(&self, id: &str) -> bool {
+        self.enums
+            .get(id)
+            .is_some_and(|enum_data| !enum_data.arithmetic)
+    }
+
+    /// The tagged union a struct variable is an instance of
+    pub fn tagged_union(&self, var_id: &str) -> Option<&TaggedUnion> {
+        match &self.current_scope().variables.get(var_id)?.var_type {
+            CType::Struct(struct_id) => self.structs.get(struct_id)?.tagged.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Falls back to translating a tagged union as a `union`
+    pub fn untag(&mut self, struct_id: &str) {
+        if let Some(struct_data) = self.structs.get_mut(struct_id) {
+            struct_data.tagged = None;
+        }
+    }
+
     pub fn get_var(&self, id: &str) -> &VarData {
         self.current_scope()
             .variables
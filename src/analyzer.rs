@@ -1,10 +1,15 @@
+use std::ops::RangeInclusive;
+
 use crate::{
     analysis_ctx::AnalysisContext,
-    ast::{NodeType, TokenNode as Node},
+    ast::{AssignmentOpType, NodeType, StorageClass, TokenNode as Node},
     data_model::{
-        FieldDefinition, FieldInfo, LineNumber, ReferenceType, StructData, UsageType, VarData,
+        FieldDefinition, FieldInfo, LineNumber, NodeRole, Place, RecursiveStrategy, ReferenceType,
+        StructData, TaggedUnion, UsageType, VarData,
     },
+    format::is_print_function,
     lexer::CType,
+    scope::ScopeType,
 };
 
 pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
@@ -23,6 +28,9 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
     }
 
     match &root.token {
+        // NOTE Redeclaring a global (`extern int n;` and then `int n = 1;`) keeps what's already
+        // been recorded about it
+        NodeType::Declaration(id, _, _) if ctx.is_global(id) => {}
         NodeType::Declaration(id, c_type, _) => {
             let instanceof_struct = match c_type {
                 CType::Struct(struct_id) => Some(struct_id.clone()),
@@ -33,8 +41,28 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
 
             ctx.declaration(id, variable);
         }
+        // NOTE Pointers to recursive structs are linked by `recursive_struct_usage`, not borrowed
+        NodeType::Assignment(op, id) if ctx.is_node_ptr(id) => {
+            assert!(
+                *op == AssignmentOpType::Eq,
+                "Unsupported: Pointer arithmetic on recursive structs"
+            );
+            ctx.new_usage(id, root.line, UsageType::LValue);
+        }
         NodeType::Assignment(_, id) => handle_assignment_analysis(ctx, id, root),
+        NodeType::PtrDeclaration(id, c_type, expr) if ctx.recursive_strategy(c_type).is_some() => {
+            determine_var_mutability(expr, ctx);
+
+            let mut v = VarData::new(c_type.clone(), false, None, None);
+            v.node_role = Some(NodeRole::Owner);
+            ctx.declaration(id, v);
+        }
         NodeType::PtrDeclaration(id, c_type, expr) => {
+            assert!(
+                expr.token != NodeType::Null,
+                "Unsupported: NULL pointers to anything but recursive structs"
+            );
+
             // TODO
             // Determine if this is needed (I think not)
             // determine_var_mutability(expr, ctx, parent_children, root_index);
@@ -44,26 +72,91 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
                 _ => None,
             };
 
+            if let Some(size) = heap_allocation_size(expr) {
+                let reference_type = match size {
+                    AllocationSize::Single => ReferenceType::OwnedBox,
+                    AllocationSize::Array(count) => {
+                        find_ids(&count)
+                            .iter()
+                            .for_each(|id| ctx.new_usage(id, root.line, UsageType::RValue));
+                        ReferenceType::OwnedVec
+                    }
+                };
+
+                let v = VarData::new(c_type.clone(), false, instanceof_struct, None);
+                ctx.declaration(id, v);
+                ctx.heap_allocation(id, c_type.clone(), reference_type, root.line);
+                return;
+            }
+
+            if let NodeType::StrLiteral(_) = expr.token {
+                let v = VarData::new(c_type.clone(), false, None, None);
+                ctx.declaration(id, v);
+                ctx.string_literal(id, root.line);
+                return;
+            }
+
             let borrowed = ptr_from_expression(root, ctx, root.line)
                 .expect("No ptr in ptr declaration rvalue");
 
             let v = VarData::new(c_type.clone(), false, instanceof_struct, None);
+            let mut derived_from = None;
 
             // WARNING Only the borrowed var should be in the rvalue
-            let rvalue_ids = find_ids(expr);
-            assert_eq!(rvalue_ids.len(), 1);
+            // Other than the offset of pointer arithmetic over an array (`arr + i`)
+            if !matches!(expr.token, NodeType::Adr(_)) {
+                let (rvalue_ptr_ids, offset_ids) = partition_ptr_ids(ctx, find_ids(expr));
+                assert_eq!(rvalue_ptr_ids.len(), 1);
+                offset_ids
+                    .iter()
+                    .for_each(|id| ctx.new_usage(id, root.line, UsageType::RValue));
+                if ctx.get_var(&rvalue_ptr_ids[0]).is_ptr() {
+                    derived_from = Some(rvalue_ptr_ids[0].clone());
+                }
+
+                // NOTE `s + 1` is only in bounds of a string literal because of its NUL terminator
+                if !matches!(expr.token, NodeType::Id(_)) {
+                    ctx.string_element_read(&rvalue_ptr_ids[0], root.line);
+                }
+            }
 
             ctx.declaration(id, v);
-            ctx.ptr_assignment(&borrowed, id, root.line);
+            match derived_from {
+                Some(from_ptr_id) => {
+                    ctx.derived_ptr_assignment(&borrowed, id, &from_ptr_id, root.line)
+                }
+                None => ctx.ptr_assignment(&borrowed, id, root.line),
+            }
+        }
+        NodeType::ArrayDeclaration(id, c_type, dimensions) => {
+            if let CType::Struct(struct_id) = c_type {
+                assert!(
+                    root.children.is_none(),
+                    "Unsupported: Initializing an array of structs"
+                );
+                zero_initialized(ctx, struct_id);
+            }
+            ctx.array_declaration(id, c_type.clone(), dimensions)
         }
-        NodeType::ArrayDeclaration(id, c_type, count) => {
-            ctx.array_declaration(id, c_type.clone(), *count)
+        NodeType::Global(storage) => {
+            let id = match &root.children.as_ref().unwrap()[0].token {
+                NodeType::Declaration(id, _, _) | NodeType::ArrayDeclaration(id, _, _) => id,
+                _ => panic!("Unsupported: Global pointers"),
+            };
+            let var_data = ctx.get_var_mut(id);
+            // NOTE An `extern` declaration doesn't change where a global is defined
+            if *storage != StorageClass::Extern || var_data.storage.is_none() {
+                var_data.storage = Some(*storage);
+            }
         }
         NodeType::DerefAssignment(_, l_side) => {
             // determine_var_mutability(&l_side, ctx, parent_children, root_index);
-            let deref_ids = find_ids(l_side);
-            // NOTE `*(t + s) = bar` is not allowed
+            // NOTE `*(t + s) = bar` is only allowed if `t` points into an array
             // However, ``**m` is fine
+            let (deref_ids, offset_ids) = partition_ptr_ids(ctx, find_ids(l_side));
+            offset_ids
+                .iter()
+                .for_each(|id| ctx.new_usage(id, root.line, UsageType::RValue));
             if deref_ids.len() > 1 {
                 panic!("Unsupported: Multiple items dereferenced");
             } else if deref_ids.len() != 1 {
@@ -80,28 +173,57 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
 
             ctx.deref_assignment(&mut ptr_chain, root.line);
         }
-        NodeType::Id(id) => {
-            ctx.mut_var(id.to_string(), |var_data| {
-                var_data.new_usage(root.line, UsageType::RValue);
-            });
-        }
+        // NOTE Using a struct as a whole uses every field, which overlaps with field borrows
+        NodeType::Id(id) => ctx.place_usage(&Place::new(id), root.line, UsageType::RValue),
         NodeType::Adr(id) => {
+            assert!(
+                !ctx.is_global(id),
+                "Unsupported: Taking the address of a global outside of a pointer declaration"
+            );
             ctx.new_usage(id, root.line, UsageType::RValue);
         }
         NodeType::DeRef(adr) => {
-            let ids = find_ids(adr);
+            let (ids, offset_ids) = partition_ptr_ids(ctx, find_ids(adr));
 
             // TODO Make raw ptr instead
             assert_eq!(ids.len(), 1, "more than one or 0 ids derefed");
+            ctx.string_element_read(&ids[0], root.line);
 
-            let id = ids[0].clone();
-            ctx.mut_var(id, |var_data| {
-                var_data.new_usage(root.line, UsageType::RValue)
-            });
+            ids.iter()
+                .chain(offset_ids.iter())
+                .for_each(|id| ctx.new_usage(id, root.line, UsageType::RValue));
+        }
+        // NOTE Elements aren't told apart, so `arr[i].x` uses all of `arr`
+        NodeType::IndexArray { id, indices, .. } | NodeType::AdrIndex { id, indices } => {
+            ctx.new_usage(id, root.line, UsageType::RValue);
+            if ctx.get_var(id).is_ptr() {
+                ctx.string_element_read(id, root.line);
+            }
+            indices
+                .iter()
+                .flat_map(find_ids)
+                .for_each(|id| ctx.new_usage(&id, root.line, UsageType::RValue));
+        }
+        NodeType::IndexArrayAssignment {
+            id,
+            assignment_op: _,
+            rside,
+            lside,
+            field_id: _,
+        } => {
+            determine_var_mutability(rside, ctx);
+            lside
+                .iter()
+                .for_each(|index| determine_var_mutability(index, ctx));
+            let mut rvalue_ids = find_ids(rside);
+            rvalue_ids.extend(lside.iter().flat_map(find_ids));
+
+            element_assignment(ctx, id, rvalue_ids, root.line);
         }
         NodeType::StructDefinition {
             struct_id,
             field_definitions,
+            is_union,
         } => {
             let field_definitions: Vec<FieldDefinition> = field_definitions
                 .iter()
@@ -117,8 +239,30 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
                     }
                 })
                 .collect();
-            ctx.new_struct(struct_id.to_string(), StructData { field_definitions });
+            assert!(
+                !is_union
+                    || field_definitions
+                        .iter()
+                        .all(|field| field.ptr_type.is_empty()),
+                "Unsupported: Pointers in unions"
+            );
+            let tagged = tagged_union(&field_definitions, ctx);
+            let recursive = recursive_struct(struct_id, &field_definitions, ctx);
+            ctx.new_struct(
+                struct_id.to_string(),
+                StructData {
+                    field_definitions,
+                    is_union: *is_union,
+                    zero_initialized: false,
+                    tagged,
+                    recursive,
+                },
+            );
         }
+        NodeType::EnumDefinition {
+            enum_id: Some(enum_id),
+            variants,
+        } => ctx.new_enum(enum_id, variants.clone()),
         NodeType::StructDeclaration {
             var_id,
             struct_id,
@@ -126,7 +270,16 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
         } => {
             let struct_data = ctx.get_struct(struct_id).clone();
 
-            assert_eq!(exprs.len(), struct_data.field_definitions.len());
+            // NOTE `struct Foo foo;` is zeroed, and a union is initialized through its first member
+            assert!(
+                exprs.is_empty()
+                    || exprs.len() == struct_data.field_definitions.len()
+                    || struct_data.is_union && exprs.len() == 1,
+                "Unsupported: Partially initialized struct"
+            );
+            if exprs.is_empty() {
+                zero_initialized(ctx, struct_id);
+            }
 
             // NOTE Insert field variables
             struct_data
@@ -134,7 +287,7 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
                 .iter()
                 .enumerate()
                 .for_each(|(i, field)| {
-                    let var_id = format!("{}.{}", var_id, field.id);
+                    let var_id = Place::new(var_id).field(&field.id).to_string();
                     ctx.declaration(
                         var_id.clone(),
                         VarData::new(
@@ -147,8 +300,12 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
                             }),
                         ),
                     );
+                    if let CType::Struct(field_struct_id) = &field.c_type {
+                        declare_fields(ctx, &var_id, field_struct_id);
+                    }
                     match field.ptr_type.len() {
                         n if n >= 1 => {
+                            assert!(!exprs.is_empty(), "Unsupported: Zeroed pointer fields");
                             let borrowed_ids = find_ids(&exprs[i]);
                             // TODO Raw pointer
                             assert_eq!(borrowed_ids.len(), 1);
@@ -156,7 +313,7 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
 
                             ctx.ptr_assignment(borrowed, var_id.as_str(), root.line)
                         }
-
+                        _ if i < exprs.len() => determine_var_mutability(&exprs[i], ctx),
                         _ => {}
                     };
                 });
@@ -174,23 +331,103 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
             var_id,
             field_id,
             assignment_op: _,
-            expr: _,
+            expr,
         } => {
-            let field_var_id = format!("{var_id}.{field_id}");
+            determine_var_mutability(expr, ctx);
+            let field_var_id = Place::new(var_id).field(field_id).to_string();
             // Handle the field as a variable itself
             handle_assignment_analysis(ctx, field_var_id.as_str(), root);
 
             // Apply mutability checking to the struct instance itself as well
-            let _field_data = ctx.get_var(format!("{var_id}.{field_id}").as_str());
-
             ctx.mut_var(var_id.clone(), |var_data| {
                 var_data.is_mut = true;
             });
 
             // NOTE We don't need to apply mutability checking to the struct fields themselves
         }
-        NodeType::FunctionCall(_name) => {
+        // NOTE `p->field` reads through `p`, and writing it mutably borrows the struct `p` points to
+        NodeType::PtrFieldId {
+            ptr_id,
+            field_id: _,
+        } => ctx.new_usage(ptr_id, root.line, UsageType::RValue),
+        NodeType::PtrFieldAssignment {
+            ptr_id,
+            field_id: _,
+            assignment_op: _,
+            expr,
+        } if ctx.is_node_ptr(ptr_id) => {
+            determine_var_mutability(expr, ctx);
+            ctx.new_usage(ptr_id, root.line, UsageType::RValue);
+        }
+        NodeType::PtrFieldAssignment {
+            ptr_id,
+            field_id: _,
+            assignment_op: _,
+            expr,
+        } => {
+            determine_var_mutability(expr, ctx);
+            let pointee_id = ctx
+                .get_var(ptr_id)
+                .current_reference_held()
+                .map(|reference| reference.borrow().get_reference_to().to_string())
+                .expect("Unsupported: `->` on a non-pointer");
+            ctx.mut_var(pointee_id, VarData::set_mut);
+
+            let mut ptr_chain = ctx
+                .construct_ptr_chain_downwards(ptr_id.clone(), 0, 1)
+                .into_iter()
+                .rev();
+            ctx.deref_assignment(&mut ptr_chain, root.line);
+        }
+        NodeType::StructFieldId { var_id, field_id } => {
+            let place = Place::new(var_id).field(field_id);
+            ctx.place_usage(&place, root.line, UsageType::RValue);
+        }
+        // NOTE Global arrays are only ever indexed, since borrowing them needs `unsafe`
+        NodeType::FunctionCall(_)
+            if root.children.iter().flatten().any(|arg| match &arg.token {
+                NodeType::Id(id) => ctx.is_global(id) && ctx.get_var(id).is_array(),
+                _ => false,
+            }) =>
+        {
+            panic!("Unsupported: Passing a global array to a function")
+        }
+        NodeType::FunctionCall(name) if name == "free" => {
+            let ids = find_ids(&root.children.as_ref().unwrap()[0]);
+            assert_eq!(ids.len(), 1, "Unsupported: free of an expression");
+
+            ctx.free(&ids[0], root.line);
+        }
+        NodeType::FunctionCall(name) if name == "sprintf" || name == "snprintf" => {
+            let dst = match &root.children.as_ref().unwrap()[0].token {
+                NodeType::Id(id) => id.clone(),
+                _ => panic!("Unsupported: Writing to an expression with {name}"),
+            };
+            element_assignment(ctx, &dst, vec![], root.line);
+        }
+        // NOTE The arguments are already used by their own nodes (`stdout` and `stderr` aren't
+        // variables)
+        NodeType::FunctionCall(name) if is_print_function(name) => {}
+        NodeType::FunctionCall(name) => {
             let args = root.children.as_ref().unwrap().to_vec();
+
+            // NOTE `strcpy`, `memset`, etc. write into the array their first argument points to
+            if writes_first_arg(name) {
+                let dst = match &args[0].token {
+                    NodeType::Id(id) => id.clone(),
+                    _ => panic!("Unsupported: Writing to an expression with {name}"),
+                };
+                element_assignment(ctx, &dst, vec![], root.line);
+            }
+
+            // NOTE Arrays decay to pointers, which become slices
+            args.iter().enumerate().for_each(|(i, arg)| match &arg.token {
+                NodeType::Id(id) if ctx.get_var(id).is_array() && !ctx.is_function_arg(id) => {
+                    ctx.get_var_mut(id).passed_to.push((name.clone(), i))
+                }
+                _ => {}
+            });
+
             let c = |node: &Node| match &node.token {
                 NodeType::Id(id) | NodeType::Adr(id) => Some(id.clone()),
                 _ => None,
@@ -229,27 +466,255 @@ pub fn determine_var_mutability(root: &Node, ctx: &mut AnalysisContext) {
                 args,
             });
         }
-        NodeType::Return { expr } => {
-            let ids_in_expr = filter_map_tree(expr, |root| match &root.token {
-                NodeType::Id(id) => Some(id.clone()),
-                _ => None,
-            });
-
-            ids_in_expr
+        // NOTE `+` operands are read before they're written
+        NodeType::Asm {
+            outputs, inputs, ..
+        } => {
+            inputs
                 .iter()
-                .for_each(|id| ctx.new_usage(id, root.line, UsageType::RValue));
+                .chain(
+                    outputs
+                        .iter()
+                        .filter(|(constraint, _)| constraint.starts_with('+')),
+                )
+                .for_each(|(_, id)| ctx.place_usage(&Place::new(id), root.line, UsageType::RValue));
+            outputs
+                .iter()
+                .for_each(|(_, id)| ctx.asm_usage(id, root.line));
         }
+        NodeType::Return { expr, .. } => determine_var_mutability(expr, ctx),
+        NodeType::While | NodeType::For => ctx.loop_borrows(lines(root)),
         _ => {}
     };
 }
 
+/// The lines a statement spans, with its body if it has one
+fn lines(root: &Node) -> RangeInclusive<LineNumber> {
+    sub_nodes(root)
+        .into_iter()
+        .map(lines)
+        .fold(root.line..=root.line, |span, sub_span| {
+            *span.start().min(sub_span.start())..=*span.end().max(sub_span.end())
+        })
+}
+
+/// The number of elements a `malloc` or `calloc` call allocates space for
+pub enum AllocationSize {
+    Single,
+    // The element count expression
+    Array(Node),
+}
+
+/// Recognizes `malloc(sizeof(T))`, `malloc(n * sizeof(T))` and `calloc(n, sizeof(T))`
+pub fn heap_allocation_size(root: &Node) -> Option<AllocationSize> {
+    let args = root.children.as_ref()?;
+    let count = match (&root.token, args.as_ref()) {
+        (NodeType::FunctionCall(name), [size]) if name == "malloc" => {
+            malloc_count(size).expect("Checked by the checker")
+        }
+        (NodeType::FunctionCall(name), [count, _size]) if name == "calloc" => Some(count.clone()),
+        _ => return None,
+    };
+
+    Some(match count {
+        Some(count) if count.token != NodeType::NumLiteral(1) => AllocationSize::Array(count),
+        _ => AllocationSize::Single,
+    })
+}
+
+/// How many items a `malloc` size is, `Some(None)` for a single one
+/// `None` if the size has no `sizeof`, so there's no telling what's allocated
+pub fn malloc_count(size: &Node) -> Option<Option<Node>> {
+    match &size.token {
+        NodeType::SizeOf(_, _) => Some(None),
+        NodeType::Mul => {
+            let factors = size.children.as_ref().unwrap();
+            match (&factors[0].token, &factors[1].token) {
+                (NodeType::SizeOf(_, _), _) => Some(Some(factors[1].clone())),
+                (_, NodeType::SizeOf(_, _)) => Some(Some(factors[0].clone())),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The type of an expression that's a number, `None` for pointers, structs and anything else
+/// Used to cast between number types, which C converts implicitly and rust doesn't
+pub fn number_type(root: &Node, ctx: &AnalysisContext) -> Option<CType> {
+    let number = |t: &CType| {
+        matches!(t, CType::Int | CType::Char | CType::Float | CType::Double).then(|| t.clone())
+    };
+    let var_type = |id: &str| {
+        let var_data = ctx.current_scope().variables.get(id)?;
+        match var_data.is_ptr() {
+            true => None,
+            false => number(&var_data.var_type),
+        }
+    };
+    let field_type = |t: &CType, field_id: &str| match t {
+        CType::Struct(struct_id) => ctx
+            .field_definition(struct_id, field_id)
+            .filter(|field| field.ptr_type.is_empty())
+            .and_then(|field| number(&field.c_type)),
+        _ => None,
+    };
+
+    match &root.token {
+        NodeType::NumLiteral(_) | NodeType::SizeOf(_, _) => Some(CType::Int),
+        NodeType::FloatLiteral(_) => Some(CType::Double),
+        NodeType::Id(id) => var_type(id),
+        NodeType::StructFieldId { var_id, field_id } => {
+            var_type(&Place::new(var_id).field(field_id).to_string())
+        }
+        NodeType::PtrFieldId { ptr_id, field_id } => {
+            field_type(&ctx.get_var(ptr_id).var_type, field_id)
+        }
+        NodeType::IndexArray {
+            id,
+            indices,
+            field_id,
+        } => {
+            let mut t = &ctx.get_var(id).var_type;
+            // NOTE `a[i]` of an `int a[2][2]` is a row
+            for _ in indices.iter() {
+                let CType::Array(sub_type) = t else {
+                    break;
+                };
+                t = sub_type;
+            }
+            match field_id {
+                Some(field_id) => field_type(t, field_id),
+                None => number(t),
+            }
+        }
+        NodeType::DeRef(expr) => {
+            let (ptr_ids, _) = partition_ptr_ids(ctx, find_ids(expr));
+            let [ptr_id] = ptr_ids.as_slice() else {
+                return None;
+            };
+            // NOTE `*q` of an `int** q` is still a pointer
+            let depth = ctx
+                .get_var(ptr_id)
+                .reference_at_line(root.line)
+                .map_or(1, |reference| {
+                    reference
+                        .borrow()
+                        .construct_reference_chain(ctx, root.line)
+                        .len()
+                });
+            match count_derefs(expr) as usize + 1 >= depth {
+                true => number(&ctx.get_var(ptr_id).var_type),
+                false => None,
+            }
+        }
+        // NOTE Their lengths and orderings are translated to an `i32`
+        NodeType::FunctionCall(name) if matches!(name.as_str(), "strlen" | "strcmp") => {
+            Some(CType::Int)
+        }
+        NodeType::FunctionCall(name) => {
+            ctx.scopes.iter().find_map(|scope| match &scope.scope_type {
+                ScopeType::Function {
+                    name: function_name,
+                    ret,
+                    args: _,
+                } if function_name == name => number(ret),
+                _ => None,
+            })
+        }
+        NodeType::Add | NodeType::Sub | NodeType::Mul | NodeType::Div => {
+            let children = root.children.as_ref()?;
+            arithmetic_type(&children[0], &children[1], ctx)
+        }
+        _ => None,
+    }
+}
+
+/// The type C does arithmetic in, which is at least an `int`, so `c - 90` can't wrap around
+pub fn arithmetic_type(left: &Node, right: &Node, ctx: &AnalysisContext) -> Option<CType> {
+    common_type(left, right, ctx).map(|t| match t {
+        CType::Char => CType::Int,
+        t => t,
+    })
+}
+
+/// The type C converts both sides of a comparison to
+///
+/// # Important
+/// Literals take the type of the other side where rust lets them, so `c != 'a'` stays a `char`
+pub fn common_type(left: &Node, right: &Node, ctx: &AnalysisContext) -> Option<CType> {
+    let rank = |t: &CType| match t {
+        CType::Char => 0,
+        CType::Int => 1,
+        CType::Float => 2,
+        _ => 3,
+    };
+    let (left_type, right_type) = (number_type(left, ctx)?, number_type(right, ctx)?);
+    if adapts(left, &right_type) {
+        return Some(right_type);
+    }
+    if adapts(right, &left_type) {
+        return Some(left_type);
+    }
+
+    Some(match rank(&left_type) >= rank(&right_type) {
+        true => left_type,
+        false => right_type,
+    })
+}
+
+/// Whether an expression is a literal rust gives the type without a cast
+pub fn adapts(expr: &Node, t: &CType) -> bool {
+    match expr.token {
+        NodeType::NumLiteral(_) => matches!(t, CType::Int | CType::Char),
+        NodeType::FloatLiteral(_) => t.is_floating(),
+        _ => false,
+    }
+}
+
+/// Assigning to an item of an array, either directly or through a pointer
+/// `ptr[i] = n` is the same as `*(ptr + i) = n`
+fn element_assignment(
+    ctx: &mut AnalysisContext,
+    id: &str,
+    rvalue_ids: Vec<String>,
+    line: LineNumber,
+) {
+    match ctx.get_var(id).is_ptr() {
+        true => {
+            rvalue_ids
+                .iter()
+                .for_each(|id| ctx.new_usage(id, line, UsageType::RValue));
+            let mut ptr_chain = ctx
+                .construct_ptr_chain_downwards(id.to_string(), 0, u8::MAX)
+                .into_iter()
+                .rev();
+            ctx.deref_assignment(&mut ptr_chain, line);
+        }
+        false => ctx.assignment(id, rvalue_ids, line),
+    }
+}
+
+/// The `<string.h>` functions that are translated into slice operations
+pub fn is_string_function(name: &str) -> bool {
+    matches!(
+        name,
+        "strlen" | "strcmp" | "strcpy" | "strncpy" | "memcpy" | "memset"
+    )
+}
+
+/// `<string.h>` functions that write into their first argument
+pub fn writes_first_arg(name: &str) -> bool {
+    matches!(name, "strcpy" | "strncpy" | "memcpy" | "memset")
+}
+
 /// Finds Adrs taken in an expression
 pub fn find_addresses(root: &Node) -> Vec<String> {
     let mut vec: Vec<String> = match root.children.as_ref() {
         Some(children) => children.iter().flat_map(find_addresses).collect(),
         None => vec![],
     };
-    if let NodeType::Adr(id) = &root.token {
+    if let NodeType::Adr(id) | NodeType::AdrIndex { id, indices: _ } = &root.token {
         vec.push(id.to_string())
     }
     vec
@@ -278,6 +743,443 @@ pub fn find_type_ids(root: &Node) -> Vec<(String, CType)> {
     type_ids
 }
 
+/// Marks the enums whose values are used as integers (arithmetic, printing, comparing with
+/// another type), which are translated to integer `const`s instead of a Rust enum
+/// Declaring, assigning, passing, returning and comparing with `==` and `!=` keep an enum a Rust
+/// enum
+pub fn enum_usage(root: &Node, ctx: &mut AnalysisContext, ret: &CType) {
+    let enum_type = |id: &str, ctx: &AnalysisContext| match ctx.current_scope().variables.get(id) {
+        Some(var_data) if !var_data.is_ptr() => match &var_data.var_type {
+            CType::Enum(enum_id) => Some(enum_id.clone()),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    match &root.token {
+        NodeType::Declaration(_, CType::Enum(enum_id), _) => root
+            .children
+            .iter()
+            .flatten()
+            .for_each(|child| enum_operand(child, enum_id, ctx, ret)),
+        NodeType::Assignment(AssignmentOpType::Eq, id) if enum_type(id, ctx).is_some() => {
+            let enum_id = enum_type(id, ctx).unwrap();
+            root.children
+                .iter()
+                .flatten()
+                .for_each(|child| enum_operand(child, &enum_id, ctx, ret))
+        }
+        NodeType::StructFieldAssignment {
+            var_id,
+            field_id,
+            assignment_op: AssignmentOpType::Eq,
+            expr,
+        } if enum_type(&Place::new(var_id).field(field_id).to_string(), ctx).is_some() => {
+            let enum_id = enum_type(&Place::new(var_id).field(field_id).to_string(), ctx).unwrap();
+            enum_operand(expr, &enum_id, ctx, ret)
+        }
+        NodeType::StructDeclaration {
+            var_id: _,
+            struct_id,
+            exprs,
+        } => {
+            let field_definitions = ctx.get_struct(struct_id).field_definitions.clone();
+            exprs
+                .iter()
+                .zip(field_definitions)
+                .for_each(|(expr, field)| match &field.c_type {
+                    CType::Enum(enum_id) if field.ptr_type.is_empty() => {
+                        enum_operand(expr, enum_id, ctx, ret)
+                    }
+                    _ => enum_usage(expr, ctx, ret),
+                })
+        }
+        NodeType::Return { expr, .. } => match ret {
+            CType::Enum(enum_id) => enum_operand(expr, enum_id, ctx, ret),
+            _ => enum_usage(expr, ctx, ret),
+        },
+        NodeType::EqCmp | NodeType::NeqCmp => {
+            let children = root.children.as_ref().unwrap();
+            match enum_value(&children[0], ctx) {
+                Some(enum_id) if enum_value(&children[1], ctx) == Some(enum_id.clone()) => children
+                    .iter()
+                    .for_each(|child| enum_operand(child, &enum_id, ctx, ret)),
+                _ => children
+                    .iter()
+                    .for_each(|child| enum_usage(child, ctx, ret)),
+            }
+        }
+        NodeType::FunctionCall(name) => {
+            enum_args(root, name, ctx, ret);
+            if let Some(enum_id) = enum_value(root, ctx) {
+                ctx.arithmetic_enum(&enum_id);
+            }
+        }
+        NodeType::FunctionDeclaration(_, t) => sub_nodes(root)
+            .into_iter()
+            .for_each(|node| enum_usage(node, ctx, t)),
+        _ => {
+            // NOTE Anything else that reaches an enum value uses it as an integer
+            if let Some(enum_id) = enum_value(root, ctx) {
+                ctx.arithmetic_enum(&enum_id);
+            }
+            sub_nodes(root)
+                .into_iter()
+                .for_each(|node| enum_usage(node, ctx, ret));
+        }
+    }
+}
+
+// An expression where a value of `enum_id` is expected
+fn enum_operand(root: &Node, enum_id: &str, ctx: &mut AnalysisContext, ret: &CType) {
+    match (enum_value(root, ctx), &root.token) {
+        (Some(value_enum_id), NodeType::FunctionCall(name)) if value_enum_id == enum_id => {
+            enum_args(root, name, ctx, ret)
+        }
+        (Some(value_enum_id), _) if value_enum_id == enum_id => {}
+        _ => {
+            ctx.arithmetic_enum(enum_id);
+            enum_usage(root, ctx, ret);
+        }
+    }
+}
+
+// Arguments passed to enum parameters are expected to be values of that enum
+fn enum_args(root: &Node, name: &str, ctx: &mut AnalysisContext, ret: &CType) {
+    let params = ctx.function_args(name).unwrap_or_default();
+    root.children
+        .iter()
+        .flatten()
+        .enumerate()
+        .for_each(|(i, arg)| {
+            let param_type = params
+                .get(i)
+                .map(|id| ctx.get_var(&format!("{id}_{name}")).var_type.clone());
+            match param_type {
+                Some(CType::Enum(enum_id)) if ctx.enums.contains_key(&enum_id) => {
+                    enum_operand(arg, &enum_id, ctx, ret)
+                }
+                _ => enum_usage(arg, ctx, ret),
+            }
+        });
+}
+
+/// The (named) enum an expression is a value of
+fn enum_value(root: &Node, ctx: &AnalysisContext) -> Option<String> {
+    let t = match &root.token {
+        NodeType::EnumConstant { enum_id, id: _ } => return enum_id.clone(),
+        NodeType::Id(id) => {
+            let var_data = ctx.current_scope().variables.get(id)?;
+            match var_data.is_ptr() {
+                true => return None,
+                false => var_data.var_type.clone(),
+            }
+        }
+        NodeType::StructFieldId { var_id, field_id } => ctx
+            .current_scope()
+            .variables
+            .get(&Place::new(var_id).field(field_id).to_string())?
+            .var_type
+            .clone(),
+        NodeType::FunctionCall(name) => {
+            ctx.scopes
+                .iter()
+                .find_map(|scope| match &scope.scope_type {
+                    ScopeType::Function {
+                        name: function_name,
+                        ret,
+                        args: _,
+                    } if function_name == name => Some(ret.clone()),
+                    _ => None,
+                })?
+        }
+        _ => return None,
+    };
+
+    match t {
+        CType::Enum(enum_id) if ctx.enums.contains_key(&enum_id) => Some(enum_id),
+        _ => None,
+    }
+}
+
+/// Checks that every instance of a tagged union has its tag written right before the matching
+/// member, and that nothing else uses the union, falling back to a `union` otherwise
+pub fn tagged_union_usage(root: &Node, ctx: &mut AnalysisContext) {
+    let statements: Vec<&Node> = root.children.iter().flatten().collect();
+
+    // NOTE (var_id, field_id) of the member the previous statement's tag selected
+    let mut expected_member: Option<(String, String)> = None;
+    statements.iter().for_each(|statement| {
+        let tagged = match &statement.token {
+            NodeType::StructFieldAssignment { var_id, .. } => ctx.tagged_union(var_id).cloned(),
+            _ => None,
+        };
+
+        match (&statement.token, tagged) {
+            (
+                NodeType::StructFieldAssignment {
+                    var_id,
+                    field_id,
+                    assignment_op,
+                    expr,
+                },
+                Some(tagged),
+            ) => {
+                let struct_id = ctx.get_var(var_id).var_type.clone();
+                let is_valid = match expected_member.take() {
+                    Some(expected) => {
+                        expected == (var_id.clone(), field_id.clone())
+                            && *assignment_op == AssignmentOpType::Eq
+                    }
+                    None => match &expr.token {
+                        NodeType::EnumConstant {
+                            enum_id: Some(enum_id),
+                            id,
+                        } if *field_id == tagged.tag_field
+                            && *assignment_op == AssignmentOpType::Eq
+                            && *enum_id == tagged.enum_id =>
+                        {
+                            let i = ctx.enums[enum_id]
+                                .variants
+                                .iter()
+                                .position(|(variant, _)| variant == id)
+                                .unwrap();
+                            let member = &ctx.get_struct(&tagged.union_id).field_definitions[i];
+                            expected_member = Some((
+                                var_id.clone(),
+                                format!("{}.{}", tagged.union_field, member.id),
+                            ));
+                            true
+                        }
+                        _ => false,
+                    },
+                };
+
+                if let (false, CType::Struct(struct_id)) = (is_valid, struct_id) {
+                    ctx.untag(&struct_id);
+                }
+            }
+            _ => {
+                if let Some((var_id, _)) = expected_member.take() {
+                    if let CType::Struct(struct_id) = ctx.get_var(&var_id).var_type.clone() {
+                        ctx.untag(&struct_id);
+                    }
+                }
+            }
+        }
+    });
+    if let Some((var_id, _)) = expected_member {
+        if let CType::Struct(struct_id) = ctx.get_var(&var_id).var_type.clone() {
+            ctx.untag(&struct_id);
+        }
+    }
+
+    let tagged_structs: Vec<(String, TaggedUnion)> = ctx
+        .structs
+        .iter()
+        .filter_map(|(struct_id, struct_data)| {
+            Some((struct_id.clone(), struct_data.tagged.clone()?))
+        })
+        .collect();
+    tagged_structs.iter().for_each(|(struct_id, tagged)| {
+        if untags(root, struct_id, tagged, ctx) {
+            ctx.untag(struct_id);
+        }
+    });
+
+    sub_nodes(root)
+        .into_iter()
+        .for_each(|node| tagged_union_usage(node, ctx));
+}
+
+// NOTE Tagged unions are only declared without an initializer, and only used through their
+// fields
+fn untags(root: &Node, struct_id: &str, tagged: &TaggedUnion, ctx: &AnalysisContext) -> bool {
+    let is_instance = |t: &CType| *t == CType::Struct(struct_id.to_string());
+    let is_union = |t: &CType| *t == CType::Struct(tagged.union_id.clone());
+
+    match &root.token {
+        NodeType::StructDeclaration {
+            struct_id: declared,
+            exprs,
+            ..
+        } => *declared == tagged.union_id || declared == struct_id && !exprs.is_empty(),
+        NodeType::PtrDeclaration(_, t, _) => is_instance(t) || is_union(t),
+        NodeType::Adr(id) => ctx
+            .current_scope()
+            .variables
+            .get(id)
+            .is_some_and(|var_data| is_instance(&var_data.var_type)),
+        NodeType::StructFieldId { var_id, field_id } => {
+            ctx.tagged_union(var_id) == Some(tagged) && *field_id == tagged.union_field
+        }
+        NodeType::StructDefinition {
+            struct_id: defined,
+            field_definitions,
+            ..
+        } => defined != struct_id && field_definitions.iter().any(|(_, _, t)| is_union(t)),
+        _ => false,
+    }
+}
+
+// Whether a struct's fields are an enum tag followed by a union with a member per variant
+fn tagged_union(fields: &[FieldDefinition], ctx: &AnalysisContext) -> Option<TaggedUnion> {
+    let [tag, union] = fields else {
+        return None;
+    };
+    let (CType::Enum(enum_id), CType::Struct(union_id)) = (&tag.c_type, &union.c_type) else {
+        return None;
+    };
+    let union_data = ctx.structs.get(union_id)?;
+    let variants = &ctx.enums.get(enum_id)?.variants;
+    let scalar_members = union_data.field_definitions.iter().all(|member| {
+        matches!(
+            member.c_type,
+            CType::Int | CType::Char | CType::Float | CType::Double
+        )
+    });
+
+    (tag.ptr_type.is_empty()
+        && union.ptr_type.is_empty()
+        && union_data.is_union
+        && scalar_members
+        && union_data.field_definitions.len() == variants.len())
+    .then(|| TaggedUnion {
+        tag_field: tag.id.clone(),
+        enum_id: enum_id.clone(),
+        union_field: union.id.clone(),
+        union_id: union_id.clone(),
+    })
+}
+
+// Whether a struct points to itself, in which case its pointer fields are links to other nodes
+// NOTE Nodes start out boxed, `recursive_struct_usage` decides how they're really linked
+fn recursive_struct(
+    struct_id: &str,
+    fields: &[FieldDefinition],
+    ctx: &AnalysisContext,
+) -> Option<RecursiveStrategy> {
+    let is_link = |field: &FieldDefinition| {
+        field.ptr_type.len() == 1 && field.c_type == CType::Struct(struct_id.to_string())
+    };
+    fields.iter().for_each(|field| match &field.c_type {
+        CType::Struct(field_struct_id)
+            if !field.ptr_type.is_empty()
+                && ctx
+                    .structs
+                    .get(field_struct_id)
+                    .is_some_and(|struct_data| struct_data.recursive.is_some()) =>
+        {
+            panic!("Unsupported: Pointers to recursive structs in other structs")
+        }
+        _ => {}
+    });
+    if !fields.iter().any(is_link) {
+        return None;
+    }
+
+    assert!(
+        fields.iter().all(|field| is_link(field)
+            || field.ptr_type.is_empty()
+                && matches!(
+                    field.c_type,
+                    CType::Int | CType::Char | CType::Float | CType::Double
+                )),
+        "Unsupported: Recursive structs with fields other than numbers and links"
+    );
+    Some(RecursiveStrategy::Boxed)
+}
+
+// Declares the fields of a nested struct or union as variables, `s.data.i`
+fn declare_fields(ctx: &mut AnalysisContext, var_id: &str, struct_id: &str) {
+    let field_definitions = ctx.get_struct(struct_id).field_definitions.clone();
+    let place = ctx.get_var(var_id).place.clone();
+    field_definitions.iter().for_each(|field| {
+        let field_var_id = place.clone().field(&field.id).to_string();
+        ctx.declaration(
+            &field_var_id,
+            VarData::new(
+                field.c_type.clone(),
+                false,
+                None,
+                Some(FieldInfo {
+                    struct_id: struct_id.to_string(),
+                    field_id: field.id.clone(),
+                }),
+            ),
+        );
+        if let CType::Struct(field_struct_id) = &field.c_type {
+            declare_fields(ctx, &field_var_id, field_struct_id);
+        }
+    });
+}
+
+// NOTE Zeroing a struct zeroes the structs held in its fields too
+fn zero_initialized(ctx: &mut AnalysisContext, struct_id: &str) {
+    ctx.get_struct_mut(struct_id).zero_initialized = true;
+    let field_definitions = ctx.get_struct(struct_id).field_definitions.clone();
+    field_definitions.iter().for_each(|field| {
+        if let CType::Struct(field_struct_id) = &field.c_type {
+            zero_initialized(ctx, field_struct_id);
+        }
+    });
+}
+
+// Children and the expressions held by the node itself
+pub fn sub_nodes(root: &Node) -> Vec<&Node> {
+    let mut nodes: Vec<&Node> = root.children.iter().flatten().collect();
+    match &root.token {
+        NodeType::DerefAssignment(_, node)
+        | NodeType::PtrDeclaration(_, _, node)
+        | NodeType::DeRef(node)
+        | NodeType::Return { expr: node, .. }
+        | NodeType::StructFieldAssignment { expr: node, .. }
+        | NodeType::PtrFieldAssignment { expr: node, .. } => nodes.push(node),
+        NodeType::IndexArray { indices, .. } | NodeType::AdrIndex { indices, .. } => {
+            nodes.extend(indices)
+        }
+        NodeType::IndexArrayAssignment { rside, lside, .. } => {
+            nodes.push(rside);
+            nodes.extend(lside);
+        }
+        NodeType::StructDeclaration { exprs, .. } => nodes.extend(exprs),
+        _ => {}
+    }
+
+    nodes
+}
+
+/// Visits the node and everything under it, including the expressions held by nodes
+pub fn for_each_node_mut(root: &mut Node, f: &mut impl FnMut(&mut Node)) {
+    f(root);
+    if let Some(children) = root.children.as_mut() {
+        children
+            .iter_mut()
+            .for_each(|child| for_each_node_mut(child, f));
+    }
+    match &mut root.token {
+        NodeType::DerefAssignment(_, node)
+        | NodeType::PtrDeclaration(_, _, node)
+        | NodeType::DeRef(node)
+        | NodeType::Return { expr: node, .. }
+        | NodeType::StructFieldAssignment { expr: node, .. }
+        | NodeType::PtrFieldAssignment { expr: node, .. } => for_each_node_mut(node, f),
+        NodeType::IndexArray { indices, .. } | NodeType::AdrIndex { indices, .. } => indices
+            .iter_mut()
+            .for_each(|index| for_each_node_mut(index, f)),
+        NodeType::IndexArrayAssignment { rside, lside, .. } => {
+            for_each_node_mut(rside, f);
+            lside
+                .iter_mut()
+                .for_each(|index| for_each_node_mut(index, f));
+        }
+        NodeType::StructDeclaration { exprs, .. } => {
+            exprs.iter_mut().for_each(|expr| for_each_node_mut(expr, f))
+        }
+        _ => {}
+    }
+}
+
 pub fn find_ids(root: &Node) -> Vec<String> {
     let mut ids = match root.children.as_ref() {
         Some(children) => children.iter().flat_map(find_ids).collect(),
@@ -286,8 +1188,14 @@ pub fn find_ids(root: &Node) -> Vec<String> {
 
     match &root.token {
         NodeType::Id(id) => ids.push(id.to_string()),
-        NodeType::StructFieldId { var_id, field_id } => ids.push(format!("{var_id}.{field_id}")),
+        NodeType::StructFieldId { var_id, field_id } => {
+            ids.push(Place::new(var_id).field(field_id).to_string())
+        }
         NodeType::Adr(id) => ids.push(id.to_string()),
+        NodeType::AdrIndex { id, indices } => {
+            ids.push(id.to_string());
+            ids.extend(indices.iter().flat_map(find_ids));
+        }
         NodeType::DeRef(node) => ids.append(&mut find_ids(node)),
         _ => {}
     }
@@ -315,19 +1223,44 @@ pub fn count_declaration_ref(root: &Node) -> Vec<ReferenceType> {
 pub fn handle_assignment_analysis(ctx: &mut AnalysisContext, id: &str, root: &Node) {
     let rvalue_ids = find_ids(root);
     let lvalue = ctx.get_var(id);
+    let is_offset = match &root.token {
+        NodeType::Assignment(AssignmentOpType::AddEq | AssignmentOpType::SubEq, _) => true,
+        NodeType::Assignment(AssignmentOpType::Eq, _) => rvalue_ids.iter().any(|rid| rid == id),
+        _ => false,
+    };
+
     match lvalue.is_ptr() {
+        // NOTE `ptr++`, `ptr += n` and `ptr = ptr + n`
+        true if is_offset => {
+            rvalue_ids
+                .iter()
+                .filter(|rvalue_id| *rvalue_id != id)
+                .for_each(|rvalue_id| ctx.new_usage(rvalue_id, root.line, UsageType::RValue));
+            ctx.ptr_offset(id, root.line);
+        }
         true => {
             let points_to =
                 &ptr_from_expression(root, ctx, root.line).expect("Ptr doesn't point to anything");
 
             // WARNING The rvalue should only be the adr/ptr
             assert_eq!(rvalue_ids.len(), 1);
-            ctx.ptr_assignment(points_to, id, root.line);
+            match ctx.get_var(&rvalue_ids[0]).is_ptr() {
+                true => ctx.derived_ptr_assignment(points_to, id, &rvalue_ids[0], root.line),
+                false => ctx.ptr_assignment(points_to, id, root.line),
+            }
         }
         false => ctx.assignment(id, rvalue_ids, root.line),
     }
 }
 
+/// Splits ids into the pointers (or arrays) and everything else, such as the offset in `*(ptr + i)`
+pub fn partition_ptr_ids(ctx: &AnalysisContext, ids: Vec<String>) -> (Vec<String>, Vec<String>) {
+    ids.into_iter().partition(|id| {
+        let var_data = ctx.get_var(id);
+        var_data.is_ptr() || var_data.is_array()
+    })
+}
+
 // All Refs are Adr
 fn ptr_type_chain(rvalue_ptrs: &[String], ctx: &mut AnalysisContext) -> Vec<ReferenceType> {
     match rvalue_ptrs.len() {
@@ -349,7 +1282,7 @@ fn ptr_from_expression(root: &Node, ctx: &mut AnalysisContext, line: LineNumber)
     let mut adrs: Vec<String> = Vec::with_capacity(4);
 
     match &root.token {
-        NodeType::Adr(id) => adrs.push(id.clone()),
+        NodeType::Adr(id) | NodeType::AdrIndex { id, indices: _ } => adrs.push(id.clone()),
         NodeType::Id(id) => ids.push(id.clone()),
         NodeType::PtrDeclaration(_id, _t, l_value) => {
             println!("\n{l_value}\n");
@@ -369,14 +1302,23 @@ fn ptr_from_expression(root: &Node, ctx: &mut AnalysisContext, line: LineNumber)
     };
 
     let ptr_to_borrowed = |ptr_id: String| {
-        ctx.get_var(&ptr_id)
+        let ptr_data = ctx.get_var(&ptr_id);
+        // NOTE A pointer that's only copied hasn't had its reference extended to this line yet
+        ptr_data
             .reference_at_line(line)
+            .or_else(|| ptr_data.current_reference_held())
             .unwrap()
             .borrow()
             .get_reference_to()
             .to_string()
     };
 
+    // NOTE Arrays decay to a pointer to their first element
+    adrs.extend(
+        ids.iter()
+            .filter(|id| ctx.get_var(id).is_array() && !ctx.get_var(id).is_ptr())
+            .cloned(),
+    );
     adrs.extend(
         ids.into_iter()
             .filter(|id| ctx.get_var(id).is_ptr())
@@ -1,15 +1,39 @@
 use crate::{
     analysis_ctx::AnalysisContext,
-    analyzer::{count_derefs, find_ids},
-    ast::{AssignmentOpType, NodeType, TokenNode as Node},
-    data_model::{FieldDefinition, Reference, ReferenceType},
+    analyzer::{
+        adapts, arithmetic_type, common_type, count_derefs, find_ids, heap_allocation_size,
+        is_string_function, number_type, partition_ptr_ids, writes_first_arg, AllocationSize,
+    },
+    ast::{AssignmentOpType, NodeType, StorageClass, TokenNode as Node},
+    data_model::{
+        FieldDefinition, GlobalStrategy, NodeRole, Place, RecursiveStrategy, Reference,
+        ReferenceType, StringMode,
+    },
+    format::{
+        format_index, format_type, is_print_function, translate_format, FormatArg, FormatType,
+    },
     lexer::CType,
+    recursive::{is_link, node_source, NodeSource},
 };
 use std::{cell::RefCell, fmt::Display, rc::Rc};
 
+/// Where a `printf`-family call writes to
+#[derive(Debug, Clone)]
+pub enum PrintTarget {
+    Stdout,
+    Stderr,
+    // `sprintf` and `snprintf`, where the limit includes the NUL terminator
+    Buffer {
+        id: String,
+        limit: Option<Box<AnnotatedNode>>,
+    },
+}
+
 #[derive(Debug, Clone)]
 pub struct AnnotatedNode {
     pub token: AnnotatedNodeT,
+    // The C line it was translated from
+    pub line: usize,
     pub children: Vec<AnnotatedNode>,
 }
 
@@ -24,6 +48,7 @@ pub enum AnnotatedNodeT {
     Id {
         id: String,
         rc: bool,
+        cell: bool,
     }, // figure out if we want this here
     EqCmp,
     NeqCmp,
@@ -42,6 +67,7 @@ pub enum AnnotatedNodeT {
     AndCmp,
     OrCmp,
     NumLiteral(usize),
+    FloatLiteral(String),
     Add,
     If,
     For,
@@ -50,10 +76,25 @@ pub enum AnnotatedNodeT {
     Break,
     FunctionCall(String),
     Scope(Option<CType>), // <-- anything that has {} is a scope, scope is how we're handling multiple statements, scopes return the last statement's result or void
+    // Statements dereferencing raw pointers, with why that's sound as its `// SAFETY:` comment
+    // When it isn't a block, its only child is a declaration or a loop or `if`, whose
+    // expressions dereferencing raw pointers are each in an `UnsafeExpr`
+    Unsafe {
+        safety: String,
+        block: bool,
+    },
+    // An expression dereferencing raw pointers, its only child
+    UnsafeExpr,
+    // A number converted to another type, which C does implicitly, the number is the only child
+    Cast(CType),
+    // A `char` read as the signed byte C has it as, before it's widened, the `char` is the only
+    // child
+    SignedChar,
     Assignment {
         op: AssignmentOpType,
         id: String,
         rc: bool,
+        cell: bool,
     },
     DerefAssignment {
         op: AssignmentOpType,
@@ -61,14 +102,22 @@ pub enum AnnotatedNodeT {
         rc: bool,
         // This is the type of each reference being dereferenced, not in total
         ref_types: Vec<ReferenceType>,
+        // `*(ptr + offset) = n` for pointers into arrays
+        offset: Option<Box<AnnotatedNode>>,
+        slice: bool,
     },
     Declaration {
         id: String,
         is_mut: bool,
         t: CType,
         rc: bool,
+        cell: bool,
         is_used: bool,
         init_value_unused: bool,
+        // Of the struct it holds, only named in function signatures
+        lifetimes: usize,
+        // A copy sonder takes of a value before it's borrowed
+        clone: bool,
     },
     PtrDeclaration {
         id: String,
@@ -81,21 +130,116 @@ pub enum AnnotatedNodeT {
         rc: bool,
         is_used: bool,
         init_value_unused: bool,
+        // Points into an array
+        slice: bool,
+        // Taken through this pointer instead of the value, `&*m`
+        reborrow: Option<String>,
+    },
+    Asm {
+        template: String,
+        // (constraint, id, type), the outputs then the inputs, which is how the template numbers
+        // them
+        operands: Vec<(String, String, CType)>,
+        clobbers: Vec<String>,
     },
-    Asm(String),
     // This is handled by the ptr declaration for now
     Adr {
         id: String,
+        global: bool,
+    },
+    // A global or `static` local, the declaration is its only child
+    Global {
+        strategy: GlobalStrategy,
+        // Declared with `extern` and never defined
+        is_extern: bool,
+    },
+    GlobalId {
+        id: String,
+        strategy: GlobalStrategy,
+    },
+    // The expression is the only child
+    GlobalAssignment {
+        id: String,
+        op: AssignmentOpType,
+        strategy: GlobalStrategy,
     },
     DeRef {
         id: String,
         rc: bool,
         count: u8,
+        ref_types: Vec<ReferenceType>,
+        offset: Option<Box<AnnotatedNode>>,
+        slice: bool,
+    },
+    IndexArray {
+        id: String,
+        indices: Vec<AnnotatedNode>,
+        field_id: Option<String>,
+        global: Option<GlobalStrategy>,
+    },
+    IndexArrayAssignment {
+        id: String,
+        op: AssignmentOpType,
+        indices: Vec<AnnotatedNode>,
+        field_id: Option<String>,
+        expr: Box<AnnotatedNode>,
+        global: Option<GlobalStrategy>,
+    },
+    AdrIndex {
+        id: String,
+        indices: Vec<AnnotatedNode>,
+    },
+    StrLiteral {
+        value: String,
+        nul_terminated: bool,
+    },
+    // A C string passed to a `<string.h>` function, as the bytes before its NUL terminator
+    StrBytes {
+        id: String,
+        nul_terminated: bool,
+    },
+    // `printf`-family calls, with the arguments being formatted as children
+    Print {
+        target: PrintTarget,
+        format: String,
+        args: Vec<FormatArg>,
+    },
+    // `t` is the type of the items of the first argument
+    StringFunctionCall {
+        name: String,
+        t: CType,
+    },
+    ArrayInitializer,
+    // `ptr + offset` as the rvalue of a ptr declaration
+    SliceFrom {
+        id: String,
+        offset: Box<AnnotatedNode>,
+    },
+    // `ptr += offset` for pointers into arrays
+    SliceAdvance {
+        id: String,
+        offset: Box<AnnotatedNode>,
+        is_mut: bool,
+    },
+    // Arrays passed as function arguments
+    SliceBorrow {
+        id: String,
+        is_mut: bool,
+    },
+    SizeOf {
+        t: CType,
+        ptr_count: usize,
+    },
+    // `malloc` and `calloc`, only valid as the rvalue of a ptr declaration
+    HeapAlloc {
+        t: CType,
+        // None for a single value
+        count: Option<Box<AnnotatedNode>>,
     },
     ArrayDeclaration {
         id: String,
         t: CType,
-        size: usize,
+        dimensions: Vec<usize>,
         is_used: bool,
         is_mut: bool,
         items: Vec<AnnotatedNode>,
@@ -104,6 +248,9 @@ pub enum AnnotatedNodeT {
     FunctionDeclaration {
         id: String,
         t: CType,
+        lifetimes: usize, // Of the returned struct
+        // The recursive structs whose nodes are allocated in the function's own arena
+        arenas: Vec<String>,
     },
     Assert,
     Return {
@@ -113,21 +260,172 @@ pub enum AnnotatedNodeT {
     StructDefinition {
         struct_id: String,
         field_definitions: Vec<FieldDefinition>,
+        // Per field, the lifetimes of its borrowed levels (outermost first), then the ones of the
+        // struct it holds
+        field_lifetimes: Vec<Vec<String>>,
         has_ref: bool,
+        heap_allocated: bool,
+        // Declared without an initializer, and zeroed with `Default`
+        zero_initialized: bool,
+        is_union: bool,
+    },
+    // A union discriminated by the tag of the struct it's in, as a Rust enum
+    TaggedUnionDefinition {
+        union_id: String,
+        variants: Vec<(String, CType)>, // enumerator, member type
+        tag_field: String,
+        enum_id: String,
+        rust_enum: bool,
     },
     StructDeclaration {
         var_id: String,
         struct_id: String,
         is_mut: bool,
+        rc: bool,
         fields: Vec<(FieldDefinition, AnnotatedNode)>,
         is_used: bool,
         init_value_unused: bool,
+        // None if initialized
+        zero: Option<ZeroValue>,
     },
     StructFieldAssignment {
         var_id: String,
         field_id: String,
         op: AssignmentOpType,
         expr: Box<AnnotatedNode>,
+        access: FieldAccess,
+    },
+    StructFieldId {
+        var_id: String,
+        field_id: String,
+        access: FieldAccess,
+    },
+    // `p->field`, the reference type of `p` decides how the struct is reached
+    PtrFieldId {
+        ptr_id: String,
+        field_id: String,
+        ref_type: Option<ReferenceType>,
+    },
+    PtrFieldAssignment {
+        ptr_id: String,
+        field_id: String,
+        op: AssignmentOpType,
+        expr: Box<AnnotatedNode>,
+        ref_type: Option<ReferenceType>,
+    },
+    // A struct pointing to itself, whose pointer fields are links to other nodes
+    RecursiveStructDefinition {
+        struct_id: String,
+        field_definitions: Vec<FieldDefinition>,
+        strategy: RecursiveStrategy,
+    },
+    NodePtrDeclaration {
+        ptr: NodePtr,
+        is_mut: bool,
+        is_used: bool,
+        value: NodeValue,
+    },
+    NodePtrAssignment {
+        ptr: NodePtr,
+        value: NodeValue,
+    },
+    // `p->field` for a field that isn't a link
+    NodeFieldId {
+        ptr: NodePtr,
+        field_id: String,
+    },
+    NodeFieldAssignment {
+        ptr: NodePtr,
+        field_id: String,
+        op: AssignmentOpType,
+        expr: Box<AnnotatedNode>,
+    },
+    // `p->next = q`
+    NodeLink {
+        ptr: NodePtr,
+        field_id: String,
+        weak: bool,
+        value: NodeValue,
+    },
+    // `p == q`, where either side may be NULL
+    NodeCmp {
+        left: NodeValue,
+        right: NodeValue,
+        eq: bool,
+    },
+    NodeFree {
+        ptr: NodePtr,
+    },
+    EnumDefinition {
+        enum_id: Option<String>,
+        variants: Vec<(String, i32)>,
+        // Otherwise integer `const`s
+        rust_enum: bool,
+    },
+    EnumConstant {
+        id: String,
+        // Only for Rust enums, which need the path
+        enum_id: Option<String>,
+    },
+}
+
+/// How a struct field is read or written
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldAccess {
+    Field,
+    // Reading a union member needs `unsafe`
+    UnionMember,
+    // A field of a struct shared in an `Rc<RefCell>`
+    Shared,
+    // The tag of a tagged union, which is the variant of its union field
+    Tag {
+        union_field: String,
+    },
+    // A member of a tagged union
+    Variant {
+        union_field: String,
+        union_id: String,
+        variant: String,
+    },
+}
+
+/// A pointer to a recursive struct, and how the node behind it is reached
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodePtr {
+    pub id: String,
+    pub struct_id: String,
+    pub strategy: RecursiveStrategy,
+    pub role: NodeRole,
+}
+
+/// A pointer to a node being assigned or linked
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeValue {
+    Null,
+    Alloc {
+        struct_id: String,
+        strategy: RecursiveStrategy,
+    },
+    Ptr(NodePtr),
+    // `p->next`
+    Link {
+        ptr: NodePtr,
+        field_id: String,
+        weak: bool,
+    },
+}
+
+/// What a struct declared without an initializer starts as
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZeroValue {
+    Default, // Only numeric fields
+    Zeroed,  // Unions, and structs with non-numeric fields
+    // The first variant of a tagged union
+    Variant {
+        union_field: String,
+        union_id: String,
+        variant: String,
+        t: CType,
     },
 }
 
@@ -147,6 +445,146 @@ impl AnnotatedNode {
         });
         *n -= 1;
     }
+
+    /// The raw pointer this node dereferences, if it's translated to a raw dereference
+    pub fn raw_ptr_id(&self) -> Option<&str> {
+        let is_raw = |ref_type: &ReferenceType| ref_type.is_raw();
+        match &self.token {
+            AnnotatedNodeT::DerefAssignment {
+                id,
+                ref_types,
+                slice: false,
+                ..
+            } if !ref_types.contains(&ReferenceType::CellRef) && ref_types.iter().any(is_raw) => {
+                Some(id)
+            }
+            AnnotatedNodeT::DeRef {
+                id,
+                rc,
+                ref_types,
+                slice: false,
+                ..
+            } if ref_types.first() != Some(&ReferenceType::OwnedVec)
+                && !ref_types.contains(&ReferenceType::CellRef) =>
+            {
+                // NOTE Only the references in front of an `Rc` are dereferenced by hand
+                let derefs = match ref_types
+                    .iter()
+                    .position(|ref_type| *ref_type == ReferenceType::RcRefClone)
+                {
+                    Some(i) => &ref_types[..i],
+                    None if *rc => &[],
+                    None => &ref_types[..],
+                };
+                derefs.iter().any(is_raw).then_some(id)
+            }
+            AnnotatedNodeT::PtrFieldId {
+                ptr_id,
+                ref_type: Some(ref_type),
+                ..
+            }
+            | AnnotatedNodeT::PtrFieldAssignment {
+                ptr_id,
+                ref_type: Some(ref_type),
+                ..
+            } if ref_type.is_raw() => Some(ptr_id),
+            _ => None,
+        }
+    }
+
+    /// The nodes right under this one, its children and the expressions held in its fields
+    pub fn nested(&self) -> Vec<&AnnotatedNode> {
+        let AnnotatedNode {
+            token, children, ..
+        } = self;
+        let mut nested: Vec<&AnnotatedNode> = children.iter().collect();
+        match token {
+            AnnotatedNodeT::DerefAssignment {
+                offset: Some(expr), ..
+            }
+            | AnnotatedNodeT::DeRef {
+                offset: Some(expr), ..
+            }
+            | AnnotatedNodeT::PtrDeclaration { adr: expr, .. }
+            | AnnotatedNodeT::SliceFrom { offset: expr, .. }
+            | AnnotatedNodeT::SliceAdvance { offset: expr, .. }
+            | AnnotatedNodeT::HeapAlloc {
+                count: Some(expr), ..
+            }
+            | AnnotatedNodeT::Return { expr }
+            | AnnotatedNodeT::StructFieldAssignment { expr, .. }
+            | AnnotatedNodeT::PtrFieldAssignment { expr, .. }
+            | AnnotatedNodeT::NodeFieldAssignment { expr, .. }
+            | AnnotatedNodeT::Print {
+                target:
+                    PrintTarget::Buffer {
+                        limit: Some(expr), ..
+                    },
+                ..
+            } => nested.push(expr),
+            AnnotatedNodeT::IndexArray { indices, .. }
+            | AnnotatedNodeT::AdrIndex { indices, .. }
+            | AnnotatedNodeT::ArrayDeclaration { items: indices, .. } => {
+                nested.extend(indices.iter())
+            }
+            AnnotatedNodeT::IndexArrayAssignment { indices, expr, .. } => {
+                nested.extend(indices.iter());
+                nested.push(expr);
+            }
+            AnnotatedNodeT::StructDeclaration { fields, .. } => {
+                nested.extend(fields.iter().map(|(_, expr)| expr))
+            }
+            _ => {}
+        }
+        nested
+    }
+
+    /// Same as `nested`, to rewrite them
+    fn nested_mut(&mut self) -> Vec<&mut AnnotatedNode> {
+        let AnnotatedNode {
+            token, children, ..
+        } = self;
+        let mut nested: Vec<&mut AnnotatedNode> = children.iter_mut().collect();
+        match token {
+            AnnotatedNodeT::DerefAssignment {
+                offset: Some(expr), ..
+            }
+            | AnnotatedNodeT::DeRef {
+                offset: Some(expr), ..
+            }
+            | AnnotatedNodeT::PtrDeclaration { adr: expr, .. }
+            | AnnotatedNodeT::SliceFrom { offset: expr, .. }
+            | AnnotatedNodeT::SliceAdvance { offset: expr, .. }
+            | AnnotatedNodeT::HeapAlloc {
+                count: Some(expr), ..
+            }
+            | AnnotatedNodeT::Return { expr }
+            | AnnotatedNodeT::StructFieldAssignment { expr, .. }
+            | AnnotatedNodeT::PtrFieldAssignment { expr, .. }
+            | AnnotatedNodeT::NodeFieldAssignment { expr, .. }
+            | AnnotatedNodeT::Print {
+                target:
+                    PrintTarget::Buffer {
+                        limit: Some(expr), ..
+                    },
+                ..
+            } => nested.push(expr),
+            AnnotatedNodeT::IndexArray { indices, .. }
+            | AnnotatedNodeT::AdrIndex { indices, .. }
+            | AnnotatedNodeT::ArrayDeclaration { items: indices, .. } => {
+                nested.extend(indices.iter_mut())
+            }
+            AnnotatedNodeT::IndexArrayAssignment { indices, expr, .. } => {
+                nested.extend(indices.iter_mut());
+                nested.push(expr);
+            }
+            AnnotatedNodeT::StructDeclaration { fields, .. } => {
+                nested.extend(fields.iter_mut().map(|(_, expr)| expr))
+            }
+            _ => {}
+        }
+        nested
+    }
 }
 
 impl Node {
@@ -154,6 +592,94 @@ impl Node {
         let root = self;
 
         let token = match &root.token {
+            NodeType::PtrDeclaration(id, _, expr) if ctx.is_node_ptr(id) => {
+                let ptr = node_ptr(id, ctx);
+                let value = node_value(expr, &ptr.struct_id, ctx);
+                let var_data = ctx.get_var(id);
+                AnnotatedNodeT::NodePtrDeclaration {
+                    ptr,
+                    is_mut: var_data.is_mut,
+                    is_used: !var_data.usages.is_empty(),
+                    value,
+                }
+            }
+            NodeType::Assignment(_, id) if ctx.is_node_ptr(id) => {
+                let ptr = node_ptr(id, ctx);
+                let value = node_value(&root.children.as_ref().unwrap()[0], &ptr.struct_id, ctx);
+                return AnnotatedNode {
+                    token: AnnotatedNodeT::NodePtrAssignment { ptr, value },
+                    line: root.c_line,
+                    children: vec![],
+                };
+            }
+            NodeType::PtrFieldAssignment {
+                ptr_id,
+                field_id,
+                assignment_op: _,
+                expr,
+            } if is_link(ptr_id, field_id, ctx) => {
+                let ptr = node_ptr(ptr_id, ctx);
+                AnnotatedNodeT::NodeLink {
+                    weak: is_weak(&ptr.strategy, field_id),
+                    value: node_value(expr, &ptr.struct_id, ctx),
+                    ptr,
+                    field_id: field_id.clone(),
+                }
+            }
+            NodeType::PtrFieldAssignment {
+                ptr_id,
+                field_id,
+                assignment_op,
+                expr,
+            } if ctx.is_node_ptr(ptr_id) => AnnotatedNodeT::NodeFieldAssignment {
+                ptr: node_ptr(ptr_id, ctx),
+                field_id: field_id.clone(),
+                op: assignment_op.clone(),
+                expr: Box::new(expr.annotate(ctx)),
+            },
+            NodeType::PtrFieldId { ptr_id, field_id } if ctx.is_node_ptr(ptr_id) => {
+                AnnotatedNodeT::NodeFieldId {
+                    ptr: node_ptr(ptr_id, ctx),
+                    field_id: field_id.clone(),
+                }
+            }
+            NodeType::EqCmp | NodeType::NeqCmp
+                if node_source(&root.children.as_ref().unwrap()[0], ctx).is_some() =>
+            {
+                let children = root.children.as_ref().unwrap();
+                let struct_id = children
+                    .iter()
+                    .find_map(|child| match node_value(child, "", ctx) {
+                        NodeValue::Ptr(ptr) | NodeValue::Link { ptr, .. } => Some(ptr.struct_id),
+                        _ => None,
+                    })
+                    .expect("Checked by the analyzer");
+                return AnnotatedNode {
+                    token: AnnotatedNodeT::NodeCmp {
+                        left: node_value(&children[0], &struct_id, ctx),
+                        right: node_value(&children[1], &struct_id, ctx),
+                        eq: root.token == NodeType::EqCmp,
+                    },
+                    line: root.c_line,
+                    children: vec![],
+                };
+            }
+            NodeType::FunctionCall(name)
+                if name == "free"
+                    && matches!(&root.children.as_ref().unwrap()[0].token,
+                        NodeType::Id(id) if ctx.is_node_ptr(id)) =>
+            {
+                let NodeType::Id(id) = &root.children.as_ref().unwrap()[0].token else {
+                    unreachable!()
+                };
+                return AnnotatedNode {
+                    token: AnnotatedNodeT::NodeFree {
+                        ptr: node_ptr(id, ctx),
+                    },
+                    line: root.c_line,
+                    children: vec![],
+                };
+            }
             NodeType::Declaration(id, t, _) => {
                 let declaration_info = ctx.get_var(id);
                 let is_used = !declaration_info.usages.is_empty();
@@ -165,13 +691,54 @@ impl Node {
                     is_mut: declaration_info.is_mut,
                     t: t.clone(),
                     rc: declaration_info.rc,
+                    cell: declaration_info.cell,
                     is_used,
                     init_value_unused,
+                    lifetimes: ctx.type_lifetimes(t),
+                    clone: declaration_info.clone,
                 }
             }
+            NodeType::FunctionDeclaration(id, t) => AnnotatedNodeT::FunctionDeclaration {
+                id: id.to_string(),
+                t: t.clone(),
+                lifetimes: ctx.type_lifetimes(t),
+                arenas: arenas(root, ctx),
+            },
             NodeType::PtrDeclaration(id, t, adr) => {
                 let ptr_var_info = ctx.get_var(id);
-                let annotated_adr = Box::new(adr.annotate(ctx));
+                let annotated_adr = Box::new(match heap_allocation_size(adr) {
+                    Some(size) => {
+                        let count = match size {
+                            AllocationSize::Single => None,
+                            AllocationSize::Array(count) => Some(Box::new(count.annotate(ctx))),
+                        };
+                        AnnotatedNode {
+                            token: AnnotatedNodeT::HeapAlloc { t: t.clone(), count },
+                            line: root.c_line,
+                            children: vec![],
+                        }
+                    }
+                    None => match (&adr.token, split_ptr_offset(adr, ctx)) {
+                        (NodeType::StrLiteral(value), _) => AnnotatedNode {
+                            token: AnnotatedNodeT::StrLiteral {
+                                value: value.clone(),
+                                nul_terminated: ctx.string_literal_of(id, root.line)
+                                    == Some(StringMode::NulTerminated),
+                            },
+                            line: root.c_line,
+                            children: vec![],
+                        },
+                        (_, Some((ptr_id, offset))) => AnnotatedNode {
+                            token: AnnotatedNodeT::SliceFrom {
+                                id: ptr_id,
+                                offset: Box::new(offset.annotate(ctx)),
+                            },
+                            line: root.c_line,
+                            children: vec![],
+                        },
+                        (_, None) => adr.annotate(ctx),
+                    },
+                });
 
                 let points_to = ptr_var_info.points_to.clone();
 
@@ -187,9 +754,24 @@ impl Node {
                 let is_used = !ptr_var_info.usages.is_empty();
                 let init_value_unused = ptr_var_info.init_value_unused;
 
+                // NOTE An alias of a heap allocation borrows it through the `Box` or `Vec` owning it
+                let reborrow = reference
+                    .borrow()
+                    .get_reborrowed_from()
+                    .or_else(|| {
+                        ctx.heap_owner(reference.borrow().get_reference_to())
+                            .filter(|owner| owner != id)
+                    })
+                    .map(str::to_string);
+
+                // NOTE A heap allocation is declared through its owner, which `&mut` aliases
+                // borrow it from
+                let owns_mut = reference.borrow().get_reference_type().is_owned()
+                    && ctx.get_var(reference.borrow().get_reference_to()).is_mut;
+
                 AnnotatedNodeT::PtrDeclaration {
                     id: id.to_string(),
-                    is_mut: ptr_var_info.is_mut,
+                    is_mut: ptr_var_info.is_mut || owns_mut,
                     points_to,
                     t: t.clone(),
                     ref_type,
@@ -197,6 +779,8 @@ impl Node {
                     rc: ptr_var_info.rc,
                     is_used,
                     init_value_unused,
+                    slice: ctx.is_array_ptr(id, root.line),
+                    reborrow,
                 }
             }
             NodeType::Adr(id) => {
@@ -204,7 +788,10 @@ impl Node {
                 // `&mut &mut &t` illegal
                 // Unsafe assumption: Adresses are always immutable unless explicitely annotated otherwise by the ptr declaration
                 // `list.append(&mut other_list)` isn't something we're going to worry about for now
-                AnnotatedNodeT::Adr { id: id.to_string() }
+                AnnotatedNodeT::Adr {
+                    id: id.to_string(),
+                    global: ctx.is_global(id),
+                }
             }
             // It seems like assignments and deref assignments need to handle referencing themselves
             // Unless we want Adr nodes to know what kind of reference they are (which actually is
@@ -212,7 +799,7 @@ impl Node {
             NodeType::DerefAssignment(op, adr) => {
                 let count = count_derefs(adr); // TODO Maybe fix function
 
-                let derefed_id = find_ids(adr)[0].clone();
+                let derefed_id = partition_ptr_ids(ctx, find_ids(adr)).0[0].clone();
                 let ptr_data = ctx.get_var(&derefed_id);
 
                 let reference = ptr_data
@@ -234,12 +821,15 @@ impl Node {
                     id: derefed_id.clone(),
                     rc,
                     ref_types,
+                    offset: split_ptr_offset(adr, ctx)
+                        .map(|(_, offset)| Box::new(offset.annotate(ctx))),
+                    slice: ctx.is_array_ptr(&derefed_id, root.line),
                 }
             }
             NodeType::DeRef(expr) => {
                 let count = count_derefs(expr) + 1;
 
-                let ids = find_ids(expr);
+                let ids = partition_ptr_ids(ctx, find_ids(expr)).0;
                 let derefed_id = ids[0].clone();
 
                 let var_data = ctx.get_var(&derefed_id);
@@ -250,18 +840,34 @@ impl Node {
                 let b = reference.borrow();
                 let sub_id = b.get_reference_to();
 
+                let mut ref_types: Vec<ReferenceType> = b
+                    .construct_reference_chain(ctx, root.line)
+                    .iter()
+                    .map(Reference::get_reference_type)
+                    .collect();
+                ref_types.truncate(count as usize);
+
                 let rc = ctx.get_var(sub_id).rc;
                 AnnotatedNodeT::DeRef {
                     id: derefed_id.clone(),
                     rc,
                     count,
+                    ref_types,
+                    offset: split_ptr_offset(expr, ctx)
+                        .map(|(_, offset)| Box::new(offset.annotate(ctx))),
+                    slice: ctx.is_array_ptr(&derefed_id, root.line),
                 }
             }
+            NodeType::Id(id) if ctx.is_global(id) => AnnotatedNodeT::GlobalId {
+                id: id.clone(),
+                strategy: ctx.global_strategy(id).unwrap(),
+            },
             NodeType::Id(id) => {
                 let rc = ctx.get_var(id).rc;
                 AnnotatedNodeT::Id {
                     id: id.to_string(),
                     rc,
+                    cell: ctx.get_var(id).cell,
                 }
             }
             NodeType::Program => {
@@ -278,7 +884,18 @@ impl Node {
                         }
                     })
                 });
+                // NOTE Shared nodes are `Rc<RefCell<Node>>` too
+                let shared_nodes: Vec<&RecursiveStrategy> = ctx
+                    .structs
+                    .values()
+                    .filter_map(|struct_data| struct_data.recursive.as_ref())
+                    .filter(|strategy| matches!(strategy, RecursiveStrategy::Shared { .. }))
+                    .collect();
+                rcclone |= !shared_nodes.is_empty();
                 let mut imports: Vec<String> = vec![];
+                if ctx.current_scope().variables.values().any(|data| data.cell) {
+                    imports.push(String::from("use std::cell::Cell;"))
+                }
                 if rc {
                     imports.push(String::from("use std::rc::Rc;"))
                 }
@@ -288,34 +905,371 @@ impl Node {
                 if rcclone {
                     imports.push(String::from("use std::{cell::RefCell, rc::Rc};"))
                 }
+                if shared_nodes.iter().any(|strategy| {
+                    matches!(strategy, RecursiveStrategy::Shared { weak_fields } if !weak_fields.is_empty())
+                }) {
+                    imports.push(String::from("use std::rc::Weak;"))
+                }
 
                 AnnotatedNodeT::Program { imports }
             }
+            NodeType::Assignment(op, id) if ctx.is_array_ptr(id, root.line) => {
+                let expr = &root.children.as_ref().unwrap()[0];
+                let offset = match op {
+                    AssignmentOpType::AddEq => expr.clone(),
+                    AssignmentOpType::Eq => split_ptr_offset(expr, ctx)
+                        .expect("Unsupported: Reassigning a pointer into an array")
+                        .1,
+                    _ => panic!("Unsupported: Moving a pointer backwards through an array"),
+                };
+
+                let reference = ctx.get_var(id).reference_at_line(root.line).unwrap();
+                let is_mut = reference.borrow().get_reference_type() == ReferenceType::MutBorrowed;
+
+                // NOTE The offset is the only child
+                return AnnotatedNode {
+                    token: AnnotatedNodeT::SliceAdvance {
+                        id: id.clone(),
+                        offset: Box::new(offset.annotate(ctx)),
+                        is_mut,
+                    },
+                    line: root.c_line,
+                    children: vec![],
+                };
+            }
+            NodeType::Assignment(op, id) if ctx.is_global(id) => AnnotatedNodeT::GlobalAssignment {
+                id: id.clone(),
+                op: op.clone(),
+                strategy: ctx.global_strategy(id).unwrap(),
+            },
             NodeType::Assignment(op, id) => {
                 let rc = ctx.get_var(id).rc;
                 AnnotatedNodeT::Assignment {
                     id: id.clone(),
                     op: op.clone(),
                     rc,
+                    cell: ctx.get_var(id).cell,
+                }
+            }
+            NodeType::IndexArray {
+                id,
+                indices,
+                field_id,
+            } => AnnotatedNodeT::IndexArray {
+                id: id.clone(),
+                indices: indices.iter().map(|index| index.annotate(ctx)).collect(),
+                field_id: field_id.clone(),
+                global: ctx.global_strategy(id),
+            },
+            NodeType::Global(storage) => {
+                let (NodeType::Declaration(id, _, _) | NodeType::ArrayDeclaration(id, _, _)) =
+                    &root.children.as_ref().unwrap()[0].token
+                else {
+                    unreachable!("Checked by the analyzer")
+                };
+                let is_extern = ctx.get_var(id).storage == Some(StorageClass::Extern);
+                // NOTE The definition is translated instead
+                if *storage == StorageClass::Extern && !is_extern {
+                    return AnnotatedNode {
+                        token: AnnotatedNodeT::Scope(None),
+                        line: root.c_line,
+                        children: vec![],
+                    };
+                }
+
+                AnnotatedNodeT::Global {
+                    strategy: ctx.global_strategy(id).unwrap(),
+                    is_extern,
                 }
             }
+            NodeType::AdrIndex { id, indices } => AnnotatedNodeT::AdrIndex {
+                id: id.clone(),
+                indices: indices.iter().map(|index| index.annotate(ctx)).collect(),
+            },
+            NodeType::ArrayInitializer => AnnotatedNodeT::ArrayInitializer,
+            NodeType::IndexArrayAssignment {
+                id,
+                assignment_op,
+                rside,
+                lside,
+                field_id,
+            } => AnnotatedNodeT::IndexArrayAssignment {
+                id: id.clone(),
+                op: assignment_op.clone(),
+                indices: lside.iter().map(|index| index.annotate(ctx)).collect(),
+                field_id: field_id.clone(),
+                expr: Box::new(cast_assigned(
+                    rside,
+                    &Node::new(
+                        NodeType::IndexArray {
+                            id: id.clone(),
+                            indices: lside.clone(),
+                            field_id: field_id.clone(),
+                        },
+                        None,
+                        root.line,
+                    ),
+                    assignment_op,
+                    ctx,
+                )),
+                global: ctx.global_strategy(id),
+            },
+            NodeType::FunctionCall(name) if is_print_function(name) => {
+                let args = root.children.as_ref().unwrap();
+                let (target, format_index) = match name.as_str() {
+                    "fprintf" if args[0].token == NodeType::Id("stderr".into()) => {
+                        (PrintTarget::Stderr, 1)
+                    }
+                    "fprintf" => (PrintTarget::Stdout, 1),
+                    "sprintf" | "snprintf" => {
+                        let NodeType::Id(id) = &args[0].token else {
+                            unreachable!("Checked by the analyzer")
+                        };
+                        let limit = (name == "snprintf").then(|| Box::new(args[1].annotate(ctx)));
+                        let format_index = format_index(name).unwrap();
+                        (
+                            PrintTarget::Buffer {
+                                id: id.clone(),
+                                limit,
+                            },
+                            format_index,
+                        )
+                    }
+                    _ => (PrintTarget::Stdout, format_index(name).unwrap_or(0)),
+                };
+
+                // NOTE `puts(s)` is `printf("%s\n", s)`
+                let (format, format_args) = match &args[format_index].token {
+                    _ if name == "puts" => ("{}\\n".to_string(), vec![FormatArg::Str]),
+                    NodeType::StrLiteral(format) => {
+                        let arg_types: Vec<FormatType> = args[format_index + 1..]
+                            .iter()
+                            .map(|arg| format_type(arg, ctx))
+                            .collect();
+                        translate_format(format, &arg_types).expect("Checked by the checker")
+                    }
+                    _ => unreachable!("Checked by the checker"),
+                };
+
+                let first_arg = if name == "puts" { 0 } else { format_index + 1 };
+                let children = args[first_arg..]
+                    .iter()
+                    .zip(format_args.iter())
+                    .map(|(arg, format_arg)| match (&arg.token, format_arg) {
+                        (NodeType::Id(id), FormatArg::Str) => AnnotatedNode {
+                            token: AnnotatedNodeT::StrBytes {
+                                id: id.clone(),
+                                nul_terminated: ctx.string_literal_of(id, root.line)
+                                    != Some(StringMode::Bytes),
+                            },
+                            line: root.c_line,
+                            children: vec![],
+                        },
+                        _ => arg.annotate(ctx),
+                    })
+                    .collect();
+
+                return AnnotatedNode {
+                    token: AnnotatedNodeT::Print {
+                        target,
+                        format,
+                        args: format_args,
+                    },
+                    line: root.c_line,
+                    children,
+                };
+            }
+            NodeType::FunctionCall(name) if is_string_function(name) => {
+                let args = root.children.as_ref().unwrap();
+                let t = match &args[0].token {
+                    NodeType::Id(id) => match &ctx.get_var(id).var_type {
+                        CType::Array(t) => *t.clone(),
+                        t => t.clone(),
+                    },
+                    _ => CType::Char,
+                };
+
+                let children = args
+                    .iter()
+                    .enumerate()
+                    .map(|(i, arg)| match &arg.token {
+                        // NOTE The destination and `memcpy`'s source are used as slices directly
+                        NodeType::Id(id)
+                            if (i == 0 && writes_first_arg(name))
+                                || (i == 1 && name == "memcpy") =>
+                        {
+                            AnnotatedNode {
+                                token: AnnotatedNodeT::Id {
+                                    id: id.clone(),
+                                    rc: false,
+                                    cell: false,
+                                },
+                                line: root.c_line,
+                                children: vec![],
+                            }
+                        }
+                        NodeType::Id(id) if i < 2 => AnnotatedNode {
+                            token: AnnotatedNodeT::StrBytes {
+                                id: id.clone(),
+                                nul_terminated: ctx.string_literal_of(id, root.line)
+                                    != Some(StringMode::Bytes),
+                            },
+                            line: root.c_line,
+                            children: vec![],
+                        },
+                        _ => arg.annotate(ctx),
+                    })
+                    .collect();
+
+                return AnnotatedNode {
+                    token: AnnotatedNodeT::StringFunctionCall {
+                        name: name.clone(),
+                        t,
+                    },
+                    line: root.c_line,
+                    children,
+                };
+            }
+            NodeType::FunctionCall(name) => {
+                let children = root
+                    .children
+                    .as_ref()
+                    .unwrap()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, arg)| match &arg.token {
+                        NodeType::Id(id)
+                            if ctx.get_var(id).is_array() && !ctx.is_function_arg(id) =>
+                        {
+                            AnnotatedNode {
+                                token: AnnotatedNodeT::SliceBorrow {
+                                    id: id.clone(),
+                                    is_mut: ctx.arg_is_mut(name, i),
+                                },
+                                line: root.c_line,
+                                children: vec![],
+                            }
+                        }
+                        _ => match ctx
+                            .function_args(name)
+                            .and_then(|args| args.get(i).cloned())
+                        {
+                            Some(arg_id) if !ctx.get_var(&arg_id).is_ptr() => {
+                                cast(arg.annotate(ctx), arg, &ctx.get_var(&arg_id).var_type, ctx)
+                            }
+                            _ => arg.annotate(ctx),
+                        },
+                    })
+                    .collect();
+
+                return AnnotatedNode {
+                    token: AnnotatedNodeT::FunctionCall(name.clone()),
+                    line: root.c_line,
+                    children,
+                };
+            }
             NodeType::StructDefinition {
                 struct_id,
                 field_definitions: _, // Field Definitions gathered by the parser
+                is_union: _,
             } => {
+                let struct_data = ctx.get_struct(struct_id);
+                if let Some(strategy) = &struct_data.recursive {
+                    return AnnotatedNode {
+                        token: AnnotatedNodeT::RecursiveStructDefinition {
+                            struct_id: struct_id.clone(),
+                            field_definitions: struct_data.field_definitions.clone(),
+                            strategy: strategy.clone(),
+                        },
+                        line: root.c_line,
+                        children: vec![],
+                    };
+                }
+                if let Some(tagged) = ctx
+                    .structs
+                    .values()
+                    .filter_map(|struct_data| struct_data.tagged.as_ref())
+                    .find(|tagged| tagged.union_id == *struct_id)
+                {
+                    return AnnotatedNode {
+                        token: AnnotatedNodeT::TaggedUnionDefinition {
+                            union_id: struct_id.clone(),
+                            variants: ctx.enums[&tagged.enum_id]
+                                .variants
+                                .iter()
+                                .zip(struct_data.field_definitions.iter())
+                                .map(|((variant, _), member)| {
+                                    (variant.clone(), member.c_type.clone())
+                                })
+                                .collect(),
+                            tag_field: tagged.tag_field.clone(),
+                            enum_id: tagged.enum_id.clone(),
+                            rust_enum: ctx.is_rust_enum(&tagged.enum_id),
+                        },
+                        line: root.c_line,
+                        children: vec![],
+                    };
+                }
+
                 // Field definitions gathered by the analyzer (smart ptr type chain)
-                let analyzed_field_definitions =
-                    ctx.get_struct(struct_id).field_definitions.clone();
+                // NOTE The tag of a tagged union is its union field's variant
+                let analyzed_field_definitions: Vec<FieldDefinition> = struct_data
+                    .field_definitions
+                    .iter()
+                    .filter(|field| {
+                        struct_data
+                            .tagged
+                            .as_ref()
+                            .is_none_or(|tagged| field.id != tagged.tag_field)
+                    })
+                    .cloned()
+                    .collect();
                 let has_ref = analyzed_field_definitions
                     .iter()
                     .any(|field| !field.ptr_type.is_empty());
+                let mut lifetimes = (0..).map(lifetime_name);
+                let field_lifetimes = analyzed_field_definitions
+                    .iter()
+                    .map(|field| {
+                        let borrows = field
+                            .ptr_type
+                            .iter()
+                            .filter(|ptr_type| ptr_type.is_borrow())
+                            .count();
+                        let held = match &field.c_type {
+                            CType::Struct(field_struct_id) if field_struct_id != struct_id => {
+                                ctx.struct_lifetimes(field_struct_id)
+                            }
+                            _ => 0,
+                        };
+                        lifetimes.by_ref().take(borrows + held).collect()
+                    })
+                    .collect();
+                let heap_allocated = ctx.current_scope().variables.values().any(|var_data| {
+                    var_data.heap_allocation.is_some()
+                        && var_data.var_type == CType::Struct(struct_id.clone())
+                });
 
                 AnnotatedNodeT::StructDefinition {
                     struct_id: struct_id.clone(),
                     field_definitions: analyzed_field_definitions,
+                    field_lifetimes,
                     has_ref,
+                    heap_allocated,
+                    zero_initialized: struct_data.zero_initialized
+                        && zero_value(struct_id, ctx) == ZeroValue::Default,
+                    is_union: struct_data.is_union,
                 }
             }
+            NodeType::EnumDefinition { enum_id, variants } => AnnotatedNodeT::EnumDefinition {
+                enum_id: enum_id.clone(),
+                variants: variants.clone(),
+                rust_enum: enum_id.as_ref().is_some_and(|id| ctx.is_rust_enum(id)),
+            },
+            NodeType::EnumConstant { enum_id, id } => AnnotatedNodeT::EnumConstant {
+                id: id.clone(),
+                enum_id: enum_id.clone().filter(|enum_id| ctx.is_rust_enum(enum_id)),
+            },
             NodeType::StructDeclaration {
                 var_id,
                 struct_id,
@@ -330,7 +1284,11 @@ impl Node {
                     .clone()
                     .into_iter()
                     .enumerate()
-                    .map(|(i, node)| (field_definitions[i].clone(), node.annotate(ctx)))
+                    .map(|(i, node)| {
+                        let field = field_definitions[i].clone();
+                        let value = cast(node.annotate(ctx), &node, &field.c_type, ctx);
+                        (field, value)
+                    })
                     .collect();
 
                 let is_used = !var_data.usages.is_empty();
@@ -340,17 +1298,25 @@ impl Node {
                     var_id: var_id.clone(),
                     struct_id: struct_id.clone(),
                     is_mut: var_data.is_mut,
+                    rc: var_data.rc,
                     fields,
                     is_used,
                     init_value_unused,
+                    zero: exprs.is_empty().then(|| zero_value(struct_id, ctx)),
                 }
             }
-            NodeType::ArrayDeclaration(id, c_type, count) => {
+            NodeType::ArrayDeclaration(id, c_type, dimensions) => {
+                if let CType::Struct(struct_id) = c_type {
+                    assert!(
+                        zero_value(struct_id, ctx) == ZeroValue::Default,
+                        "Unsupported: Arrays of structs holding unions"
+                    );
+                }
                 let var = ctx.get_var(id);
-                let is_mut = var.is_mut;
+                let is_mut = var.is_mut || ctx.passed_mutably(id);
                 let is_used = !var.usages.is_empty();
                 let items: Vec<AnnotatedNode> = match root.children.as_ref() {
-                    Some(vec) => vec.iter().map(|node| node.annotate(ctx)).collect(),
+                    Some(vec) => annotate_items(vec, c_type, ctx),
                     None => Vec::new(),
                 };
                 let init_value_unused = var.init_value_unused;
@@ -358,7 +1324,7 @@ impl Node {
                 AnnotatedNodeT::ArrayDeclaration {
                     id: id.clone(),
                     t: c_type.clone(),
-                    size: *count,
+                    dimensions: dimensions.clone(),
                     is_used,
                     is_mut,
                     items,
@@ -374,22 +1340,630 @@ impl Node {
                 var_id: var_id.clone(),
                 field_id: field_id.clone(),
                 op: assignment_op.clone(),
-                expr: Box::new(expr.annotate(ctx)),
+                expr: Box::new(cast_assigned(
+                    expr,
+                    &Node::new(
+                        NodeType::StructFieldId {
+                            var_id: var_id.clone(),
+                            field_id: field_id.clone(),
+                        },
+                        None,
+                        root.line,
+                    ),
+                    assignment_op,
+                    ctx,
+                )),
+                access: field_access(var_id, field_id, ctx),
             },
-            NodeType::Return { expr } => AnnotatedNodeT::Return {
-                expr: Box::new(expr.annotate(ctx)),
+            NodeType::StructFieldId { var_id, field_id } => AnnotatedNodeT::StructFieldId {
+                var_id: var_id.clone(),
+                field_id: field_id.clone(),
+                access: field_access(var_id, field_id, ctx),
+            },
+            NodeType::PtrFieldId { ptr_id, field_id } => AnnotatedNodeT::PtrFieldId {
+                ptr_id: ptr_id.clone(),
+                field_id: field_id.clone(),
+                ref_type: ptr_ref_type(ptr_id, root.line, ctx),
+            },
+            NodeType::PtrFieldAssignment {
+                ptr_id,
+                field_id,
+                assignment_op,
+                expr,
+            } => AnnotatedNodeT::PtrFieldAssignment {
+                ptr_id: ptr_id.clone(),
+                field_id: field_id.clone(),
+                op: assignment_op.clone(),
+                expr: Box::new(cast_assigned(
+                    expr,
+                    &Node::new(
+                        NodeType::PtrFieldId {
+                            ptr_id: ptr_id.clone(),
+                            field_id: field_id.clone(),
+                        },
+                        None,
+                        root.line,
+                    ),
+                    assignment_op,
+                    ctx,
+                )),
+                ref_type: ptr_ref_type(ptr_id, root.line, ctx),
+            },
+            NodeType::Asm {
+                template,
+                outputs,
+                inputs,
+                clobbers,
+            } => AnnotatedNodeT::Asm {
+                template: template.clone(),
+                operands: outputs
+                    .iter()
+                    .chain(inputs)
+                    .map(|(constraint, id)| {
+                        let t = ctx.get_var(id).var_type.clone();
+                        (constraint.clone(), id.clone(), t)
+                    })
+                    .collect(),
+                clobbers: clobbers.clone(),
+            },
+            NodeType::Return { expr, t } => AnnotatedNodeT::Return {
+                expr: Box::new(cast(expr.annotate(ctx), expr, t, ctx)),
             },
             node => node.to_annotated_node(),
         };
         let children = root.children.as_ref();
         let annotated_node_children = match children {
+            Some(children) if matches!(token, AnnotatedNodeT::Scope(_)) => group_unsafe(
+                children,
+                children.iter().map(|node| node.annotate(ctx)).collect(),
+                ctx,
+            ),
             Some(children) => children.iter().map(|node| node.annotate(ctx)).collect(),
             None => Vec::new(),
         };
+        let mut token = token;
+        let annotated_node_children = cast_children(root, &mut token, annotated_node_children, ctx);
 
         AnnotatedNode {
             token,
+            line: root.c_line,
             children: annotated_node_children,
         }
     }
 }
+
+/// Casts an expression to a number type, unless it already is one or is a literal rust infers
+fn cast(annotated: AnnotatedNode, expr: &Node, t: &CType, ctx: &AnalysisContext) -> AnnotatedNode {
+    match number_type(expr, ctx) {
+        Some(expr_type) if expr_type != *t && number_type_of(t) && !adapts(expr, t) => {
+            // NOTE A `char` is a `u8`, which only holds its bits, and C's `char` is signed
+            let annotated = match expr_type {
+                CType::Char => AnnotatedNode {
+                    token: AnnotatedNodeT::SignedChar,
+                    line: annotated.line,
+                    children: vec![annotated],
+                },
+                _ => annotated,
+            };
+            AnnotatedNode {
+                token: AnnotatedNodeT::Cast(t.clone()),
+                line: annotated.line,
+                children: vec![annotated],
+            }
+        }
+        _ => annotated,
+    }
+}
+
+fn number_type_of(t: &CType) -> bool {
+    matches!(t, CType::Int | CType::Char | CType::Float | CType::Double)
+}
+
+/// Annotates the value assigned to `target`, cast to its type
+///
+/// # Important
+/// `n += f` for an integer `n` and a float `f` is `n = (int)(n + f)` in C, which is only
+/// rewritten for plain variables
+fn cast_assigned(
+    expr: &Node,
+    target: &Node,
+    op: &AssignmentOpType,
+    ctx: &AnalysisContext,
+) -> AnnotatedNode {
+    let annotated = expr.annotate(ctx);
+    let Some(t) = number_type(target, ctx) else {
+        return annotated;
+    };
+    if *op != AssignmentOpType::Eq
+        && !t.is_floating()
+        && number_type(expr, ctx).is_some_and(|expr_type| expr_type.is_floating())
+    {
+        panic!("Unsupported: Adding a float into an integer through a pointer, array or field")
+    }
+
+    cast(annotated, expr, &t, ctx)
+}
+
+/// Annotates the items of an initializer list, cast to the type of the array
+fn annotate_items(items: &[Node], t: &CType, ctx: &AnalysisContext) -> Vec<AnnotatedNode> {
+    items
+        .iter()
+        .map(|item| match &item.token {
+            NodeType::ArrayInitializer => AnnotatedNode {
+                token: AnnotatedNodeT::ArrayInitializer,
+                line: item.c_line,
+                children: annotate_items(item.children.as_deref().unwrap_or_default(), t, ctx),
+            },
+            _ => cast(item.annotate(ctx), item, t, ctx),
+        })
+        .collect()
+}
+
+/// Casts the operands of arithmetic and comparisons to the same type, and what's assigned to the
+/// type of the variable, like C does implicitly
+fn cast_children(
+    root: &Node,
+    token: &mut AnnotatedNodeT,
+    mut children: Vec<AnnotatedNode>,
+    ctx: &AnalysisContext,
+) -> Vec<AnnotatedNode> {
+    let Some(c_children) = root.children.as_deref() else {
+        return children;
+    };
+
+    match (&root.token, &mut *token) {
+        (
+            NodeType::Add
+            | NodeType::Sub
+            | NodeType::Mul
+            | NodeType::Div
+            | NodeType::EqCmp
+            | NodeType::NeqCmp,
+            _,
+        ) => {
+            let t = match root.token {
+                NodeType::EqCmp | NodeType::NeqCmp => {
+                    common_type(&c_children[0], &c_children[1], ctx)
+                }
+                _ => arithmetic_type(&c_children[0], &c_children[1], ctx),
+            };
+            let Some(t) = t else {
+                return children;
+            };
+            children
+                .into_iter()
+                .zip(c_children)
+                .map(|(child, c_child)| cast(child, c_child, &t, ctx))
+                .collect()
+        }
+        (NodeType::Declaration(_, t, _), AnnotatedNodeT::Declaration { .. }) => {
+            let value = children.remove(0);
+            vec![cast(value, &c_children[0], t, ctx)]
+        }
+        (NodeType::DerefAssignment(op, adr), AnnotatedNodeT::DerefAssignment { .. }) => {
+            children.remove(0);
+            let target = Node::new(NodeType::DeRef(adr.clone()), None, root.line);
+            vec![cast_assigned(&c_children[0], &target, op, ctx)]
+        }
+        (
+            NodeType::Assignment(_, id),
+            AnnotatedNodeT::Assignment { op, .. } | AnnotatedNodeT::GlobalAssignment { op, .. },
+        ) => {
+            let target = Node::new(NodeType::Id(id.clone()), None, root.line);
+            let (Some(t), Some(expr_type)) =
+                (number_type(&target, ctx), number_type(&c_children[0], ctx))
+            else {
+                return children;
+            };
+            let value = children.remove(0);
+            let operator = match op {
+                AssignmentOpType::AddEq => AnnotatedNodeT::Add,
+                AssignmentOpType::SubEq => AnnotatedNodeT::Sub,
+                AssignmentOpType::MulEq => AnnotatedNodeT::Mul,
+                AssignmentOpType::DivEq => AnnotatedNodeT::Div,
+                _ => return vec![cast(value, &c_children[0], &t, ctx)],
+            };
+            if t.is_floating() || !expr_type.is_floating() {
+                return vec![cast(value, &c_children[0], &t, ctx)];
+            }
+
+            // NOTE `n += f` is `n = (n as f64 + f) as i32`, the sum is truncated rather than `f`
+            let mut read = target.clone();
+            read.c_line = root.c_line;
+            let sum = AnnotatedNode {
+                token: operator,
+                line: root.c_line,
+                children: vec![cast(read.annotate(ctx), &target, &expr_type, ctx), value],
+            };
+            *op = AssignmentOpType::Eq;
+            vec![AnnotatedNode {
+                token: AnnotatedNodeT::Cast(t),
+                line: root.c_line,
+                children: vec![sum],
+            }]
+        }
+        _ => children,
+    }
+}
+
+/// Puts consecutive statements dereferencing raw pointers in one `unsafe` block
+///
+/// # Important
+/// Declarations can't go in a block, their bindings would end with it, and neither can loops and
+/// `if`s, which would take their bodies with them, so only their own raw dereferences are `unsafe`
+/// Prints only read through the pointers, so their arguments are `unsafe` instead of the whole call
+fn group_unsafe(
+    statements: &[Node],
+    annotated: Vec<AnnotatedNode>,
+    ctx: &AnalysisContext,
+) -> Vec<AnnotatedNode> {
+    let mut grouped = vec![];
+    let mut block = vec![];
+    // Each with the line it's dereferenced on
+    let mut raw_ptrs: Vec<(String, usize)> = vec![];
+
+    statements
+        .iter()
+        .zip(annotated)
+        .for_each(|(statement, mut node)| {
+            let in_block = !matches!(
+                node.token,
+                AnnotatedNodeT::Declaration { .. }
+                    | AnnotatedNodeT::PtrDeclaration { .. }
+                    | AnnotatedNodeT::ArrayDeclaration { .. }
+                    | AnnotatedNodeT::StructDeclaration { .. }
+                    | AnnotatedNodeT::NodePtrDeclaration { .. }
+                    | AnnotatedNodeT::Global { .. }
+                    | AnnotatedNodeT::Print { .. }
+            ) && !node
+                .children
+                .iter()
+                .any(|child| matches!(child.token, AnnotatedNodeT::Scope(_)));
+
+            let mut ids: Vec<String> = node.raw_ptr_id().map(String::from).into_iter().collect();
+            raw_derefs(&mut node, !in_block && ids.is_empty(), &mut ids);
+            let ids = ids.into_iter().map(|id| (id, statement.line));
+            match (ids.len(), in_block) {
+                (0, _) => {
+                    end_block(&mut grouped, &mut block, &mut raw_ptrs, ctx);
+                    grouped.push(node);
+                }
+                (_, true) => {
+                    block.push(node);
+                    raw_ptrs.extend(ids);
+                }
+                (_, false) => {
+                    end_block(&mut grouped, &mut block, &mut raw_ptrs, ctx);
+                    grouped.push(AnnotatedNode {
+                        token: AnnotatedNodeT::Unsafe {
+                            safety: safety(&ids.collect::<Vec<_>>(), ctx),
+                            block: false,
+                        },
+                        line: node.line,
+                        children: vec![node],
+                    });
+                }
+            }
+        });
+    end_block(&mut grouped, &mut block, &mut raw_ptrs, ctx);
+
+    grouped
+}
+
+fn end_block(
+    grouped: &mut Vec<AnnotatedNode>,
+    block: &mut Vec<AnnotatedNode>,
+    raw_ptrs: &mut Vec<(String, usize)>,
+    ctx: &AnalysisContext,
+) {
+    if block.is_empty() {
+        return;
+    }
+    grouped.push(AnnotatedNode {
+        token: AnnotatedNodeT::Unsafe {
+            safety: safety(raw_ptrs, ctx),
+            block: true,
+        },
+        line: block[0].line,
+        children: std::mem::take(block),
+    });
+    raw_ptrs.clear();
+}
+
+/// Finds the raw pointers dereferenced under a statement, and puts each of its expressions that
+/// dereferences one in an `UnsafeExpr` if `wrap` is set, so `int z = *x + p->y;` is
+/// `let z: i32 = unsafe { *x + (*p).y };`
+///
+/// # Important
+/// Nested scopes have their own `unsafe` blocks, and aren't searched
+fn raw_derefs(root: &mut AnnotatedNode, wrap: bool, ids: &mut Vec<String>) {
+    root.nested_mut().into_iter().for_each(|node| {
+        if matches!(node.token, AnnotatedNodeT::Scope(_)) {
+            return;
+        }
+        let mut derefs: Vec<String> = node.raw_ptr_id().map(String::from).into_iter().collect();
+        raw_derefs(node, false, &mut derefs);
+        if wrap && !derefs.is_empty() {
+            let expr = std::mem::replace(
+                node,
+                AnnotatedNode {
+                    token: AnnotatedNodeT::UnsafeExpr,
+                    line: node.line,
+                    children: vec![],
+                },
+            );
+            node.children.push(expr);
+        }
+        derefs.into_iter().for_each(|id| {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        });
+    });
+}
+
+/// The `// SAFETY:` comment of an `unsafe` block, from what the analysis found the raw pointers
+/// in it point to
+fn safety(raw_ptrs: &[(String, usize)], ctx: &AnalysisContext) -> String {
+    // NOTE Pointers into the same variable, like `p = &s` and `x = &s.x`, are described together
+    // Each with the pointers into it, and what each points to
+    let mut variables: Vec<(Option<String>, Vec<String>, Vec<String>)> = vec![];
+    raw_ptrs.iter().for_each(|(id, line)| {
+        let var_data = ctx.get_var(id);
+        let target = var_data
+            .reference_at_line(*line)
+            .or_else(|| var_data.current_reference_held())
+            .map(|reference| reference.borrow().get_reference_to().to_string());
+        let place = target.as_deref().map(Place::parse);
+        let variable = place.as_ref().map(|place| place.base.clone());
+        // NOTE A heap allocation has no name in the rust, so it's described by its owner
+        let points = match (&place, target) {
+            (Some(place), Some(target)) => match ctx.heap_owner(&place.base) {
+                Some(owner) if place.projections.is_empty() => {
+                    format!("`{id}` points to the allocation owned by `{owner}`")
+                }
+                Some(owner) => format!(
+                    "`{id}` points to `{}` in the allocation owned by `{owner}`",
+                    &target[place.base.len()..].trim_start_matches('.')
+                ),
+                None => format!("`{id}` points to `{target}`"),
+            },
+            _ => format!("`{id}` points to memory sonder couldn't trace"),
+        };
+        match variables
+            .iter_mut()
+            .find(|(other, _, _)| *other == variable)
+        {
+            Some((_, ids, _)) if ids.contains(id) => {}
+            Some((_, ids, points_to)) => {
+                ids.push(id.clone());
+                points_to.push(points);
+            }
+            None => variables.push((variable, vec![id.clone()], vec![points])),
+        }
+    });
+
+    let facts: Vec<String> = variables
+        .iter()
+        .map(|(variable, ids, points_to)| {
+            let points = points_to.join(" and ");
+            match variable {
+                // WARNING Nothing the analysis tracked vouches for it
+                None => format!("{points}, check that it's still alive"),
+                Some(variable) if ctx.is_global(variable) => {
+                    format!(
+                        "{points}, and `{variable}` is a global that lives as long as the program"
+                    )
+                }
+                Some(variable) => {
+                    let owner = ctx.heap_owner(variable);
+                    let mut aliases: Vec<String> = vec![];
+                    ctx.get_var(variable)
+                        .pointed_to
+                        .iter()
+                        .for_each(|reference| {
+                            let borrower = reference.borrow().get_borrower().to_string();
+                            if !ids.contains(&borrower)
+                                && !aliases.contains(&borrower)
+                                && owner != Some(borrower.as_str())
+                            {
+                                aliases.push(borrower);
+                            }
+                        });
+                    // NOTE The checker rejects uses after the allocation is freed
+                    let alive = match owner {
+                        Some(_) => "it isn't freed yet".to_string(),
+                        None => format!("`{variable}` is still in scope"),
+                    };
+                    match aliases.is_empty() {
+                        true => format!("{points}, and {alive}"),
+                        false => format!(
+                            "{points}, and {alive} and also reached through `{}`",
+                            aliases.join("`, `")
+                        ),
+                    }
+                }
+            }
+        })
+        .collect();
+
+    facts.join("; ")
+}
+
+/// Splits `ptr + offset` (or `*(ptr + offset)`) into the pointer into an array and the offset
+fn split_ptr_offset(root: &Node, ctx: &AnalysisContext) -> Option<(String, Node)> {
+    let mut expr = root;
+    while let NodeType::DeRef(inner) = &expr.token {
+        expr = inner;
+    }
+
+    let children = expr.children.as_ref()?;
+    match &expr.token {
+        NodeType::Add => {
+            let (left_ptrs, _) = partition_ptr_ids(ctx, find_ids(&children[0]));
+            let (right_ptrs, _) = partition_ptr_ids(ctx, find_ids(&children[1]));
+            match (left_ptrs.as_slice(), right_ptrs.as_slice()) {
+                ([ptr_id], []) => Some((ptr_id.clone(), children[1].clone())),
+                ([], [ptr_id]) => Some((ptr_id.clone(), children[0].clone())),
+                _ => panic!("Unsupported: Adding pointers together"),
+            }
+        }
+        NodeType::Sub => panic!("Unsupported: Moving a pointer backwards through an array"),
+        _ => None,
+    }
+}
+
+fn node_ptr(id: &str, ctx: &AnalysisContext) -> NodePtr {
+    let var_data = ctx.get_var(id);
+    let CType::Struct(struct_id) = &var_data.var_type else {
+        unreachable!("Only pointers to recursive structs are nodes")
+    };
+    NodePtr {
+        id: id.to_string(),
+        struct_id: struct_id.clone(),
+        strategy: ctx.recursive_strategy(&var_data.var_type).unwrap().clone(),
+        role: var_data.node_role.unwrap(),
+    }
+}
+
+// NOTE Only an allocation needs to be told which struct it's a node of
+fn node_value(expr: &Node, struct_id: &str, ctx: &AnalysisContext) -> NodeValue {
+    match node_source(expr, ctx).expect("Checked by the analyzer") {
+        NodeSource::Null => NodeValue::Null,
+        NodeSource::Alloc => NodeValue::Alloc {
+            struct_id: struct_id.to_string(),
+            strategy: ctx
+                .recursive_strategy(&CType::Struct(struct_id.to_string()))
+                .cloned()
+                .unwrap_or(RecursiveStrategy::Boxed),
+        },
+        NodeSource::Ptr(id) => NodeValue::Ptr(node_ptr(&id, ctx)),
+        NodeSource::Link { ptr_id, field_id } => {
+            let ptr = node_ptr(&ptr_id, ctx);
+            NodeValue::Link {
+                weak: is_weak(&ptr.strategy, &field_id),
+                ptr,
+                field_id,
+            }
+        }
+    }
+}
+
+fn is_weak(strategy: &RecursiveStrategy, field_id: &str) -> bool {
+    matches!(strategy, RecursiveStrategy::Shared { weak_fields }
+        if weak_fields.iter().any(|weak_field| weak_field == field_id))
+}
+
+// The recursive structs that have nodes allocated within a function, which are put in an arena
+fn arenas(root: &Node, ctx: &AnalysisContext) -> Vec<String> {
+    let mut arenas: Vec<String> = vec![];
+    fn find(root: &Node, ctx: &AnalysisContext, arenas: &mut Vec<String>) {
+        if let NodeType::PtrDeclaration(_, t @ CType::Struct(struct_id), _) = &root.token {
+            if ctx.recursive_strategy(t) == Some(&RecursiveStrategy::Arena)
+                && !arenas.contains(struct_id)
+            {
+                arenas.push(struct_id.clone());
+            }
+        }
+        root.children
+            .iter()
+            .flatten()
+            .for_each(|child| find(child, ctx, arenas));
+    }
+    find(root, ctx, &mut arenas);
+    arenas
+}
+
+/// `'a` to `'z`, then `'a26` onwards
+fn lifetime_name(i: usize) -> String {
+    match i < 26 {
+        true => format!("'{}", (b'a' + i as u8) as char),
+        false => format!("'a{i}"),
+    }
+}
+
+fn ptr_ref_type(ptr_id: &str, line: usize, ctx: &AnalysisContext) -> Option<ReferenceType> {
+    ctx.get_var(ptr_id)
+        .reference_at_line(line)
+        .map(|reference| reference.borrow().get_reference_type())
+}
+
+fn field_access(var_id: &str, field_id: &str, ctx: &AnalysisContext) -> FieldAccess {
+    if ctx.get_var(var_id).rc {
+        return FieldAccess::Shared;
+    }
+
+    let (field, member) = match field_id.split_once('.') {
+        Some((field, member)) => (field, Some(member)),
+        None => (field_id, None),
+    };
+
+    if let Some(tagged) = ctx.tagged_union(var_id) {
+        match member {
+            _ if field == tagged.tag_field => {
+                return FieldAccess::Tag {
+                    union_field: tagged.union_field.clone(),
+                }
+            }
+            Some(member) if field == tagged.union_field => {
+                let i = ctx
+                    .get_struct(&tagged.union_id)
+                    .field_definitions
+                    .iter()
+                    .position(|definition| definition.id == member)
+                    .unwrap();
+                return FieldAccess::Variant {
+                    union_field: tagged.union_field.clone(),
+                    union_id: tagged.union_id.clone(),
+                    variant: ctx.enums[&tagged.enum_id].variants[i].0.clone(),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    // NOTE `u.i`, or `s.u.i` for a union field
+    let is_union = |id: &str| match ctx.current_scope().variables.get(id) {
+        Some(var_data) => match &var_data.var_type {
+            CType::Struct(struct_id) => ctx
+                .structs
+                .get(struct_id)
+                .is_some_and(|struct_data| struct_data.is_union),
+            _ => false,
+        },
+        None => false,
+    };
+    match member.is_some() && is_union(&format!("{var_id}.{field}")) || is_union(var_id) {
+        true => FieldAccess::UnionMember,
+        false => FieldAccess::Field,
+    }
+}
+
+fn zero_value(struct_id: &str, ctx: &AnalysisContext) -> ZeroValue {
+    let struct_data = ctx.get_struct(struct_id);
+    if let Some(tagged) = &struct_data.tagged {
+        return ZeroValue::Variant {
+            union_field: tagged.union_field.clone(),
+            union_id: tagged.union_id.clone(),
+            variant: ctx.enums[&tagged.enum_id].variants[0].0.clone(),
+            t: ctx.get_struct(&tagged.union_id).field_definitions[0]
+                .c_type
+                .clone(),
+        };
+    }
+
+    let numeric = struct_data.field_definitions.iter().all(|field| {
+        field.ptr_type.is_empty()
+            && match &field.c_type {
+                CType::Int | CType::Char | CType::Float | CType::Double => true,
+                CType::Struct(field_struct_id) => {
+                    zero_value(field_struct_id, ctx) == ZeroValue::Default
+                }
+                _ => false,
+            }
+    });
+    match !struct_data.is_union && numeric {
+        true => ZeroValue::Default,
+        false => ZeroValue::Zeroed,
+    }
+}
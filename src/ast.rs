@@ -36,6 +36,7 @@ pub enum NodeType {
     AndCmp,
     OrCmp,
     NumLiteral(usize),
+    FloatLiteral(String),
     Add,
     If,
     For,
@@ -48,28 +49,60 @@ pub enum NodeType {
     DerefAssignment(AssignmentOpType, Box<TokenNode>), // deref_node, deref count
     Declaration(String, CType, usize), // id, type, additional_reserved_size (for arrays)
     PtrDeclaration(String, CType, Box<TokenNode>),
-    Asm(String),
+    // `asm("..." : outputs : inputs : clobbers)`, operands are (constraint, id)
+    // NOTE A basic asm's template is escaped to the extended form (`%` is `%%`)
+    Asm {
+        template: String,
+        outputs: Vec<(String, String)>,
+        inputs: Vec<(String, String)>,
+        clobbers: Vec<String>,
+    },
     Adr(String),
     DeRef(Box<TokenNode>),
-    ArrayDeclaration(String, CType, usize), // id, type, count
+    ArrayDeclaration(String, CType, Vec<usize>), // id, type, dimensions
+    // `{a, {b, c}}`, with designators already resolved to positions
+    ArrayInitializer,
+    StrLiteral(String), // escapes are kept as written
+    Null,
     IndexArray {
         id: String,
-        expr: Box<TokenNode>,
+        indices: Vec<TokenNode>,
+        field_id: Option<String>, // `arr[i].x`
     },
     IndexArrayAssignment {
         id: String,
+        assignment_op: AssignmentOpType,
         rside: Box<TokenNode>,
-        lside: Box<TokenNode>,
+        lside: Vec<TokenNode>, // indices
+        field_id: Option<String>,
+    },
+    // A file-scope, `static` or `extern` variable, the declaration is its only child
+    Global(StorageClass),
+    // `&arr[i]`
+    AdrIndex {
+        id: String,
+        indices: Vec<TokenNode>,
     },
     FunctionDeclaration(String, CType),
+    SizeOf(CType, usize), // type, ptr_count
     Assert,
     Return {
         expr: Box<TokenNode>,
+        t: CType, // The enclosing function's return type
     },
     PutChar,
     StructDefinition {
         struct_id: String,
         field_definitions: Vec<(String, usize, CType)>, // id, ptr_count, underlying type
+        is_union: bool,
+    },
+    EnumDefinition {
+        enum_id: Option<String>, // None for `enum { A, B };`
+        variants: Vec<(String, i32)>,
+    },
+    EnumConstant {
+        enum_id: Option<String>,
+        id: String,
     },
     StructDeclaration {
         var_id: String,
@@ -87,6 +120,25 @@ pub enum NodeType {
         var_id: String,
         field_id: String,
     },
+    // `p->field` and `(*p).field`
+    PtrFieldId {
+        ptr_id: String,
+        field_id: String,
+    },
+    PtrFieldAssignment {
+        ptr_id: String,
+        field_id: String,
+        assignment_op: AssignmentOpType,
+        expr: Box<TokenNode>,
+    },
+}
+
+/// Where a variable that outlives a single call is declared
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StorageClass {
+    File,   // `int n;` outside of a function (also `static int n;`)
+    Static, // `static int n;` inside a function
+    Extern, // `extern int n;`, defined by another declaration
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -164,6 +216,7 @@ impl NodeType {
             Token::MulEq => Ok(NodeType::MulEq),
             Token::Star => Ok(NodeType::Mul), // exception for pointer
             Token::NumLiteral(i) => Ok(NodeType::NumLiteral(*i)),
+            Token::FloatLiteral(f) => Ok(NodeType::FloatLiteral(f.clone())),
             Token::Add => Ok(NodeType::Add),
             Token::For => Ok(NodeType::For),
             Token::While => Ok(NodeType::While),
@@ -197,6 +250,7 @@ impl NodeType {
             NodeType::AndCmp => AnnotatedNodeT::AndCmp,
             NodeType::OrCmp => AnnotatedNodeT::OrCmp,
             NodeType::NumLiteral(size) => AnnotatedNodeT::NumLiteral(*size),
+            NodeType::FloatLiteral(f) => AnnotatedNodeT::FloatLiteral(f.clone()),
             NodeType::Add => AnnotatedNodeT::Add,
             NodeType::If => AnnotatedNodeT::If,
             NodeType::For => AnnotatedNodeT::For,
@@ -205,10 +259,13 @@ impl NodeType {
             NodeType::Break => AnnotatedNodeT::Break,
             NodeType::FunctionCall(s) => AnnotatedNodeT::FunctionCall(s.to_string()),
             NodeType::Scope(s) => AnnotatedNodeT::Scope(s.clone()),
-            NodeType::Asm(asm) => AnnotatedNodeT::Asm(asm.to_string()),
-            NodeType::FunctionDeclaration(id, t) => AnnotatedNodeT::FunctionDeclaration {
-                id: id.to_string(),
+            NodeType::SizeOf(t, ptr_count) => AnnotatedNodeT::SizeOf {
                 t: t.clone(),
+                ptr_count: *ptr_count,
+            },
+            NodeType::StrLiteral(value) => AnnotatedNodeT::StrLiteral {
+                value: value.clone(),
+                nul_terminated: false,
             },
             NodeType::Assert => AnnotatedNodeT::Assert,
             NodeType::PutChar => AnnotatedNodeT::PutChar,
@@ -223,6 +280,9 @@ impl NodeType {
 pub struct TokenNode {
     pub token: NodeType,
     pub line: usize,
+    // NOTE The line in the C source, which stays put when statements are rearranged and `line`
+    // is renumbered
+    pub c_line: usize,
     pub children: Option<Box<[TokenNode]>>,
 }
 
@@ -237,6 +297,7 @@ impl TokenNode {
         TokenNode {
             token,
             line,
+            c_line: line,
             children,
         }
     }
@@ -1,21 +1,44 @@
 use crate::{
     analysis_ctx::AnalysisContext,
+    analyzer::{malloc_count, sub_nodes},
+    ast::{NodeType, TokenNode as Node},
     data_model::{LineNumber, Reference, ReferenceType, Usage, UsageType, VarData},
+    error::{ErrType, RhErr},
+    format::{format_index, format_type, translate_format, FormatType},
+    lexer::CType,
 };
-use std::ops::Range;
+use std::{collections::HashMap, ops::Range};
 
 // TODO: Figure out how to include line numbers in error reports
 pub fn borrow_check(ctx: &mut AnalysisContext) -> Vec<BorrowError> {
     // ctx.print_refs();
-    ctx.current_scope_mut().variables
+    let mut place_overlaps = check_place_overlaps(ctx);
+    // NOTE Aliases of a heap allocation overlap with the uses of its owner, not its whole lifetime
+    let owner_usages: HashMap<String, Vec<Usage>> = ctx
+        .current_scope()
+        .variables
+        .values()
+        .filter_map(|var_data| var_data.heap_allocation.as_ref())
+        .map(|heap_allocation| {
+            let owner = heap_allocation.owner.clone();
+            let usages = ctx.get_var(&owner).usages.clone();
+            (owner, usages)
+        })
+        .collect();
+    let mut errors: Vec<BorrowError> = ctx.current_scope_mut().variables
         .iter_mut()
         .flat_map(|(var_id, var_data)| -> Vec<BorrowError> {
+            // NOTE Raw pointers and shared values aren't borrow checked
             let pointed_to_by: Vec<Reference> = var_data
                 .pointed_to
                 .iter()
                 .map(|reference_block| {
                     reference_block.borrow().clone()
                 })
+                .filter(|reference| {
+                    let reference_type = reference.get_reference_type();
+                    !reference_type.is_raw() && !reference_type.is_shared()
+                })
                 .collect();
 
             let pointed_to_by_mutably  = pointed_to_by
@@ -29,7 +52,7 @@ pub fn borrow_check(ctx: &mut AnalysisContext) -> Vec<BorrowError> {
 
             let mut value_overlaps_with_mut_ptr: Vec<BorrowError> = check_value_overlaps_with_mut_ptr(var_id, var_data, pointed_to_by_mutably.clone());
             let mut value_overlaps_with_const_ptr: Vec<BorrowError> = check_value_overlaps_with_const_ptr(var_id, lvalue_usages, pointed_to_by.iter());
-            let mut mutable_ref_overlaps_with_ptr: Vec<BorrowError> = check_mutable_ref_overlaps_with_ptr(var_id, pointed_to_by_mutably, pointed_to_by.iter());
+            let mut mutable_ref_overlaps_with_ptr: Vec<BorrowError> = check_mutable_ref_overlaps_with_ptr(var_id, pointed_to_by_mutably, pointed_to_by.iter(), &owner_usages);
 
             println!(
                 "value_overlaps_with_mut_ptr {var_id}: {:?}\nvalue_overlaps_with_const_ptr: {:?}\nmutable_ref_overlaps {var_id}: {:?}",
@@ -39,9 +62,201 @@ pub fn borrow_check(ctx: &mut AnalysisContext) -> Vec<BorrowError> {
             value_overlaps_with_mut_ptr.append(&mut mutable_ref_overlaps_with_ptr);
             value_overlaps_with_mut_ptr
         })
+        .collect();
+    errors.append(&mut place_overlaps);
+    errors
+}
+
+/// Borrows of overlapping places (`&s` and `&s.x`) conflict like borrows of the same variable,
+/// while borrows of disjoint fields (`&s.x` and `&s.y`) never do
+fn check_place_overlaps(ctx: &AnalysisContext) -> Vec<BorrowError> {
+    let scope = ctx.current_scope();
+    let variables = &scope.variables;
+    let borrows = |var_data: &VarData| -> Vec<Reference> {
+        var_data
+            .pointed_to
+            .iter()
+            .map(|reference| reference.borrow().clone())
+            .filter(|reference| {
+                let reference_type = reference.get_reference_type();
+                !reference_type.is_raw() && !reference_type.is_owned()
+            })
+            .collect()
+    };
+
+    // NOTE Only places in the same variable can overlap
+    scope
+        .places
+        .values()
+        .flat_map(|ids| ids.iter().map(move |value_id| (value_id, ids)))
+        .flat_map(|(value_id, ids)| {
+            let var_data = &variables[value_id];
+            ids.iter()
+                .filter(|inner_id| {
+                    *inner_id != value_id
+                        && var_data.place.is_prefix_of(&variables[*inner_id].place)
+                })
+                .flat_map(|inner_id| {
+                    let inner_data = &variables[inner_id];
+                    let inner_borrows = borrows(inner_data);
+                    borrows(var_data)
+                        .into_iter()
+                        .flat_map(|outer| {
+                            inner_borrows
+                                .iter()
+                                .filter(|inner| {
+                                    outer.get_reference_type() == ReferenceType::MutBorrowed
+                                        || inner.get_reference_type() == ReferenceType::MutBorrowed
+                                })
+                                .filter(|inner| {
+                                    ptr_range_overlap(outer.get_range(), inner.get_range())
+                                        != OverlapState::NoOverlap
+                                })
+                                .map(|inner| BorrowError::PlaceOverlap {
+                                    outer_ptr_id: outer.get_borrower().to_string(),
+                                    inner_ptr_id: inner.get_borrower().to_string(),
+                                    value_id: value_id.clone(),
+                                })
+                                .collect::<Vec<BorrowError>>()
+                        })
+                        .collect::<Vec<BorrowError>>()
+                })
+                .collect::<Vec<BorrowError>>()
+        })
         .collect()
 }
 
+/// Rejects heap allocations that are used, or freed again, after being freed
+/// Unlike borrow errors, these can't be fixed by changing the pointer type
+pub fn heap_check(ctx: &AnalysisContext) -> Vec<RhErr> {
+    ctx.current_scope()
+        .variables
+        .values()
+        .filter_map(|var_data| Some((var_data, var_data.heap_allocation.as_ref()?)))
+        .flat_map(|(var_data, heap_allocation)| {
+            let mut frees = heap_allocation.frees.clone();
+            frees.sort();
+
+            let Some(first_free) = frees.first().copied() else {
+                return vec![];
+            };
+
+            let double_frees = frees.iter().skip(1).map(|line| RhErr {
+                err: ErrType::DoubleFree(heap_allocation.owner.clone()),
+                line: *line,
+            });
+
+            // NOTE Aliases of the owner are checked too, since they point at the same allocation
+            let uses_after_free = var_data.pointed_to.iter().filter_map(|reference| {
+                let reference = reference.borrow();
+                let ptr_id = reference.get_borrower();
+                ctx.get_var(ptr_id)
+                    .usages
+                    .iter()
+                    .map(Usage::get_line_number)
+                    .filter(|line| reference.within_current_range(*line))
+                    .find(|line| *line > first_free && !frees.contains(line))
+                    .map(|line| RhErr {
+                        err: ErrType::UseAfterFree(ptr_id.to_string()),
+                        line,
+                    })
+            });
+
+            double_frees.chain(uses_after_free).collect()
+        })
+        .collect()
+}
+
+/// Rejects `malloc`s whose size has no `sizeof`, like `malloc(4)`, since the type of what's
+/// allocated can't be known
+pub fn allocation_check(root: &Node) -> Vec<RhErr> {
+    let mut errors: Vec<RhErr> = sub_nodes(root)
+        .into_iter()
+        .flat_map(allocation_check)
+        .collect();
+
+    if let (NodeType::FunctionCall(name), Some([size])) = (&root.token, root.children.as_deref()) {
+        if name == "malloc" && malloc_count(size).is_none() {
+            errors.push(RhErr {
+                err: ErrType::UnsizedAllocation,
+                line: root.line,
+            });
+        }
+    }
+
+    errors
+}
+
+/// Rejects `memset`s that fill something other than bytes with anything but zeros, since C sets
+/// every byte of each item to the value, and `fill` would set each item to it
+pub fn memset_check(root: &Node, ctx: &AnalysisContext) -> Vec<RhErr> {
+    let mut errors: Vec<RhErr> = sub_nodes(root)
+        .into_iter()
+        .flat_map(|node| memset_check(node, ctx))
+        .collect();
+
+    if let (NodeType::FunctionCall(name), Some([dest, value, _])) =
+        (&root.token, root.children.as_deref())
+    {
+        if let (NodeType::Id(id), "memset") = (&dest.token, name.as_str()) {
+            // NOTE Pointers have the type of what they point to
+            let is_bytes = match &ctx.get_var(id).var_type {
+                CType::Array(t) => **t == CType::Char,
+                t => *t == CType::Char,
+            };
+            if !is_bytes && value.token != NodeType::NumLiteral(0) {
+                errors.push(RhErr {
+                    err: ErrType::NonZeroFill(id.clone()),
+                    line: root.line,
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+/// Rejects `printf`-family calls whose format string can't be translated into a rust one
+pub fn format_check(root: &Node, ctx: &AnalysisContext) -> Vec<RhErr> {
+    let mut errors: Vec<RhErr> = match root.children.as_ref() {
+        Some(children) => children.iter().flat_map(|child| format_check(child, ctx)).collect(),
+        None => vec![],
+    };
+
+    let NodeType::FunctionCall(name) = &root.token else {
+        return errors;
+    };
+    let Some(format_index) = format_index(name) else {
+        return errors;
+    };
+
+    let args = root.children.as_deref().unwrap_or_default();
+    let invalid = |reason: String| RhErr {
+        err: ErrType::InvalidFormat(reason),
+        line: root.line,
+    };
+
+    match args.get(format_index).map(|arg| &arg.token) {
+        Some(NodeType::StrLiteral(format)) => {
+            let arg_types: Vec<FormatType> = args[format_index + 1..]
+                .iter()
+                .map(|arg| format_type(arg, ctx))
+                .collect();
+            if let Err(reason) = translate_format(format, &arg_types) {
+                errors.push(invalid(reason));
+            }
+        }
+        _ => errors.push(invalid(format!("the format string of `{name}` isn't a literal"))),
+    }
+
+    if name == "fprintf" && !matches!(&args[0].token, NodeType::Id(id) if id == "stdout" || id == "stderr")
+    {
+        errors.push(invalid("only `stdout` and `stderr` can be printed to".into()));
+    }
+
+    errors
+}
+
 fn check_value_overlaps_with_mut_ptr<'a, T>(
     var_id: &str,
     var_data: &VarData,
@@ -105,6 +320,7 @@ fn check_mutable_ref_overlaps_with_ptr<'a, T, I>(
     var_id: &str,
     pointed_to_by_mutably: T,
     pointed_to_by: I,
+    owner_usages: &HashMap<String, Vec<Usage>>,
 ) -> Vec<BorrowError>
 where
     T: Iterator<Item = &'a Reference>,
@@ -113,6 +329,7 @@ where
     pointed_to_by_mutably.flat_map(|mut_ref| {
         pointed_to_by.clone()
         .filter(|other_ref| mut_ref.get_borrower() != other_ref.get_borrower())
+        .filter(|other_ref| !other_ref.reborrows(mut_ref) && !mut_ref.reborrows(other_ref))
         .filter_map(|other_ref| {
             let overlap_state = ptr_range_overlap(
                 mut_ref.get_range(),
@@ -122,7 +339,19 @@ where
             let other_id = other_ref.get_borrower();
             let mut_id = mut_ref.get_borrower();
 
+            // NOTE A mutable alias of a heap allocation borrows it from its owner, which can't
+            // be used until the alias is done with it
+            let overlap_state = match other_ref.get_reference_type().is_owned() {
+                true => var_ptr_range_overlap(
+                    owner_usages.get(other_id).cloned().unwrap_or_default(),
+                    mut_ref.get_range(),
+                ),
+                false => overlap_state,
+            };
+            let derived = other_ref.derives(mut_ref) || mut_ref.derives(other_ref);
+
             match (other_ref.get_reference_type().clone(), overlap_state) {
+                (_, OverlapState::SameLine) if derived => None,
                 // NOTE In these cases, an Rc<RefCell> solution works, since they overlap and borrows can be
                 // made on different lines and both dropped after one line
                 (ReferenceType::MutBorrowed, OverlapState::Overlap) => {
@@ -151,6 +380,20 @@ where
                 }
                 (ReferenceType::ConstBorrowed, OverlapState::SameLine) => panic!("ConstRef on same line, this is fine\n This actually might be a problem if we have a mutable and immutable reference overlapping on the same line"),
                 (_, OverlapState::NoOverlap) => None,
+                (ReferenceType::OwnedBox | ReferenceType::OwnedVec, OverlapState::Overlap) => {
+                    Some(BorrowError::MutMutOverlap {
+                        first_ptr_id: mut_id.to_string(),
+                        second_ptr_id: other_id.to_string(),
+                        value_id: var_id.to_string(),
+                    })
+                }
+                (ReferenceType::OwnedBox | ReferenceType::OwnedVec, OverlapState::SameLine) => {
+                    Some(BorrowError::MutMutSameLine {
+                        first_ptr_id: mut_id.to_string(),
+                        second_ptr_id: other_id.to_string(),
+                        value_id: var_id.to_string(),
+                    })
+                }
                 (_, _) => panic!("Basic ref should not have smart ptr type"),
             }
         }).collect::<Vec<BorrowError>>()
@@ -216,18 +459,19 @@ pub fn var_ptr_range_overlap(
         .map(|value_line| usage_in_block(value_line, &ptr_range))
         .collect();
 
-    if overlaps.contains(&OverlapState::SameLine) {
-        OverlapState::SameLine
-    } else if overlaps.contains(&OverlapState::Overlap) {
+    // NOTE A use while the reference is held is an overlap even if another one is on its last line
+    if overlaps.contains(&OverlapState::Overlap) {
         OverlapState::Overlap
+    } else if overlaps.contains(&OverlapState::SameLine) {
+        OverlapState::SameLine
     } else {
         OverlapState::NoOverlap
     }
 }
 
-// TODO: Derermine if overlapping value uses mutate or don't mutate
-// If it doesn't mutate, clone the underlying value instead
-#[derive(Debug, Clone)]
+// NOTE Ordered so the solver goes through errors the same way every time, rather than in the
+// order the variables come out of the scope's `HashMap`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum BorrowError {
     MutMutOverlap {
         first_ptr_id: String,
@@ -267,46 +511,86 @@ pub enum BorrowError {
         ptr_id: String,
         value_id: String,
     },
+    // A borrow of `value_id` overlapping a borrow of a field inside of it
+    PlaceOverlap {
+        outer_ptr_id: String,
+        inner_ptr_id: String,
+        value_id: String,
+    },
 }
-impl PartialEq for BorrowError {
-    fn eq(&self, other: &Self) -> bool {
-        std::mem::discriminant(self) == std::mem::discriminant(other)
+
+impl BorrowError {
+    pub fn value_id(&self) -> &str {
+        match self {
+            BorrowError::MutMutOverlap { value_id, .. }
+            | BorrowError::MutConstOverlap { value_id, .. }
+            | BorrowError::MutMutSameLine { value_id, .. }
+            | BorrowError::MutConstSameLine { value_id, .. }
+            | BorrowError::ValueMutOverlap { value_id, .. }
+            | BorrowError::ValueMutSameLine { value_id, .. }
+            | BorrowError::ValueConstOverlap { value_id, .. }
+            | BorrowError::ValueConstSameLine { value_id, .. }
+            | BorrowError::PlaceOverlap { value_id, .. } => value_id,
+        }
+    }
+
+    /// The pointers borrowing the value, one for overlaps with the value itself
+    pub fn ptr_ids(&self) -> Vec<&str> {
+        match self {
+            BorrowError::MutMutOverlap {
+                first_ptr_id,
+                second_ptr_id,
+                ..
+            }
+            | BorrowError::MutMutSameLine {
+                first_ptr_id,
+                second_ptr_id,
+                ..
+            }
+            | BorrowError::MutConstOverlap {
+                mut_ptr_id: first_ptr_id,
+                const_ptr_id: second_ptr_id,
+                ..
+            }
+            | BorrowError::MutConstSameLine {
+                mut_ptr_id: first_ptr_id,
+                const_ptr_id: second_ptr_id,
+                ..
+            }
+            | BorrowError::PlaceOverlap {
+                outer_ptr_id: first_ptr_id,
+                inner_ptr_id: second_ptr_id,
+                ..
+            } => vec![first_ptr_id, second_ptr_id],
+            BorrowError::ValueMutOverlap { ptr_id, .. }
+            | BorrowError::ValueMutSameLine { ptr_id, .. }
+            | BorrowError::ValueConstOverlap { ptr_id, .. }
+            | BorrowError::ValueConstSameLine { ptr_id, .. } => vec![ptr_id],
+        }
     }
-}
-impl Eq for BorrowError {}
 
-impl PartialOrd for BorrowError {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+    /// The kind of error and what it names, `MutMutOverlap of `x` by `p`, `q``
+    pub fn describe(&self) -> String {
+        let ptr_ids: Vec<String> = self.ptr_ids().iter().map(|id| format!("`{id}`")).collect();
+        format!(
+            "{} of `{}` by {}",
+            self.kind(),
+            self.value_id(),
+            ptr_ids.join(", ")
+        )
     }
-}
 
-impl Ord for BorrowError {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match (self, other) {
-            (
-                BorrowError::ValueMutOverlap {
-                    ptr_id: _,
-                    value_id: _,
-                },
-                BorrowError::MutConstOverlap {
-                    mut_ptr_id: _,
-                    const_ptr_id: _,
-                    value_id: _,
-                },
-            ) => std::cmp::Ordering::Greater,
-            (
-                BorrowError::MutConstOverlap {
-                    mut_ptr_id: _,
-                    const_ptr_id: _,
-                    value_id: _,
-                },
-                BorrowError::ValueMutOverlap {
-                    ptr_id: _,
-                    value_id: _,
-                },
-            ) => std::cmp::Ordering::Less,
-            (_, _) => std::cmp::Ordering::Equal,
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BorrowError::MutMutOverlap { .. } => "MutMutOverlap",
+            BorrowError::MutConstOverlap { .. } => "MutConstOverlap",
+            BorrowError::MutMutSameLine { .. } => "MutMutSameLine",
+            BorrowError::MutConstSameLine { .. } => "MutConstSameLine",
+            BorrowError::ValueMutOverlap { .. } => "ValueMutOverlap",
+            BorrowError::ValueMutSameLine { .. } => "ValueMutSameLine",
+            BorrowError::ValueConstOverlap { .. } => "ValueConstOverlap",
+            BorrowError::ValueConstSameLine { .. } => "ValueConstSameLine",
+            BorrowError::PlaceOverlap { .. } => "PlaceOverlap",
         }
     }
 }
@@ -1,6 +1,13 @@
 use crate::{
-    annotater::{AnnotatedNode, AnnotatedNodeT},
-    data_model::{FieldDefinition, ReferenceType},
+    annotater::{
+        AnnotatedNode, AnnotatedNodeT, FieldAccess, NodePtr, NodeValue, PrintTarget, ZeroValue,
+    },
+    ast::AssignmentOpType,
+    data_model::{FieldDefinition, GlobalStrategy, NodeRole, RecursiveStrategy, ReferenceType},
+    format::FormatArg,
+    lexer::CType,
+    parser::c_string_len,
+    source_map,
 };
 
 impl AnnotatedNode {
@@ -17,26 +24,51 @@ impl AnnotatedNode {
                 rc: _,
                 is_used,
                 init_value_unused,
+                slice,
+                reborrow,
             } => {
                 let unused = match is_used {
                     true => "",
                     false => "_",
                 };
-                let rust_t = t.to_rust_type();
                 let rust_adr = adr.convert();
                 let mut_binding = if *is_mut { "mut " } else { "" };
-
-                let ref_type_iter = &mut ref_type.iter().cloned();
-                let rust_ref_type = construct_ptr_type(ref_type_iter, &rust_t);
+                let rust_ref_type = ptr_declaration_type(t, ref_type, *slice);
 
                 let rust_reference = match points_to[0].borrow().get_reference_type() {
-                    ReferenceType::MutBorrowed => format!("&mut {rust_adr}"),
-                    ReferenceType::ConstBorrowed => format!("&{rust_adr}"),
+                    // NOTE Byte string literals are already references
+                    _ if matches!(adr.token, AnnotatedNodeT::StrLiteral { .. }) => rust_adr,
+                    ReferenceType::MutBorrowed => match reborrow {
+                        Some(from_ptr_id) => format!("&mut *{from_ptr_id}"),
+                        None => format!("&mut {rust_adr}"),
+                    },
+                    ReferenceType::ConstBorrowed => match reborrow {
+                        Some(from_ptr_id) => format!("&*{from_ptr_id}"),
+                        None => format!("&{rust_adr}"),
+                    },
                     ReferenceType::RcRefClone => format!("{rust_adr}.clone()"),
-                    ReferenceType::MutPtr => format!("&mut {rust_adr} as {rust_ref_type}"),
-                    ReferenceType::ConstPtr => {
-                        format!("&{rust_adr} as {rust_ref_type}")
+                    ReferenceType::CellRef => format!("&{rust_adr}"),
+                    // NOTE Pointed to globals are a `static mut`, which can't be borrowed
+                    ReferenceType::MutPtr
+                        if matches!(adr.token, AnnotatedNodeT::Adr { global: true, .. }) =>
+                    {
+                        format!("std::ptr::addr_of_mut!({rust_adr})")
+                    }
+                    ReferenceType::ConstPtr
+                        if matches!(adr.token, AnnotatedNodeT::Adr { global: true, .. }) =>
+                    {
+                        format!("std::ptr::addr_of!({rust_adr})")
                     }
+                    ReferenceType::MutPtr => match reborrow {
+                        Some(from_ptr_id) => format!("&mut *{from_ptr_id} as {rust_ref_type}"),
+                        None => format!("&mut {rust_adr} as {rust_ref_type}"),
+                    },
+                    ReferenceType::ConstPtr => match reborrow {
+                        Some(from_ptr_id) => format!("&*{from_ptr_id} as {rust_ref_type}"),
+                        None => format!("&{rust_adr} as {rust_ref_type}"),
+                    },
+                    // NOTE The heap allocation node creates the owner itself
+                    ReferenceType::OwnedBox | ReferenceType::OwnedVec => rust_adr,
                 };
 
                 let l_side = if *init_value_unused {
@@ -58,44 +90,64 @@ impl AnnotatedNode {
                 id,
                 rc: _, // TODO Create a clearer distinction between rc variables and rc pointers
                 ref_types,
+                offset,
+                slice,
             } => {
-                let mut expr_child = root
+                let expr_child = root
                     .children
                     .iter()
                     .map(Self::convert)
                     .collect::<Vec<String>>()[0]
                     .clone();
+                if *slice {
+                    let index = convert_index(offset.as_deref());
+                    return format!("{id}[{index}] {op} {expr_child};");
+                }
+                if ref_types.contains(&ReferenceType::CellRef) {
+                    return convert_cell_set(id, op, &expr_child);
+                }
+
                 let mut l_side = id.clone();
                 let is_rc_clone = ref_types.contains(&ReferenceType::RcRefClone);
+                let mut is_raw = false;
 
                 ref_types.iter().for_each(|deref_type| match deref_type {
+                    // NOTE Raw pointers aren't dereferenced by method calls
+                    ReferenceType::RcRefClone if is_raw => {
+                        l_side = format!("({l_side}).borrow_mut()")
+                    }
                     ReferenceType::RcRefClone => l_side = format!("{l_side}.borrow_mut()"),
                     ReferenceType::MutBorrowed if !is_rc_clone => l_side = format!("*{l_side}"),
                     ReferenceType::MutBorrowed => {
                         println!("DEREFFED PTR BOTH MutBorrowed and is_rc_clone");
                     }
+                    ReferenceType::OwnedBox => l_side = format!("*{l_side}"),
+                    ReferenceType::OwnedVec => l_side = format!("{l_side}[0]"),
                     ReferenceType::MutPtr => {
-                        l_side = format!("unsafe {{ *{l_side}");
-                        expr_child.push_str(" }");
+                        l_side = format!("*{l_side}");
+                        is_raw = true;
                     }
                     t => panic!(
                         "Invalid Ptr Type being Derefferenced on lside of deref assignment: {:?}",
                         t
                     ),
                 });
-                if is_rc_clone {
-                    l_side = format!("*{l_side}");
+                // NOTE Raw dereferences are put in `unsafe` by the `Unsafe` node around them
+                match is_rc_clone {
+                    true => convert_rc_set(&format!("*{l_side}"), op, &expr_child),
+                    false => format!("{l_side} {op} {expr_child};"),
                 }
-
-                format!("{l_side} {op} {expr_child};")
             }
             AnnotatedNodeT::Declaration {
                 id,
                 is_mut,
                 t,
                 rc,
+                cell,
                 is_used,
                 init_value_unused,
+                lifetimes: _,
+                clone: _,
             } => {
                 let unused = match is_used {
                     true => "",
@@ -108,17 +160,20 @@ impl AnnotatedNode {
                     .map(Self::convert)
                     .collect::<Vec<String>>();
                 if !expr_children.is_empty() {
-                    let expr_child = expr_children[0].clone();
+                    let expr_child = root.children[0].convert();
                     let l_side = if *init_value_unused {
                         "".into()
                     } else {
                         format!(" = {expr_child}")
                     };
 
+                    let shared_t = shared_type(&rust_t, *rc, *cell);
                     if *rc {
                         format!(
-                        "let {unused}{id}: Rc<RefCell<{rust_t}>> = Rc::new(RefCell::new({expr_child}));"
-                    )
+                            "let {unused}{id}: {shared_t} = Rc::new(RefCell::new({expr_child}));"
+                        )
+                    } else if *cell {
+                        format!("let {unused}{id}: {shared_t} = Cell::new({expr_child});")
                     } else {
                         let binding = if *is_mut { "mut " } else { "" };
                         format!("let {binding}{unused}{id}: {rust_t}{l_side};")
@@ -130,62 +185,353 @@ impl AnnotatedNode {
                     format!("let {id}: {rust_t};")
                 }
             }
-            AnnotatedNodeT::DeRef { id, rc, count } => {
+            AnnotatedNodeT::DeRef {
+                id,
+                rc,
+                count,
+                ref_types,
+                offset,
+                slice,
+            } => {
                 let derefs: String = (0..*count).fold(String::new(), |mut acc, _| {
                     acc.push('*');
                     acc
                 });
-                if *rc {
+                if *slice {
+                    // NOTE `*(ptr + n)` is `ptr[n]`
+                    let index = convert_index(offset.as_deref());
+                    format!("{}{id}[{index}]", &derefs[1..])
+                } else if ref_types.first() == Some(&ReferenceType::OwnedVec) {
+                    // NOTE `*arr` is the first element
+                    format!("{}{id}[0]", &derefs[1..])
+                } else if ref_types.contains(&ReferenceType::CellRef) {
+                    format!("{}{id}.get()", &derefs[1..])
+                } else if let Some(i) = ref_types
+                    .iter()
+                    .position(|ref_type| *ref_type == ReferenceType::RcRefClone)
+                {
+                    // NOTE The references in front of the `Rc` are dereferenced by `borrow()`, but
+                    // raw pointers aren't
+                    match ref_types[..i].iter().any(ReferenceType::is_raw) {
+                        true => format!("{}({}{id}).borrow()", &derefs[i..], &derefs[..i]),
+                        false => format!("{}{id}.borrow()", &derefs[i..]),
+                    }
+                } else if *rc {
                     format!("{derefs}{id}.borrow()")
                 } else {
                     format!("{derefs}{id}")
                 }
             }
-            AnnotatedNodeT::Adr { id } => {
+            AnnotatedNodeT::Global {
+                strategy,
+                is_extern,
+            } => convert_global(&root.children[0], *strategy, *is_extern),
+            AnnotatedNodeT::GlobalId { id, strategy } => match strategy {
+                GlobalStrategy::Const | GlobalStrategy::Static => id.clone(),
+                GlobalStrategy::Atomic => {
+                    format!("{id}.load(std::sync::atomic::Ordering::SeqCst)")
+                }
+                GlobalStrategy::ThreadLocal => format!("{id}.with(|cell| cell.get())"),
+                GlobalStrategy::StaticMut => format!("unsafe {{ {id} }}"),
+            },
+            AnnotatedNodeT::GlobalAssignment { id, op, strategy } => {
+                let rust_expr = root.children[0].convert();
+                let ordering = "std::sync::atomic::Ordering::SeqCst";
+                match (strategy, op) {
+                    (GlobalStrategy::Atomic, AssignmentOpType::Eq) => {
+                        format!("{id}.store({rust_expr}, {ordering});")
+                    }
+                    (GlobalStrategy::Atomic, AssignmentOpType::AddEq) => {
+                        format!("{id}.fetch_add({rust_expr}, {ordering});")
+                    }
+                    (GlobalStrategy::Atomic, AssignmentOpType::SubEq) => {
+                        format!("{id}.fetch_sub({rust_expr}, {ordering});")
+                    }
+                    (GlobalStrategy::Atomic, AssignmentOpType::BOrEq) => {
+                        format!("{id}.fetch_or({rust_expr}, {ordering});")
+                    }
+                    (GlobalStrategy::Atomic, AssignmentOpType::BAndEq) => {
+                        format!("{id}.fetch_and({rust_expr}, {ordering});")
+                    }
+                    (GlobalStrategy::Atomic, AssignmentOpType::BXorEq) => {
+                        format!("{id}.fetch_xor({rust_expr}, {ordering});")
+                    }
+                    (GlobalStrategy::Atomic, op) => format!(
+                        "{id}.store({id}.load({ordering}) {} ({rust_expr}), {ordering});",
+                        binary_op(op)
+                    ),
+                    (GlobalStrategy::ThreadLocal, AssignmentOpType::Eq) => {
+                        format!("{id}.with(|cell| cell.set({rust_expr}));")
+                    }
+                    (GlobalStrategy::ThreadLocal, op) => format!(
+                        "{id}.with(|cell| cell.set(cell.get() {} ({rust_expr})));",
+                        binary_op(op)
+                    ),
+                    (GlobalStrategy::StaticMut, op) => {
+                        format!("unsafe {{ {id} {op} {rust_expr}; }}")
+                    }
+                    (GlobalStrategy::Const | GlobalStrategy::Static, _) => {
+                        unreachable!("Assigned globals are mutable")
+                    }
+                }
+            }
+            AnnotatedNodeT::Adr { id, global: _ } => {
                 id.to_string() // NOTE This isnt' a bug, just cursed
             }
+            AnnotatedNodeT::HeapAlloc { t, count } => {
+                let default = t.zero_value();
+                match count {
+                    Some(count) => format!("vec![{default}; ({}) as usize]", count.convert()),
+                    None => format!("Box::new({default})"),
+                }
+            }
+            AnnotatedNodeT::SizeOf { t, ptr_count } => {
+                let rust_t = construct_ptr_type(
+                    &mut (0..*ptr_count).map(|_| ReferenceType::ConstPtr),
+                    &t.to_rust_type(),
+                );
+                format!("std::mem::size_of::<{rust_t}>() as i32")
+            }
             AnnotatedNodeT::ArrayDeclaration {
                 id,
                 t,
-                size,
+                dimensions,
                 is_used,
                 is_mut,
                 items,
                 init_value_unused,
             } => {
-                let rust_t = t.to_rust_type();
+                let rust_t = array_type(t, dimensions);
                 let used = if *is_used { "" } else { "_" };
                 let mut_str = if *is_mut { "mut " } else { "" };
-                let items_str = items
-                    .iter()
-                    .map(Self::convert)
-                    .collect::<Vec<String>>()
-                    .join(", ");
 
-                let l_side = if *init_value_unused {
-                    "".into()
-                } else {
-                    format!(" = &[{size}; {items_str}]")
+                let l_side = match (init_value_unused, t) {
+                    (true, _) => "".into(),
+                    // NOTE Arrays of structs are always zeroed, and their structs derive `Default`
+                    (false, CType::Struct(_)) => " = Default::default()".into(),
+                    (false, _) => format!(" = {}", convert_array_items(items, t, dimensions)),
                 };
 
                 format!("let {mut_str}{used}{id}: {rust_t}{l_side};")
             }
+            AnnotatedNodeT::IndexArray {
+                id,
+                indices,
+                field_id,
+                global,
+            } => {
+                let indices = convert_indices(indices) + &convert_field(field_id);
+                match global {
+                    Some(GlobalStrategy::ThreadLocal) => {
+                        format!("{id}.with(|cell| cell.borrow(){indices})")
+                    }
+                    Some(GlobalStrategy::StaticMut) => format!("unsafe {{ {id}{indices} }}"),
+                    _ => format!("{id}{indices}"),
+                }
+            }
+            AnnotatedNodeT::IndexArrayAssignment {
+                id,
+                op,
+                indices,
+                field_id,
+                expr,
+                global,
+            } => {
+                let rust_expr = expr.convert();
+                let indices = convert_indices(indices) + &convert_field(field_id);
+                match global {
+                    Some(GlobalStrategy::ThreadLocal) => {
+                        format!("{id}.with(|cell| cell.borrow_mut(){indices} {op} {rust_expr});")
+                    }
+                    Some(GlobalStrategy::StaticMut) => {
+                        format!("unsafe {{ {id}{indices} {op} {rust_expr}; }}")
+                    }
+                    _ => format!("{id}{indices} {op} {rust_expr};"),
+                }
+            }
+            // NOTE A pointer to an item points into the rest of the array
+            AnnotatedNodeT::AdrIndex { id, indices } => {
+                let (last, outer) = indices.split_last().unwrap();
+                let last = convert_index(Some(last));
+                format!("{id}{}[{last}..]", convert_indices(outer))
+            }
+            AnnotatedNodeT::StrLiteral {
+                value,
+                nul_terminated,
+            } => match nul_terminated {
+                true => format!("b\"{value}\\0\""),
+                false => format!("b\"{value}\""),
+            },
+            AnnotatedNodeT::StrBytes { id, nul_terminated } => match nul_terminated {
+                true => format!("std::ffi::CStr::from_bytes_until_nul(&{id}).unwrap().to_bytes()"),
+                false => id.clone(),
+            },
+            AnnotatedNodeT::Print {
+                target,
+                format,
+                args,
+            } => {
+                let args: String = root
+                    .children
+                    .iter()
+                    .zip(args)
+                    .map(|(arg, format_arg)| {
+                        let rust_arg = arg.convert();
+                        match format_arg {
+                            FormatArg::Display => format!(", {rust_arg}"),
+                            FormatArg::Char => format!(", ({rust_arg}) as u8 as char"),
+                            FormatArg::Unsigned => format!(", ({rust_arg}) as u32"),
+                            FormatArg::SignedChar => format!(", ({rust_arg}) as i8 as i32"),
+                            FormatArg::UnsignedChar => format!(", ({rust_arg}) as i8 as u32"),
+                            FormatArg::Str => format!(", String::from_utf8_lossy({rust_arg})"),
+                        }
+                    })
+                    .collect();
+
+                match target {
+                    PrintTarget::Stdout => format!("print!(\"{format}\"{args});"),
+                    PrintTarget::Stderr => format!("eprint!(\"{format}\"{args});"),
+                    // NOTE The last byte is kept for the NUL terminator, and `snprintf` truncates
+                    // rather than overflowing, with a limit of 0 writing nothing at all
+                    PrintTarget::Buffer { id, limit } => match limit {
+                        Some(limit) => format!(
+                            "{{ use std::io::Write; if let Some(end) = usize::checked_sub({}, 1).map(|end| end.min({id}.len() - 1)) {{ let mut w = &mut {id}[..end]; let _ = write!(w, \"{format}\"{args}); let len = end - w.len(); {id}[len] = 0; }} }}",
+                            convert_index(Some(limit))
+                        ),
+                        None => format!(
+                            "{{ use std::io::Write; let end = {id}.len() - 1; let mut w = &mut {id}[..end]; write!(w, \"{format}\"{args}).unwrap(); let len = end - w.len(); {id}[len] = 0; }}"
+                        ),
+                    },
+                }
+            }
+            AnnotatedNodeT::Assert => {
+                let condition = convert_condition(&root.children[0]);
+                format!("assert!({condition});")
+            }
+            AnnotatedNodeT::Asm {
+                template,
+                operands,
+                clobbers,
+            } => {
+                let template = convert_asm_template(template, operands);
+                let operands: String = operands
+                    .iter()
+                    .map(|(constraint, id, t)| {
+                        let (class, _) = asm_register(t);
+                        let direction = match constraint.as_str() {
+                            "r" => "in",
+                            "=r" => "out",
+                            "+r" => "inout",
+                            constraint => panic!("Unsupported: {constraint:?} asm constraint"),
+                        };
+                        format!(", {direction}({class}) {id}")
+                    })
+                    .collect();
+                // NOTE `asm!` already assumes the flags and memory are clobbered
+                let clobbers: String = clobbers
+                    .iter()
+                    .filter(|clobber| !matches!(clobber.as_str(), "cc" | "memory"))
+                    .map(|clobber| format!(", out(\"{}\") _", clobber.trim_start_matches('%')))
+                    .collect();
+                // NOTE GCC uses AT&T syntax, rust defaults to Intel syntax
+                format!(
+                    "unsafe {{ core::arch::asm!(\"{template}\"{operands}{clobbers}, options(att_syntax)); }}"
+                )
+            }
+            AnnotatedNodeT::PutChar => {
+                let c = root.children[0].convert();
+                format!("print!(\"{{}}\", ({c}) as u8 as char);")
+            }
+            AnnotatedNodeT::FloatLiteral(f) => f.clone(),
+            AnnotatedNodeT::StringFunctionCall { name, t } => {
+                let args: Vec<String> = root
+                    .children
+                    .iter()
+                    .map(|arg| match arg.token {
+                        // NOTE Literals are arrays, not slices
+                        AnnotatedNodeT::StrLiteral { .. } => format!("&{}[..]", arg.convert()),
+                        _ => arg.convert(),
+                    })
+                    .collect();
+                // NOTE C counts in bytes, rust counts in items
+                let count = |n: &AnnotatedNode| {
+                    format!(
+                        "({}) as usize / std::mem::size_of::<{}>()",
+                        n.convert(),
+                        t.to_rust_type()
+                    )
+                };
+                match name.as_str() {
+                    "strlen" => format!("{}.len() as i32", args[0]),
+                    "strcmp" => format!("{}.cmp({}) as i32", args[0], args[1]),
+                    "strcpy" => format!(
+                        "{{ let src = {1}; {0}[..src.len()].copy_from_slice(src); {0}[src.len()] = 0; }}",
+                        args[0], args[1]
+                    ),
+                    // NOTE The rest of the destination is zeroed, like C does
+                    "strncpy" => format!(
+                        "{{ let src = {1}; let n = {2}; let len = src.len().min(n); {0}[..len].copy_from_slice(&src[..len]); {0}[len..n].fill(0); }}",
+                        args[0],
+                        args[1],
+                        convert_index(Some(&root.children[2]))
+                    ),
+                    "memcpy" => {
+                        let count = count(&root.children[2]);
+                        format!(
+                            "{0}[..{count}].copy_from_slice(&{1}[..{count}])",
+                            args[0], args[1]
+                        )
+                    }
+                    // NOTE Only zeroing, or filling arrays of chars, is let through by the checker
+                    "memset" => format!(
+                        "{}[..{}].fill(({}) as {})",
+                        args[0],
+                        count(&root.children[2]),
+                        args[1],
+                        t.to_rust_type()
+                    ),
+                    name => panic!("Unsupported: {name}"),
+                }
+            }
+            AnnotatedNodeT::SliceFrom { id, offset } => {
+                let index = convert_index(Some(offset));
+                format!("{id}[{index}..]")
+            }
+            AnnotatedNodeT::SliceAdvance { id, offset, is_mut } => {
+                let index = convert_index(Some(offset));
+                match is_mut {
+                    // NOTE The slice is taken out so it can be split without borrowing itself
+                    true => format!("{id} = std::mem::take(&mut {id}).split_at_mut({index}).1;"),
+                    false => format!("{id} = {id}.split_at({index}).1;"),
+                }
+            }
+            AnnotatedNodeT::SliceBorrow { id, is_mut } => match is_mut {
+                true => format!("&mut {id}"),
+                false => format!("&{id}"),
+            },
             AnnotatedNodeT::NumLiteral(n) => {
                 format!("{n}")
             }
-            AnnotatedNodeT::Assignment { op, id, rc } => {
+            AnnotatedNodeT::Assignment { op, id, rc, cell } => {
                 let rust_expr = &root.children[0].convert();
 
-                if *rc {
-                    format!("*{id}.borrow_mut() {op} {rust_expr};")
+                if *cell {
+                    convert_cell_set(id, op, rust_expr)
+                } else if *rc {
+                    convert_rc_set(&format!("*{id}.borrow_mut()"), op, rust_expr)
                 } else {
                     format!("{id} {op} {rust_expr};")
                 }
             }
-            AnnotatedNodeT::FunctionDeclaration { id, t } => {
+            AnnotatedNodeT::FunctionDeclaration {
+                id,
+                t,
+                lifetimes,
+                arenas,
+            } => {
                 let rust_t = match id == "main" {
                     true => "()".into(),
-                    false => t.to_rust_type(),
+                    false => signature_type(t, *lifetimes),
                 };
                 let args = root
                     .children
@@ -194,16 +540,36 @@ impl AnnotatedNode {
                     .map(convert_argument)
                     .collect::<Vec<String>>()
                     .join(", ");
+                let shared: String = root
+                    .children
+                    .iter()
+                    .take(i32::max(root.children.len() as i32 - 1, 0) as usize)
+                    .filter_map(share_argument)
+                    .collect();
                 let scope = root
                     .children
                     .last()
                     .unwrap_or(&AnnotatedNode {
                         token: AnnotatedNodeT::Scope(None),
+                        line: 0,
                         children: vec![],
                     })
                     .convert();
+                let arenas: String = arenas
+                    .iter()
+                    .map(|struct_id| {
+                        format!(
+                            "\tlet mut {}: Vec<{struct_id}> = Vec::new();\n",
+                            arena_id(struct_id)
+                        )
+                    })
+                    .collect();
 
-                format!("fn {id}({args}) -> {rust_t} {{\n{scope}\n}}")
+                format!("fn {id}({args}) -> {rust_t} {{\n{arenas}{shared}{scope}\n}}")
+            }
+            AnnotatedNodeT::FunctionCall(id) if id == "free" => {
+                let ptr = root.children[0].convert();
+                format!("drop({ptr});")
             }
             AnnotatedNodeT::FunctionCall(id) => {
                 let args = root
@@ -222,10 +588,13 @@ impl AnnotatedNode {
             }
             AnnotatedNodeT::Program { imports } => {
                 let mut t = imports.clone();
+                // NOTE Typedefs without a body have nothing to translate
                 t.push(
                     root.children
                         .iter()
-                        .map(AnnotatedNode::convert)
+                        .map(|child| (child.convert(), child.line))
+                        .filter(|(item, _)| !item.is_empty())
+                        .map(|(item, line)| source_map::mark(line, &item))
                         .collect::<Vec<String>>()
                         .join("\n"),
                 );
@@ -236,9 +605,11 @@ impl AnnotatedNode {
                 var_id,
                 struct_id,
                 is_mut,
+                rc,
                 fields,
                 is_used,
                 init_value_unused,
+                zero,
             } => {
                 let unused = match is_used {
                     true => "",
@@ -250,6 +621,25 @@ impl AnnotatedNode {
                 };
                 let l_side = if *init_value_unused {
                     "".into()
+                } else if let Some(zero) = zero {
+                    match zero {
+                        ZeroValue::Default => format!(" = {struct_id}::default()"),
+                        ZeroValue::Zeroed => {
+                            format!(": {struct_id} = unsafe {{ std::mem::zeroed() }}")
+                        }
+                        ZeroValue::Variant {
+                            union_field,
+                            union_id,
+                            variant,
+                            t,
+                        } => {
+                            let value = match t.is_floating() {
+                                true => "0.0",
+                                false => "0",
+                            };
+                            format!(" = {struct_id} {{ {union_field}: {union_id}::{variant}({value}) }}")
+                        }
+                    }
                 } else {
                     let mut l_side = format!(" = {struct_id} {{");
                     fields.iter().for_each(|(field, expr)| {
@@ -260,12 +650,116 @@ impl AnnotatedNode {
                     l_side
                 };
 
+                if *rc {
+                    // NOTE Every initializer above is ` = value`, apart from a zeroed union
+                    let value = l_side
+                        .strip_prefix(" = ")
+                        .expect("Unsupported: Sharing a struct that isn't initialized");
+                    return format!("let {unused}{var_id}: Rc<RefCell<{struct_id}>> = Rc::new(RefCell::new({value}));");
+                }
                 format!("let {mut_binding}{unused}{var_id}{l_side};")
             }
             _ => root.non_ptr_conversion(),
         }
     }
 
+    /// The type every variable is declared with in the translation, with arguments as they are
+    /// in the signature, since a shared argument is only moved into its `Cell` or `Rc` after
+    pub fn declared_types(&self) -> Vec<(String, String)> {
+        let declared = match &self.token {
+            AnnotatedNodeT::FunctionDeclaration { .. } => {
+                let (args, body) = self
+                    .children
+                    .split_at(self.children.len().saturating_sub(1));
+                return args
+                    .iter()
+                    .map(|arg| {
+                        let (id, _, rust_t) = argument_type(arg);
+                        (id.to_string(), rust_t)
+                    })
+                    .chain(body.iter().flat_map(Self::declared_types))
+                    .collect();
+            }
+            AnnotatedNodeT::Global { strategy, .. } => {
+                return global_type(&self.children[0], *strategy)
+                    .into_iter()
+                    .collect();
+            }
+            AnnotatedNodeT::Declaration {
+                id, t, rc, cell, ..
+            } => {
+                // NOTE A declaration without a value is never shared
+                let shared = !self.children.is_empty();
+                let rust_t = shared_type(&t.to_rust_type(), *rc && shared, *cell && shared);
+                Some((id.clone(), rust_t))
+            }
+            AnnotatedNodeT::PtrDeclaration {
+                id,
+                t,
+                ref_type,
+                slice,
+                ..
+            } => Some((id.clone(), ptr_declaration_type(t, ref_type, *slice))),
+            AnnotatedNodeT::ArrayDeclaration {
+                id, t, dimensions, ..
+            } => Some((id.clone(), array_type(t, dimensions))),
+            AnnotatedNodeT::StructDeclaration {
+                var_id,
+                struct_id,
+                rc,
+                ..
+            } => Some((var_id.clone(), shared_type(struct_id, *rc, false))),
+            AnnotatedNodeT::NodePtrDeclaration { ptr, .. } => {
+                Some((ptr.id.clone(), node_ptr_type(ptr)))
+            }
+            _ => None,
+        };
+        declared
+            .into_iter()
+            .chain(self.children.iter().flat_map(Self::declared_types))
+            .collect()
+    }
+
+    /// Whether the node's own rust is in an `unsafe` block, or opens one around its statements
+    pub fn is_unsafe(&self) -> bool {
+        match &self.token {
+            AnnotatedNodeT::Unsafe { block, .. } => *block,
+            AnnotatedNodeT::StructFieldAssignment {
+                access: FieldAccess::UnionMember,
+                op,
+                ..
+            } => *op != AssignmentOpType::Eq,
+            AnnotatedNodeT::StructDeclaration {
+                zero: Some(ZeroValue::Zeroed),
+                init_value_unused,
+                ..
+            } => !init_value_unused,
+            AnnotatedNodeT::UnsafeExpr
+            | AnnotatedNodeT::Asm { .. }
+            | AnnotatedNodeT::GlobalId {
+                strategy: GlobalStrategy::StaticMut,
+                ..
+            }
+            | AnnotatedNodeT::GlobalAssignment {
+                strategy: GlobalStrategy::StaticMut,
+                ..
+            }
+            | AnnotatedNodeT::IndexArray {
+                global: Some(GlobalStrategy::StaticMut),
+                ..
+            }
+            | AnnotatedNodeT::IndexArrayAssignment {
+                global: Some(GlobalStrategy::StaticMut),
+                ..
+            }
+            | AnnotatedNodeT::StructFieldId {
+                access: FieldAccess::UnionMember,
+                ..
+            } => true,
+            _ => false,
+        }
+    }
+
     fn convert_field_literal(&self, field: FieldDefinition) -> String {
         // NOTE If self is a ptr, it must be just a ptr
         // i don't have the mental sauce right now for transpiling stuff like this
@@ -284,20 +778,23 @@ impl AnnotatedNode {
         // totally separate system for converting expression that involve raw ptrs, meaning we
         // shouldn't worry about them for now
         let mut converted_expr: String = self.convert();
-        // let rust_type = field.c_type.to_rust_type();
+        let rust_type = field.c_type.to_rust_type();
         if !field.ptr_type.is_empty() {
             // NOTE If it's a ptr, only one factor, an adr
             // The reference taking is handled by the statement node
-            // let reference_type = construct_ptr_type(&mut field.ptr_type.into_iter(), &rust_type);
             converted_expr = match field.ptr_type[0] {
                 ReferenceType::MutBorrowed => format!("&mut {converted_expr}"),
                 ReferenceType::ConstBorrowed => format!("&{converted_expr}"),
                 ReferenceType::RcRefClone => format!("{converted_expr}.clone()"),
-                // ReferenceType::MutPtr => format!("&mut {converted_expr} as {reference_type}"),
-                // ReferenceType::ConstPtr => {
-                //     format!("&{converted_expr} as {reference_type}")
-                // }
-                _ => panic!("Not supporting raw ptrs yet"),
+                ReferenceType::MutPtr => format!(
+                    "&mut {converted_expr} as {}",
+                    construct_ptr_type(&mut field.ptr_type.into_iter(), &rust_type)
+                ),
+                ReferenceType::ConstPtr => format!(
+                    "&{converted_expr} as {}",
+                    construct_ptr_type(&mut field.ptr_type.into_iter(), &rust_type)
+                ),
+                _ => panic!("Unsupported: Owned pointers in a struct literal"),
             };
         }
 
@@ -330,9 +827,32 @@ impl AnnotatedNode {
             AnnotatedNodeT::EqCmp => {
                 format!("{} == {}", left.unwrap(), right.unwrap())
             }
-            AnnotatedNodeT::Id { id, rc } => {
+            AnnotatedNodeT::NeqCmp => {
+                format!("{} != {}", left.unwrap(), right.unwrap())
+            }
+            AnnotatedNodeT::AndCmp | AnnotatedNodeT::OrCmp => {
+                let op = match root.token {
+                    AnnotatedNodeT::AndCmp => "&&",
+                    _ => "||",
+                };
+                // NOTE Keeps the grouping of nested `&&`s and `||`s
+                let operand = |expr: &AnnotatedNode| match expr.token {
+                    AnnotatedNodeT::AndCmp | AnnotatedNodeT::OrCmp => {
+                        format!("({})", convert_condition(expr))
+                    }
+                    _ => convert_condition(expr),
+                };
+                format!(
+                    "{} {op} {}",
+                    operand(&root.children[0]),
+                    operand(&root.children[1])
+                )
+            }
+            AnnotatedNodeT::Id { id, rc, cell } => {
                 if *rc {
                     format!("*{id}.borrow()")
+                } else if *cell {
+                    format!("{id}.get()")
                 } else {
                     id.to_string()
                 }
@@ -340,11 +860,13 @@ impl AnnotatedNode {
             AnnotatedNodeT::NumLiteral(n) => {
                 format!("{n}")
             }
-            AnnotatedNodeT::Assignment { op, id, rc } => {
+            AnnotatedNodeT::Assignment { op, id, rc, cell } => {
                 let rust_expr = root.children[0].convert();
 
-                if *rc {
-                    format!("*{id}.borrow_mut() {op} {rust_expr};")
+                if *cell {
+                    convert_cell_set(id, op, &rust_expr)
+                } else if *rc {
+                    convert_rc_set(&format!("*{id}.borrow_mut()"), op, &rust_expr)
                 } else {
                     format!("{id} {op} {rust_expr};")
                 }
@@ -364,47 +886,83 @@ impl AnnotatedNode {
             AnnotatedNodeT::StructDefinition {
                 struct_id,
                 field_definitions,
+                field_lifetimes,
                 has_ref,
+                heap_allocated,
+                zero_initialized,
+                is_union,
             } => {
-                let lifetime = match has_ref {
-                    true => "<'a>",
-                    false => "",
+                let params: Vec<String> = field_lifetimes.iter().flatten().cloned().collect();
+                let lifetime = match params.is_empty() {
+                    true => String::new(),
+                    false => format!("<{}>", params.join(", ")),
                 };
-                let mut ret = format!("struct {struct_id}{lifetime} {{\n");
-                field_definitions.iter().for_each(|field| {
-                    let mut field_type = field.c_type.to_rust_type();
+                // NOTE Heap allocations are initialized with `Default::default()`
+                // Union fields are `Copy`, and read through `unsafe`
+                let derive = match (is_union, (*heap_allocated || *zero_initialized) && !has_ref) {
+                    (true, _) => "#[repr(C)]\n#[derive(Clone, Copy)]\n",
+                    (false, true) => "#[derive(Default)]\n",
+                    (false, false) => "",
+                };
+                let keyword = match is_union {
+                    true => "union",
+                    false => "struct",
+                };
+                let mut ret = format!("{derive}{keyword} {struct_id}{lifetime} {{\n");
+                field_definitions
+                    .iter()
+                    .zip(field_lifetimes)
+                    .for_each(|(field, lifetimes)| {
+                        // NOTE Each borrowed level has its own lifetime, so the struct isn't
+                        // invariant over one lifetime shared by all of them
+                        let (level_lifetimes, held_lifetimes) = lifetimes.split_at(
+                            field.ptr_type.iter().filter(|p| p.is_borrow()).count(),
+                        );
+                        let mut field_type = match held_lifetimes.is_empty() {
+                            true => field.c_type.to_rust_type(),
+                            false => format!(
+                                "{}<{}>",
+                                field.c_type.to_rust_type(),
+                                held_lifetimes.join(", ")
+                            ),
+                        };
+                        let mut level_lifetimes = level_lifetimes.iter().rev();
 
-                    field
-                        .ptr_type
-                        .iter()
-                        .rev()
-                        .for_each(|p| match (p, has_ref) {
-                            (ReferenceType::MutBorrowed, true) => {
-                                field_type = format!("&'a mut {field_type}")
-                            }
-                            (ReferenceType::ConstBorrowed, true) => {
-                                field_type = format!("&'a {field_type}")
-                            }
-
-                            (ReferenceType::MutBorrowed, false) => {
-                                field_type = format!("&mut {field_type}")
-                            }
-                            (ReferenceType::ConstBorrowed, false) => {
-                                field_type = format!("&{field_type}")
-                            }
-                            (ReferenceType::RcRefClone, _) => {
-                                field_type = format!("Rc<RefCell<{field_type}>>")
-                            }
-                            // TODO Check if rc is used for original rc ptrs or if RcRefClone is used
-                            // for all
-                            (ReferenceType::MutPtr, _) => field_type = format!("*mut {field_type}"),
-                            (ReferenceType::ConstPtr, _) => {
-                                field_type = format!("*const {field_type}")
-                            }
-                        });
-                    let field_ret = format!("{}: {}", field.id, field_type);
-                    ret.push_str(format!("\t{},\n", field_ret).as_str());
-                });
+                        field
+                            .ptr_type
+                            .iter()
+                            .rev()
+                            .for_each(|p| match p {
+                                ReferenceType::MutBorrowed => {
+                                    let lifetime = level_lifetimes.next().unwrap();
+                                    field_type = format!("&{lifetime} mut {field_type}")
+                                }
+                                ReferenceType::ConstBorrowed => {
+                                    let lifetime = level_lifetimes.next().unwrap();
+                                    field_type = format!("&{lifetime} {field_type}")
+                                }
+                                ReferenceType::RcRefClone => {
+                                    field_type = format!("Rc<RefCell<{field_type}>>")
+                                }
+                                ReferenceType::CellRef => {
+                                    unreachable!("Cells aren't shared through struct fields")
+                                }
+                                // TODO Check if rc is used for original rc ptrs or if RcRefClone is used
+                                // for all
+                                ReferenceType::MutPtr => field_type = format!("*mut {field_type}"),
+                                ReferenceType::ConstPtr => {
+                                    field_type = format!("*const {field_type}")
+                                }
+                                ReferenceType::OwnedBox => {
+                                    field_type = format!("Box<{field_type}>")
+                                }
+                                ReferenceType::OwnedVec => {
+                                    field_type = format!("Vec<{field_type}>")
+                                }
+                            });
+                        let field_ret = format!("{}: {}", field.id, field_type);
+                        ret.push_str(format!("\t{},\n", field_ret).as_str());
+                    });
                 ret.push('}');
                 ret
             }
@@ -413,23 +971,263 @@ impl AnnotatedNode {
                 field_id,
                 op,
                 expr,
+                access,
             } => {
                 let rust_expr = expr.convert();
-                format!("{var_id}.{field_id} {op} {rust_expr};")
+                match access {
+                    // NOTE The member write that follows sets the variant
+                    FieldAccess::Tag { union_field: _ } => String::new(),
+                    FieldAccess::Variant {
+                        union_field,
+                        union_id,
+                        variant,
+                    } => format!("{var_id}.{union_field} = {union_id}::{variant}({rust_expr});"),
+                    FieldAccess::UnionMember if *op != AssignmentOpType::Eq => {
+                        format!("unsafe {{ {var_id}.{field_id} {op} {rust_expr}; }}")
+                    }
+                    FieldAccess::Shared => {
+                        convert_rc_set(&format!("{var_id}.borrow_mut().{field_id}"), op, &rust_expr)
+                    }
+                    _ => format!("{var_id}.{field_id} {op} {rust_expr};"),
+                }
+            }
+            AnnotatedNodeT::StructFieldId {
+                var_id,
+                field_id,
+                access,
+            } => match access {
+                FieldAccess::Field => format!("{var_id}.{field_id}"),
+                FieldAccess::UnionMember => format!("unsafe {{ {var_id}.{field_id} }}"),
+                FieldAccess::Shared => format!("{var_id}.borrow().{field_id}"),
+                FieldAccess::Tag { union_field } => {
+                    let tag_field = field_id;
+                    format!("{var_id}.{union_field}.{tag_field}()")
+                }
+                FieldAccess::Variant {
+                    union_field,
+                    union_id,
+                    variant,
+                } => format!(
+                    "match {var_id}.{union_field} {{ {union_id}::{variant}(value) => value, _ => unreachable!() }}"
+                ),
+            },
+            AnnotatedNodeT::PtrFieldId {
+                ptr_id,
+                field_id,
+                ref_type,
+            } => match ref_type {
+                Some(ReferenceType::RcRefClone) => format!("{ptr_id}.borrow().{field_id}"),
+                Some(ReferenceType::MutPtr | ReferenceType::ConstPtr) => {
+                    format!("(*{ptr_id}).{field_id}")
+                }
+                Some(ReferenceType::OwnedVec) => format!("{ptr_id}[0].{field_id}"),
+                // NOTE Borrows and boxes auto-deref
+                _ => format!("{ptr_id}.{field_id}"),
+            },
+            AnnotatedNodeT::PtrFieldAssignment {
+                ptr_id,
+                field_id,
+                op,
+                expr,
+                ref_type,
+            } => {
+                let rust_expr = expr.convert();
+                match ref_type {
+                    Some(ReferenceType::RcRefClone) => {
+                        convert_rc_set(&format!("{ptr_id}.borrow_mut().{field_id}"), op, &rust_expr)
+                    }
+                    Some(ReferenceType::MutPtr | ReferenceType::ConstPtr) => {
+                        format!("(*{ptr_id}).{field_id} {op} {rust_expr};")
+                    }
+                    Some(ReferenceType::OwnedVec) => {
+                        format!("{ptr_id}[0].{field_id} {op} {rust_expr};")
+                    }
+                    _ => format!("{ptr_id}.{field_id} {op} {rust_expr};"),
+                }
+            }
+            AnnotatedNodeT::RecursiveStructDefinition {
+                struct_id,
+                field_definitions,
+                strategy,
+            } => {
+                let mut ret = format!("#[derive(Default)]\nstruct {struct_id} {{\n");
+                field_definitions.iter().for_each(|field| {
+                    let field_type = match (field.ptr_type.is_empty(), strategy) {
+                        (true, _) => field.c_type.to_rust_type(),
+                        (false, RecursiveStrategy::Boxed) => format!("Option<Box<{struct_id}>>"),
+                        (false, RecursiveStrategy::Shared { weak_fields })
+                            if weak_fields.contains(&field.id) =>
+                        {
+                            format!("Option<Weak<RefCell<{struct_id}>>>")
+                        }
+                        (false, RecursiveStrategy::Shared { .. }) => {
+                            format!("Option<Rc<RefCell<{struct_id}>>>")
+                        }
+                        (false, RecursiveStrategy::Arena) => "Option<usize>".to_string(),
+                    };
+                    ret.push_str(&format!("\t{}: {field_type},\n", field.id));
+                });
+                ret.push('}');
+                ret
+            }
+            AnnotatedNodeT::NodePtrDeclaration {
+                ptr,
+                is_mut,
+                is_used,
+                value,
+            } => {
+                let mut_binding = if *is_mut { "mut " } else { "" };
+                let unused = if *is_used { "" } else { "_" };
+                format!(
+                    "let {mut_binding}{unused}{}: {} = {};",
+                    ptr.id,
+                    node_ptr_type(ptr),
+                    convert_node_value(value, Some(ptr), false)
+                )
+            }
+            AnnotatedNodeT::NodePtrAssignment { ptr, value } => {
+                format!("{} = {};", ptr.id, convert_node_value(value, Some(ptr), false))
+            }
+            AnnotatedNodeT::NodeFieldId { ptr, field_id } => {
+                format!("{}.{field_id}", convert_node(ptr, false))
+            }
+            AnnotatedNodeT::NodeFieldAssignment {
+                ptr,
+                field_id,
+                op,
+                expr,
+            } => {
+                let node = convert_node(ptr, true);
+                // NOTE A shared node read in the expression would still be borrowed while the
+                // node is borrowed mutably
+                match reads_shared_node(expr) {
+                    true => format!(
+                        "{{ let value = {}; {node}.{field_id} {op} value; }}",
+                        expr.convert()
+                    ),
+                    false => format!("{node}.{field_id} {op} {};", expr.convert()),
+                }
+            }
+            AnnotatedNodeT::NodeLink {
+                ptr,
+                field_id,
+                weak,
+                value,
+            } => format!(
+                "{}.{field_id} = {};",
+                convert_node(ptr, true),
+                convert_node_value(value, None, *weak)
+            ),
+            AnnotatedNodeT::NodeCmp { left, right, eq } => match (left, right, eq) {
+                (NodeValue::Null, value, true) | (value, NodeValue::Null, true) => {
+                    format!("{}.is_none()", convert_node_read(value))
+                }
+                (NodeValue::Null, value, false) | (value, NodeValue::Null, false) => {
+                    format!("{}.is_some()", convert_node_read(value))
+                }
+                // NOTE Only nodes in an arena are compared, by their index
+                (left, right, eq) => format!(
+                    "{} {} {}",
+                    convert_node_read(left),
+                    if *eq { "==" } else { "!=" },
+                    convert_node_read(right)
+                ),
+            },
+            AnnotatedNodeT::NodeFree { ptr } => format!("{} = None;", ptr.id),
+            AnnotatedNodeT::EnumDefinition {
+                enum_id,
+                variants,
+                rust_enum: true,
+            } => {
+                let enum_id = enum_id.as_ref().unwrap();
+                let mut ret = format!(
+                    "#[derive(Clone, Copy, PartialEq)]\n#[allow(non_camel_case_types)]\n#[repr(i32)]\nenum {enum_id} {{\n"
+                );
+                variants
+                    .iter()
+                    .for_each(|(variant, value)| ret.push_str(&format!("\t{variant} = {value},\n")));
+                ret.push('}');
+                ret
+            }
+            // NOTE Enums used as integers are an alias of `i32` and a `const` per enumerator
+            AnnotatedNodeT::EnumDefinition {
+                enum_id,
+                variants,
+                rust_enum: false,
+            } => {
+                let mut items = Vec::with_capacity(variants.len() + 1);
+                let t = match enum_id {
+                    Some(enum_id) => {
+                        items.push(format!(
+                            "#[allow(non_camel_case_types)]\ntype {enum_id} = i32;"
+                        ));
+                        enum_id.as_str()
+                    }
+                    None => "i32",
+                };
+                variants
+                    .iter()
+                    .for_each(|(variant, value)| items.push(format!("const {variant}: {t} = {value};")));
+                items.join("\n")
+            }
+            AnnotatedNodeT::EnumConstant { id, enum_id } => match enum_id {
+                Some(enum_id) => format!("{enum_id}::{id}"),
+                None => id.clone(),
+            },
+            AnnotatedNodeT::TaggedUnionDefinition {
+                union_id,
+                variants,
+                tag_field,
+                enum_id,
+                rust_enum,
+            } => {
+                let mut ret = format!(
+                    "#[derive(Clone, Copy)]\n#[allow(non_camel_case_types)]\nenum {union_id} {{\n"
+                );
+                variants.iter().for_each(|(variant, t)| {
+                    ret.push_str(&format!("\t{variant}({}),\n", t.to_rust_type()))
+                });
+                ret.push_str(&format!(
+                    "}}\n\nimpl {union_id} {{\n\tfn {tag_field}(&self) -> {enum_id} {{\n\t\tmatch self {{\n"
+                ));
+                variants.iter().for_each(|(variant, _)| {
+                    let tag = match rust_enum {
+                        true => format!("{enum_id}::{variant}"),
+                        false => variant.clone(),
+                    };
+                    ret.push_str(&format!("\t\t\t{union_id}::{variant}(_) => {tag},\n"))
+                });
+                ret.push_str("\t\t}\n\t}\n}");
+                ret
             }
             AnnotatedNodeT::While => {
                 let condition = left.unwrap();
                 let scope = right.unwrap();
 
-                format!("while {condition} {{\n\t\t{scope}\n\t}}")
+                format!("while {condition} {{\n{scope}\n}}")
             }
 
-            AnnotatedNodeT::Scope(_) => root
-                .children
-                .iter()
-                .map(Self::convert)
-                .collect::<Vec<String>>()
-                .join("\n\t"),
+            AnnotatedNodeT::Scope(_) => indent(&convert_statements(&root.children)),
+            AnnotatedNodeT::Unsafe {
+                safety,
+                block: true,
+            } => format!(
+                "// SAFETY: {safety}\n{}\n{}\n}}",
+                source_map::mark(root.line, "unsafe {"),
+                indent(&convert_statements(&root.children))
+            ),
+            AnnotatedNodeT::Unsafe {
+                safety,
+                block: false,
+            } => format!(
+                "// SAFETY: {safety}\n{}",
+                convert_statements(&root.children)
+            ),
+            AnnotatedNodeT::Cast(t) => {
+                format!("({}) as {}", root.children[0].convert(), t.to_rust_type())
+            }
+            AnnotatedNodeT::SignedChar => format!("({}) as i8", root.children[0].convert()),
+            AnnotatedNodeT::UnsafeExpr => format!("unsafe {{ {} }}", root.children[0].convert()),
             AnnotatedNodeT::Return { expr } => {
                 let expr = expr.convert();
 
@@ -440,7 +1238,233 @@ impl AnnotatedNode {
     }
 }
 
-fn construct_ptr_type<T>(points_to: &mut T, rust_t: &str) -> String
+/// Converts the statements of a scope, each tagged with the C line it's from
+///
+/// # Important
+/// `Unsafe` nodes tag their own lines, which don't start with the statement
+fn convert_statements(statements: &[AnnotatedNode]) -> String {
+    statements
+        .iter()
+        .map(|child| match &child.token {
+            // NOTE Calls used as statements need to be terminated
+            AnnotatedNodeT::FunctionCall(id) if id != "free" => {
+                (format!("{};", child.convert()), child.line)
+            }
+            AnnotatedNodeT::StringFunctionCall { .. } => {
+                (format!("{};", child.convert()), child.line)
+            }
+            AnnotatedNodeT::Unsafe { .. } => (child.convert(), 0),
+            _ => (child.convert(), child.line),
+        })
+        .filter(|(statement, _)| !statement.is_empty())
+        .map(|(statement, line)| source_map::mark(line, &statement))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Puts every line of a block's statements a level deeper than the block, so nested blocks end up
+/// as deep as they're nested
+fn indent(statements: &str) -> String {
+    statements
+        .split('\n')
+        .map(|line| match line.is_empty() {
+            true => line.to_string(),
+            false => format!("\t{line}"),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Converts the declaration of a global (or `static` local) to an item
+fn convert_global(
+    declaration: &AnnotatedNode,
+    strategy: GlobalStrategy,
+    is_extern: bool,
+) -> String {
+    let (id, t, rust_t, value) = match &declaration.token {
+        AnnotatedNodeT::Declaration { id, t, .. } => (
+            id,
+            t,
+            t.to_rust_type(),
+            declaration.children.first().map(AnnotatedNode::convert),
+        ),
+        AnnotatedNodeT::ArrayDeclaration {
+            id,
+            t,
+            dimensions,
+            items,
+            ..
+        } => (
+            id,
+            t,
+            array_type(t, dimensions),
+            Some(convert_array_items(items, t, dimensions)),
+        ),
+        node => unreachable!("Not a global declaration: {node:?}"),
+    };
+    // NOTE C names globals like any other variable
+    let allow = "#[allow(non_upper_case_globals)]";
+
+    let Some(value) = value.filter(|_| !is_extern) else {
+        return format!("extern \"C\" {{\n\t{allow}\n\tstatic mut {id}: {rust_t};\n}}");
+    };
+    match strategy {
+        GlobalStrategy::Const => format!("{allow}\nconst {id}: {rust_t} = {value};"),
+        GlobalStrategy::Static => format!("{allow}\nstatic {id}: {rust_t} = {value};"),
+        GlobalStrategy::StaticMut => format!("{allow}\nstatic mut {id}: {rust_t} = {value};"),
+        GlobalStrategy::Atomic => {
+            let atomic = atomic_type(t);
+            format!("{allow}\nstatic {id}: {atomic} = {atomic}::new({value});")
+        }
+        GlobalStrategy::ThreadLocal => {
+            let cell = thread_local_cell(declaration);
+            format!(
+                "thread_local! {{\n\t{allow}\n\tstatic {id}: {cell}<{rust_t}> = {cell}::new({value});\n}}"
+            )
+        }
+    }
+}
+
+/// The operator a compound assignment applies (`+` for `+=`)
+fn atomic_type(t: &CType) -> &'static str {
+    match t {
+        CType::Char => "std::sync::atomic::AtomicU8",
+        _ => "std::sync::atomic::AtomicI32",
+    }
+}
+
+fn thread_local_cell(declaration: &AnnotatedNode) -> &'static str {
+    match declaration.token {
+        AnnotatedNodeT::ArrayDeclaration { .. } => "std::cell::RefCell",
+        _ => "std::cell::Cell",
+    }
+}
+
+/// The type a global is declared with, which for the ones that are written is what makes
+/// writing them safe
+fn global_type(declaration: &AnnotatedNode, strategy: GlobalStrategy) -> Option<(String, String)> {
+    let (id, t, rust_t) = match &declaration.token {
+        AnnotatedNodeT::Declaration { id, t, .. } => (id, t, t.to_rust_type()),
+        AnnotatedNodeT::ArrayDeclaration {
+            id, t, dimensions, ..
+        } => (id, t, array_type(t, dimensions)),
+        _ => return None,
+    };
+    let rust_t = match strategy {
+        GlobalStrategy::Atomic => atomic_type(t).to_string(),
+        GlobalStrategy::ThreadLocal => format!("{}<{rust_t}>", thread_local_cell(declaration)),
+        GlobalStrategy::Const | GlobalStrategy::Static | GlobalStrategy::StaticMut => rust_t,
+    };
+    Some((id.clone(), rust_t))
+}
+
+fn shared_type(rust_t: &str, rc: bool, cell: bool) -> String {
+    match (rc, cell) {
+        (true, _) => format!("Rc<RefCell<{rust_t}>>"),
+        (false, true) => format!("Cell<{rust_t}>"),
+        (false, false) => rust_t.to_string(),
+    }
+}
+
+/// Pointers into arrays point to the rest of the array
+fn ptr_declaration_type(t: &CType, ref_type: &[ReferenceType], slice: bool) -> String {
+    let rust_t = match slice {
+        true => format!("[{}]", t.to_rust_type()),
+        false => t.to_rust_type(),
+    };
+    construct_ptr_type(&mut ref_type.iter().cloned(), &rust_t)
+}
+
+fn array_type(t: &CType, dimensions: &[usize]) -> String {
+    dimensions
+        .iter()
+        .rev()
+        .fold(t.to_rust_type(), |t, size| format!("[{t}; {size}]"))
+}
+
+fn binary_op(op: &AssignmentOpType) -> &'static str {
+    match op {
+        AssignmentOpType::SubEq => "-",
+        AssignmentOpType::AddEq | AssignmentOpType::AddO => "+",
+        AssignmentOpType::DivEq => "/",
+        AssignmentOpType::MulEq => "*",
+        AssignmentOpType::BOrEq => "|",
+        AssignmentOpType::BAndEq => "&",
+        AssignmentOpType::BXorEq => "^",
+        AssignmentOpType::Eq => unreachable!("`=` isn't a compound assignment"),
+    }
+}
+
+/// Writes a value into a `Cell`, which can only be copied in and out of whole
+fn convert_cell_set(id: &str, op: &AssignmentOpType, rust_expr: &str) -> String {
+    match op {
+        AssignmentOpType::Eq => format!("{id}.set({rust_expr});"),
+        op => format!("{id}.set({id}.get() {} ({rust_expr}));", binary_op(op)),
+    }
+}
+
+/// Writes a value through a `RefCell` borrow, reading the value first if that borrows a `RefCell`
+/// too, since the read's borrow lasts until the end of the statement and the write would panic
+fn convert_rc_set(place: &str, op: &AssignmentOpType, rust_expr: &str) -> String {
+    match rust_expr.contains(".borrow()") {
+        true => format!("{{ let value = {rust_expr}; {place} {op} value; }}"),
+        false => format!("{place} {op} {rust_expr};"),
+    }
+}
+
+/// Converts a C condition to a `bool`, since any non-zero value is true in C
+fn convert_condition(expr: &AnnotatedNode) -> String {
+    match expr.token {
+        AnnotatedNodeT::EqCmp
+        | AnnotatedNodeT::NeqCmp
+        | AnnotatedNodeT::AndCmp
+        | AnnotatedNodeT::OrCmp
+        | AnnotatedNodeT::NodeCmp { .. } => expr.convert(),
+        _ => format!("({}) != 0", expr.convert()),
+    }
+}
+
+/// The register class of an asm operand, and the template modifier that names the register at
+/// the operand's size
+fn asm_register(t: &CType) -> (&'static str, &'static str) {
+    match t {
+        CType::Char => ("reg_byte", ""),
+        CType::Int | CType::Float => ("reg", ":e"),
+        CType::Double => ("reg", ""),
+        t => panic!("Unsupported: {t:?} asm operand"),
+    }
+}
+
+/// Escapes an extended asm template for `asm!`, where `%0` is `{0}` and `%%` is `%`
+fn convert_asm_template(template: &str, operands: &[(String, String, CType)]) -> String {
+    let mut rust_template = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => rust_template.push_str("{{"),
+            '}' => rust_template.push_str("}}"),
+            '%' if chars.next_if_eq(&'%').is_some() => rust_template.push('%'),
+            '%' => {
+                let mut n = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    n.push(digit);
+                }
+                let (_, _, t) = n
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| operands.get(n))
+                    .expect("Unsupported: asm operand that isn't a number in the constraint lists");
+                let (_, modifier) = asm_register(t);
+                rust_template.push_str(&format!("{{{n}{modifier}}}"));
+            }
+            c => rust_template.push(c),
+        }
+    }
+
+    rust_template
+}
+
+pub fn construct_ptr_type<T>(points_to: &mut T, rust_t: &str) -> String
 where
     T: Iterator<Item = ReferenceType>,
 {
@@ -454,46 +1478,299 @@ where
         Some(ReferenceType::RcRefClone) => {
             format!("Rc<RefCell<{}>>", construct_ptr_type(points_to, rust_t))
         }
+        Some(ReferenceType::CellRef) => {
+            format!("&Cell<{}>", construct_ptr_type(points_to, rust_t))
+        }
         Some(ReferenceType::MutPtr) => format!("*mut {}", construct_ptr_type(points_to, rust_t)),
         Some(ReferenceType::ConstPtr) => {
             format!("*const {}", construct_ptr_type(points_to, rust_t))
         }
+        Some(ReferenceType::OwnedBox) => {
+            format!("Box<{}>", construct_ptr_type(points_to, rust_t))
+        }
+        Some(ReferenceType::OwnedVec) => {
+            format!("Vec<{}>", construct_ptr_type(points_to, rust_t))
+        }
         None => rust_t.to_string(),
     }
 }
 
+/// Structs holding references have their lifetimes elided in signatures, `Point<'_, '_>`
+fn signature_type(t: &CType, lifetimes: usize) -> String {
+    match lifetimes {
+        0 => t.to_rust_type(),
+        n => format!("{}<{}>", t.to_rust_type(), vec!["'_"; n].join(", ")),
+    }
+}
+
+/// Moves an argument the function shares between pointers into a `Cell` or an `Rc<RefCell>`
+fn share_argument(expr: &AnnotatedNode) -> Option<String> {
+    match &expr.token {
+        AnnotatedNodeT::Declaration {
+            id, t, rc: true, ..
+        } => Some(format!(
+            "\tlet {id}: Rc<RefCell<{}>> = Rc::new(RefCell::new({id}));\n",
+            t.to_rust_type()
+        )),
+        AnnotatedNodeT::Declaration {
+            id, t, cell: true, ..
+        } => Some(format!(
+            "\tlet {id}: Cell<{}> = Cell::new({id});\n",
+            t.to_rust_type()
+        )),
+        _ => None,
+    }
+}
+
 fn convert_argument(expr: &AnnotatedNode) -> String {
+    let (id, is_mut, rust_t) = argument_type(expr);
+    let mut_str = if is_mut { "mut " } else { "" };
+    format!("{mut_str}{id}: {rust_t}")
+}
+
+/// An argument's name, whether its binding is mutable, and its type
+fn argument_type(expr: &AnnotatedNode) -> (&str, bool, String) {
     match &expr.token {
         AnnotatedNodeT::Declaration {
             id,
             is_mut,
             t,
-            rc: _,
-            is_used,
+            rc,
+            cell,
+            is_used: _,
             init_value_unused: _,
+            lifetimes,
+            clone: _,
         } => {
-            let mut_str = if *is_mut { "mut " } else { "" };
-            let _used_str = if *is_used { "_" } else { "" };
-            format!("{mut_str}{id}: {}", t.to_rust_type())
+            // NOTE A shared argument is written through its `Cell` or `Rc`, not its binding
+            let is_mut = *is_mut && !rc && !cell;
+            match t {
+                // NOTE Mutating an array argument mutates the slice, not the binding
+                CType::Array(sub_type) if is_mut => {
+                    (id, false, format!("&mut [{}]", sub_type.to_rust_type()))
+                }
+                _ => (id, is_mut, signature_type(t, *lifetimes)),
+            }
         }
         AnnotatedNodeT::PtrDeclaration {
             id,
             is_mut,
             t,
             rc: _,
-            is_used,
+            is_used: _,
             points_to: _,
             adr: _,
             ref_type,
             init_value_unused: _,
+            slice: _,
+            reborrow: _,
+        } => (id, *is_mut, ptr_declaration_type(t, ref_type, false)),
+        node_t => panic!("Unexpected Argument Node Type: {:?}", node_t),
+    }
+}
+
+/// Converts an index expression into a `usize`, with no offset being the first element
+fn convert_index(index: Option<&AnnotatedNode>) -> String {
+    match index.map(|index| &index.token) {
+        None => "0".to_string(),
+        Some(AnnotatedNodeT::NumLiteral(n)) => format!("{n}"),
+        Some(AnnotatedNodeT::Id {
+            id,
+            rc: false,
+            cell: false,
+        }) => format!("{id} as usize"),
+        Some(_) => format!("({}) as usize", index.unwrap().convert()),
+    }
+}
+
+fn convert_indices(indices: &[AnnotatedNode]) -> String {
+    indices
+        .iter()
+        .map(|index| format!("[{}]", convert_index(Some(index))))
+        .collect()
+}
+
+/// Converts initializer items into a (nested) array literal, zeroing the missing items
+/// The field of an array element, `.x` in `arr[i].x`
+fn convert_field(field_id: &Option<String>) -> String {
+    field_id
+        .as_ref()
+        .map_or(String::new(), |field_id| format!(".{field_id}"))
+}
+
+/// Converts an initializer list, filling in the items it leaves out with zeros of the array's type
+fn convert_array_items(items: &[AnnotatedNode], t: &CType, dimensions: &[usize]) -> String {
+    let (size, sub_dimensions) = dimensions.split_first().unwrap();
+    let zeroed = sub_dimensions
+        .iter()
+        .rev()
+        .fold(t.zero_value(), |zero, sub_size| format!("[{zero}; {sub_size}]"));
+    if items.is_empty() {
+        return format!("[{zeroed}; {size}]");
+    }
+
+    // NOTE `char buf[8] = "hi"` is followed by NULs up to the size of the array
+    if let AnnotatedNodeT::StrLiteral { value, .. } = &items[0].token {
+        let nuls = "\\0".repeat(size - c_string_len(value));
+        return format!("*b\"{value}{nuls}\"");
+    }
+
+    let items = items
+        .iter()
+        .map(|item| match item.token {
+            AnnotatedNodeT::ArrayInitializer => {
+                convert_array_items(&item.children, t, sub_dimensions)
+            }
+            _ => item.convert(),
+        })
+        .chain((items.len()..*size).map(|_| zeroed.clone()))
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!("[{items}]")
+}
+
+/// The arena the nodes of a recursive struct are allocated in, `node_arena` for `Node`
+fn arena_id(struct_id: &str) -> String {
+    format!("{}_arena", struct_id.to_lowercase())
+}
+
+fn node_ptr_type(ptr: &NodePtr) -> String {
+    let struct_id = &ptr.struct_id;
+    match (&ptr.strategy, ptr.role) {
+        (RecursiveStrategy::Boxed, NodeRole::Owner) => format!("Option<Box<{struct_id}>>"),
+        (RecursiveStrategy::Boxed, NodeRole::Cursor { is_mut: false }) => {
+            format!("Option<&{struct_id}>")
+        }
+        (RecursiveStrategy::Boxed, NodeRole::Cursor { is_mut: true }) => {
+            format!("Option<&mut {struct_id}>")
+        }
+        (RecursiveStrategy::Shared { .. }, _) => format!("Option<Rc<RefCell<{struct_id}>>>"),
+        (RecursiveStrategy::Arena, _) => "Option<usize>".to_string(),
+    }
+}
+
+/// The node behind a pointer, to read or write its fields
+fn convert_node(ptr: &NodePtr, write: bool) -> String {
+    let id = &ptr.id;
+    match (&ptr.strategy, write) {
+        (RecursiveStrategy::Boxed, false) if ptr.role == NodeRole::Cursor { is_mut: false } => {
+            format!("{id}.unwrap()")
+        }
+        (RecursiveStrategy::Boxed, false) => format!("{id}.as_ref().unwrap()"),
+        (RecursiveStrategy::Boxed, true) => format!("{id}.as_mut().unwrap()"),
+        (RecursiveStrategy::Shared { .. }, false) => format!("{id}.as_ref().unwrap().borrow()"),
+        (RecursiveStrategy::Shared { .. }, true) => {
+            format!("{id}.as_ref().unwrap().borrow_mut()")
+        }
+        (RecursiveStrategy::Arena, _) => format!("{}[{id}.unwrap()]", arena_id(&ptr.struct_id)),
+    }
+}
+
+/// A pointer to a node being assigned to a pointer, or linked from a node when there's no
+/// pointer (boxed links own their node, and `weak` links point back)
+fn convert_node_value(value: &NodeValue, target: Option<&NodePtr>, weak: bool) -> String {
+    let role = target.map_or(NodeRole::Owner, |target| target.role);
+    let reassigned = |ptr: &NodePtr| target.is_some_and(|target| target.id == ptr.id);
+    match value {
+        NodeValue::Null => "None".to_string(),
+        NodeValue::Alloc {
+            struct_id,
+            strategy,
+        } => match strategy {
+            RecursiveStrategy::Boxed => format!("Some(Box::new({struct_id}::default()))"),
+            RecursiveStrategy::Shared { .. } => {
+                format!("Some(Rc::new(RefCell::new({struct_id}::default())))")
+            }
+            RecursiveStrategy::Arena => {
+                let arena = arena_id(struct_id);
+                format!("{{ {arena}.push({struct_id}::default()); Some({arena}.len() - 1) }}")
+            }
+        },
+        NodeValue::Ptr(ptr) => {
+            let id = &ptr.id;
+            match (&ptr.strategy, role, ptr.role) {
+                // NOTE The owner gives up its node, like a C pointer that isn't used again
+                (RecursiveStrategy::Boxed, NodeRole::Owner, _) => format!("{id}.take()"),
+                (
+                    RecursiveStrategy::Boxed,
+                    NodeRole::Cursor { is_mut: false },
+                    NodeRole::Cursor { is_mut: false },
+                ) => id.clone(),
+                (RecursiveStrategy::Boxed, NodeRole::Cursor { is_mut: false }, _) => {
+                    format!("{id}.as_deref()")
+                }
+                (RecursiveStrategy::Boxed, NodeRole::Cursor { is_mut: true }, _) => {
+                    format!("{id}.as_deref_mut()")
+                }
+                (RecursiveStrategy::Shared { .. }, ..) if weak => {
+                    format!("{id}.as_ref().map(Rc::downgrade)")
+                }
+                (RecursiveStrategy::Shared { .. }, ..) => format!("{id}.clone()"),
+                (RecursiveStrategy::Arena, ..) => id.clone(),
+            }
+        }
+        NodeValue::Link {
+            ptr,
+            field_id,
+            weak: weak_link,
         } => {
-            let mut_str = if *is_mut { "mut " } else { "" };
-            let _used_str = if *is_used { "_" } else { "" };
-            let ref_type_iter = &mut ref_type.iter().cloned();
-            let ptr_type = construct_ptr_type(ref_type_iter, &t.to_rust_type());
+            let id = &ptr.id;
+            match (&ptr.strategy, role) {
+                (RecursiveStrategy::Boxed, NodeRole::Owner) => {
+                    format!("{id}.as_mut().unwrap().{field_id}.take()")
+                }
+                (RecursiveStrategy::Boxed, NodeRole::Cursor { is_mut: false }) => {
+                    format!("{}.{field_id}.as_deref()", convert_node(ptr, false))
+                }
+                // NOTE Moving the cursor forward gives up its borrow of the node it was on
+                (RecursiveStrategy::Boxed, NodeRole::Cursor { is_mut: true })
+                    if reassigned(ptr) =>
+                {
+                    format!("{id}.unwrap().{field_id}.as_deref_mut()")
+                }
+                (RecursiveStrategy::Boxed, NodeRole::Cursor { is_mut: true }) => {
+                    format!("{id}.as_mut().unwrap().{field_id}.as_deref_mut()")
+                }
+                (RecursiveStrategy::Shared { .. }, _) => {
+                    // NOTE The pointer can't be reassigned while the node it holds is borrowed
+                    let node = match reassigned(ptr) {
+                        true => format!("Rc::clone({id}.as_ref().unwrap()).borrow()"),
+                        false => convert_node(ptr, false),
+                    };
+                    match (weak_link, weak) {
+                        (true, false) => {
+                            format!("{node}.{field_id}.as_ref().and_then(Weak::upgrade)")
+                        }
+                        (false, true) => format!("{node}.{field_id}.as_ref().map(Rc::downgrade)"),
+                        _ => format!("{node}.{field_id}.clone()"),
+                    }
+                }
+                (RecursiveStrategy::Arena, _) => {
+                    format!("{}.{field_id}", convert_node(ptr, false))
+                }
+            }
+        }
+    }
+}
 
-            format!("{mut_str}{id}: {ptr_type}")
+/// A pointer to a node being compared
+fn convert_node_read(value: &NodeValue) -> String {
+    match value {
+        NodeValue::Ptr(ptr) => ptr.id.clone(),
+        NodeValue::Link { ptr, field_id, .. } => {
+            format!("{}.{field_id}", convert_node(ptr, false))
         }
-        node_t => panic!("Unexpected Argument Node Type: {:?}", node_t),
+        NodeValue::Null | NodeValue::Alloc { .. } => {
+            panic!("Unsupported: Comparing to a new node")
+        }
+    }
+}
+
+fn reads_shared_node(expr: &AnnotatedNode) -> bool {
+    match &expr.token {
+        AnnotatedNodeT::NodeFieldId { ptr, .. } => {
+            matches!(ptr.strategy, RecursiveStrategy::Shared { .. })
+        }
+        _ => expr.children.iter().any(reads_shared_node),
     }
 }
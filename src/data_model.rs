@@ -1,6 +1,10 @@
-use std::{cell::RefCell, ops::Range, rc::Rc};
+use std::{
+    cell::RefCell,
+    ops::{Range, RangeInclusive},
+    rc::Rc,
+};
 
-use crate::{analysis_ctx::AnalysisContext, lexer::CType};
+use crate::{analysis_ctx::AnalysisContext, ast::StorageClass, lexer::CType};
 
 pub type LineNumber = usize;
 
@@ -9,11 +13,13 @@ pub struct VarData {
     // Usage / Block
     pub pointed_to: Vec<Rc<RefCell<Reference>>>, // References held to this variable
     pub usages: Vec<Usage>,
+    pub place: Place, // Set when it's declared
 
     // General Information
     pub var_type: CType,
     pub points_to: Vec<Rc<RefCell<Reference>>>, // References held by this variable
     pub rc: bool,
+    pub cell: bool,
 
     pub raw: bool,
     pub clone: bool, // Cloned value (generated by sonder)
@@ -23,6 +29,21 @@ pub struct VarData {
     // Struct handling
     pub instanceof_struct: Option<String>,
     pub fieldof_struct: Option<FieldInfo>,
+
+    // Heap handling
+    pub heap_allocation: Option<HeapAllocation>,
+
+    // Array handling
+    pub passed_to: Vec<(String, usize)>, // function id, argument index
+
+    // String handling
+    pub string_literal: Option<StringMode>,
+
+    // Global handling
+    pub storage: Option<StorageClass>,
+
+    // Recursive struct handling
+    pub node_role: Option<NodeRole>,
 }
 
 impl VarData {
@@ -34,16 +55,23 @@ impl VarData {
     ) -> Self {
         VarData {
             usages: Vec::new(),
+            place: Place::default(),
             var_type,
             points_to: Vec::new(),
             pointed_to: Vec::new(),
             rc: false,
+            cell: false,
             raw: false,
             clone,
             is_mut: false,
             init_value_unused: false,
             instanceof_struct,
             fieldof_struct,
+            heap_allocation: None,
+            passed_to: Vec::new(),
+            string_literal: None,
+            storage: None,
+            node_role: None,
         }
     }
 
@@ -80,6 +108,10 @@ impl VarData {
         !self.points_to.is_empty()
     }
 
+    pub fn is_array(&self) -> bool {
+        matches!(self.var_type, CType::Array(_))
+    }
+
     pub fn set_raw(&mut self) {
         self.raw = true;
         self.is_mut = false; // WARNING fails for `raw = other_raw`
@@ -144,6 +176,20 @@ pub struct Reference {
     borrower: String,
     start: LineNumber,
     end: LineNumber,
+    // NOTE Taken through another pointer to the same value, like `&*m`, instead of the value
+    reborrowed_from: Option<String>,
+    // NOTE Taken from another pointer by a copy or pointer arithmetic, like `p + 2`
+    derived_from: Option<String>,
+}
+
+/// How a global (or `static` local) is translated, chosen by how it's accessed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GlobalStrategy {
+    Const,       // Never written
+    Static,      // Never written arrays
+    Atomic,      // Written integers
+    ThreadLocal, // Written floats and arrays, in a `Cell` or `RefCell`
+    StaticMut,   // Pointed to or defined elsewhere, accessed through `unsafe`
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -154,6 +200,33 @@ pub enum ReferenceType {
     ConstPtr,
 
     RcRefClone,
+    // NOTE A shared `&Cell`, the value is only ever copied in and out of it
+    CellRef,
+
+    // NOTE These aren't borrows, the pointer owns an anonymous heap allocation
+    OwnedBox,
+    OwnedVec,
+}
+
+impl ReferenceType {
+    pub fn is_owned(&self) -> bool {
+        matches!(self, ReferenceType::OwnedBox | ReferenceType::OwnedVec)
+    }
+
+    pub fn is_borrow(&self) -> bool {
+        matches!(
+            self,
+            ReferenceType::MutBorrowed | ReferenceType::ConstBorrowed
+        )
+    }
+
+    pub fn is_raw(&self) -> bool {
+        matches!(self, ReferenceType::MutPtr | ReferenceType::ConstPtr)
+    }
+
+    pub fn is_shared(&self) -> bool {
+        matches!(self, ReferenceType::RcRefClone | ReferenceType::CellRef)
+    }
 }
 
 impl Reference {
@@ -164,6 +237,8 @@ impl Reference {
             borrower: borrower.to_string(),
             start: line,
             end: line,
+            reborrowed_from: None,
+            derived_from: None,
         }
     }
 
@@ -204,23 +279,71 @@ impl Reference {
     }
 
     pub fn set_mut(&mut self) {
-        self.reference_type = ReferenceType::MutBorrowed;
+        // NOTE Owned allocations are mutated through their owner, they're never borrowed
+        self.reference_type = match self.reference_type {
+            ReferenceType::OwnedBox => ReferenceType::OwnedBox,
+            ReferenceType::OwnedVec => ReferenceType::OwnedVec,
+            // NOTE Raw pointers stay raw
+            ReferenceType::MutPtr | ReferenceType::ConstPtr => ReferenceType::MutPtr,
+            // NOTE Cells are written through shared references
+            ReferenceType::CellRef => ReferenceType::CellRef,
+            _ => ReferenceType::MutBorrowed,
+        };
+    }
+
+    pub fn set_owned(&mut self, reference_type: ReferenceType) {
+        assert!(reference_type.is_owned());
+        self.reference_type = reference_type;
     }
 
     pub fn set_rc(&mut self) {
         self.reference_type = ReferenceType::RcRefClone;
     }
 
+    pub fn set_cell(&mut self) {
+        self.reference_type = ReferenceType::CellRef;
+    }
+
     pub fn set_raw(&mut self) {
         self.reference_type = match &self.reference_type.clone() {
             ReferenceType::MutBorrowed => ReferenceType::MutPtr,
             ReferenceType::ConstBorrowed => ReferenceType::ConstPtr,
             ReferenceType::MutPtr => ReferenceType::MutPtr,
             ReferenceType::ConstPtr => ReferenceType::ConstPtr,
-            ReferenceType::RcRefClone => ReferenceType::MutPtr,
+            ReferenceType::RcRefClone | ReferenceType::CellRef => ReferenceType::MutPtr,
+            // NOTE Raw pointers can still be taken from the owner, but the owner stays owned
+            ReferenceType::OwnedBox => ReferenceType::OwnedBox,
+            ReferenceType::OwnedVec => ReferenceType::OwnedVec,
         };
     }
 
+    pub fn set_reborrow(&mut self, ptr_id: impl ToString) {
+        self.reborrowed_from = Some(ptr_id.to_string());
+    }
+
+    pub fn get_reborrowed_from(&self) -> Option<&str> {
+        self.reborrowed_from.as_deref()
+    }
+
+    /// A reborrow only lives while the pointer it's taken through isn't used, so they never overlap
+    pub fn reborrows(&self, other: &Reference) -> bool {
+        self.get_reborrowed_from() == Some(other.get_borrower())
+    }
+
+    pub fn set_derived(&mut self, ptr_id: impl ToString) {
+        self.derived_from = Some(ptr_id.to_string());
+    }
+
+    pub fn get_derived_from(&self) -> Option<&str> {
+        self.derived_from.as_deref()
+    }
+
+    /// A pointer derived from another one is taken on the line the other one is last used, which
+    /// is a reborrow through it rather than an overlap
+    pub fn derives(&self, other: &Reference) -> bool {
+        self.derived_from.as_deref() == Some(other.get_borrower())
+    }
+
     pub fn get_reference_to(&self) -> &str {
         &self.ref_to
     }
@@ -233,6 +356,14 @@ impl Reference {
         self.reference_type.clone()
     }
 
+    /// A borrow taken before a loop and used in it is held for every iteration, so it lasts until
+    /// the loop ends rather than its last use
+    pub fn extend_over_loop(&mut self, loop_lines: RangeInclusive<LineNumber>) {
+        if self.start < *loop_lines.start() && loop_lines.contains(&self.end) {
+            self.end = *loop_lines.end();
+        }
+    }
+
     pub fn get_range(&self) -> Range<LineNumber> {
         Range {
             start: self.start,
@@ -244,6 +375,55 @@ impl Reference {
 #[derive(Debug, Clone, PartialEq)]
 pub struct StructData {
     pub field_definitions: Vec<FieldDefinition>,
+    pub is_union: bool,
+    // Declared without an initializer somewhere
+    pub zero_initialized: bool,
+    pub tagged: Option<TaggedUnion>,
+    // Has a pointer to itself, like `struct Node { struct Node* next; }`
+    pub recursive: Option<RecursiveStrategy>,
+}
+
+/// How a recursive struct and the pointers to it are translated, decided by how the nodes are
+/// linked together
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecursiveStrategy {
+    /// Every node is owned by the one pointer linking to it, `Option<Box<Node>>`
+    Boxed,
+    /// Nodes are shared by several pointers, `Option<Rc<RefCell<Node>>>`
+    /// The fields pointing back to the node that links to them are `Weak`
+    Shared { weak_fields: Vec<String> },
+    /// Nodes link to each other in a cycle, so they're kept in a `Vec<Node>` and pointers to
+    /// them are indices into it
+    Arena,
+}
+
+/// What a pointer to a recursive struct is when the nodes are boxed
+/// Shared and arena pointers are all handles to their node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeRole {
+    /// `Option<Box<Node>>`, which gives up its node when it's linked
+    Owner,
+    /// `Option<&Node>` or `Option<&mut Node>`, borrowed from an owner or a link
+    Cursor { is_mut: bool },
+}
+
+/// A struct with an enum field saying which member of its union field is set
+/// `struct Shape { enum Kind kind; union Dims dims; };` becomes `struct Shape { dims: Dims }`,
+/// with `Dims` a Rust enum whose variants are `Kind`'s
+/// Only kept if every write to the tag is followed by a write to the matching member
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedUnion {
+    pub tag_field: String,
+    pub enum_id: String,
+    pub union_field: String,
+    pub union_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumData {
+    pub variants: Vec<(String, i32)>,
+    // Used as an integer somewhere, so it can't be a Rust enum
+    pub arithmetic: bool,
 }
 
 impl StructData {
@@ -261,6 +441,92 @@ impl StructData {
     }
 }
 
+/// A variable or a part of one, like a MIR place: the base variable followed by the fields and
+/// elements taken from it, so `s.a.b` is `s` projected through `a` and then `b`
+/// Its id (`s.a.b`, `arr[].x`) is the key of the place in the variable map, and it's only parsed
+/// when the place is declared
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Place {
+    pub base: String,
+    pub projections: Vec<Projection>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Projection {
+    Field(String),
+    // NOTE Indices aren't known while analyzing, so every element of an array is the same place
+    Index,
+}
+
+impl Place {
+    pub fn new(base: impl ToString) -> Self {
+        Place {
+            base: base.to_string(),
+            projections: Vec::new(),
+        }
+    }
+
+    /// Parses a place's id, `s.a.b` or `arr[].x`
+    pub fn parse(id: &str) -> Self {
+        let (base, mut rest) = id.split_at(id.find(['.', '[']).unwrap_or(id.len()));
+        let mut place = Place::new(base);
+        while !rest.is_empty() {
+            match rest.strip_prefix("[]") {
+                Some(after) => {
+                    place.projections.push(Projection::Index);
+                    rest = after;
+                }
+                None => {
+                    let after = &rest[1..];
+                    let end = after.find(['.', '[']).unwrap_or(after.len());
+                    place
+                        .projections
+                        .push(Projection::Field(after[..end].to_string()));
+                    rest = &after[end..];
+                }
+            }
+        }
+        place
+    }
+
+    /// Projects through a field path, which may be nested (`a.b`)
+    pub fn field(mut self, field_path: &str) -> Self {
+        self.projections.extend(
+            field_path
+                .split('.')
+                .map(|field_id| Projection::Field(field_id.to_string())),
+        );
+        self
+    }
+
+    pub fn index(mut self) -> Self {
+        self.projections.push(Projection::Index);
+        self
+    }
+
+    pub fn is_prefix_of(&self, other: &Place) -> bool {
+        self.base == other.base && other.projections.starts_with(&self.projections)
+    }
+
+    /// Places overlap when one contains the other, so `s` and `s.a.b` overlap while `s.a` and
+    /// `s.b` are disjoint
+    pub fn overlaps(&self, other: &Place) -> bool {
+        self.is_prefix_of(other) || other.is_prefix_of(self)
+    }
+}
+
+impl std::fmt::Display for Place {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.base)?;
+        self.projections
+            .iter()
+            .try_for_each(|projection| match projection {
+                Projection::Field(field_id) => write!(f, ".{field_id}"),
+                Projection::Index => write!(f, "[]"),
+            })
+    }
+}
+
 /// Collected during declaration
 #[derive(Debug, Clone, PartialEq)]
 pub struct FieldInfo {
@@ -275,3 +541,22 @@ pub struct FieldDefinition {
     pub ptr_type: Vec<ReferenceType>,
     pub c_type: CType,
 }
+
+/// An anonymous value created by `malloc` or `calloc`
+/// It's owned by the pointer it was first assigned to
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeapAllocation {
+    pub owner: String,
+    pub line: LineNumber,
+    pub frees: Vec<LineNumber>,
+}
+
+/// How a string literal is translated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StringMode {
+    /// Only used through `<string.h>`, so it's just its bytes (`b"hi"`)
+    Bytes,
+    /// Its characters are read directly, so the program may rely on the NUL terminator being there
+    /// (`b"hi\0"`), and it's read through `CStr` when passed to `<string.h>`
+    NulTerminated,
+}
@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+
+use crate::{
+    analysis_ctx::AnalysisContext,
+    analyzer::{find_ids, writes_first_arg},
+    ast::{AssignmentOpType, NodeType, TokenNode as Node},
+};
+
+// NOTE What's printed, which keeps prints in order
+const OUTPUT: &str = "<output>";
+
+/// The variables a statement reads and writes, which decide if it can be moved past another one
+#[derive(Debug, Default)]
+pub struct Effects {
+    reads: HashSet<String>,
+    writes: HashSet<String>,
+    // NOTE Anything that could touch memory the analysis doesn't follow, like calls and jumps
+    barrier: bool,
+}
+
+impl Effects {
+    pub fn of(statement: &Node, ctx: &AnalysisContext) -> Self {
+        let mut effects = Effects::default();
+        effects.visit(statement, ctx);
+        effects
+    }
+
+    /// Two statements can be swapped if neither reads or writes what the other one writes
+    pub fn independent(&self, other: &Effects) -> bool {
+        !self.barrier
+            && !other.barrier
+            && self.writes.is_disjoint(&other.reads)
+            && self.writes.is_disjoint(&other.writes)
+            && self.reads.is_disjoint(&other.writes)
+    }
+
+    fn read(&mut self, id: &str, ctx: &AnalysisContext) {
+        self.reads.insert(ctx.base_var(id).to_string());
+    }
+
+    fn write(&mut self, id: &str, ctx: &AnalysisContext) {
+        self.writes.insert(ctx.base_var(id).to_string());
+    }
+
+    /// Reading or writing through a pointer touches everything it could point to
+    fn pointees(&mut self, ptr_id: &str, ctx: &AnalysisContext) -> Vec<String> {
+        let Some(ptr_data) = ctx.current_scope().variables.get(ptr_id) else {
+            self.barrier = true;
+            return vec![];
+        };
+        if ptr_data.node_role.is_some() || ptr_data.points_to.is_empty() {
+            self.barrier = true;
+            return vec![];
+        }
+
+        let mut pointees: Vec<String> = ptr_data
+            .points_to
+            .iter()
+            .map(|reference| reference.borrow().get_reference_to().to_string())
+            .collect();
+        pointees.clone().iter().for_each(|pointee| {
+            if ctx
+                .current_scope()
+                .variables
+                .get(pointee)
+                .is_some_and(|data| data.is_ptr())
+            {
+                pointees.extend(self.pointees(pointee, ctx));
+            }
+        });
+        pointees
+    }
+
+    fn read_through(&mut self, ptr_id: &str, ctx: &AnalysisContext) {
+        self.read(ptr_id, ctx);
+        self.pointees(ptr_id, ctx)
+            .iter()
+            .for_each(|pointee| self.read(pointee, ctx));
+    }
+
+    fn write_through(&mut self, ptr_id: &str, op: &AssignmentOpType, ctx: &AnalysisContext) {
+        self.read(ptr_id, ctx);
+        self.pointees(ptr_id, ctx).iter().for_each(|pointee| {
+            self.write(pointee, ctx);
+            if *op != AssignmentOpType::Eq {
+                self.read(pointee, ctx);
+            }
+        });
+    }
+
+    fn is_ptr(id: &str, ctx: &AnalysisContext) -> bool {
+        ctx.current_scope()
+            .variables
+            .get(ctx.base_var(id))
+            .is_some_and(|data| data.is_ptr() || data.node_role.is_some())
+    }
+
+    /// What a pointer is set to only borrows the array it decays from, like `&arr` does, so
+    /// `int* p = arr;` doesn't read `arr`
+    fn visit_borrowed(&mut self, expr: &Node, ctx: &AnalysisContext) {
+        match &expr.token {
+            NodeType::Id(id) if ctx.get_var(id).is_array() => {}
+            NodeType::Add | NodeType::Sub => expr
+                .children
+                .iter()
+                .flat_map(|children| children.iter())
+                .for_each(|child| self.visit_borrowed(child, ctx)),
+            _ => self.visit(expr, ctx),
+        }
+    }
+
+    fn visit(&mut self, root: &Node, ctx: &AnalysisContext) {
+        match &root.token {
+            NodeType::Id(id) => self.read(id, ctx),
+            NodeType::Adr(_) => {}
+            NodeType::AdrIndex { indices, .. } => {
+                indices.iter().for_each(|index| self.visit(index, ctx))
+            }
+            NodeType::DeRef(expr) => {
+                self.visit(expr, ctx);
+                find_ids(expr)
+                    .iter()
+                    .filter(|id| Self::is_ptr(id, ctx))
+                    .for_each(|id| self.read_through(id, ctx));
+            }
+            NodeType::Assignment(op, id) if Self::is_ptr(id, ctx) => {
+                self.write(id, ctx);
+                if *op != AssignmentOpType::Eq {
+                    self.read(id, ctx);
+                }
+                root.children
+                    .iter()
+                    .flat_map(|children| children.iter())
+                    .for_each(|child| self.visit_borrowed(child, ctx));
+                return;
+            }
+            NodeType::Assignment(op, id) => {
+                self.write(id, ctx);
+                if *op != AssignmentOpType::Eq {
+                    self.read(id, ctx);
+                }
+            }
+            NodeType::DerefAssignment(op, adr) => {
+                self.visit(adr, ctx);
+                find_ids(adr)
+                    .iter()
+                    .filter(|id| Self::is_ptr(id, ctx))
+                    .for_each(|id| self.write_through(id, op, ctx));
+            }
+            NodeType::Declaration(id, _, _) | NodeType::ArrayDeclaration(id, _, _) => {
+                self.write(id, ctx)
+            }
+            NodeType::PtrDeclaration(id, _, expr) => {
+                self.write(id, ctx);
+                self.visit_borrowed(expr, ctx);
+            }
+            NodeType::StructDeclaration { var_id, exprs, .. } => {
+                self.write(var_id, ctx);
+                exprs.iter().for_each(|expr| self.visit(expr, ctx));
+            }
+            NodeType::StructFieldId { var_id, .. } => self.read(var_id, ctx),
+            NodeType::StructFieldAssignment {
+                var_id,
+                assignment_op,
+                expr,
+                ..
+            } => {
+                self.write(var_id, ctx);
+                if *assignment_op != AssignmentOpType::Eq {
+                    self.read(var_id, ctx);
+                }
+                self.visit(expr, ctx);
+            }
+            NodeType::PtrFieldId { ptr_id, .. } => self.read_through(ptr_id, ctx),
+            NodeType::PtrFieldAssignment {
+                ptr_id,
+                assignment_op,
+                expr,
+                ..
+            } => {
+                self.write_through(ptr_id, assignment_op, ctx);
+                self.visit(expr, ctx);
+            }
+            NodeType::IndexArray { id, indices, .. } => {
+                match Self::is_ptr(id, ctx) {
+                    true => self.read_through(id, ctx),
+                    false => self.read(id, ctx),
+                }
+                indices.iter().for_each(|index| self.visit(index, ctx));
+            }
+            NodeType::IndexArrayAssignment {
+                id,
+                assignment_op,
+                rside,
+                lside,
+                ..
+            } => {
+                match Self::is_ptr(id, ctx) {
+                    true => self.write_through(id, assignment_op, ctx),
+                    false => {
+                        self.write(id, ctx);
+                        self.read(id, ctx);
+                    }
+                }
+                self.visit(rside, ctx);
+                lside.iter().for_each(|index| self.visit(index, ctx));
+            }
+            // NOTE Only calls known to just read their arguments are followed
+            NodeType::FunctionCall(name) if matches!(name.as_str(), "printf" | "fprintf") => {
+                self.write(OUTPUT, ctx)
+            }
+            NodeType::PutChar => self.write(OUTPUT, ctx),
+            NodeType::FunctionCall(name) if writes_first_arg(name) => {
+                let args = root.children.as_deref().unwrap_or_default();
+                if let Some(NodeType::Id(id)) = args.first().map(|arg| &arg.token) {
+                    match Self::is_ptr(id, ctx) {
+                        true => self.write_through(id, &AssignmentOpType::Eq, ctx),
+                        false => self.write(id, ctx),
+                    }
+                }
+            }
+            NodeType::FunctionCall(_)
+            | NodeType::Return { .. }
+            | NodeType::Break
+            | NodeType::Asm { .. }
+            | NodeType::Assert
+            | NodeType::Global(_) => self.barrier = true,
+            _ => {}
+        }
+
+        root.children
+            .iter()
+            .flat_map(|children| children.iter())
+            .for_each(|child| self.visit(child, ctx));
+    }
+}
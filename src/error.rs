@@ -25,6 +25,24 @@ pub enum ErrType {
     ExpectedArithExprOp,
     ExpectedArithTermOp,
     ExpectedComma,
+    // An initializer list longer than its array
+    TooManyItems,
+
+    // Analysis errors, these wrap the id of the offending pointer
+    UseAfterFree(String),
+    DoubleFree(String),
+    // Wraps why the format string can't be translated
+    InvalidFormat(String),
+    // Wraps the error `rustc` still reports on the translation
+    Unverified(String),
+    // Wraps the id of a pointer that would be raw, with `--deny-unsafe`
+    Unsafe(String),
+    // A `malloc` whose size has no `sizeof`
+    UnsizedAllocation,
+    // Wraps the id of a non-`char` array `memset` fills with something other than zeros
+    NonZeroFill(String),
+    // Wraps the borrow errors no fix was found for
+    UnfixedBorrows(Vec<String>),
 }
 
 #[allow(unused)]
@@ -33,3 +51,46 @@ pub struct RhErr {
     pub err: ErrType,
     pub line: usize,
 }
+
+impl std::fmt::Display for RhErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.err {
+            ErrType::UseAfterFree(id) => {
+                write!(f, "line {}: `{id}` is used after being freed", self.line)
+            }
+            ErrType::DoubleFree(id) => {
+                write!(f, "line {}: `{id}` is freed more than once", self.line)
+            }
+            ErrType::InvalidFormat(reason) => {
+                write!(f, "line {}: can't translate format string, {reason}", self.line)
+            }
+            ErrType::Unverified(message) => {
+                write!(
+                    f,
+                    "line {}: the translation doesn't compile, {message}",
+                    self.line
+                )
+            }
+            ErrType::Unsafe(id) => {
+                write!(f, "line {}: `{id}` would be a raw pointer", self.line)
+            }
+            ErrType::UnsizedAllocation => write!(
+                f,
+                "line {}: `malloc` needs a `sizeof` to know what it allocates",
+                self.line
+            ),
+            ErrType::NonZeroFill(id) => write!(
+                f,
+                "line {}: `memset` can only fill `{id}` with zeros, since its items aren't bytes",
+                self.line
+            ),
+            ErrType::UnfixedBorrows(errors) => write!(
+                f,
+                "line {}: no fix was found for {}",
+                self.line,
+                errors.join(", ")
+            ),
+            err => write!(f, "line {}: {:?}", self.line, err),
+        }
+    }
+}
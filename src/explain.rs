@@ -0,0 +1,392 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
+
+use crate::{
+    analysis_ctx::AnalysisContext,
+    analyzer::sub_nodes,
+    annotater::AnnotatedNode,
+    ast::TokenNode as Node,
+    checker::BorrowError,
+    data_model::LineNumber,
+    solver::{Fix, UNFIXABLE},
+};
+
+/// Why every variable ended up with the type it has in the translation, from the borrow errors
+/// found on it to the fixes tried and the ones picked
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Explanations {
+    // NOTE Ordered by id, so the report comes out the same every time
+    pub variables: BTreeMap<String, Explanation>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Explanation {
+    pub events: Vec<Event>,
+    // The type the variable ended up with, `*mut i32`
+    pub choice: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    // A borrow error the checker found, with the C lines each pointer borrows the value over
+    Error {
+        error: BorrowError,
+        borrows: Vec<(String, Range<usize>)>,
+    },
+    // A way of fixing errors, without a cost if it couldn't be done
+    Tried {
+        strategy: String,
+        fixes: Vec<Fix>,
+        cost: Option<usize>,
+        picked: bool,
+    },
+    Fixed(Fix),
+    // An error `rustc` still found in the translation, and what was done about it
+    Rustc {
+        message: String,
+        action: String,
+    },
+}
+
+impl Explanations {
+    fn log(&mut self, id: &str, event: Event) {
+        let events = &mut self.variables.entry(id.to_string()).or_default().events;
+        // NOTE The same errors are found by every round of fixes until they're fixed
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+
+    fn log_error_ids(&mut self, error: &BorrowError, event: Event) {
+        let mut ids = error.ptr_ids();
+        ids.push(error.value_id());
+        ids.iter().for_each(|id| self.log(id, event.clone()));
+    }
+
+    /// Logged for the value and the pointers borrowing it
+    pub fn error(&mut self, error: &BorrowError, root: &Node, ctx: &AnalysisContext) {
+        let borrows = error
+            .ptr_ids()
+            .iter()
+            .filter_map(|ptr_id| {
+                let reference = ctx.get_var(ptr_id).reference_to_var(error.value_id())?;
+                let range = reference.borrow().get_range();
+                let lines = c_line(root, range.start).unwrap_or(range.start)
+                    ..c_line(root, range.end).unwrap_or(range.end);
+                Some((ptr_id.to_string(), lines))
+            })
+            .collect();
+        let event = Event::Error {
+            error: error.clone(),
+            borrows,
+        };
+        self.log_error_ids(error, event);
+    }
+
+    /// A fix for the error that couldn't be made, like a rearrangement with nothing to move
+    pub fn failed(&mut self, strategy: &str, error: &BorrowError) {
+        let event = Event::Tried {
+            strategy: strategy.to_string(),
+            fixes: vec![],
+            cost: None,
+            picked: false,
+        };
+        self.log_error_ids(error, event);
+    }
+
+    /// Fixes weighed against others for the errors, logged for every variable they name
+    pub fn weighed(
+        &mut self,
+        strategy: &str,
+        errors: &[&BorrowError],
+        fixes: &[Fix],
+        cost: usize,
+        picked: bool,
+    ) {
+        let event = Event::Tried {
+            strategy: strategy.to_string(),
+            fixes: fixes.to_vec(),
+            cost: (cost != UNFIXABLE).then_some(cost),
+            picked,
+        };
+        let mut ids: Vec<&str> = errors
+            .iter()
+            .flat_map(|error| {
+                let mut ids = error.ptr_ids();
+                ids.push(error.value_id());
+                ids
+            })
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids.iter().for_each(|id| self.log(id, event.clone()));
+    }
+
+    /// Adds what was logged separately, with none of its fixes picked if they were passed over
+    pub fn merge(&mut self, other: Explanations, passed_over: bool) {
+        other.variables.into_iter().for_each(|(id, explanation)| {
+            explanation.events.into_iter().for_each(|mut event| {
+                if let Event::Tried { picked, .. } = &mut event {
+                    *picked &= !passed_over;
+                }
+                self.log(&id, event);
+            })
+        });
+    }
+
+    /// Logged for every variable whose type the fix changes
+    pub fn fixed(&mut self, fix: &Fix, ctx: &AnalysisContext) {
+        let ids: Vec<String> = match fix {
+            Fix::Rearrange(error) => error
+                .ptr_ids()
+                .into_iter()
+                .chain([error.value_id()])
+                .map(str::to_string)
+                .collect(),
+            Fix::Raw(ptr_id) => vec![ptr_id.clone()],
+            Fix::Reborrow { ptr_id, .. } => vec![ptr_id.clone()],
+            Fix::Clone { ptr_id, value_id } => vec![ptr_id.clone(), value_id.clone()],
+            Fix::Cell(value_id) | Fix::Rc(value_id) => ctx
+                .get_var(value_id)
+                .pointed_to
+                .iter()
+                .map(|reference| reference.borrow().get_borrower().to_string())
+                .chain([value_id.clone()])
+                .collect(),
+        };
+        ids.iter()
+            .for_each(|id| self.log(id, Event::Fixed(fix.clone())));
+    }
+
+    pub fn rustc(&mut self, ids: &[String], message: &str, action: &str) {
+        let event = Event::Rustc {
+            message: message.to_string(),
+            action: action.to_string(),
+        };
+        ids.iter().for_each(|id| self.log(id, event.clone()));
+    }
+
+    /// Records the type every variable ended up with, as the translation declares it
+    ///
+    /// # Important
+    /// The values without a declaration of their own, like heap allocations, keep their C type
+    pub fn finish(&mut self, annotated: &AnnotatedNode, ctx: &AnalysisContext) {
+        let mut declared: HashMap<String, String> = HashMap::new();
+        annotated
+            .declared_types()
+            .into_iter()
+            .for_each(|(id, rust_t)| {
+                declared.entry(id).or_insert(rust_t);
+            });
+        ctx.current_scope()
+            .variables
+            .iter()
+            .for_each(|(id, var_data)| {
+                let choice = declared
+                    .get(id)
+                    .cloned()
+                    .unwrap_or_else(|| var_data.var_type.to_rust_type());
+                self.variables.entry(id.clone()).or_default().choice = choice.trim().to_string();
+            });
+    }
+
+    /// Every variable with its type, and below it how it got there
+    pub fn report(&self) -> String {
+        self.variables
+            .iter()
+            .map(|(id, explanation)| {
+                let events: String = explanation
+                    .events
+                    .iter()
+                    .map(|event| format!("    {}\n", describe_event(event)))
+                    .collect();
+                let events = match events.is_empty() {
+                    true => "    no borrow errors\n".to_string(),
+                    false => events,
+                };
+                format!("{id}: {}\n{events}", explanation.choice)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn to_json(&self) -> String {
+        let variables: Vec<String> = self
+            .variables
+            .iter()
+            .map(|(id, explanation)| {
+                let events: Vec<String> = explanation.events.iter().map(event_json).collect();
+                let events = match events.is_empty() {
+                    true => "[]".to_string(),
+                    false => format!("[\n        {}\n      ]", events.join(",\n        ")),
+                };
+                format!(
+                    "    {}: {{\n      \"type\": {},\n      \"events\": {events}\n    }}",
+                    json_string(id),
+                    json_string(&explanation.choice),
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"variables\": {{\n{}\n  }}\n}}\n",
+            variables.join(",\n")
+        )
+    }
+}
+
+/// The C line of the statement on the (possibly renumbered) line
+pub fn c_line(root: &Node, line: LineNumber) -> Option<usize> {
+    match root.line == line {
+        true => Some(root.c_line),
+        false => sub_nodes(root)
+            .into_iter()
+            .find_map(|node| c_line(node, line)),
+    }
+}
+
+fn describe_fix(fix: &Fix) -> String {
+    match fix {
+        Fix::Rearrange(error) => {
+            let ptrs: Vec<String> = error.ptr_ids().iter().map(|id| format!("`{id}`")).collect();
+            format!(
+                "move statements out of the borrows of `{}` by {}",
+                error.value_id(),
+                ptrs.join(" and ")
+            )
+        }
+        Fix::Raw(ptr_id) => format!("make `{ptr_id}` a raw pointer"),
+        Fix::Reborrow {
+            ptr_id,
+            from_ptr_id,
+            value_id: _,
+        } => format!("take `{ptr_id}` through `{from_ptr_id}`, as `&*{from_ptr_id}`"),
+        Fix::Clone { ptr_id, value_id } => {
+            format!("copy `{value_id}` before `{ptr_id}` borrows it")
+        }
+        Fix::Cell(value_id) => format!("share `{value_id}` in a `Cell`"),
+        Fix::Rc(value_id) => format!("share `{value_id}` in an `Rc<RefCell>`"),
+    }
+}
+
+fn describe_event(event: &Event) -> String {
+    match event {
+        Event::Error { error, borrows } => {
+            let borrows: Vec<String> = borrows
+                .iter()
+                .map(|(ptr_id, lines)| {
+                    format!(
+                        "`{ptr_id}` borrows it lines {} to {}",
+                        lines.start, lines.end
+                    )
+                })
+                .collect();
+            format!(
+                "error: {} of `{}`, {}",
+                error.kind(),
+                error.value_id(),
+                borrows.join(" and ")
+            )
+        }
+        Event::Tried {
+            strategy,
+            fixes,
+            cost,
+            picked,
+        } => {
+            let fixes: Vec<String> = fixes.iter().map(describe_fix).collect();
+            let fixes = match fixes.is_empty() {
+                true => String::new(),
+                false => format!(" ({})", fixes.join(", ")),
+            };
+            let outcome = match (cost, picked) {
+                (None, _) => "not possible".to_string(),
+                (Some(cost), true) => format!("costs {cost}, picked"),
+                (Some(cost), false) => format!("costs {cost}"),
+            };
+            format!("tried {strategy}{fixes}: {outcome}")
+        }
+        Event::Fixed(fix) => format!("fixed: {}", describe_fix(fix)),
+        Event::Rustc { message, action } => format!("rustc: {message}, {action}"),
+    }
+}
+
+fn fix_json(fix: &Fix) -> String {
+    let (kind, ids) = match fix {
+        Fix::Rearrange(error) => {
+            let mut ids = error.ptr_ids();
+            ids.push(error.value_id());
+            ("Rearrange", ids)
+        }
+        Fix::Raw(ptr_id) => ("Raw", vec![ptr_id.as_str()]),
+        Fix::Reborrow {
+            ptr_id,
+            from_ptr_id,
+            value_id,
+        } => ("Reborrow", vec![ptr_id.as_str(), from_ptr_id, value_id]),
+        Fix::Clone { ptr_id, value_id } => ("Clone", vec![ptr_id.as_str(), value_id]),
+        Fix::Cell(value_id) => ("Cell", vec![value_id.as_str()]),
+        Fix::Rc(value_id) => ("Rc", vec![value_id.as_str()]),
+    };
+    let ids: Vec<String> = ids.into_iter().map(json_string).collect();
+    format!("{{\"kind\": \"{kind}\", \"ids\": [{}]}}", ids.join(", "))
+}
+
+fn event_json(event: &Event) -> String {
+    match event {
+        Event::Error { error, borrows } => {
+            let borrows: Vec<String> = borrows
+                .iter()
+                .map(|(ptr_id, lines)| {
+                    format!(
+                        "{{\"ptr\": {}, \"lines\": [{}, {}]}}",
+                        json_string(ptr_id),
+                        lines.start,
+                        lines.end
+                    )
+                })
+                .collect();
+            format!(
+                "{{\"event\": \"error\", \"kind\": \"{}\", \"value\": {}, \"borrows\": [{}]}}",
+                error.kind(),
+                json_string(error.value_id()),
+                borrows.join(", ")
+            )
+        }
+        Event::Tried {
+            strategy,
+            fixes,
+            cost,
+            picked,
+        } => {
+            let fixes: Vec<String> = fixes.iter().map(fix_json).collect();
+            let cost = cost.map_or("null".to_string(), |cost| cost.to_string());
+            format!(
+                "{{\"event\": \"tried\", \"strategy\": {}, \"fixes\": [{}], \"cost\": {cost}, \"picked\": {picked}}}",
+                json_string(strategy),
+                fixes.join(", ")
+            )
+        }
+        Event::Fixed(fix) => format!("{{\"event\": \"fixed\", \"fix\": {}}}", fix_json(fix)),
+        Event::Rustc { message, action } => format!(
+            "{{\"event\": \"rustc\", \"message\": {}, \"action\": {}}}",
+            json_string(message),
+            json_string(action)
+        ),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    s.chars().for_each(|c| match c {
+        '"' => json.push_str("\\\""),
+        '\\' => json.push_str("\\\\"),
+        '\n' => json.push_str("\\n"),
+        '\t' => json.push_str("\\t"),
+        c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+        c => json.push(c),
+    });
+    json.push('"');
+    json
+}
@@ -0,0 +1,260 @@
+use crate::{
+    analysis_ctx::AnalysisContext,
+    ast::{NodeType, TokenNode as Node},
+    data_model::Place,
+    lexer::CType,
+    scope::ScopeType,
+};
+
+/// The C type of a formatted value, as far as format specifiers care
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormatType {
+    Int,
+    Char,
+    Float,
+    Str,
+    Unknown,
+}
+
+/// How an argument is converted so rust formats it the same way C does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormatArg {
+    Display,
+    Char,     // `(c) as u8 as char`
+    Unsigned, // `(n) as u32`
+    Str,      // `String::from_utf8_lossy(s)`
+    // NOTE A `char` is promoted to a (signed) `int` like C does, not printed as the `u8` it's in
+    SignedChar,   // `(c) as i8 as i32`
+    UnsignedChar, // `(c) as i8 as u32`
+}
+
+/// The index of the format string in the arguments of a `printf`-family function
+pub fn format_index(name: &str) -> Option<usize> {
+    match name {
+        "printf" => Some(0),
+        "fprintf" | "sprintf" => Some(1),
+        "snprintf" => Some(2),
+        _ => None,
+    }
+}
+
+pub fn is_print_function(name: &str) -> bool {
+    format_index(name).is_some() || name == "puts"
+}
+
+/// Translates a C format string into a rust one, checking the arguments against it
+/// Errors are a description of what can't be translated
+pub fn translate_format(
+    format: &str,
+    arg_types: &[FormatType],
+) -> Result<(String, Vec<FormatArg>), String> {
+    let mut rust_format = String::with_capacity(format.len());
+    let mut args = Vec::with_capacity(arg_types.len());
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => rust_format.push_str("{{"),
+            '}' => rust_format.push_str("}}"),
+            '%' if chars.peek() == Some(&'%') => {
+                chars.next();
+                rust_format.push('%');
+            }
+            '%' => {
+                let mut flags = String::new();
+                while let Some(flag) = chars.next_if(|c| "-0+ #".contains(*c)) {
+                    flags.push(flag);
+                }
+                if chars.peek() == Some(&'*') {
+                    return Err("`*` widths aren't supported".into());
+                }
+                let mut width = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    width.push(digit);
+                }
+                let precision = match chars.next_if_eq(&'.') {
+                    Some(_) => {
+                        let mut precision = String::new();
+                        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                            precision.push(digit);
+                        }
+                        Some(precision)
+                    }
+                    None => None,
+                };
+                // NOTE Every integer is an i32 and every float is promoted to a double, so the
+                // length doesn't change anything
+                while chars.next_if(|c| "hlz".contains(*c)).is_some() {}
+
+                let Some(conversion) = chars.next() else {
+                    return Err("the format string ends in the middle of a `%`".into());
+                };
+                if flags.contains(' ') {
+                    return Err(format!("the ` ` flag of `%{conversion}` has no rust equivalent"));
+                }
+
+                let arg_type = *arg_types
+                    .get(args.len())
+                    .ok_or_else(|| format!("`%{conversion}` is missing an argument"))?;
+                let (rust_type, arg, expected) = match conversion {
+                    'd' | 'i' => ("", FormatArg::Display, [FormatType::Int, FormatType::Char]),
+                    'u' => ("", FormatArg::Unsigned, [FormatType::Int, FormatType::Char]),
+                    'x' => ("x", FormatArg::Display, [FormatType::Int, FormatType::Char]),
+                    'X' => ("X", FormatArg::Display, [FormatType::Int, FormatType::Char]),
+                    'o' => ("o", FormatArg::Display, [FormatType::Int, FormatType::Char]),
+                    'c' => ("", FormatArg::Char, [FormatType::Int, FormatType::Char]),
+                    's' => ("", FormatArg::Str, [FormatType::Str, FormatType::Str]),
+                    'f' | 'F' => ("", FormatArg::Display, [FormatType::Float, FormatType::Float]),
+                    'e' | 'E' | 'g' | 'G' | 'a' | 'A' => {
+                        return Err(format!(
+                            "`%{conversion}` prints exponents differently than rust does"
+                        ))
+                    }
+                    conversion => return Err(format!("`%{conversion}` isn't supported")),
+                };
+                let arg = match (arg, arg_type) {
+                    (FormatArg::Display, FormatType::Char) => FormatArg::SignedChar,
+                    (FormatArg::Unsigned, FormatType::Char) => FormatArg::UnsignedChar,
+                    (arg, _) => arg,
+                };
+                if arg_type != FormatType::Unknown && !expected.contains(&arg_type) {
+                    return Err(format!(
+                        "`%{conversion}` expects {:?}, but argument {} is {arg_type:?}",
+                        expected[0],
+                        args.len() + 1
+                    ));
+                }
+
+                let precision = match (conversion, precision) {
+                    ('f' | 'F', None) => Some("6".to_string()),
+                    ('d' | 'i' | 'u' | 'x' | 'X' | 'o' | 'c', Some(_)) => {
+                        return Err(format!("the precision of `%{conversion}` isn't supported"))
+                    }
+                    (_, precision) => precision,
+                };
+
+                rust_format.push_str("{:");
+                // NOTE C right-aligns everything, rust left-aligns strings
+                if flags.contains('-') {
+                    rust_format.push('<');
+                } else if !width.is_empty() && !flags.contains('0') {
+                    rust_format.push('>');
+                }
+                if flags.contains('+') {
+                    rust_format.push('+');
+                }
+                if flags.contains('#') {
+                    rust_format.push('#');
+                }
+                if flags.contains('0') && !flags.contains('-') {
+                    rust_format.push('0');
+                }
+                rust_format.push_str(&width);
+                if let Some(precision) = precision {
+                    rust_format.push('.');
+                    rust_format.push_str(&precision);
+                }
+                rust_format.push_str(rust_type);
+                rust_format.push('}');
+
+                args.push(arg);
+            }
+            c => rust_format.push(c),
+        }
+    }
+
+    if args.len() != arg_types.len() {
+        return Err(format!(
+            "there are {} arguments, but only {} are formatted",
+            arg_types.len(),
+            args.len()
+        ));
+    }
+
+    Ok((rust_format.replace("{:}", "{}"), args))
+}
+
+/// The type of an expression being formatted
+pub fn format_type(root: &Node, ctx: &AnalysisContext) -> FormatType {
+    let field_type = |t: &CType, field_id: &str| match t {
+        CType::Struct(struct_id) => ctx
+            .field_definition(struct_id, field_id)
+            .filter(|field| field.ptr_type.is_empty())
+            .map_or(FormatType::Unknown, |field| c_format_type(&field.c_type)),
+        _ => FormatType::Unknown,
+    };
+    let var_type = |id: &str| {
+        let var_data = ctx.get_var(id);
+        match (&var_data.var_type, var_data.is_ptr()) {
+            (CType::Char, true) => FormatType::Str,
+            (CType::Array(t), _) if **t == CType::Char => FormatType::Str,
+            (CType::Array(_), _) => FormatType::Unknown,
+            (_, true) => FormatType::Unknown,
+            (t, false) => c_format_type(t),
+        }
+    };
+
+    match &root.token {
+        NodeType::NumLiteral(_) | NodeType::SizeOf(_, _) | NodeType::EnumConstant { .. } => {
+            FormatType::Int
+        }
+        NodeType::FloatLiteral(_) => FormatType::Float,
+        NodeType::StrLiteral(_) => FormatType::Str,
+        NodeType::Id(id) => var_type(id),
+        NodeType::StructFieldId { var_id, field_id } => {
+            var_type(&Place::new(var_id).field(field_id).to_string())
+        }
+        NodeType::PtrFieldId { ptr_id, field_id } => {
+            field_type(&ctx.get_var(ptr_id).var_type, field_id)
+        }
+        NodeType::IndexArray {
+            id,
+            indices: _,
+            field_id,
+        } => {
+            let mut t = &ctx.get_var(id).var_type;
+            while let CType::Array(sub_type) = t {
+                t = sub_type;
+            }
+            match field_id {
+                Some(field_id) => field_type(t, field_id),
+                None => c_format_type(t),
+            }
+        }
+        NodeType::DeRef(expr) => match &expr.token {
+            NodeType::Id(id) if ctx.get_var(id).is_ptr() => {
+                c_format_type(&ctx.get_var(id).var_type)
+            }
+            _ => FormatType::Unknown,
+        },
+        NodeType::FunctionCall(name) => ctx
+            .scopes
+            .iter()
+            .find_map(|scope| match &scope.scope_type {
+                ScopeType::Function {
+                    name: function_name,
+                    ret,
+                    args: _,
+                } if function_name == name => Some(c_format_type(ret)),
+                _ => None,
+            })
+            .unwrap_or(FormatType::Unknown),
+        NodeType::Add | NodeType::Sub | NodeType::Mul | NodeType::Div => {
+            let children = root.children.as_ref().unwrap();
+            match children.iter().any(|child| format_type(child, ctx) == FormatType::Float) {
+                true => FormatType::Float,
+                false => FormatType::Int,
+            }
+        }
+        _ => FormatType::Unknown,
+    }
+}
+
+fn c_format_type(t: &CType) -> FormatType {
+    match t {
+        CType::Int | CType::Enum(_) => FormatType::Int,
+        CType::Char => FormatType::Char,
+        CType::Float | CType::Double => FormatType::Float,
+        _ => FormatType::Unknown,
+    }
+}
@@ -0,0 +1,974 @@
+use std::collections::HashSet;
+
+// NOTE Loops run at most this many times, and nest at most this deep, so values stay small enough
+// to never overflow
+const MAX_TIMES: i32 = 3;
+const MAX_LOOP_DEPTH: usize = 2;
+const MAX_STATEMENTS: usize = 12;
+
+/// xorshift64*, so that a seed always makes the same program
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next() % 100 < percent
+    }
+
+    fn pick<T: Clone>(&mut self, items: &[T]) -> Option<T> {
+        match items.is_empty() {
+            true => None,
+            false => Some(items[self.below(items.len())].clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Type {
+    Int,
+    Double,
+    Char,
+}
+
+impl Type {
+    fn to_c(self) -> &'static str {
+        match self {
+            Type::Int => "int",
+            Type::Double => "double",
+            Type::Char => "char",
+        }
+    }
+
+    fn format(self) -> &'static str {
+        match self {
+            Type::Int => "%d",
+            Type::Double => "%f",
+            Type::Char => "%c",
+        }
+    }
+
+    /// A literal of the type, which a shrunk expression is replaced by
+    fn literal(self) -> Expr {
+        match self {
+            Type::Int => Expr::Lit(1),
+            Type::Double => Expr::Half(1),
+            Type::Char => Expr::Char(b'a'),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Lit(i32),
+    // `n.5`, which doubles hold exactly
+    Half(i32),
+    // `'a'`
+    Char(u8),
+    Var(String),
+    // `*p` or `**p`
+    Deref(String, usize),
+    // `s.f0`
+    Field(String, usize),
+    // `p->f0`
+    PtrField(String, usize),
+    // `arr[2]`, or `arr[i]` for a loop counter
+    Index(String, Box<Expr>),
+    Call(String, Vec<Expr>),
+    // `e + 3`, or `e - 3` when negative
+    Add(Box<Expr>, i32),
+    // `a + b`, which can mix an `int` and a `double`
+    Sum(Box<Expr>, Box<Expr>),
+    // `strlen(str)`
+    StrLen(String),
+    // `*(p + 2)` for a pointer into an array
+    ArrayPtrDeref(String, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Int(String, Expr),
+    Double(String, Expr),
+    Char(String, u8),
+    Assign(String, Expr),
+    // `int* p = &n;`, or `int** p = &q;` when it's two levels deep
+    Ptr {
+        id: String,
+        level: usize,
+        target: String,
+    },
+    DerefAssign {
+        ptr_id: String,
+        level: usize,
+        expr: Expr,
+    },
+    // `int arr[4] = { a, b };`, the items left out are zeroed
+    Array {
+        id: String,
+        t: Type,
+        len: usize,
+        items: Vec<Expr>,
+    },
+    IndexAssign(String, Expr, Expr),
+    Struct(String, Vec<Expr>),
+    StructPtr(String, String),
+    FieldAssign(String, usize, Expr),
+    PtrFieldAssign(String, usize, Expr),
+    // `int i = 0; while (i != times) { ...; i++; }`
+    Loop {
+        counter: String,
+        times: i32,
+        body: Vec<Stmt>,
+    },
+    Print(Type, Expr),
+    // `int* h = malloc(sizeof(int)); *h = e;`
+    Malloc(String, Expr),
+    Free(String),
+    // `char str[] = "abc";`
+    Str(String, String),
+    // `printf("%s\n", str);`
+    PrintStr(String),
+    PutChar(Expr),
+    // `int* p = arr + 1;`
+    ArrayPtr {
+        id: String,
+        array: String,
+        offset: usize,
+    },
+    // `*(p + 2) = e;`
+    ArrayPtrAssign(String, usize, Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Vec<Stmt>,
+    pub ret: Expr,
+}
+
+/// A random C program within the subset sonder translates
+///
+/// # Important
+/// Programs are kept structured rather than as C, so a failing one can be shrunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub fields: Vec<Type>,
+    // `int g = 3;`, read, written and pointed to from every function
+    pub globals: Vec<(String, i32)>,
+    pub functions: Vec<Function>,
+    pub main: Vec<Stmt>,
+}
+
+/// What's declared where a statement is generated, by what can be done with it
+#[derive(Debug, Clone, Default)]
+struct Scope {
+    ints: Vec<String>,
+    doubles: Vec<String>,
+    chars: Vec<String>,
+    // NOTE Loop counters are read, but never written or pointed to
+    counters: Vec<String>,
+    ptrs: Vec<(String, usize)>,
+    arrays: Vec<(String, Type, usize)>,
+    structs: Vec<String>,
+    struct_ptrs: Vec<String>,
+    globals: Vec<String>,
+    // Pointers to `int`s from `malloc`, which are only dereferenced
+    heap: Vec<String>,
+    strings: Vec<String>,
+    // Each with how many items of its array are left from where it points
+    array_ptrs: Vec<(String, usize)>,
+    // NOTE An array is only reached through the one pointer into it once there is one, sonder
+    // rejects the array being borrowed twice
+    pointed_arrays: Vec<(String, Type, usize)>,
+    // NOTE Only statements that run once add two values, so nothing can double on every iteration
+    // until it overflows
+    once: bool,
+}
+
+struct Generator {
+    rng: Rng,
+    names: usize,
+    fields: Vec<Type>,
+    globals: Vec<String>,
+    functions: Vec<(String, usize)>,
+}
+
+impl Generator {
+    fn name(&mut self, prefix: &str) -> String {
+        self.names += 1;
+        format!("{prefix}{}", self.names)
+    }
+
+    /// The fields of `struct S` with one of the types
+    fn fields_of(&self, types: &[Type]) -> Vec<usize> {
+        (0..self.fields.len())
+            .filter(|field| types.contains(&self.fields[*field]))
+            .collect()
+    }
+
+    /// An item of one of the arrays with one of the types, indexed by a loop counter when every
+    /// counter is within the array
+    fn index(&mut self, scope: &Scope, types: &[Type]) -> Option<Expr> {
+        let arrays: Vec<(String, Type, usize)> = scope
+            .arrays
+            .iter()
+            .filter(|(_, t, _)| types.contains(t))
+            .cloned()
+            .collect();
+        let (id, _, len) = self.rng.pick(&arrays)?;
+        let index = match self.rng.pick(&scope.counters) {
+            Some(counter) if len >= MAX_TIMES as usize && self.rng.chance(50) => Expr::Var(counter),
+            _ => Expr::Lit(self.rng.below(len) as i32),
+        };
+        Some(Expr::Index(id, Box::new(index)))
+    }
+
+    fn expr(&mut self, scope: &Scope, depth: usize) -> Expr {
+        let int_fields = self.fields_of(&[Type::Int]);
+        let expr = match self.rng.below(11) {
+            0 if !scope.ints.is_empty()
+                || !scope.counters.is_empty()
+                || !scope.globals.is_empty() =>
+            {
+                let readable = [
+                    scope.ints.clone(),
+                    scope.counters.clone(),
+                    scope.globals.clone(),
+                ]
+                .concat();
+                Expr::Var(self.rng.pick(&readable).unwrap())
+            }
+            1 if !scope.ptrs.is_empty() => {
+                let (ptr_id, level) = self.rng.pick(&scope.ptrs).unwrap();
+                Expr::Deref(ptr_id, level)
+            }
+            2 if !scope.structs.is_empty() && !int_fields.is_empty() => Expr::Field(
+                self.rng.pick(&scope.structs).unwrap(),
+                self.rng.pick(&int_fields).unwrap(),
+            ),
+            3 if !scope.struct_ptrs.is_empty() && !int_fields.is_empty() => Expr::PtrField(
+                self.rng.pick(&scope.struct_ptrs).unwrap(),
+                self.rng.pick(&int_fields).unwrap(),
+            ),
+            // NOTE A `char` is promoted to an `int` in arithmetic
+            4 if !scope.chars.is_empty() => Expr::Var(self.rng.pick(&scope.chars).unwrap()),
+            5 if scope.arrays.iter().any(|(_, t, _)| *t == Type::Int) => {
+                self.index(scope, &[Type::Int]).unwrap()
+            }
+            6 if !scope.heap.is_empty() => Expr::Deref(self.rng.pick(&scope.heap).unwrap(), 1),
+            7 if !scope.strings.is_empty() => Expr::StrLen(self.rng.pick(&scope.strings).unwrap()),
+            8 if !scope.array_ptrs.is_empty() => {
+                let (ptr_id, len) = self.rng.pick(&scope.array_ptrs).unwrap();
+                Expr::ArrayPtrDeref(ptr_id, self.rng.below(len))
+            }
+            _ => Expr::Lit(self.rng.below(10) as i32),
+        };
+
+        match depth != 0 && self.rng.chance(30) {
+            true if scope.once && self.rng.chance(50) => {
+                Expr::Sum(Box::new(expr), Box::new(self.expr(scope, 0)))
+            }
+            true => Expr::Add(Box::new(expr), self.rng.below(19) as i32 - 9),
+            false => expr,
+        }
+    }
+
+    /// A `double`, which can be arithmetic mixing in an `int`
+    fn double_expr(&mut self, scope: &Scope, depth: usize) -> Expr {
+        let double_fields = self.fields_of(&[Type::Double]);
+        let expr = match self.rng.below(6) {
+            0 if !scope.doubles.is_empty() => Expr::Var(self.rng.pick(&scope.doubles).unwrap()),
+            1 if !scope.structs.is_empty() && !double_fields.is_empty() => Expr::Field(
+                self.rng.pick(&scope.structs).unwrap(),
+                self.rng.pick(&double_fields).unwrap(),
+            ),
+            2 if !scope.struct_ptrs.is_empty() && !double_fields.is_empty() => Expr::PtrField(
+                self.rng.pick(&scope.struct_ptrs).unwrap(),
+                self.rng.pick(&double_fields).unwrap(),
+            ),
+            3 if scope.arrays.iter().any(|(_, t, _)| *t == Type::Double) => {
+                self.index(scope, &[Type::Double]).unwrap()
+            }
+            // NOTE Mixes in an `int`, which C converts, without ever adding two variables
+            4 => Expr::Sum(
+                Box::new(Expr::Half(self.rng.below(10) as i32)),
+                Box::new(self.expr(scope, 0)),
+            ),
+            _ => Expr::Half(self.rng.below(10) as i32),
+        };
+
+        match depth != 0 && self.rng.chance(30) {
+            true if scope.once && self.rng.chance(50) => {
+                Expr::Sum(Box::new(expr), Box::new(self.expr(scope, 0)))
+            }
+            true => Expr::Add(Box::new(expr), self.rng.below(19) as i32 - 9),
+            false => expr,
+        }
+    }
+
+    /// What's assigned to an `int` is sometimes a `double`, which C truncates, and the other way
+    /// around
+    fn assigned(&mut self, t: Type, scope: &Scope) -> Expr {
+        match t {
+            Type::Double if self.rng.chance(20) => self.expr(scope, 1),
+            Type::Double => self.double_expr(scope, 1),
+            Type::Char => Expr::Char(b'a' + self.rng.below(26) as u8),
+            Type::Int if self.rng.chance(20) => self.double_expr(scope, 1),
+            Type::Int => self.expr(scope, 1),
+        }
+    }
+
+    fn item(&mut self, t: Type, scope: &Scope) -> Expr {
+        match t {
+            Type::Double => self.double_expr(scope, 0),
+            _ => self.expr(scope, 0),
+        }
+    }
+
+    fn stmts(&mut self, scope: &mut Scope, loop_depth: usize) -> Vec<Stmt> {
+        let len = 1 + self.rng.below(MAX_STATEMENTS >> loop_depth);
+        (0..len).map(|_| self.stmt(scope, loop_depth)).collect()
+    }
+
+    fn stmt(&mut self, scope: &mut Scope, loop_depth: usize) -> Stmt {
+        loop {
+            match self.rng.below(22) {
+                0 => {
+                    let expr = match self.rng.pick(&self.functions.clone()) {
+                        Some((name, params)) if self.rng.chance(30) => {
+                            Expr::Call(name, (0..params).map(|_| self.expr(scope, 1)).collect())
+                        }
+                        _ => self.assigned(Type::Int, scope),
+                    };
+                    let id = self.name("v");
+                    scope.ints.push(id.clone());
+                    return Stmt::Int(id, expr);
+                }
+                1 => {
+                    let assignable: Vec<(String, Type)> = [
+                        (&scope.ints, Type::Int),
+                        (&scope.globals, Type::Int),
+                        (&scope.doubles, Type::Double),
+                        (&scope.chars, Type::Char),
+                    ]
+                    .into_iter()
+                    .flat_map(|(ids, t)| ids.iter().map(move |id| (id.clone(), t)))
+                    .collect();
+                    if let Some((id, t)) = self.rng.pick(&assignable) {
+                        return Stmt::Assign(id, self.assigned(t, scope));
+                    }
+                }
+                2 if !scope.ints.is_empty() || !scope.globals.is_empty() => {
+                    let targets = [scope.ints.clone(), scope.globals.clone()].concat();
+                    let target = self.rng.pick(&targets).unwrap();
+                    let id = self.name("p");
+                    scope.ptrs.push((id.clone(), 1));
+                    return Stmt::Ptr {
+                        id,
+                        level: 1,
+                        target,
+                    };
+                }
+                3 if scope.ptrs.iter().any(|(_, level)| *level == 1) => {
+                    let singles: Vec<String> = scope
+                        .ptrs
+                        .iter()
+                        .filter(|(_, level)| *level == 1)
+                        .map(|(id, _)| id.clone())
+                        .collect();
+                    let target = self.rng.pick(&singles).unwrap();
+                    let id = self.name("p");
+                    scope.ptrs.push((id.clone(), 2));
+                    return Stmt::Ptr {
+                        id,
+                        level: 2,
+                        target,
+                    };
+                }
+                4 if !scope.ptrs.is_empty() || !scope.heap.is_empty() => {
+                    let heap = scope.heap.iter().map(|id| (id.clone(), 1));
+                    let ptrs: Vec<(String, usize)> =
+                        scope.ptrs.iter().cloned().chain(heap).collect();
+                    let (ptr_id, level) = self.rng.pick(&ptrs).unwrap();
+                    return Stmt::DerefAssign {
+                        ptr_id,
+                        level,
+                        expr: self.assigned(Type::Int, scope),
+                    };
+                }
+                5 => {
+                    let exprs = self
+                        .fields
+                        .clone()
+                        .into_iter()
+                        .map(|t| self.item(t, scope))
+                        .collect();
+                    let id = self.name("s");
+                    scope.structs.push(id.clone());
+                    return Stmt::Struct(id, exprs);
+                }
+                6 if !scope.structs.is_empty() => {
+                    let target = self.rng.pick(&scope.structs).unwrap();
+                    let id = self.name("sp");
+                    scope.struct_ptrs.push(id.clone());
+                    return Stmt::StructPtr(id, target);
+                }
+                7 if !scope.structs.is_empty() => {
+                    let id = self.rng.pick(&scope.structs).unwrap();
+                    let field = self.rng.below(self.fields.len());
+                    return Stmt::FieldAssign(id, field, self.assigned(self.fields[field], scope));
+                }
+                8 if !scope.struct_ptrs.is_empty() => {
+                    let ptr_id = self.rng.pick(&scope.struct_ptrs).unwrap();
+                    let field = self.rng.below(self.fields.len());
+                    let expr = self.assigned(self.fields[field], scope);
+                    return Stmt::PtrFieldAssign(ptr_id, field, expr);
+                }
+                9 if loop_depth < MAX_LOOP_DEPTH => {
+                    let counter = self.name("i");
+                    let mut body_scope = Scope {
+                        once: false,
+                        ..scope.clone()
+                    };
+                    body_scope.counters.push(counter.clone());
+                    return Stmt::Loop {
+                        counter,
+                        times: 1 + self.rng.below(MAX_TIMES as usize) as i32,
+                        body: self.stmts(&mut body_scope, loop_depth + 1),
+                    };
+                }
+                10 => return Stmt::Print(Type::Int, self.expr(scope, 1)),
+                11 => {
+                    let id = self.name("d");
+                    let expr = self.assigned(Type::Double, scope);
+                    scope.doubles.push(id.clone());
+                    return Stmt::Double(id, expr);
+                }
+                12 => {
+                    let id = self.name("c");
+                    scope.chars.push(id.clone());
+                    return Stmt::Char(id, b'a' + self.rng.below(26) as u8);
+                }
+                13 => {
+                    let t = self.rng.pick(&[Type::Int, Type::Double]).unwrap();
+                    let len = 2 + self.rng.below(3);
+                    let items = (0..1 + self.rng.below(len))
+                        .map(|_| self.item(t, scope))
+                        .collect();
+                    let id = self.name("arr");
+                    scope.arrays.push((id.clone(), t, len));
+                    return Stmt::Array { id, t, len, items };
+                }
+                14 if !scope.arrays.is_empty() => {
+                    let Some(Expr::Index(id, index)) =
+                        self.index(scope, &[Type::Int, Type::Double])
+                    else {
+                        unreachable!("There's an array")
+                    };
+                    let (_, t, _) = scope
+                        .arrays
+                        .iter()
+                        .find(|(other, _, _)| *other == id)
+                        .unwrap();
+                    let expr = self.assigned(*t, scope);
+                    return Stmt::IndexAssign(id, *index, expr);
+                }
+                15 if !scope.doubles.is_empty() => {
+                    return Stmt::Print(Type::Double, self.double_expr(scope, 1));
+                }
+                16 => {
+                    let id = self.name("h");
+                    let expr = self.assigned(Type::Int, scope);
+                    scope.heap.push(id.clone());
+                    return Stmt::Malloc(id, expr);
+                }
+                17 => {
+                    let value = (0..1 + self.rng.below(5))
+                        .map(|_| (b'a' + self.rng.below(26) as u8) as char)
+                        .collect();
+                    let id = self.name("str");
+                    scope.strings.push(id.clone());
+                    return Stmt::Str(id, value);
+                }
+                18 if !scope.strings.is_empty() => {
+                    return Stmt::PrintStr(self.rng.pick(&scope.strings).unwrap());
+                }
+                19 => {
+                    let c = match self.rng.pick(&scope.chars) {
+                        Some(id) if self.rng.chance(50) => Expr::Var(id),
+                        _ => Expr::Char(b'a' + self.rng.below(26) as u8),
+                    };
+                    return Stmt::PutChar(c);
+                }
+                20 if scope.arrays.iter().any(|(_, t, _)| *t == Type::Int) => {
+                    let arrays: Vec<(String, Type, usize)> = scope
+                        .arrays
+                        .iter()
+                        .filter(|(_, t, _)| *t == Type::Int)
+                        .cloned()
+                        .collect();
+                    let (array, t, len) = self.rng.pick(&arrays).unwrap();
+                    scope.arrays.retain(|(other, _, _)| *other != array);
+                    scope.pointed_arrays.push((array.clone(), t, len));
+                    let offset = self.rng.below(len);
+                    let id = self.name("q");
+                    scope.array_ptrs.push((id.clone(), len - offset));
+                    return Stmt::ArrayPtr { id, array, offset };
+                }
+                21 if !scope.array_ptrs.is_empty() => {
+                    let (ptr_id, len) = self.rng.pick(&scope.array_ptrs).unwrap();
+                    let offset = self.rng.below(len);
+                    let expr = self.assigned(Type::Int, scope);
+                    return Stmt::ArrayPtrAssign(ptr_id, offset, expr);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn function(&mut self) -> Function {
+        let params: Vec<String> = (0..self.rng.below(3)).map(|_| self.name("a")).collect();
+        let mut scope = Scope {
+            ints: params.clone(),
+            globals: self.globals.clone(),
+            ..Scope::default()
+        };
+        let body = self.stmts(&mut scope, 1);
+        Function {
+            name: self.name("f"),
+            params,
+            body,
+            ret: self.expr(&scope, 1),
+        }
+    }
+}
+
+impl Program {
+    pub fn generate(seed: u64) -> Self {
+        let mut generator = Generator {
+            rng: Rng::new(seed),
+            names: 0,
+            fields: vec![],
+            globals: vec![],
+            functions: vec![],
+        };
+        generator.fields = (0..1 + generator.rng.below(3))
+            .map(|_| match generator.rng.chance(30) {
+                true => Type::Double,
+                false => Type::Int,
+            })
+            .collect();
+        let globals: Vec<(String, i32)> = (0..generator.rng.below(3))
+            .map(|_| (generator.name("g"), generator.rng.below(10) as i32))
+            .collect();
+        generator.globals = globals.iter().map(|(id, _)| id.clone()).collect();
+
+        let functions: Vec<Function> = (0..generator.rng.below(3))
+            .map(|_| {
+                let function = generator.function();
+                generator
+                    .functions
+                    .push((function.name.clone(), function.params.len()));
+                function
+            })
+            .collect();
+
+        let mut scope = Scope {
+            globals: generator.globals.clone(),
+            once: true,
+            ..Scope::default()
+        };
+        let mut main = generator.stmts(&mut scope, 0);
+        // NOTE Everything main computed is printed, so the C and the translation can be compared
+        [
+            (&scope.ints, Type::Int),
+            (&scope.globals, Type::Int),
+            (&scope.doubles, Type::Double),
+            (&scope.chars, Type::Char),
+        ]
+        .into_iter()
+        .for_each(|(ids, t)| {
+            main.extend(ids.iter().map(|id| Stmt::Print(t, Expr::Var(id.clone()))))
+        });
+        scope.heap.iter().for_each(|id| {
+            main.push(Stmt::Print(Type::Int, Expr::Deref(id.clone(), 1)));
+            main.push(Stmt::Free(id.clone()));
+        });
+        main.extend(scope.strings.iter().cloned().map(Stmt::PrintStr));
+        scope
+            .arrays
+            .iter()
+            .chain(&scope.pointed_arrays)
+            .for_each(|(id, t, len)| {
+                main.extend((0..*len).map(|i| {
+                    Stmt::Print(*t, Expr::Index(id.clone(), Box::new(Expr::Lit(i as i32))))
+                }))
+            });
+
+        Program {
+            fields: generator.fields,
+            globals,
+            functions,
+            main,
+        }
+    }
+
+    pub fn to_c(&self) -> String {
+        let mut c_code = String::from("struct S {\n");
+        self.fields.iter().enumerate().for_each(|(field, t)| {
+            c_code += &format!("    {} f{field};\n", t.to_c());
+        });
+        c_code += "};\n";
+        self.globals.iter().for_each(|(id, value)| {
+            c_code += &format!("int {id} = {value};\n");
+        });
+
+        self.functions.iter().for_each(|function| {
+            let params: Vec<String> = function.params.iter().map(|p| format!("int {p}")).collect();
+            c_code += &format!("\nint {}({}) {{\n", function.name, params.join(", "));
+            function
+                .body
+                .iter()
+                .for_each(|stmt| stmt_to_c(stmt, 1, &mut c_code));
+            c_code += &format!("    return {};\n}}\n", expr_to_c(&function.ret));
+        });
+
+        c_code += "\nint main() {\n";
+        self.main
+            .iter()
+            .for_each(|stmt| stmt_to_c(stmt, 1, &mut c_code));
+        c_code += "}\n";
+        c_code
+    }
+
+    /// Every program one step smaller than this one, some of which won't declare what they use
+    fn smaller(&self) -> Vec<Program> {
+        let mut smaller: Vec<Program> = smaller_stmts(&self.main)
+            .into_iter()
+            .map(|main| Program {
+                main,
+                ..self.clone()
+            })
+            .collect();
+
+        self.functions.iter().enumerate().for_each(|(i, function)| {
+            let mut without = self.clone();
+            without.functions.remove(i);
+            smaller.push(without);
+
+            if !matches!(function.ret, Expr::Lit(_)) {
+                let mut simpler = self.clone();
+                simpler.functions[i].ret = Expr::Lit(1);
+                smaller.push(simpler);
+            }
+            smaller_stmts(&function.body).into_iter().for_each(|body| {
+                let mut simpler = self.clone();
+                simpler.functions[i].body = body;
+                smaller.push(simpler);
+            });
+        });
+
+        smaller
+    }
+
+    /// Only uses what it declares, which removing statements can break
+    fn well_formed(&self) -> bool {
+        let functions: Vec<(&str, usize)> = self
+            .functions
+            .iter()
+            .map(|function| (function.name.as_str(), function.params.len()))
+            .collect();
+
+        let globals: HashSet<String> = self.globals.iter().map(|(id, _)| id.clone()).collect();
+        self.functions.iter().all(|function| {
+            let mut declared: HashSet<String> = function.params.iter().cloned().collect();
+            declared.extend(globals.iter().cloned());
+            stmts_well_formed(&function.body, &mut declared, &functions)
+                && expr_well_formed(&function.ret, &declared, &functions)
+        }) && stmts_well_formed(&self.main, &mut globals.clone(), &functions)
+    }
+
+    /// The smallest program that still fails, found by taking steps for as long as one keeps it
+    /// failing
+    pub fn shrink(self, fails: impl Fn(&Program) -> bool) -> Program {
+        let mut program = self;
+        while let Some(smaller) = program
+            .smaller()
+            .into_iter()
+            .find(|smaller| smaller.well_formed() && fails(smaller))
+        {
+            program = smaller;
+        }
+        program
+    }
+}
+
+fn expr_to_c(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(n) => n.to_string(),
+        Expr::Half(n) => format!("{n}.5"),
+        Expr::Char(c) => format!("'{}'", *c as char),
+        Expr::Var(id) => id.clone(),
+        Expr::Deref(ptr_id, level) => format!("{}{ptr_id}", "*".repeat(*level)),
+        Expr::Field(id, field) => format!("{id}.f{field}"),
+        Expr::PtrField(ptr_id, field) => format!("{ptr_id}->f{field}"),
+        Expr::Index(id, index) => format!("{id}[{}]", expr_to_c(index)),
+        Expr::Call(name, args) => {
+            let args: Vec<String> = args.iter().map(expr_to_c).collect();
+            format!("{name}({})", args.join(", "))
+        }
+        Expr::Add(expr, n) if *n < 0 => format!("{} - {}", expr_to_c(expr), -n),
+        Expr::Add(expr, n) => format!("{} + {n}", expr_to_c(expr)),
+        Expr::Sum(left, right) => format!("{} + {}", expr_to_c(left), expr_to_c(right)),
+        Expr::StrLen(id) => format!("strlen({id})"),
+        Expr::ArrayPtrDeref(ptr_id, 0) => format!("*{ptr_id}"),
+        Expr::ArrayPtrDeref(ptr_id, offset) => format!("*({ptr_id} + {offset})"),
+    }
+}
+
+fn stmt_to_c(stmt: &Stmt, depth: usize, c_code: &mut String) {
+    let indent = "    ".repeat(depth);
+    let line = match stmt {
+        Stmt::Int(id, expr) => format!("int {id} = {};", expr_to_c(expr)),
+        Stmt::Double(id, expr) => format!("double {id} = {};", expr_to_c(expr)),
+        Stmt::Char(id, c) => format!("char {id} = {};", expr_to_c(&Expr::Char(*c))),
+        Stmt::Assign(id, expr) => format!("{id} = {};", expr_to_c(expr)),
+        Stmt::Ptr { id, level, target } => {
+            format!("int{} {id} = &{target};", "*".repeat(*level))
+        }
+        Stmt::DerefAssign {
+            ptr_id,
+            level,
+            expr,
+        } => format!("{}{ptr_id} = {};", "*".repeat(*level), expr_to_c(expr)),
+        Stmt::Array { id, t, len, items } => {
+            let items: Vec<String> = items.iter().map(expr_to_c).collect();
+            format!("{} {id}[{len}] = {{ {} }};", t.to_c(), items.join(", "))
+        }
+        Stmt::IndexAssign(id, index, expr) => {
+            format!("{id}[{}] = {};", expr_to_c(index), expr_to_c(expr))
+        }
+        Stmt::Struct(id, exprs) => {
+            let exprs: Vec<String> = exprs.iter().map(expr_to_c).collect();
+            format!("struct S {id} = {{ {} }};", exprs.join(", "))
+        }
+        Stmt::StructPtr(id, target) => format!("struct S* {id} = &{target};"),
+        Stmt::FieldAssign(id, field, expr) => format!("{id}.f{field} = {};", expr_to_c(expr)),
+        Stmt::PtrFieldAssign(ptr_id, field, expr) => {
+            format!("{ptr_id}->f{field} = {};", expr_to_c(expr))
+        }
+        Stmt::Loop {
+            counter,
+            times,
+            body,
+        } => {
+            *c_code += &format!("{indent}int {counter} = 0;\n");
+            *c_code += &format!("{indent}while ({counter} != {times}) {{\n");
+            body.iter()
+                .for_each(|stmt| stmt_to_c(stmt, depth + 1, c_code));
+            *c_code += &format!("{indent}    {counter}++;\n");
+            "}".to_string()
+        }
+        Stmt::Print(t, expr) => format!("printf(\"{}\\n\", {});", t.format(), expr_to_c(expr)),
+        Stmt::Malloc(id, expr) => {
+            *c_code += &format!("{indent}int* {id} = malloc(sizeof(int));\n");
+            format!("*{id} = {};", expr_to_c(expr))
+        }
+        Stmt::Free(id) => format!("free({id});"),
+        Stmt::Str(id, value) => format!("char {id}[] = \"{value}\";"),
+        Stmt::PrintStr(id) => format!("printf(\"%s\\n\", {id});"),
+        Stmt::PutChar(expr) => format!("putchar({});", expr_to_c(expr)),
+        Stmt::ArrayPtr {
+            id,
+            array,
+            offset: 0,
+        } => format!("int* {id} = {array};"),
+        Stmt::ArrayPtr { id, array, offset } => format!("int* {id} = {array} + {offset};"),
+        Stmt::ArrayPtrAssign(ptr_id, offset, expr) => format!(
+            "{} = {};",
+            expr_to_c(&Expr::ArrayPtrDeref(ptr_id.clone(), *offset)),
+            expr_to_c(expr)
+        ),
+    };
+    *c_code += &format!("{indent}{line}\n");
+}
+
+/// Each statement removed, loops replaced by their body, and expressions replaced by a literal
+fn smaller_stmts(stmts: &[Stmt]) -> Vec<Vec<Stmt>> {
+    let mut smaller = vec![];
+    stmts.iter().enumerate().for_each(|(i, stmt)| {
+        let with = |replacement: Vec<Stmt>| -> Vec<Stmt> {
+            [&stmts[..i], &replacement, &stmts[i + 1..]].concat()
+        };
+        smaller.push(with(vec![]));
+
+        let simpler = |expr: &Expr| -> Option<Expr> {
+            (!matches!(expr, Expr::Lit(_))).then_some(Expr::Lit(1))
+        };
+        let simplified = match stmt {
+            Stmt::Loop {
+                counter,
+                times,
+                body,
+            } => {
+                smaller.push(with(body.clone()));
+                smaller_stmts(body).into_iter().for_each(|body| {
+                    smaller.push(with(vec![Stmt::Loop {
+                        counter: counter.clone(),
+                        times: *times,
+                        body,
+                    }]))
+                });
+                None
+            }
+            Stmt::Int(id, expr) => simpler(expr).map(|expr| Stmt::Int(id.clone(), expr)),
+            Stmt::Double(id, expr) => simpler(expr).map(|expr| Stmt::Double(id.clone(), expr)),
+            Stmt::Array { id, t, len, items } if items.len() > 1 => Some(Stmt::Array {
+                id: id.clone(),
+                t: *t,
+                len: *len,
+                items: items[..items.len() - 1].to_vec(),
+            }),
+            Stmt::IndexAssign(id, index, expr) => {
+                simpler(expr).map(|expr| Stmt::IndexAssign(id.clone(), index.clone(), expr))
+            }
+            Stmt::Assign(id, expr) => simpler(expr).map(|expr| Stmt::Assign(id.clone(), expr)),
+            Stmt::DerefAssign {
+                ptr_id,
+                level,
+                expr,
+            } => simpler(expr).map(|expr| Stmt::DerefAssign {
+                ptr_id: ptr_id.clone(),
+                level: *level,
+                expr,
+            }),
+            Stmt::FieldAssign(id, field, expr) => {
+                simpler(expr).map(|expr| Stmt::FieldAssign(id.clone(), *field, expr))
+            }
+            Stmt::PtrFieldAssign(ptr_id, field, expr) => {
+                simpler(expr).map(|expr| Stmt::PtrFieldAssign(ptr_id.clone(), *field, expr))
+            }
+            // NOTE `printf` needs the type its format says
+            Stmt::Print(t, expr) => (*expr != t.literal()).then(|| Stmt::Print(*t, t.literal())),
+            Stmt::Malloc(id, expr) => simpler(expr).map(|expr| Stmt::Malloc(id.clone(), expr)),
+            Stmt::ArrayPtrAssign(ptr_id, offset, expr) => {
+                simpler(expr).map(|expr| Stmt::ArrayPtrAssign(ptr_id.clone(), *offset, expr))
+            }
+            Stmt::PutChar(expr) => {
+                (*expr != Type::Char.literal()).then(|| Stmt::PutChar(Type::Char.literal()))
+            }
+            Stmt::Ptr { .. }
+            | Stmt::Char(..)
+            | Stmt::Array { .. }
+            | Stmt::Struct(..)
+            | Stmt::StructPtr(..)
+            | Stmt::Free(_)
+            | Stmt::Str(..)
+            | Stmt::PrintStr(_)
+            | Stmt::ArrayPtr { .. } => None,
+        };
+        if let Some(simplified) = simplified {
+            smaller.push(with(vec![simplified]));
+        }
+    });
+    smaller
+}
+
+fn expr_well_formed(expr: &Expr, declared: &HashSet<String>, functions: &[(&str, usize)]) -> bool {
+    match expr {
+        Expr::Lit(_) | Expr::Half(_) | Expr::Char(_) => true,
+        Expr::Var(id)
+        | Expr::Deref(id, _)
+        | Expr::Field(id, _)
+        | Expr::PtrField(id, _)
+        | Expr::StrLen(id)
+        | Expr::ArrayPtrDeref(id, _) => declared.contains(id),
+        Expr::Index(id, index) => {
+            declared.contains(id) && expr_well_formed(index, declared, functions)
+        }
+        Expr::Call(name, args) => {
+            functions.contains(&(name.as_str(), args.len()))
+                && args
+                    .iter()
+                    .all(|arg| expr_well_formed(arg, declared, functions))
+        }
+        Expr::Add(expr, _) => expr_well_formed(expr, declared, functions),
+        Expr::Sum(left, right) => {
+            expr_well_formed(left, declared, functions)
+                && expr_well_formed(right, declared, functions)
+        }
+    }
+}
+
+fn stmts_well_formed(
+    stmts: &[Stmt],
+    declared: &mut HashSet<String>,
+    functions: &[(&str, usize)],
+) -> bool {
+    stmts.iter().all(|stmt| {
+        let (used, expr, declares): (Option<&String>, Option<&Expr>, Option<&String>) = match stmt {
+            Stmt::Int(id, expr) | Stmt::Double(id, expr) | Stmt::Malloc(id, expr) => {
+                (None, Some(expr), Some(id))
+            }
+            Stmt::Char(id, _) | Stmt::Str(id, _) => (None, None, Some(id)),
+            Stmt::Free(id) | Stmt::PrintStr(id) => (Some(id), None, None),
+            Stmt::PutChar(expr) => (None, Some(expr), None),
+            Stmt::ArrayPtr { id, array, .. } => (Some(array), None, Some(id)),
+            Stmt::ArrayPtrAssign(id, _, expr) => (Some(id), Some(expr), None),
+            Stmt::IndexAssign(id, index, expr) => {
+                if !expr_well_formed(index, declared, functions) {
+                    return false;
+                }
+                (Some(id), Some(expr), None)
+            }
+            Stmt::Assign(id, expr)
+            | Stmt::DerefAssign {
+                ptr_id: id, expr, ..
+            }
+            | Stmt::FieldAssign(id, _, expr)
+            | Stmt::PtrFieldAssign(id, _, expr) => (Some(id), Some(expr), None),
+            Stmt::Ptr { id, target, .. } | Stmt::StructPtr(id, target) => {
+                (Some(target), None, Some(id))
+            }
+            Stmt::Struct(id, exprs)
+            | Stmt::Array {
+                id, items: exprs, ..
+            } => {
+                if !exprs
+                    .iter()
+                    .all(|expr| expr_well_formed(expr, declared, functions))
+                {
+                    return false;
+                }
+                (None, None, Some(id))
+            }
+            Stmt::Loop { counter, body, .. } => {
+                let mut body_declared = declared.clone();
+                body_declared.insert(counter.clone());
+                return stmts_well_formed(body, &mut body_declared, functions);
+            }
+            Stmt::Print(_, expr) => (None, Some(expr), None),
+        };
+
+        let well_formed = used.is_none_or(|id| declared.contains(id))
+            && expr.is_none_or(|expr| expr_well_formed(expr, declared, functions));
+        if let Some(id) = declares {
+            declared.insert(id.clone());
+        }
+        well_formed
+    })
+}
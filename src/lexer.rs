@@ -89,6 +89,18 @@ pub fn string_to_tokens(
                     continue;
                 }
             }
+            // NOTE `1.5`
+            let dot = i + num.len();
+            if is_dec && chars[dot] == '.' && chars[dot + 1].is_ascii_digit() {
+                let fraction: String = chars
+                    .iter()
+                    .skip(dot + 1)
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect();
+                i = dot + 1 + fraction.len();
+                ret.push(Token::FloatLiteral(format!("{num}.{fraction}")));
+                continue;
+            }
             if is_dec {
                 ret.push(Token::NumLiteral(num.parse::<usize>().unwrap()));
                 i += num.len();
@@ -102,7 +114,7 @@ pub fn string_to_tokens(
                 && chars[i + 3] == 'e'
                 && chars[i + 4] == 'r'
                 && chars[i + 5] == 't'
-                && chars[i + 6] == ' ' =>
+                && (chars[i + 6] == ' ' || chars[i + 6] == '(') =>
             {
                 ret.push(Token::Assert);
                 i += 5;
@@ -116,21 +128,92 @@ pub fn string_to_tokens(
             }
             'p' if chars[i + 1] == 'u'
                 && chars[i + 2] == 't'
-                && (chars[i + 3] == '(' || chars[i + 3] == ' ') =>
+                && chars[i + 3] == 'c'
+                && chars[i + 4] == 'h'
+                && chars[i + 5] == 'a'
+                && chars[i + 6] == 'r'
+                && (chars[i + 7] == '(' || chars[i + 7] == ' ') =>
             {
                 ret.push(Token::PutChar);
-                i += 2;
+                i += 6;
             }
             's' if chars[i + 1] == 't'
                 && chars[i + 2] == 'r'
                 && chars[i + 3] == 'u'
                 && chars[i + 4] == 'c'
                 && chars[i + 5] == 't'
-                && chars[i + 6] == ' ' =>
+                && (chars[i + 6] == ' ' || chars[i + 6] == '{') =>
             {
                 ret.push(Token::Struct);
+                i += 5;
+            }
+            'u' if chars[i + 1] == 'n'
+                && chars[i + 2] == 'i'
+                && chars[i + 3] == 'o'
+                && chars[i + 4] == 'n'
+                && (chars[i + 5] == ' ' || chars[i + 5] == '{') =>
+            {
+                ret.push(Token::Union);
+                i += 4;
+            }
+            'e' if chars[i + 1] == 'n'
+                && chars[i + 2] == 'u'
+                && chars[i + 3] == 'm'
+                && (chars[i + 4] == ' ' || chars[i + 4] == '{') =>
+            {
+                ret.push(Token::Enum);
+                i += 3;
+            }
+            't' if chars[i + 1] == 'y'
+                && chars[i + 2] == 'p'
+                && chars[i + 3] == 'e'
+                && chars[i + 4] == 'd'
+                && chars[i + 5] == 'e'
+                && chars[i + 6] == 'f'
+                && chars[i + 7] == ' ' =>
+            {
+                ret.push(Token::Typedef);
+                i += 7;
+            }
+
+            's' if chars[i + 1] == 't'
+                && chars[i + 2] == 'a'
+                && chars[i + 3] == 't'
+                && chars[i + 4] == 'i'
+                && chars[i + 5] == 'c'
+                && chars[i + 6] == ' ' =>
+            {
+                ret.push(Token::Static);
                 i += 6;
             }
+            'e' if chars[i + 1] == 'x'
+                && chars[i + 2] == 't'
+                && chars[i + 3] == 'e'
+                && chars[i + 4] == 'r'
+                && chars[i + 5] == 'n'
+                && chars[i + 6] == ' ' =>
+            {
+                ret.push(Token::Extern);
+                i += 6;
+            }
+            's' if chars[i + 1] == 'i'
+                && chars[i + 2] == 'z'
+                && chars[i + 3] == 'e'
+                && chars[i + 4] == 'o'
+                && chars[i + 5] == 'f'
+                && (chars[i + 6] == '(' || chars[i + 6] == ' ') =>
+            {
+                ret.push(Token::SizeOf);
+                i += 5;
+                // NOTE The type isn't followed by the space a declaration has, as in `sizeof(double*)`
+                if let Some((t, ptr_count, len)) = sizeof_type(&chars[i + 1..]) {
+                    ret.push(Token::OParen);
+                    ret.push(Token::Type(t));
+                    (0..ptr_count).for_each(|_| ret.push(Token::Star));
+                    ret.push(Token::CParen);
+                    i += len;
+                }
+            }
 
             'L' if chars[i + 1] == 'A'
                 && chars[i + 2] == 'B'
@@ -178,6 +261,68 @@ pub fn string_to_tokens(
                 ret.push(Token::Type(CType::Int));
                 i += 2; // NOTE Not a bug, don't move past a star
             }
+            'i' if chars[i + 1] == 'n' && chars[i + 2] == 't' && chars[i + 3] == ')' => {
+                ret.push(Token::Type(CType::Int));
+                i += 2; // NOTE Don't move past the paren (`sizeof(int)`)
+            }
+            'c' if chars[i + 1] == 'h'
+                && chars[i + 2] == 'a'
+                && chars[i + 3] == 'r'
+                && (chars[i + 4] == ' ' || chars[i + 4] == '*' || chars[i + 4] == ')') =>
+            {
+                ret.push(Token::Type(CType::Char));
+                i += 3; // NOTE Don't move past a star or paren
+            }
+            'f' if chars[i + 1] == 'l'
+                && chars[i + 2] == 'o'
+                && chars[i + 3] == 'a'
+                && chars[i + 4] == 't'
+                && (chars[i + 5] == ' ' || chars[i + 5] == '*') =>
+            {
+                ret.push(Token::Type(CType::Float));
+                i += 4;
+            }
+            'd' if chars[i + 1] == 'o'
+                && chars[i + 2] == 'u'
+                && chars[i + 3] == 'b'
+                && chars[i + 4] == 'l'
+                && chars[i + 5] == 'e'
+                && (chars[i + 6] == ' ' || chars[i + 6] == '*') =>
+            {
+                ret.push(Token::Type(CType::Double));
+                i += 5;
+            }
+            // NOTE Nothing is written through a const pointer in valid C, so the analysis already
+            // figures out that it's immutable
+            'c' if chars[i + 1] == 'o'
+                && chars[i + 2] == 'n'
+                && chars[i + 3] == 's'
+                && chars[i + 4] == 't'
+                && chars[i + 5] == ' ' =>
+            {
+                i += 5;
+            }
+            // NOTE Preprocessor directives (`#include <string.h>`) are skipped
+            '#' => {
+                // NOTE The file can end in the middle of one
+                while i + 1 < chars.len() && chars[i + 1] != '\n' {
+                    i += 1;
+                }
+            }
+            '"' => {
+                let mut literal = String::new();
+                i += 1;
+                while chars[i] != '"' {
+                    // NOTE Escapes are kept as they're written, since rust uses the same ones
+                    if chars[i] == '\\' {
+                        literal.push(chars[i]);
+                        i += 1;
+                    }
+                    literal.push(chars[i]);
+                    i += 1;
+                }
+                ret.push(Token::StrLiteral(literal));
+            }
             'i' if chars[i + 1] == 'f' && (chars[i + 2] == ' ' || chars[i + 2] == '(') => {
                 ret.push(Token::If);
                 i += 1;
@@ -456,6 +601,14 @@ pub fn string_to_tokens(
                     curr = String::new();
 
                     lex_id(&chars, &mut curr, &mut i);
+                    // NOTE Nested fields (`s.data.i`) are kept together as the field id `data.i`
+                    while chars[i] == '.' {
+                        curr.push('.');
+                        i += 1;
+                        let mut sub_field = String::new();
+                        lex_id(&chars, &mut sub_field, &mut i);
+                        curr.push_str(&sub_field);
+                    }
 
                     let field_id = curr.clone();
                     ret.push(Token::StructFieldId {
@@ -474,6 +627,23 @@ pub fn string_to_tokens(
     Ok((ret, line_tracker))
 }
 
+/// The type in `(<type>)` after a `sizeof`, how many pointers deep it is, and how many chars it
+/// takes up, `None` for structs and anything else that's lexed like usual
+fn sizeof_type(chars: &[char]) -> Option<(CType, usize, usize)> {
+    let len = chars.iter().position(|c| *c == ')')? + 1;
+    let parens: String = chars[..len].iter().collect();
+    let inner = parens.trim_start().strip_prefix('(')?.strip_suffix(')')?;
+    let t = match inner.trim_end_matches(['*', ' ']).trim() {
+        "int" => CType::Int,
+        "char" => CType::Char,
+        "float" => CType::Float,
+        "double" => CType::Double,
+        _ => return None,
+    };
+
+    Some((t, inner.matches('*').count(), len))
+}
+
 fn lex_id(chars: &[char], curr: &mut String, i: &mut usize) {
     for c in chars.iter().skip(*i) {
         if !c.is_alphanumeric() && *c != '_' {
@@ -489,6 +659,9 @@ fn lex_id(chars: &[char], curr: &mut String, i: &mut usize) {
 pub enum Token {
     If,
     Struct,
+    Union,
+    Enum,
+    Typedef,
     Break,
     For,
     While,
@@ -499,6 +672,7 @@ pub enum Token {
     Star,
     // Var(String),
     NumLiteral(usize),
+    FloatLiteral(String),
     StrLiteral(String),
     Add,
     AddO,
@@ -553,6 +727,9 @@ pub enum Token {
     Colon,
     Arrow,
     Return,
+    Static,
+    Extern,
+    SizeOf,
     PutChar,
     Assert, // this might be to much for the lexer to do
             // FuncDeclare((String, Vec<String>, CType)), // function name, args, return type
@@ -564,17 +741,35 @@ pub enum CType {
     Void,
     Int,
     Char,
-    Struct(String),
+    Float,
+    Double,
+    Struct(String), // also unions
+    Enum(String),
     Array(Box<CType>),
 }
 
 impl CType {
+    pub fn is_floating(&self) -> bool {
+        matches!(self, CType::Float | CType::Double)
+    }
+
+    /// What missing items and fresh allocations of the type start as
+    pub fn zero_value(&self) -> String {
+        match self {
+            CType::Float | CType::Double => "0.0".into(),
+            CType::Struct(_) => "Default::default()".into(),
+            _ => "0".into(),
+        }
+    }
+
     pub fn to_rust_type(&self) -> String {
         match self {
             CType::Int => "i32".into(),
             CType::Char => "u8".into(),
+            CType::Float => "f32".into(),
+            CType::Double => "f64".into(),
             CType::Void => "()".into(),
-            CType::Struct(id) => id.into(),
+            CType::Struct(id) | CType::Enum(id) => id.into(),
             CType::Array(sub_type) => format!("&[{}]", sub_type.to_rust_type()),
         }
     }
@@ -1,7 +1,16 @@
-use std::fs::read_to_string;
+use std::{
+    env,
+    fs::{read_to_string, write},
+};
 
 use analysis_ctx::AnalysisContext;
 use ast::TokenNode;
+use error::{ErrType, RhErr};
+use explain::Explanations;
+use solver::{CostModel, Fix};
+use source_map::SourceMap;
+use summary::Summary;
+use verify::RustcError;
 
 mod adjuster;
 mod analysis_ctx;
@@ -12,19 +21,85 @@ mod ast;
 mod checker;
 mod converter;
 mod data_model;
+mod dependence;
 mod error;
+mod explain;
+mod format;
+#[cfg(test)]
+mod generator;
 mod lexer;
 mod parser;
+mod recursive;
 pub mod scope;
+mod solver;
+mod source_map;
+mod summary;
 #[cfg(test)]
 mod test;
 mod token_handler;
+mod verify;
+
+// NOTE The C file sonder translates, which source maps and line comments point back to
+const C_FILE: &str = "test.c";
 
 fn main() {
-    let contents = read_to_string("test.c").expect("Please provide a valid file for parsing");
+    let contents = read_to_string(C_FILE).expect("Please provide a valid file for parsing");
 
     let ast = parse_c(contents);
-    let _rust_code = convert_to_rust_code(ast);
+    let options = Options::from_args(env::args().skip(1));
+    let _rust_code = convert_to_rust_code(ast, &options).unwrap_or_else(|err| panic!("{err}"));
+}
+
+/// How a translation is done
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub costs: CostModel,
+    // `--verify`, compiles the translation with `rustc`, and downgrades the variables it rejects
+    pub verify: bool,
+    // `--source-map <path>`, writes which C line every line of the translation came from
+    pub source_map: Option<String>,
+    // `--line-comments`, ends every translated line with a `// c: test.c:12` comment
+    pub line_comments: bool,
+    // `--explain <path>`, writes why every variable got its type, `--explain-json <path>` as JSON
+    pub explain: Option<String>,
+    pub explain_json: Option<String>,
+    // `--summary <path>`, writes the raw pointers, `unsafe` blocks, `Rc`s and clones of every
+    // function
+    pub summary: Option<String>,
+    // `--deny-unsafe`, fails the translation rather than have it use raw pointers
+    pub deny_unsafe: bool,
+}
+
+impl Options {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let mut options = Options::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--verify" => options.verify = true,
+                "--source-map" => {
+                    let path = args.next();
+                    options.source_map = Some(path.expect("Please provide a path for the map"));
+                }
+                "--line-comments" => options.line_comments = true,
+                "--explain" => {
+                    let path = args.next();
+                    options.explain = Some(path.expect("Please provide a path for the report"));
+                }
+                "--explain-json" => {
+                    let path = args.next();
+                    options.explain_json =
+                        Some(path.expect("Please provide a path for the report"));
+                }
+                "--summary" => {
+                    let path = args.next();
+                    options.summary = Some(path.expect("Please provide a path for the summary"));
+                }
+                "--deny-unsafe" => options.deny_unsafe = true,
+                arg => panic!("Unsupported: argument `{arg}`"),
+            }
+        }
+        options
+    }
 }
 
 fn parse_c(contents: String) -> TokenNode {
@@ -35,22 +110,204 @@ fn parse_c(contents: String) -> TokenNode {
     parser::program(tokens, line_numbers, true).expect("Failed to parse token stream")
 }
 
-fn convert_to_rust_code(mut ast: TokenNode) -> String {
-    ast.print(&mut 0);
+// NOTE Statements are only moved this many times, in case moving them back and forth never settles
+const MAX_REARRANGEMENTS: usize = 16;
+// NOTE Each downgrade makes a value shared, or its pointers raw, so this is plenty
+const MAX_DOWNGRADES: usize = 16;
+
+fn analyze(ast: &TokenNode) -> AnalysisContext {
     let mut ctx: AnalysisContext = AnalysisContext::new();
 
-    analyzer::determine_var_mutability(&ast, &mut ctx);
+    analyzer::determine_var_mutability(ast, &mut ctx);
+    analyzer::enum_usage(ast, &mut ctx, &lexer::CType::Void);
+    analyzer::tagged_union_usage(ast, &mut ctx);
+    recursive::recursive_struct_usage(ast, &mut ctx);
+
+    ctx
+}
+
+fn convert_to_rust_code(mut ast: TokenNode, options: &Options) -> Result<String, RhErr> {
+    ast.print(&mut 0);
+    // NOTE The analysis needs to know what every allocation holds
+    if let Some(err) = checker::allocation_check(&ast).into_iter().next() {
+        return Err(err);
+    }
+    let mut ctx = analyze(&ast);
 
     println!("variables: {:?}", ctx.current_scope().variables);
 
-    let mut temp_ctx = ctx.clone();
-    let errors = checker::borrow_check(&mut temp_ctx);
-    ctx.adjust_ptr_type(errors, &mut ast);
+    if let Some(err) = checker::heap_check(&ctx)
+        .into_iter()
+        .chain(checker::format_check(&ast, &ctx))
+        .chain(checker::memset_check(&ast, &ctx))
+        .next()
+    {
+        return Err(err);
+    }
 
-    let annotated_ast = ast.annotate(&ctx);
-    // annotated_ast.print(&mut 0);
+    // NOTE Moving statements changes the lines everything was found on, so the analysis is done
+    // again rather than patched
+    // The borrows are checked again after every round of fixes, until there's nothing left to fix
+    let mut rearrangements = 0;
+    let mut applied: Vec<Fix> = vec![];
+    let mut explanations = Explanations::default();
+    loop {
+        let mut temp_ctx = ctx.clone();
+        let errors = checker::borrow_check(&mut temp_ctx);
+        // NOTE A fix that didn't get rid of its error the first time won't the second time either
+        let fixes: Vec<Fix> = solver::solve(
+            &errors,
+            &ast,
+            &ctx,
+            &options.costs,
+            MAX_REARRANGEMENTS - rearrangements,
+            &mut explanations,
+        )
+        .into_iter()
+        .filter(|fix| !applied.contains(fix))
+        .collect();
+        if fixes.is_empty() {
+            break;
+        }
+
+        let mut moved = false;
+        for fix in fixes.iter() {
+            explanations.fixed(fix, &ctx);
+            moved |= ctx.apply_fix(fix, &mut ast);
+        }
+        match moved {
+            true => {
+                rearrangements += 1;
+                ctx = analyze(&ast);
+                applied.clear();
+            }
+            false => applied.extend(fixes),
+        }
+    }
+    // NOTE The fixes can run out before the errors do, and then the translation won't compile
+    let errors = checker::borrow_check(&mut ctx.clone());
+    let mut unfixed = solver::unfixed(&errors, &ast, &ctx);
+    unfixed.sort();
+    if let Some(first) = unfixed.first() {
+        let line = first
+            .ptr_ids()
+            .iter()
+            .filter_map(|ptr_id| ctx.get_var(ptr_id).reference_to_var(first.value_id()))
+            .map(|reference| reference.borrow().get_range().start)
+            .min()
+            .and_then(|line| explain::c_line(&ast, line))
+            .unwrap_or_default();
+        return Err(RhErr {
+            err: ErrType::UnfixedBorrows(unfixed.iter().map(|error| error.describe()).collect()),
+            line,
+        });
+    }
 
-    let converted_rust = annotated_ast.convert();
+    let (mut converted_rust, mut source_map) = generate(&ast, &ctx);
+    if options.verify {
+        (converted_rust, source_map) = verify_rust_code(
+            converted_rust,
+            source_map,
+            &ast,
+            &mut ctx,
+            &mut explanations,
+        )?;
+    }
+    if options.summary.is_some() || options.deny_unsafe {
+        let summary = Summary::new(&ast.annotate(&ctx), &converted_rust, &source_map);
+        if let Some(path) = &options.summary {
+            write(path, summary.report(C_FILE)).expect("Failed to write the summary");
+        }
+        if let Some((id, line)) = summary.first_raw_ptr().filter(|_| options.deny_unsafe) {
+            return Err(RhErr {
+                err: ErrType::Unsafe(id),
+                line,
+            });
+        }
+    }
+    explanations.finish(&ast.annotate(&ctx), &ctx);
+    if let Some(path) = &options.explain {
+        write(path, explanations.report()).expect("Failed to write the report");
+    }
+    if let Some(path) = &options.explain_json {
+        write(path, explanations.to_json()).expect("Failed to write the report");
+    }
+    if let Some(path) = &options.source_map {
+        write(path, source_map.to_file(C_FILE)).expect("Failed to write the source map");
+    }
+    if options.line_comments {
+        converted_rust = source_map.comment(&converted_rust, C_FILE);
+    }
     println!("\n{converted_rust}");
-    converted_rust
+    Ok(converted_rust)
+}
+
+/// The translation, and the C line each of its lines came from
+fn generate(ast: &TokenNode, ctx: &AnalysisContext) -> (String, SourceMap) {
+    let annotated_ast = ast.annotate(ctx);
+    // annotated_ast.print(&mut 0);
+
+    SourceMap::strip(&annotated_ast.convert())
+}
+
+/// The lexical borrow checking misses some errors, like borrows used again by the next iteration
+/// of a loop, so the variables `rustc` names in its errors are downgraded until it's satisfied, or
+/// declared `mut` when that's all it asks for
+///
+/// # Important
+/// Sonder keeps the names of C variables, which is how the errors are traced back to them
+fn verify_rust_code(
+    mut converted_rust: String,
+    mut source_map: SourceMap,
+    ast: &TokenNode,
+    ctx: &mut AnalysisContext,
+    explanations: &mut Explanations,
+) -> Result<(String, SourceMap), RhErr> {
+    let mut downgrades = 0;
+    loop {
+        let errors = verify::rustc_errors(&converted_rust).map_err(|message| RhErr {
+            err: ErrType::Unverified(message),
+            line: 0,
+        })?;
+        let Some(first) = errors.first() else {
+            return Ok((converted_rust, source_map));
+        };
+
+        let (immutable, borrowed): (Vec<&RustcError>, Vec<&RustcError>) = errors
+            .iter()
+            .filter(|error| error.is_borrow_error())
+            .partition(|error| error.is_immutable_binding());
+        let immutable_ids: Vec<String> = immutable.iter().flat_map(|e| e.named_ids()).collect();
+        let ids: Vec<String> = borrowed.iter().flat_map(|e| e.named_ids()).collect();
+        let fixable = errors.iter().all(|error| error.is_borrow_error());
+        if !fixable
+            || downgrades == MAX_DOWNGRADES
+            || !(ctx.make_mut(&immutable_ids) | ctx.downgrade(&ids))
+        {
+            // NOTE The C line the error is on, or else the one the first named variable is
+            // declared on
+            let line = source_map.c_line(first.line).unwrap_or_else(|| {
+                first
+                    .named_ids()
+                    .iter()
+                    .filter_map(|id| ctx.current_scope().variables.get(id))
+                    .find_map(|var_data| var_data.usages.first())
+                    .map_or(0, |usage| usage.get_line_number())
+            });
+            return Err(RhErr {
+                err: ErrType::Unverified(first.message.clone()),
+                line,
+            });
+        }
+
+        immutable.iter().for_each(|error| {
+            explanations.rustc(&error.named_ids(), &error.message, "declared `mut`")
+        });
+        borrowed
+            .iter()
+            .for_each(|error| explanations.rustc(&error.named_ids(), &error.message, "downgraded"));
+
+        downgrades += 1;
+        (converted_rust, source_map) = generate(ast, ctx);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::ast::{AssignmentOpType, NodeType, ScopeType, TokenNode};
+use crate::ast::{AssignmentOpType, NodeType, ScopeType, StorageClass, TokenNode};
 use crate::error::{ErrType as ET, RhErr};
 use crate::lexer::{CType, LineNumHandler, Token};
 use crate::token_handler::TokenHandler;
@@ -64,7 +64,20 @@ pub fn statement(
     let statement_token = token_handler.get_token();
     println!("Statement Token: {:?}", statement_token);
     match statement_token {
-        Token::Type(t) => type_statement(token_handler, t.clone()),
+        Token::Type(t) if scope_type == ScopeType::Program => {
+            type_statement(token_handler, t.clone(), Some(StorageClass::File))
+        }
+        Token::Type(t) => type_statement(token_handler, t.clone(), None),
+        Token::Static | Token::Extern => storage_statement(token_handler, scope_type),
+        _ if is_ptr_field(token_handler) => ptr_field_assignment(token_handler),
+        // NOTE `Foo * x;` is a declaration only because `Foo` is a typedef
+        Token::Id(name)
+            if token_handler.typedefs.contains_key(name)
+                && matches!(token_handler.peek(1), Token::Id(_) | Token::Star) =>
+        {
+            let t = token_handler.typedefs[name].clone();
+            declaration_statement(token_handler, t, scope_storage(&scope_type))
+        }
         Token::Id(name) => id_statement(token_handler, name.to_string()),
         Token::Star => deref_assignment(token_handler),
         Token::If => if_statement(token_handler),
@@ -81,7 +94,9 @@ pub fn statement(
         Token::Assert => assert_statement(token_handler),
         Token::Return => return_statement(token_handler),
         Token::PutChar => putchar_statement(token_handler),
-        Token::Struct => struct_statement(token_handler),
+        Token::Struct | Token::Union => struct_statement(token_handler),
+        Token::Enum => enum_statement(token_handler, scope_type),
+        Token::Typedef => typedef_statement(token_handler),
         Token::StructFieldId {
             struct_id,
             field_id,
@@ -160,6 +175,14 @@ fn arithmetic_term(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr>
 fn arithmetic_factor(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
     let token = token_handler.get_token().clone();
     let ret = match token {
+        _ if is_ptr_field(token_handler) => {
+            let (ptr_id, field_id) = ptr_field(token_handler)?;
+            Ok(TokenNode::new(
+                NodeType::PtrFieldId { ptr_id, field_id },
+                None,
+                token_handler.line(),
+            ))
+        }
         Token::NumLiteral(num) => Ok(TokenNode::new(
             NodeType::NumLiteral(num),
             None,
@@ -176,18 +199,40 @@ fn arithmetic_factor(token_handler: &mut TokenHandler) -> Result<TokenNode, RhEr
             None,
             token_handler.line(),
         )),
+        Token::FloatLiteral(literal) => Ok(TokenNode::new(
+            NodeType::FloatLiteral(literal),
+            None,
+            token_handler.line(),
+        )),
+        Token::StrLiteral(literal) => Ok(TokenNode::new(
+            NodeType::StrLiteral(literal),
+            None,
+            token_handler.line(),
+        )),
+        Token::Id(id) if id == "NULL" => {
+            Ok(TokenNode::new(NodeType::Null, None, token_handler.line()))
+        }
+        Token::Id(id) if token_handler.enum_constants.contains_key(&id) => Ok(TokenNode::new(
+            NodeType::EnumConstant {
+                enum_id: token_handler.enum_constants[&id].clone(),
+                id,
+            },
+            None,
+            token_handler.line(),
+        )),
         Token::Id(id) if *token_handler.peek(1) == Token::OParen => {
             Ok(function_call(token_handler, id.to_string())?)
         }
         Token::Id(id) if *token_handler.peek(1) == Token::OSquare => {
             token_handler.next_token();
-            let expr = Box::new(arithmetic_expression(token_handler)?);
-            token_handler.next_token();
-            if *token_handler.get_token() != Token::CSquare {
-                return Err(token_handler.new_err(ET::ExpectedCSquare));
-            }
+            let indices = array_indices(token_handler)?;
+            let field_id = element_field(token_handler)?;
             Ok(TokenNode::new(
-                NodeType::IndexArray { id, expr },
+                NodeType::IndexArray {
+                    id,
+                    indices,
+                    field_id,
+                },
                 None,
                 token_handler.line(),
             ))
@@ -202,15 +247,58 @@ fn arithmetic_factor(token_handler: &mut TokenHandler) -> Result<TokenNode, RhEr
         Token::BAnd => {
             token_handler.next_token();
             match &token_handler.get_token() {
+                Token::Id(id) if *token_handler.peek(1) == Token::OSquare => {
+                    let id = id.clone();
+                    token_handler.next_token();
+                    let indices = array_indices(token_handler)?;
+                    Ok(TokenNode::new(
+                        NodeType::AdrIndex { id, indices },
+                        None,
+                        token_handler.line(),
+                    ))
+                }
                 Token::Id(id) => Ok(TokenNode::new(
                     NodeType::Adr(id.to_string()),
                     None,
                     token_handler.line(),
                 )),
+                // NOTE Fields are borrowed on their own, like Rust's disjoint field borrows
+                Token::StructFieldId {
+                    struct_id,
+                    field_id,
+                } => Ok(TokenNode::new(
+                    NodeType::Adr(format!("{struct_id}.{field_id}")),
+                    None,
+                    token_handler.line(),
+                )),
                 _ => Err(token_handler.new_err(ET::ExpectedId)),
             }
         }
 
+        Token::SizeOf => {
+            token_handler.next_token();
+            if *token_handler.get_token() != Token::OParen {
+                return Err(token_handler.new_err(ET::ExpectedOParen));
+            }
+            token_handler.next_token();
+            let t = get_type_name(token_handler)?;
+            token_handler.next_token();
+            // NOTE Every pointer is the same size, so this is only recorded for `sizeof(int*)`
+            let mut ptr_count = 0;
+            while *token_handler.get_token() == Token::Star {
+                ptr_count += 1;
+                token_handler.next_token();
+            }
+            if *token_handler.get_token() != Token::CParen {
+                return Err(token_handler.new_err(ET::ExpectedCParen));
+            }
+            Ok(TokenNode::new(
+                NodeType::SizeOf(t, ptr_count),
+                None,
+                token_handler.line(),
+            ))
+        }
+
         Token::Star => {
             token_handler.next_token();
             let factor = arithmetic_factor(token_handler)?;
@@ -276,24 +364,73 @@ fn index_array_assignment(
     token_handler: &mut TokenHandler,
     id: String,
 ) -> Result<TokenNode, RhErr> {
+    let lside = array_indices(token_handler)?;
+    let field_id = element_field(token_handler)?;
     token_handler.next_token();
-    let lside = Box::new(arithmetic_expression(token_handler)?);
-    if *token_handler.get_token() != Token::CSquare {
-        return Err(token_handler.new_err(ET::ExpectedCSquare));
-    }
+    let mut assignment_op = match AssignmentOpType::from_token(token_handler.get_token()) {
+        Ok(op) => op,
+        Err(_) => return Err(token_handler.new_err(ET::ExpectedAssignment)),
+    };
     token_handler.next_token();
-    let rside = Box::new(condition_expr(token_handler)?);
-    if *token_handler.get_token() != Token::CSquare {
+    let rside = match assignment_op == AssignmentOpType::AddO {
+        true => {
+            assignment_op = AssignmentOpType::AddEq;
+            TokenNode::new(NodeType::NumLiteral(1), None, token_handler.line())
+        }
+        false => condition_expr(token_handler)?,
+    };
+    if *token_handler.get_token() != Token::Semi {
         return Err(token_handler.new_err(ET::ExpectedSemi));
     }
 
     Ok(TokenNode::new(
-        NodeType::IndexArrayAssignment { id, rside, lside },
+        NodeType::IndexArrayAssignment {
+            id,
+            assignment_op,
+            rside: Box::new(rside),
+            lside,
+            field_id,
+        },
         None,
         token_handler.line(),
     ))
 }
 
+// First token is [, last token is the final ]
+// `[i][j]` => [i, j]
+fn array_indices(token_handler: &mut TokenHandler) -> Result<Vec<TokenNode>, RhErr> {
+    let mut indices = Vec::with_capacity(2);
+    loop {
+        token_handler.next_token();
+        indices.push(arithmetic_expression(token_handler)?);
+        if *token_handler.get_token() != Token::CSquare {
+            return Err(token_handler.new_err(ET::ExpectedCSquare));
+        }
+
+        if *token_handler.peek(1) != Token::OSquare {
+            return Ok(indices);
+        }
+        token_handler.next_token();
+    }
+}
+
+// `.x` after the final ], which ends on the field if there is one
+fn element_field(token_handler: &mut TokenHandler) -> Result<Option<String>, RhErr> {
+    if *token_handler.peek(1) != Token::Dot {
+        return Ok(None);
+    }
+    token_handler.next_token();
+    token_handler.next_token();
+    match token_handler.get_token() {
+        Token::Id(field_id) => Ok(Some(field_id.clone())),
+        Token::StructFieldId {
+            struct_id,
+            field_id,
+        } => Ok(Some(format!("{struct_id}.{field_id}"))),
+        _ => Err(token_handler.new_err(ET::ExpectedId)),
+    }
+}
+
 // Token coming in should be (, id or [
 // if [] => Some(name)
 // else => None
@@ -323,6 +460,7 @@ fn deref_assignment(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr
 }
 
 fn while_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
+    let start_line = token_handler.line();
     token_handler.next_token();
     let condition_node = condition(token_handler)?;
 
@@ -335,14 +473,15 @@ fn while_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr>
     );
 
     let while_children = Box::new([condition_node, scope_node]);
-    Ok(TokenNode::new(
-        NodeType::While,
-        Some(while_children),
-        token_handler.line(),
-    ))
+    let mut while_node =
+        TokenNode::new(NodeType::While, Some(while_children), token_handler.line());
+    // NOTE Nodes are on the line they end on, the C source points to where they start
+    while_node.c_line = start_line;
+    Ok(while_node)
 }
 
 fn if_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
+    let start_line = token_handler.line();
     token_handler.next_token(); // might make semi handled by the called functions instead
     let condition_node = condition(token_handler)?;
 
@@ -355,11 +494,9 @@ fn if_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
     );
 
     let if_children = Box::new([condition_node, scope_node]);
-    Ok(TokenNode::new(
-        NodeType::If,
-        Some(if_children),
-        token_handler.line(),
-    ))
+    let mut if_node = TokenNode::new(NodeType::If, Some(if_children), token_handler.line());
+    if_node.c_line = start_line;
+    Ok(if_node)
 }
 
 fn function_declare_statement(
@@ -367,9 +504,10 @@ fn function_declare_statement(
     t: CType,
     id: String,
 ) -> Result<TokenNode, RhErr> {
+    let start_line = token_handler.line();
     token_handler.next_token();
     let mut args_scope = Vec::with_capacity(4);
-    while let Token::Type(t) = token_handler.get_token().clone() {
+    while let Ok(t) = get_type_name(token_handler) {
         token_handler.next_token();
         let id = match token_handler.get_token() {
             Token::Id(id) => id.clone(),
@@ -377,6 +515,19 @@ fn function_declare_statement(
         };
         token_handler.next_token();
 
+        // NOTE `int arr[]` arguments are slices
+        let t = match token_handler.get_token() {
+            Token::OSquare => {
+                token_handler.next_token();
+                if *token_handler.get_token() != Token::CSquare {
+                    return Err(token_handler.new_err(ET::ExpectedCSquare));
+                }
+                token_handler.next_token();
+                CType::Array(Box::new(t))
+            }
+            _ => t,
+        };
+
         let arg_node = TokenNode::new(NodeType::Declaration(id, t, 0), None, token_handler.line());
         args_scope.push(arg_node);
 
@@ -392,22 +543,35 @@ fn function_declare_statement(
     token_handler.next_token();
     token_handler.next_token();
 
-    let scope_node = TokenNode::new(
+    let mut scope_node = TokenNode::new(
         NodeType::Scope(None),
         Some(scope(token_handler, ScopeType::Function(t.clone()))?.into_boxed_slice()),
         token_handler.line(),
     );
+    set_return_type(&mut scope_node, &t);
     args_scope.push(scope_node);
 
-    let function_node = TokenNode::new(
+    let mut function_node = TokenNode::new(
         NodeType::FunctionDeclaration(id.clone(), t.clone()),
         Some(args_scope.into_boxed_slice()),
         token_handler.line(),
     );
+    function_node.c_line = start_line;
 
     Ok(function_node)
 }
 
+/// Gives every `return` in a function body the type the function returns
+fn set_return_type(node: &mut TokenNode, ret: &CType) {
+    if let NodeType::Return { t, .. } = &mut node.token {
+        *t = ret.clone();
+    }
+    node.children
+        .iter_mut()
+        .flatten()
+        .for_each(|child| set_return_type(child, ret));
+}
+
 fn function_call_statement(
     token_handler: &mut TokenHandler,
     name: String,
@@ -464,12 +628,65 @@ fn id_statement(token_handler: &mut TokenHandler, id: String) -> Result<TokenNod
     println!("id statement token: {:?}", token_handler.get_token());
     match token_handler.peek(1) {
         Token::OParen => function_call_statement(token_handler, id),
-        Token::OSquare => index_array_assignment(token_handler, id),
+        Token::OSquare => {
+            token_handler.next_token();
+            index_array_assignment(token_handler, id)
+        }
         _ => assignment(token_handler, id),
     }
 }
 
-fn type_statement(token_handler: &mut TokenHandler, t: CType) -> Result<TokenNode, RhErr> {
+// First token is `static` or `extern`
+fn storage_statement(
+    token_handler: &mut TokenHandler,
+    scope_type: ScopeType,
+) -> Result<TokenNode, RhErr> {
+    let storage = match (token_handler.get_token(), scope_type) {
+        (Token::Extern, _) => StorageClass::Extern,
+        (_, ScopeType::Program) => StorageClass::File,
+        _ => StorageClass::Static,
+    };
+    token_handler.next_token();
+    match get_type_name(token_handler)? {
+        CType::Struct(_) => panic!("Unsupported: Struct globals"),
+        t => type_statement(token_handler, t, Some(storage)),
+    }
+}
+
+fn scope_storage(scope_type: &ScopeType) -> Option<StorageClass> {
+    match scope_type {
+        ScopeType::Program => Some(StorageClass::File),
+        _ => None,
+    }
+}
+
+// The current token is the last token of the type, `Foo` in `Foo* x` or `struct Foo x`
+fn declaration_statement(
+    token_handler: &mut TokenHandler,
+    t: CType,
+    storage: Option<StorageClass>,
+) -> Result<TokenNode, RhErr> {
+    match t {
+        CType::Struct(struct_id) => {
+            token_handler.next_token();
+            match token_handler.get_token() {
+                Token::Id(var_id) => {
+                    struct_variable_declaration(struct_id, var_id.clone(), token_handler)
+                }
+                Token::Star => struct_ptr_declaration(struct_id, token_handler),
+                _ => Err(token_handler.new_err(ET::ExpectedId)),
+            }
+        }
+        t => type_statement(token_handler, t, storage),
+    }
+}
+
+// Declarations with a storage class are wrapped in a `Global` node
+fn type_statement(
+    token_handler: &mut TokenHandler,
+    t: CType,
+    storage: Option<StorageClass>,
+) -> Result<TokenNode, RhErr> {
     // let id = if let Token::Id(id) = token_handler.get_token() {
     //     id
     // } else {
@@ -492,11 +709,37 @@ fn type_statement(token_handler: &mut TokenHandler, t: CType) -> Result<TokenNod
     };
 
     token_handler.next_token();
-    match token_handler.get_token() {
-        Token::OParen => function_declare_statement(token_handler, t, id.clone()),
-        Token::OSquare => array_declare_statement(token_handler, t, id.clone()),
-        _ => scalar_declaration_statement(token_handler, t, id.clone(), ptr_cnt),
-    }
+    let declaration = match token_handler.get_token() {
+        Token::OParen => return function_declare_statement(token_handler, t, id.clone()),
+        Token::OSquare => array_declare_statement(token_handler, t, id.clone())?,
+        // NOTE Globals and statics without an initializer are zeroed, and `extern` declarations
+        // are initialized by their definition
+        Token::Semi if storage.is_some() && ptr_cnt == 0 => {
+            let zero = match t.is_floating() {
+                true => NodeType::FloatLiteral("0.0".into()),
+                false => NodeType::NumLiteral(0),
+            };
+            let children: Option<Box<[TokenNode]>> = match storage {
+                Some(StorageClass::Extern) => None,
+                _ => Some(Box::new([TokenNode::new(zero, None, token_handler.line())])),
+            };
+            TokenNode::new(
+                NodeType::Declaration(id.clone(), t, 0),
+                children,
+                token_handler.line(),
+            )
+        }
+        _ => scalar_declaration_statement(token_handler, t, id.clone(), ptr_cnt)?,
+    };
+
+    Ok(match storage {
+        Some(storage) => TokenNode::new(
+            NodeType::Global(storage),
+            Some(Box::new([declaration])),
+            token_handler.line(),
+        ),
+        None => declaration,
+    })
 }
 
 fn array_declare_statement(
@@ -504,44 +747,155 @@ fn array_declare_statement(
     t: CType,
     id: String,
 ) -> Result<TokenNode, RhErr> {
-    token_handler.next_token(); // Already checked open square bracket
-    let alloc_count = match token_handler.get_token() {
-        Token::NumLiteral(n) => *n,
-        _ => return Err(token_handler.new_err(ET::ExpectedNumLiteral)),
-    };
+    // NOTE The first dimension can be left out and inferred from the initializer, which is
+    // marked with a 0 until then
+    let mut dimensions: Vec<usize> = Vec::with_capacity(2);
+    while *token_handler.get_token() == Token::OSquare {
+        token_handler.next_token();
+        match token_handler.get_token() {
+            Token::NumLiteral(n) => {
+                dimensions.push(*n);
+                token_handler.next_token();
+            }
+            Token::CSquare if dimensions.is_empty() => dimensions.push(0),
+            _ => return Err(token_handler.new_err(ET::ExpectedNumLiteral)),
+        };
 
-    token_handler.next_token();
-    if *token_handler.get_token() != Token::CSquare {
-        return Err(token_handler.new_err(ET::ExpectedCSquare));
+        if *token_handler.get_token() != Token::CSquare {
+            return Err(token_handler.new_err(ET::ExpectedCSquare));
+        }
+        token_handler.next_token();
     }
 
     if *token_handler.get_token() != Token::Eq {
         if *token_handler.get_token() != Token::Semi {
             return Err(token_handler.new_err(ET::ExpectedSemi));
         }
+        if dimensions[0] == 0 {
+            return Err(token_handler.new_err(ET::ExpectedNumLiteral));
+        }
         return Ok(TokenNode::new(
-            NodeType::ArrayDeclaration(id.clone(), t, alloc_count),
+            NodeType::ArrayDeclaration(id.clone(), t, dimensions),
             None,
             token_handler.line(),
         ));
     }
 
     token_handler.next_token();
+    let items = match token_handler.get_token().clone() {
+        // `char buf[] = "hi"`, which includes the NUL terminator
+        Token::StrLiteral(literal) => {
+            if dimensions[0] == 0 {
+                dimensions[0] = c_string_len(&literal) + 1;
+            }
+            if c_string_len(&literal) > dimensions[0] {
+                return Err(token_handler.new_err(ET::TooManyItems));
+            }
+            let item = TokenNode::new(NodeType::StrLiteral(literal), None, token_handler.line());
+            vec![item].into_boxed_slice()
+        }
+        _ => {
+            let initializer = array_initializer(token_handler, dimensions.len())?;
+            let items = initializer.children.unwrap_or_default();
+            if dimensions[0] == 0 {
+                dimensions[0] = items.len();
+            }
+            if !fits(&items, &dimensions) {
+                return Err(token_handler.new_err(ET::TooManyItems));
+            }
+            items
+        }
+    };
+
+    token_handler.next_token();
+    if *token_handler.get_token() != Token::Semi {
+        return Err(token_handler.new_err(ET::ExpectedSemi));
+    }
+
+    Ok(TokenNode::new(
+        NodeType::ArrayDeclaration(id, t, dimensions),
+        Some(items),
+        token_handler.line(),
+    ))
+}
+
+/// Whether every row of an initializer list has no more items than the array has room for
+fn fits(items: &[TokenNode], dimensions: &[usize]) -> bool {
+    let Some((size, sub_dimensions)) = dimensions.split_first() else {
+        return true;
+    };
+    items.len() <= *size
+        && items.iter().all(|item| match &item.token {
+            NodeType::ArrayInitializer => {
+                fits(item.children.as_deref().unwrap_or_default(), sub_dimensions)
+            }
+            _ => true,
+        })
+}
+
+/// The number of characters in a string literal, not including the NUL terminator
+pub fn c_string_len(literal: &str) -> usize {
+    let mut chars = literal.chars();
+    let mut len = 0;
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        }
+        len += 1;
+    }
+    len
+}
+
+// First token is {, last token is }
+// Designated items (`[2] = 5`) are placed at their index, and the gaps they leave are zeroed.
+// Missing trailing items are left for the converter to zero
+fn array_initializer(
+    token_handler: &mut TokenHandler,
+    depth: usize,
+) -> Result<TokenNode, RhErr> {
     if *token_handler.get_token() != Token::OCurl {
         return Err(token_handler.new_err(ET::ExpectedOCurl));
     }
-
     token_handler.next_token();
-    let mut items: Vec<TokenNode> = Vec::with_capacity(4);
-    while let Token::NumLiteral(n) = *token_handler.get_token() {
-        let item_node = TokenNode::new(NodeType::NumLiteral(n), None, token_handler.line());
-        items.push(item_node);
-        token_handler.next_token();
-        let tok = token_handler.get_token();
-        if *tok != Token::Comma && *tok != Token::CCurl {
-            return Err(token_handler.new_err(ET::ExpectedCCurl));
+
+    let mut items: Vec<Option<TokenNode>> = Vec::with_capacity(4);
+    let mut position = 0;
+    while *token_handler.get_token() != Token::CCurl {
+        if *token_handler.get_token() == Token::OSquare {
+            token_handler.next_token();
+            position = match token_handler.get_token() {
+                Token::NumLiteral(n) => *n,
+                _ => return Err(token_handler.new_err(ET::ExpectedNumLiteral)),
+            };
+            token_handler.next_token();
+            if *token_handler.get_token() != Token::CSquare {
+                return Err(token_handler.new_err(ET::ExpectedCSquare));
+            }
+            token_handler.next_token();
+            if *token_handler.get_token() != Token::Eq {
+                return Err(token_handler.new_err(ET::ExpectedAssignment));
+            }
+            token_handler.next_token();
+        }
+
+        let item = match depth > 1 {
+            true => {
+                let item = array_initializer(token_handler, depth - 1)?;
+                token_handler.next_token();
+                item
+            }
+            false => arithmetic_expression(token_handler)?,
+        };
+
+        if items.len() <= position {
+            items.resize(position + 1, None);
         }
+        items[position] = Some(item);
+        position += 1;
 
+        if *token_handler.get_token() != Token::Comma {
+            break;
+        }
         token_handler.next_token();
     }
 
@@ -549,15 +903,21 @@ fn array_declare_statement(
         return Err(token_handler.new_err(ET::ExpectedCCurl));
     }
 
-    token_handler.next_token();
-    if *token_handler.get_token() != Token::Semi {
-        return Err(token_handler.new_err(ET::ExpectedSemi));
-    }
+    let line = token_handler.line();
+    let items: Vec<TokenNode> = items
+        .into_iter()
+        .map(|item| {
+            item.unwrap_or_else(|| match depth > 1 {
+                true => TokenNode::new(NodeType::ArrayInitializer, None, line),
+                false => TokenNode::new(NodeType::NumLiteral(0), None, line),
+            })
+        })
+        .collect();
 
     Ok(TokenNode::new(
-        NodeType::ArrayDeclaration(id, t, alloc_count),
+        NodeType::ArrayInitializer,
         Some(items.into_boxed_slice()),
-        token_handler.line(),
+        line,
     ))
 }
 
@@ -661,6 +1021,25 @@ fn asm_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
         Token::StrLiteral(str) => {
             println!("Asm string: {}", str);
             token_handler.next_token();
+            // NOTE Without any `:`, it's a basic asm, where `%` isn't special
+            let template = match *token_handler.get_token() == Token::Colon {
+                true => str.to_string(),
+                false => str.replace('%', "%%"),
+            };
+            let outputs = asm_operands(token_handler)?;
+            let inputs = asm_operands(token_handler)?;
+            let mut clobbers = vec![];
+            if *token_handler.get_token() == Token::Colon {
+                token_handler.next_token();
+                while let Token::StrLiteral(clobber) = token_handler.get_token().clone() {
+                    clobbers.push(clobber);
+                    token_handler.next_token();
+                    if *token_handler.get_token() != Token::Comma {
+                        break;
+                    }
+                    token_handler.next_token();
+                }
+            }
             if *token_handler.get_token() != Token::CParen {
                 return Err(token_handler.new_err(ET::ExpectedCParen));
             }
@@ -670,7 +1049,12 @@ fn asm_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
                 return Err(token_handler.new_err(ET::ExpectedSemi));
             }
             Ok(TokenNode::new(
-                NodeType::Asm(str.to_string()),
+                NodeType::Asm {
+                    template,
+                    outputs,
+                    inputs,
+                    clobbers,
+                },
                 None,
                 token_handler.line(),
             ))
@@ -679,6 +1063,37 @@ fn asm_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
     }
 }
 
+/// One `: "constraint"(id), ...` list of an extended asm, empty if there's no `:`
+fn asm_operands(token_handler: &mut TokenHandler) -> Result<Vec<(String, String)>, RhErr> {
+    let mut operands = vec![];
+    if *token_handler.get_token() != Token::Colon {
+        return Ok(operands);
+    }
+    token_handler.next_token();
+    while let Token::StrLiteral(constraint) = token_handler.get_token().clone() {
+        token_handler.next_token();
+        if *token_handler.get_token() != Token::OParen {
+            return Err(token_handler.new_err(ET::ExpectedOParen));
+        }
+        token_handler.next_token();
+        let Token::Id(id) = token_handler.get_token().clone() else {
+            return Err(token_handler.new_err(ET::ExpectedId));
+        };
+        token_handler.next_token();
+        if *token_handler.get_token() != Token::CParen {
+            return Err(token_handler.new_err(ET::ExpectedCParen));
+        }
+        operands.push((constraint, id));
+        token_handler.next_token();
+        if *token_handler.get_token() != Token::Comma {
+            break;
+        }
+        token_handler.next_token();
+    }
+
+    Ok(operands)
+}
+
 fn for_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
     token_handler.next_token();
     if *token_handler.get_token() != Token::OParen {
@@ -741,7 +1156,7 @@ pub fn assert_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, R
         token_handler.line(),
     );
 
-    if *token_handler.get_token() == Token::CParen {
+    if *token_handler.get_token() != Token::CParen {
         return Err(token_handler.new_err(ET::ExpectedCParen));
     }
     token_handler.next_token();
@@ -786,6 +1201,8 @@ pub fn return_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, R
     let return_token = TokenNode::new(
         NodeType::Return {
             expr: Box::new(expr_node),
+            // NOTE Filled in once the whole function is parsed
+            t: CType::Void,
         },
         None,
         token_handler.line(),
@@ -819,7 +1236,9 @@ pub fn compound_literal(token_handler: &mut TokenHandler) -> Result<Vec<TokenNod
     Ok(fields)
 }
 
+// First token is `struct` or `union`
 pub fn struct_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
+    let is_union = *token_handler.get_token() == Token::Union;
     token_handler.next_token();
     let struct_id = match token_handler.get_token() {
         Token::Id(struct_id) => struct_id.clone(),
@@ -828,12 +1247,41 @@ pub fn struct_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, R
 
     token_handler.next_token();
     match token_handler.get_token() {
-        Token::OCurl => struct_definition(struct_id, token_handler),
-        Token::Id(var_id) => struct_variable_declaration(struct_id, var_id.clone(), token_handler),
-        _ => Err(token_handler.new_err(ET::ExpectedId)),
+        Token::OCurl => {
+            let definition = struct_definition(struct_id, is_union, token_handler)?;
+            token_handler.next_token();
+            if *token_handler.get_token() != Token::Semi {
+                return Err(token_handler.new_err(ET::ExpectedSemi));
+            }
+            Ok(definition)
+        }
+        _ => {
+            token_handler.prev_token();
+            declaration_statement(token_handler, CType::Struct(struct_id), None)
+        }
     }
 }
 
+// First token is *
+fn struct_ptr_declaration(
+    struct_id: String,
+    token_handler: &mut TokenHandler,
+) -> Result<TokenNode, RhErr> {
+    let mut ptr_cnt = 0;
+    while *token_handler.get_token() == Token::Star {
+        ptr_cnt += 1;
+        token_handler.next_token();
+    }
+
+    let id = match token_handler.get_token() {
+        Token::Id(id) => id.clone(),
+        _ => return Err(token_handler.new_err(ET::ExpectedId)),
+    };
+
+    token_handler.next_token();
+    scalar_declaration_statement(token_handler, CType::Struct(struct_id), id, ptr_cnt)
+}
+
 pub fn struct_variable_declaration(
     struct_id: String,
     var_id: String,
@@ -841,12 +1289,18 @@ pub fn struct_variable_declaration(
 ) -> Result<TokenNode, RhErr> {
     token_handler.next_token();
     let exprs = match token_handler.get_token() {
-        Token::Eq => compound_literal(token_handler)?,
+        Token::Eq => {
+            let exprs = compound_literal(token_handler)?;
+            token_handler.next_token();
+            exprs
+        }
         Token::Semi => Vec::new(),
+        Token::OSquare => {
+            return array_declare_statement(token_handler, CType::Struct(struct_id), var_id)
+        }
         _ => return Err(token_handler.new_err(ET::ExpectedSemi)),
     };
 
-    token_handler.next_token();
     if *token_handler.get_token() != Token::Semi {
         return Err(token_handler.new_err(ET::ExpectedSemi));
     }
@@ -862,10 +1316,13 @@ pub fn struct_variable_declaration(
     ))
 }
 
+// From `{` to `}`
 pub fn struct_definition(
     struct_id: String,
+    is_union: bool,
     token_handler: &mut TokenHandler,
 ) -> Result<TokenNode, RhErr> {
+    let start_line = token_handler.line();
     let mut field_definitions: Vec<(String, usize, CType)> = vec![];
     token_handler.next_token();
     while let Ok(t) = get_type_name(token_handler) {
@@ -893,19 +1350,17 @@ pub fn struct_definition(
         return Err(token_handler.new_err(ET::ExpectedCCurl));
     }
 
-    token_handler.next_token();
-    if *token_handler.get_token() != Token::Semi {
-        return Err(token_handler.new_err(ET::ExpectedSemi));
-    }
-
-    Ok(TokenNode::new(
+    let mut struct_node = TokenNode::new(
         NodeType::StructDefinition {
             struct_id,
             field_definitions,
+            is_union,
         },
         None,
         token_handler.line(),
-    ))
+    );
+    struct_node.c_line = start_line;
+    Ok(struct_node)
 }
 
 pub fn struct_field_assignment(
@@ -939,16 +1394,255 @@ pub fn struct_field_assignment(
     ))
 }
 
+fn is_ptr_field(token_handler: &TokenHandler) -> bool {
+    match token_handler.get_token() {
+        Token::Id(_) => *token_handler.peek(1) == Token::Arrow,
+        Token::OParen => {
+            *token_handler.peek(1) == Token::Star
+                && matches!(token_handler.peek(2), Token::Id(_))
+                && *token_handler.peek(3) == Token::CParen
+                && *token_handler.peek(4) == Token::Dot
+        }
+        _ => false,
+    }
+}
+
+// `p->field` or `(*p).field`, ends on the field
+fn ptr_field(token_handler: &mut TokenHandler) -> Result<(String, String), RhErr> {
+    if *token_handler.get_token() == Token::OParen {
+        token_handler.next_token();
+        token_handler.next_token();
+    }
+    let ptr_id = match token_handler.get_token() {
+        Token::Id(id) => id.clone(),
+        _ => return Err(token_handler.new_err(ET::ExpectedId)),
+    };
+
+    token_handler.next_token();
+    if *token_handler.get_token() == Token::CParen {
+        token_handler.next_token();
+    }
+    token_handler.next_token();
+    let field_id = match token_handler.get_token() {
+        Token::Id(field_id) => field_id.clone(),
+        Token::StructFieldId {
+            struct_id,
+            field_id,
+        } => format!("{struct_id}.{field_id}"),
+        _ => return Err(token_handler.new_err(ET::ExpectedId)),
+    };
+
+    Ok((ptr_id, field_id))
+}
+
+pub fn ptr_field_assignment(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
+    let (ptr_id, field_id) = ptr_field(token_handler)?;
+    token_handler.next_token();
+    let assignment_op = match AssignmentOpType::from_token(token_handler.get_token()) {
+        Ok(op) => op,
+        Err(_) => return Err(token_handler.new_err(ET::ExpectedAssignment)),
+    };
+
+    token_handler.next_token();
+    let expr = Box::new(condition_expr(token_handler)?);
+
+    if *token_handler.get_token() != Token::Semi {
+        return Err(token_handler.new_err(ET::ExpectedSemi));
+    }
+
+    Ok(TokenNode::new(
+        NodeType::PtrFieldAssignment {
+            ptr_id,
+            field_id,
+            assignment_op,
+            expr,
+        },
+        None,
+        token_handler.line(),
+    ))
+}
+
 pub fn get_type_name(token_handler: &mut TokenHandler) -> Result<CType, RhErr> {
     match token_handler.get_token() {
-        Token::Struct => {
+        Token::Struct | Token::Union => {
             token_handler.next_token();
             match token_handler.get_token() {
                 Token::Id(id) => Ok(CType::Struct(id.clone())),
                 _ => Err(token_handler.new_err(ET::ExpectedType)),
             }
         }
+        Token::Enum => {
+            token_handler.next_token();
+            match token_handler.get_token() {
+                Token::Id(id) => Ok(CType::Enum(id.clone())),
+                _ => Err(token_handler.new_err(ET::ExpectedType)),
+            }
+        }
         Token::Type(t) => Ok(t.clone()),
+        Token::Id(id) if token_handler.typedefs.contains_key(id) => {
+            Ok(token_handler.typedefs[id].clone())
+        }
         _ => Err(token_handler.new_err(ET::ExpectedType)),
     }
 }
+
+// First token is `enum`
+fn enum_statement(
+    token_handler: &mut TokenHandler,
+    scope_type: ScopeType,
+) -> Result<TokenNode, RhErr> {
+    token_handler.next_token();
+    let enum_id = match token_handler.get_token() {
+        Token::Id(id) => {
+            let id = id.clone();
+            token_handler.next_token();
+            Some(id)
+        }
+        _ => None,
+    };
+
+    match (token_handler.get_token(), enum_id) {
+        (Token::OCurl, enum_id) => {
+            let variants = enum_body(token_handler)?;
+            token_handler.next_token();
+            if *token_handler.get_token() != Token::Semi {
+                return Err(token_handler.new_err(ET::ExpectedSemi));
+            }
+            Ok(enum_definition(token_handler, enum_id, variants))
+        }
+        (_, Some(enum_id)) => {
+            token_handler.prev_token();
+            declaration_statement(
+                token_handler,
+                CType::Enum(enum_id),
+                scope_storage(&scope_type),
+            )
+        }
+        _ => Err(token_handler.new_err(ET::ExpectedId)),
+    }
+}
+
+// From `{` to `}`, values without an initializer count up from the previous one
+fn enum_body(token_handler: &mut TokenHandler) -> Result<Vec<(String, i32)>, RhErr> {
+    token_handler.next_token();
+    let mut variants = vec![];
+    let mut value = 0;
+    while let Token::Id(id) = token_handler.get_token().clone() {
+        token_handler.next_token();
+        if *token_handler.get_token() == Token::Eq {
+            token_handler.next_token();
+            let sign = match token_handler.get_token() {
+                Token::Sub => {
+                    token_handler.next_token();
+                    -1
+                }
+                _ => 1,
+            };
+            value = match token_handler.get_token() {
+                Token::NumLiteral(n) => sign * *n as i32,
+                _ => return Err(token_handler.new_err(ET::ExpectedNumLiteral)),
+            };
+            token_handler.next_token();
+        }
+        variants.push((id, value));
+        value += 1;
+
+        if *token_handler.get_token() != Token::Comma {
+            break;
+        }
+        token_handler.next_token();
+    }
+
+    if *token_handler.get_token() != Token::CCurl {
+        return Err(token_handler.new_err(ET::ExpectedCCurl));
+    }
+    Ok(variants)
+}
+
+fn enum_definition(
+    token_handler: &mut TokenHandler,
+    enum_id: Option<String>,
+    variants: Vec<(String, i32)>,
+) -> TokenNode {
+    variants.iter().for_each(|(id, _)| {
+        token_handler
+            .enum_constants
+            .insert(id.clone(), enum_id.clone());
+    });
+
+    TokenNode::new(
+        NodeType::EnumDefinition { enum_id, variants },
+        None,
+        token_handler.line(),
+    )
+}
+
+// `typedef int Count;`, `typedef struct Node Node;`, `typedef enum { A, B } Letter;`
+// Aliases are resolved by the parser, so a typedef without a body has nothing to translate
+fn typedef_statement(token_handler: &mut TokenHandler) -> Result<TokenNode, RhErr> {
+    token_handler.next_token();
+    let keyword = token_handler.get_token().clone();
+    let has_body = *token_handler.peek(1) == Token::OCurl || *token_handler.peek(2) == Token::OCurl;
+
+    let (name, t, node) = match keyword {
+        Token::Struct | Token::Union | Token::Enum if has_body => {
+            token_handler.next_token();
+            let tag = match token_handler.get_token() {
+                Token::Id(id) => {
+                    let id = id.clone();
+                    token_handler.next_token();
+                    Some(id)
+                }
+                _ => None,
+            };
+
+            // NOTE Anonymous definitions take the typedef name
+            if keyword == Token::Enum {
+                let variants = enum_body(token_handler)?;
+                let name = typedef_name(token_handler)?;
+                let id = tag.unwrap_or(name.clone());
+                let node = enum_definition(token_handler, Some(id.clone()), variants);
+                (name, CType::Enum(id), node)
+            } else {
+                let mut node = struct_definition(
+                    tag.clone().unwrap_or_default(),
+                    keyword == Token::Union,
+                    token_handler,
+                )?;
+                let name = typedef_name(token_handler)?;
+                let id = tag.unwrap_or(name.clone());
+                if let NodeType::StructDefinition { struct_id, .. } = &mut node.token {
+                    *struct_id = id.clone();
+                }
+                (name, CType::Struct(id), node)
+            }
+        }
+        _ => {
+            let t = get_type_name(token_handler)?;
+            let name = typedef_name(token_handler)?;
+            let node = TokenNode::new(NodeType::Scope(None), None, token_handler.line());
+            (name, t, node)
+        }
+    };
+    token_handler.typedefs.insert(name, t);
+
+    Ok(node)
+}
+
+// The current token is the last token of the aliased type, ends on `;`
+fn typedef_name(token_handler: &mut TokenHandler) -> Result<String, RhErr> {
+    token_handler.next_token();
+    if *token_handler.get_token() == Token::Star {
+        panic!("Unsupported: Pointer typedefs");
+    }
+    let name = match token_handler.get_token() {
+        Token::Id(name) => name.clone(),
+        _ => return Err(token_handler.new_err(ET::ExpectedId)),
+    };
+    token_handler.next_token();
+    if *token_handler.get_token() != Token::Semi {
+        return Err(token_handler.new_err(ET::ExpectedSemi));
+    }
+
+    Ok(name)
+}
@@ -0,0 +1,652 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    analysis_ctx::AnalysisContext,
+    analyzer::{heap_allocation_size, sub_nodes, AllocationSize},
+    ast::{NodeType, TokenNode as Node},
+    data_model::{LineNumber, NodeRole, RecursiveStrategy, VarData},
+    lexer::CType,
+};
+
+/// Where a pointer to a node gets its value from
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeSource {
+    Null,
+    // `malloc(sizeof(struct Node))`
+    Alloc,
+    Ptr(String),
+    // `p->next`
+    Link { ptr_id: String, field_id: String },
+}
+
+/// What a statement does with the pointers to nodes, in the order the statements are written
+#[derive(Debug, Clone)]
+enum NodeOp {
+    // `p = q`, or `struct Node* p = q` for a declaration
+    Assign {
+        id: String,
+        source: NodeSource,
+        declaration: bool,
+    },
+    // `p->next = q`
+    Link {
+        ptr_id: String,
+        field_id: String,
+        source: NodeSource,
+    },
+    // Reading or writing a field that isn't a link, `p->v`
+    Access {
+        ptr_id: String,
+        write: bool,
+    },
+    // `p == q`, where either side may be NULL
+    Compare {
+        left: NodeSource,
+        right: NodeSource,
+    },
+    Free {
+        id: String,
+    },
+}
+
+/// What happens to a pointer when a boxed node is linked, in the order it happens
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Event {
+    Read,
+    Write,
+    // The owner gives up its node
+    Move,
+    // A link is taken out of the owner's node
+    Take,
+    // The pointer is reassigned or freed
+    Kill,
+}
+
+impl NodeOp {
+    fn ptr_id(&self) -> Option<&str> {
+        match self {
+            NodeOp::Assign { id, .. } | NodeOp::Free { id } => Some(id),
+            NodeOp::Link { ptr_id, .. } | NodeOp::Access { ptr_id, .. } => Some(ptr_id),
+            NodeOp::Compare { left, right } => source_ptr(left).or(source_ptr(right)),
+        }
+    }
+}
+
+fn source_ptr(source: &NodeSource) -> Option<&str> {
+    match source {
+        NodeSource::Ptr(id) | NodeSource::Link { ptr_id: id, .. } => Some(id),
+        NodeSource::Null | NodeSource::Alloc => None,
+    }
+}
+
+/// Whether `p->field` is a link to another node
+pub fn is_link(ptr_id: &str, field_id: &str, ctx: &AnalysisContext) -> bool {
+    let var_data = ctx.get_var(ptr_id);
+    let CType::Struct(struct_id) = &var_data.var_type else {
+        return false;
+    };
+    var_data.node_role.is_some()
+        && ctx
+            .field_definition(struct_id, field_id)
+            .is_some_and(|field| !field.ptr_type.is_empty())
+}
+
+/// The pointer to a node an expression evaluates to, if it evaluates to one
+pub fn node_source(expr: &Node, ctx: &AnalysisContext) -> Option<NodeSource> {
+    match &expr.token {
+        NodeType::Null => Some(NodeSource::Null),
+        NodeType::Id(id) if ctx.is_node_ptr(id) => Some(NodeSource::Ptr(id.clone())),
+        NodeType::PtrFieldId { ptr_id, field_id } if is_link(ptr_id, field_id, ctx) => {
+            Some(NodeSource::Link {
+                ptr_id: ptr_id.clone(),
+                field_id: field_id.clone(),
+            })
+        }
+        _ => match heap_allocation_size(expr) {
+            Some(AllocationSize::Single) => Some(NodeSource::Alloc),
+            Some(AllocationSize::Array(_)) => panic!("Unsupported: Arrays of recursive structs"),
+            None => None,
+        },
+    }
+}
+
+fn expect_node_source(expr: &Node, ctx: &AnalysisContext) -> NodeSource {
+    node_source(expr, ctx).expect("Unsupported: Pointers to nodes computed from an expression")
+}
+
+/// Decides how each recursive struct is translated from how its nodes are linked
+/// - Nodes that are only ever linked to by one pointer at a time are boxed, with the pointers
+///   only walking the list being borrows
+/// - A field pointing back to the node that links to it (`n->prev = tail` after
+///   `tail->next = n`) makes the nodes shared, with the back pointer being `Weak`
+/// - Nodes linked in a cycle, or pointers compared to each other, put the nodes in an arena
+///
+/// WARNING Nodes are told apart by the pointer they're assigned to, so a cycle built through
+/// several assignments to the same pointer (`tail->next = n; tail = n;` in a loop) isn't seen
+pub fn recursive_struct_usage(root: &Node, ctx: &mut AnalysisContext) {
+    let struct_ids: Vec<String> = ctx
+        .structs
+        .iter()
+        .filter(|(_, struct_data)| struct_data.recursive.is_some())
+        .map(|(struct_id, _)| struct_id.clone())
+        .collect();
+    if struct_ids.is_empty() {
+        return;
+    }
+
+    let mut ops = vec![];
+    let mut loops = vec![];
+    node_ops(root, ctx, &mut ops, &mut loops);
+
+    struct_ids.iter().for_each(|struct_id| {
+        let t = CType::Struct(struct_id.clone());
+        let ops: Vec<(LineNumber, NodeOp)> = ops
+            .iter()
+            .filter(|(_, op)| op.ptr_id().is_some_and(|id| ctx.get_var(id).var_type == t))
+            .cloned()
+            .collect();
+
+        let strategy = match (needs_arena(&ops), weak_fields(&ops)) {
+            (true, _) => RecursiveStrategy::Arena,
+            (false, weak_fields) if !weak_fields.is_empty() => {
+                RecursiveStrategy::Shared { weak_fields }
+            }
+            _ => match boxed_roles(&ops, &loops) {
+                Some(roles) => {
+                    roles
+                        .into_iter()
+                        .for_each(|(id, role)| ctx.get_var_mut(&id).node_role = Some(role));
+                    RecursiveStrategy::Boxed
+                }
+                None => RecursiveStrategy::Shared {
+                    weak_fields: vec![],
+                },
+            },
+        };
+
+        if let RecursiveStrategy::Shared { weak_fields } = &strategy {
+            ops.iter().for_each(|(_, op)| {
+                if let NodeOp::Link {
+                    field_id,
+                    source: NodeSource::Alloc,
+                    ..
+                } = op
+                {
+                    assert!(
+                        !weak_fields.contains(field_id),
+                        "Unsupported: Allocating a node only pointed back to"
+                    );
+                }
+            });
+        }
+
+        // NOTE Reassigned and freed pointers are `mut`, and boxed pointers are `mut` to write
+        // through them or give up their node
+        let is_boxed = strategy == RecursiveStrategy::Boxed;
+        let mutated: Vec<String> = ops
+            .iter()
+            .flat_map(|(_, op)| match op {
+                NodeOp::Assign {
+                    id,
+                    source,
+                    declaration,
+                } => {
+                    let mut ids: Vec<String> = match declaration {
+                        true => vec![],
+                        false => vec![id.clone()],
+                    };
+                    // NOTE Giving up a node, taking a link out of one or borrowing it mutably
+                    let role = ctx.get_var(id).node_role;
+                    if let (true, Some(source_id)) = (
+                        is_boxed && role != Some(NodeRole::Cursor { is_mut: false }),
+                        source_ptr(source),
+                    ) {
+                        ids.push(source_id.to_string());
+                    }
+                    ids
+                }
+                NodeOp::Free { id } => vec![id.clone()],
+                NodeOp::Link { ptr_id, source, .. } if is_boxed => {
+                    let mut ids = vec![ptr_id.clone()];
+                    ids.extend(source_ptr(source).map(str::to_string));
+                    ids
+                }
+                NodeOp::Access {
+                    ptr_id,
+                    write: true,
+                } if is_boxed => vec![ptr_id.clone()],
+                _ => vec![],
+            })
+            .collect();
+        mutated
+            .into_iter()
+            .for_each(|id| ctx.mut_var(id, VarData::set_mut));
+
+        ctx.get_struct_mut(struct_id).recursive = Some(strategy);
+    });
+}
+
+// Collects what's done with pointers to nodes, and the lines of each loop
+fn node_ops(
+    root: &Node,
+    ctx: &AnalysisContext,
+    ops: &mut Vec<(LineNumber, NodeOp)>,
+    loops: &mut Vec<(LineNumber, LineNumber)>,
+) {
+    let mut push = |op: NodeOp| ops.push((root.line, op));
+    match &root.token {
+        NodeType::PtrDeclaration(id, _, expr) if ctx.is_node_ptr(id) => push(NodeOp::Assign {
+            id: id.clone(),
+            source: expect_node_source(expr, ctx),
+            declaration: true,
+        }),
+        NodeType::Assignment(_, id) if ctx.is_node_ptr(id) => push(NodeOp::Assign {
+            id: id.clone(),
+            source: expect_node_source(&root.children.as_ref().unwrap()[0], ctx),
+            declaration: false,
+        }),
+        NodeType::PtrFieldAssignment {
+            ptr_id,
+            field_id,
+            assignment_op: _,
+            expr,
+        } if is_link(ptr_id, field_id, ctx) => push(NodeOp::Link {
+            ptr_id: ptr_id.clone(),
+            field_id: field_id.clone(),
+            source: expect_node_source(expr, ctx),
+        }),
+        NodeType::PtrFieldAssignment { ptr_id, expr, .. } if ctx.is_node_ptr(ptr_id) => {
+            node_ops(expr, ctx, ops, loops);
+            ops.push((
+                root.line,
+                NodeOp::Access {
+                    ptr_id: ptr_id.clone(),
+                    write: true,
+                },
+            ));
+        }
+        NodeType::PtrFieldId { ptr_id, field_id } if is_link(ptr_id, field_id, ctx) => {
+            panic!("Unsupported: Links used outside of assignments and comparisons")
+        }
+        NodeType::PtrFieldId { ptr_id, .. } if ctx.is_node_ptr(ptr_id) => push(NodeOp::Access {
+            ptr_id: ptr_id.clone(),
+            write: false,
+        }),
+        NodeType::EqCmp | NodeType::NeqCmp => {
+            let children = root.children.as_ref().unwrap();
+            match (
+                node_source(&children[0], ctx),
+                node_source(&children[1], ctx),
+            ) {
+                (Some(NodeSource::Null), Some(NodeSource::Null)) => {
+                    panic!("Unsupported: Comparing NULL to itself")
+                }
+                (Some(left), Some(right)) => push(NodeOp::Compare { left, right }),
+                (None, None) => children
+                    .iter()
+                    .for_each(|child| node_ops(child, ctx, ops, loops)),
+                _ => panic!("Unsupported: Comparing a pointer to a node to something else"),
+            }
+        }
+        NodeType::FunctionCall(name) if name == "free" => {
+            match &root.children.as_ref().unwrap()[0].token {
+                NodeType::Id(id) if ctx.is_node_ptr(id) => push(NodeOp::Free { id: id.clone() }),
+                _ => {}
+            }
+        }
+        NodeType::Id(id) if ctx.is_node_ptr(id) => {
+            panic!("Unsupported: Passing pointers to nodes around")
+        }
+        NodeType::Adr(id) if ctx.is_node_ptr(id) => {
+            panic!("Unsupported: Pointers to pointers to nodes")
+        }
+        NodeType::Null => panic!("Unsupported: NULL pointers to anything but recursive structs"),
+        NodeType::While => {
+            loops.push(line_span(root));
+            sub_nodes(root)
+                .into_iter()
+                .for_each(|node| node_ops(node, ctx, ops, loops));
+        }
+        _ => sub_nodes(root)
+            .into_iter()
+            .for_each(|node| node_ops(node, ctx, ops, loops)),
+    }
+}
+
+fn line_span(root: &Node) -> (LineNumber, LineNumber) {
+    sub_nodes(root).into_iter().map(line_span).fold(
+        (root.line, root.line),
+        |(start, end), (sub_start, sub_end)| (start.min(sub_start), end.max(sub_end)),
+    )
+}
+
+// NOTE Indices compare like pointers do, and cycles are fine in an arena
+fn needs_arena(ops: &[(LineNumber, NodeOp)]) -> bool {
+    let compares_ptrs = ops.iter().any(|(_, op)| {
+        matches!(op, NodeOp::Compare { left, right }
+            if *left != NodeSource::Null && *right != NodeSource::Null)
+    });
+
+    let weak_fields = weak_fields(ops);
+    let mut links: HashMap<&str, Vec<&str>> = HashMap::new();
+    ops.iter().for_each(|(_, op)| {
+        if let NodeOp::Link {
+            ptr_id,
+            field_id,
+            source: NodeSource::Ptr(target),
+        } = op
+        {
+            if !weak_fields.contains(field_id) {
+                links.entry(ptr_id).or_default().push(target);
+            }
+        }
+    });
+
+    // NOTE Every node on the path so far, so reaching one again is a cycle
+    fn reaches_cycle<'a>(
+        id: &'a str,
+        links: &HashMap<&'a str, Vec<&'a str>>,
+        path: &mut Vec<&'a str>,
+        done: &mut HashSet<&'a str>,
+    ) -> bool {
+        if path.contains(&id) {
+            return true;
+        }
+        if !done.insert(id) {
+            return false;
+        }
+        path.push(id);
+        let cycle = links
+            .get(id)
+            .into_iter()
+            .flatten()
+            .any(|target| reaches_cycle(target, links, path, done));
+        path.pop();
+        cycle
+    }
+    let mut done = HashSet::new();
+    let cyclic = links
+        .keys()
+        .any(|id| reaches_cycle(id, &links, &mut vec![], &mut done));
+
+    compares_ptrs || cyclic
+}
+
+/// The fields pointing back to the node linking to them, `n->prev = tail` after `tail->next = n`
+/// NOTE The link from the older node to the newer one is the one keeping it alive, since nodes
+/// are usually allocated and then linked under a node that's already there
+fn weak_fields(ops: &[(LineNumber, NodeOp)]) -> Vec<String> {
+    let mut declared: HashMap<&str, usize> = HashMap::new();
+    let mut links: Vec<(&str, &str, &str)> = vec![];
+    ops.iter().enumerate().for_each(|(i, (_, op))| match op {
+        NodeOp::Assign {
+            id,
+            declaration: true,
+            ..
+        } => {
+            declared.entry(id).or_insert(i);
+        }
+        NodeOp::Link {
+            ptr_id,
+            field_id,
+            source: NodeSource::Ptr(target),
+        } if ptr_id != target => links.push((ptr_id, field_id, target)),
+        _ => {}
+    });
+
+    // NOTE Each pair of links between two nodes votes for its newer node's field being the back
+    // pointer
+    let mut votes: HashMap<&str, i32> = HashMap::new();
+    links.iter().for_each(|(from, field_id, to)| {
+        let Some((_, back_field, _)) = links.iter().find(|(other_from, other_field, other_to)| {
+            other_from == to && other_to == from && other_field != field_id
+        }) else {
+            return;
+        };
+        let age = |id: &str| declared.get(id).copied().unwrap_or(usize::MAX);
+        let vote = match age(from) <= age(to) {
+            true => (*back_field, *field_id),
+            false => (*field_id, *back_field),
+        };
+        *votes.entry(vote.0).or_default() += 1;
+        *votes.entry(vote.1).or_default() -= 1;
+    });
+
+    let mut weak_fields: Vec<String> = votes
+        .into_iter()
+        .filter(|(_, votes)| *votes > 0)
+        .map(|(field_id, _)| field_id.to_string())
+        .collect();
+    weak_fields.sort();
+    weak_fields
+}
+
+/// The role of each pointer if every node has a single owner, with the pointers that are never
+/// linked, allocated or freed borrowing from the owners
+/// None if a node is still used after its owner gave it up, or an owner changes while a pointer
+/// borrows from it
+fn boxed_roles(
+    ops: &[(LineNumber, NodeOp)],
+    loops: &[(LineNumber, LineNumber)],
+) -> Option<HashMap<String, NodeRole>> {
+    // NOTE `p->next = q->next` leaves both pointing to the same node
+    if ops.iter().any(|(_, op)| {
+        matches!(
+            op,
+            NodeOp::Link {
+                source: NodeSource::Link { .. },
+                ..
+            }
+        )
+    }) {
+        return None;
+    }
+
+    let mut owners: HashSet<&str> = HashSet::new();
+    let mut changed = true;
+    while changed {
+        let count = owners.len();
+        ops.iter().for_each(|(_, op)| match op {
+            NodeOp::Assign {
+                id,
+                source: NodeSource::Alloc,
+                ..
+            }
+            | NodeOp::Free { id }
+            | NodeOp::Link {
+                source: NodeSource::Ptr(id),
+                ..
+            } => {
+                owners.insert(id);
+            }
+            NodeOp::Assign {
+                id,
+                source: NodeSource::Ptr(source_id),
+                ..
+            } if owners.contains(id.as_str()) => {
+                owners.insert(source_id);
+            }
+            _ => {}
+        });
+        changed = owners.len() != count;
+    }
+
+    // NOTE Cursors writing through themselves need to be borrowed mutably, and so do the cursors
+    // they're borrowed from
+    let mut mut_cursors: HashSet<&str> = HashSet::new();
+    changed = true;
+    while changed {
+        let count = mut_cursors.len();
+        ops.iter().for_each(|(_, op)| match op {
+            NodeOp::Access {
+                ptr_id,
+                write: true,
+            }
+            | NodeOp::Link { ptr_id, .. }
+                if !owners.contains(ptr_id.as_str()) =>
+            {
+                mut_cursors.insert(ptr_id);
+            }
+            NodeOp::Assign {
+                id,
+                source:
+                    NodeSource::Ptr(source_id)
+                    | NodeSource::Link {
+                        ptr_id: source_id, ..
+                    },
+                ..
+            } if mut_cursors.contains(id.as_str()) && !owners.contains(source_id.as_str()) => {
+                mut_cursors.insert(source_id);
+            }
+            _ => {}
+        });
+        changed = mut_cursors.len() != count;
+    }
+
+    let role = |id: &str| match (owners.contains(id), mut_cursors.contains(id)) {
+        (true, _) => NodeRole::Owner,
+        (false, is_mut) => NodeRole::Cursor { is_mut },
+    };
+
+    // (op index, pointer, event, the pointer being assigned)
+    let mut events: Vec<(usize, &str, Event, Option<&str>)> = vec![];
+    ops.iter().enumerate().for_each(|(i, (_, op))| match op {
+        NodeOp::Assign { id, source, .. } => {
+            let event = match (role(id), source) {
+                (NodeRole::Owner, NodeSource::Ptr(_)) => Event::Move,
+                (NodeRole::Owner, _) => Event::Take,
+                (NodeRole::Cursor { is_mut: true }, _) => Event::Write,
+                (NodeRole::Cursor { is_mut: false }, _) => Event::Read,
+            };
+            if let Some(source_id) = source_ptr(source) {
+                events.push((i, source_id, event, Some(id)));
+            }
+            events.push((i, id, Event::Kill, None));
+        }
+        NodeOp::Link { ptr_id, source, .. } => {
+            if let Some(source_id) = source_ptr(source) {
+                events.push((i, source_id, Event::Move, None));
+            }
+            events.push((i, ptr_id, Event::Write, None));
+        }
+        NodeOp::Access { ptr_id, write } => {
+            let event = match write {
+                true => Event::Write,
+                false => Event::Read,
+            };
+            events.push((i, ptr_id, event, None));
+        }
+        NodeOp::Compare { left, right } => [left, right]
+            .into_iter()
+            .filter_map(source_ptr)
+            .for_each(|id| events.push((i, id, Event::Read, None))),
+        NodeOp::Free { id } => events.push((i, id, Event::Kill, None)),
+    });
+
+    let line = |i: usize| ops[i].0;
+    let declared = |id: &str| {
+        ops.iter()
+            .find(|(_, op)| matches!(op, NodeOp::Assign { id: assigned, .. } if assigned == id))
+            .map_or(0, |(line, _)| *line)
+    };
+
+    // NOTE A node given up by its owner can't be used through the owner until it's reassigned,
+    // going around the loop the owner was given up in if it was declared outside of it
+    let used_after_move = events.iter().enumerate().any(|(i, (op, id, event, _))| {
+        if !matches!(event, Event::Move | Event::Take) {
+            return false;
+        }
+        if *event == Event::Take && role(id) != NodeRole::Owner {
+            return true;
+        }
+        let own_events: Vec<(usize, Event)> = events
+            .iter()
+            .filter(|(_, other_id, _, _)| other_id == id)
+            .map(|(other_op, _, event, _)| (*other_op, *event))
+            .collect();
+        let position = events[..i]
+            .iter()
+            .filter(|(_, other_id, _, _)| other_id == id)
+            .count();
+        let enclosing_loop = loops
+            .iter()
+            .filter(|(start, end)| (*start..=*end).contains(&line(*op)) && declared(id) < *start)
+            .min_by_key(|(start, end)| end - start);
+
+        let next = match enclosing_loop {
+            Some((start, end)) => own_events[position + 1..]
+                .iter()
+                .find(|(other_op, _)| line(*other_op) <= *end)
+                .or_else(|| {
+                    own_events[..position]
+                        .iter()
+                        .find(|(other_op, _)| line(*other_op) >= *start)
+                })
+                .or_else(|| own_events[position + 1..].first()),
+            None => own_events[position + 1..].first(),
+        };
+        next.is_some_and(|(_, event)| *event != Event::Kill)
+    });
+    if used_after_move {
+        return None;
+    }
+
+    // NOTE The owners a cursor borrows from, through the cursors it's assigned from
+    let mut roots: HashMap<&str, HashSet<&str>> = HashMap::new();
+    changed = true;
+    while changed {
+        changed = false;
+        ops.iter().for_each(|(_, op)| {
+            let NodeOp::Assign { id, source, .. } = op else {
+                return;
+            };
+            let Some(source_id) = source_ptr(source) else {
+                return;
+            };
+            if owners.contains(id.as_str()) {
+                return;
+            }
+            let borrowed: HashSet<&str> = match owners.contains(source_id) {
+                true => HashSet::from([source_id]),
+                false => roots.get(source_id).cloned().unwrap_or_default(),
+            };
+            let id_roots = roots.entry(id).or_default();
+            let count = id_roots.len();
+            id_roots.extend(borrowed);
+            changed |= id_roots.len() != count;
+        });
+    }
+
+    let borrow_conflict = roots.iter().any(|(cursor, cursor_roots)| {
+        let first = ops.iter().position(|(_, op)| {
+            matches!(op, NodeOp::Assign { id, source, .. }
+                if id == cursor && source_ptr(source).is_some())
+        });
+        let last = events
+            .iter()
+            .filter(|(_, id, _, _)| id == cursor)
+            .map(|(op, _, _, _)| *op)
+            .max();
+        let (Some(first), Some(last)) = (first, last) else {
+            return false;
+        };
+        let is_mut = role(cursor) == NodeRole::Cursor { is_mut: true };
+
+        events.iter().any(|(op, id, event, assigned)| {
+            cursor_roots.contains(id)
+                && (first + 1..=last).contains(op)
+                && *assigned != Some(cursor)
+                && (is_mut || *event != Event::Read)
+        })
+    });
+    if borrow_conflict {
+        return None;
+    }
+
+    Some(
+        ops.iter()
+            .filter_map(|(_, op)| op.ptr_id())
+            .map(|id| (id.to_string(), role(id)))
+            .collect(),
+    )
+}
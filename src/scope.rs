@@ -1,4 +1,7 @@
-use crate::{data_model::VarData, lexer::CType};
+use crate::{
+    data_model::{Place, VarData},
+    lexer::CType,
+};
 use std::collections::HashMap;
 
 /// Represents a single scope
@@ -9,6 +12,8 @@ use std::collections::HashMap;
 pub struct ScopeContext {
     pub scope_type: ScopeType,
     pub variables: HashMap<String, VarData>,
+    // The ids of a variable and every place in it (`s`, `s.a`, `s.a.b`), by the variable's id
+    pub places: HashMap<String, Vec<String>>,
 }
 
 impl ScopeContext {
@@ -16,8 +21,18 @@ impl ScopeContext {
         ScopeContext {
             scope_type,
             variables: HashMap::new(),
+            places: HashMap::new(),
         }
     }
+
+    /// The variables overlapping a place, which hold it or are held by it
+    pub fn overlapping<'a>(&'a self, place: &'a Place) -> impl Iterator<Item = &'a String> + 'a {
+        self.places
+            .get(&place.base)
+            .into_iter()
+            .flatten()
+            .filter(|id| self.variables[*id].place.overlaps(place))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,525 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    adjuster::{
+        clone_before_borrow, rearrange, reborrow_source, renumber_lines, value_lines_within,
+    },
+    analysis_ctx::AnalysisContext,
+    analyze,
+    ast::TokenNode as Node,
+    checker::{self, BorrowError},
+    explain::Explanations,
+};
+
+// NOTE Past this, picking a candidate for each error is done greedily
+const MAX_COMBINATIONS: usize = 4096;
+
+// NOTE The cost of fixes that leave an error with nothing the converter can emit
+pub const UNFIXABLE: usize = usize::MAX;
+
+/// A change to the translation that fixes one or more borrow errors
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Fix {
+    // NOTE Moving statements makes the analysis out of date, so it's done before, and instead of,
+    // everything else
+    Rearrange(BorrowError),
+    // NOTE Raw pointers come before shared values, since a value with a raw pointer to it can't be
+    // in a `Cell`
+    Raw(String),
+    // `let g = &*m;` instead of `let g = &n;`
+    Reborrow {
+        ptr_id: String,
+        from_ptr_id: String,
+        value_id: String,
+    },
+    // `let t_clone = t;` before the borrow
+    Clone {
+        ptr_id: String,
+        value_id: String,
+    },
+    Cell(String),
+    Rc(String),
+}
+
+/// What each kind of fix costs, the solver picks the cheapest set of fixes that fixes every error
+#[derive(Debug, Clone)]
+pub struct CostModel {
+    pub rearrange: usize,
+    pub reborrow: usize,
+    pub clone: usize,
+    pub cell: usize,
+    pub rc: usize,
+    pub raw: usize,
+}
+
+impl Default for CostModel {
+    /// Plain references first, then shared values that can't panic at runtime, then ones that
+    /// can, and raw pointers last since they need `unsafe`
+    fn default() -> Self {
+        CostModel {
+            rearrange: 1,
+            reborrow: 1,
+            clone: 2,
+            cell: 4,
+            rc: 8,
+            raw: 16,
+        }
+    }
+}
+
+impl CostModel {
+    fn cost(&self, fix: &Fix) -> usize {
+        match fix {
+            Fix::Rearrange(_) => self.rearrange,
+            Fix::Raw(_) => self.raw,
+            Fix::Reborrow { .. } => self.reborrow,
+            Fix::Clone { .. } => self.clone,
+            Fix::Cell(_) => self.cell,
+            Fix::Rc(_) => self.rc,
+        }
+    }
+
+    /// What a fix adds to the ones already picked
+    fn added(&self, fix: &Fix, picked: &[Fix]) -> usize {
+        match picked.contains(fix) {
+            true => 0,
+            false => self.cost(fix),
+        }
+    }
+
+    fn total(&self, fixes: &[Fix]) -> usize {
+        fixes.iter().map(|fix| self.cost(fix)).sum()
+    }
+}
+
+/// Picks the fixes for every error at once, so that what's done about one error doesn't depend on
+/// the order the errors were found in
+///
+/// Errors are grouped by the value they borrow, and each value is either shared between its
+/// pointers, which fixes most of its errors at once, or has every error fixed on its own
+///
+/// # Important
+/// A rearrangement is returned by itself, the errors have to be found again after it's applied
+pub fn solve(
+    errors: &[BorrowError],
+    root: &Node,
+    ctx: &AnalysisContext,
+    costs: &CostModel,
+    rearrangements: usize,
+    explanations: &mut Explanations,
+) -> Vec<Fix> {
+    plan(errors, root, ctx, costs, rearrangements, explanations).0
+}
+
+/// The fixes and what they cost in total
+///
+/// Moving statements often only makes room for the next move, so rather than guessing, each
+/// possible move is made on a copy of the program, which is solved again to find the cost of the
+/// whole series of moves
+fn plan(
+    errors: &[BorrowError],
+    root: &Node,
+    ctx: &AnalysisContext,
+    costs: &CostModel,
+    rearrangements: usize,
+    explanations: &mut Explanations,
+) -> (Vec<Fix>, usize) {
+    let mut groups: BTreeMap<&str, BTreeSet<&BorrowError>> = BTreeMap::new();
+    errors.iter().for_each(|error| {
+        groups.entry(error.value_id()).or_default().insert(error);
+    });
+    groups
+        .values()
+        .flatten()
+        .for_each(|error| explanations.error(error, root, ctx));
+
+    // NOTE Moving statements can still win over the fixes picked for each value
+    let mut value_explanations = Explanations::default();
+    let fixes: BTreeSet<Fix> = groups
+        .iter()
+        .flat_map(|(value_id, errors)| {
+            let errors: Vec<&BorrowError> = errors.iter().copied().collect();
+            solve_value(value_id, &errors, root, ctx, costs, &mut value_explanations)
+        })
+        .collect();
+    let mut fixes: Vec<Fix> = fixes.into_iter().collect();
+    // NOTE A reborrow through a pointer made raw for another value is left for the next round,
+    // when it won't be a candidate anymore
+    let raw = raw_ptrs(&fixes);
+    fixes.retain(|fix| !reborrows_raw(fix, &raw));
+    let stuck = errors
+        .iter()
+        .any(|error| candidates(error, root, ctx, &mut Explanations::default()).is_empty());
+    let cost = match stuck {
+        true => UNFIXABLE,
+        false => costs.total(&fixes),
+    };
+
+    // NOTE The moves are tried in order, and the first one that costs no more than the fixes wins
+    let mut tried: Vec<Node> = vec![];
+    let mut weighed = vec![];
+    let mut rearranged = None;
+    for error in groups.values().flatten().filter(|_| rearrangements != 0) {
+        let mut moved_root = root.clone();
+        if !rearrange(error, &mut moved_root, ctx) {
+            explanations.failed(rearrangement(error), error);
+            continue;
+        }
+        renumber_lines(&mut moved_root);
+        // NOTE Different errors can make the same move
+        if tried.contains(&moved_root) {
+            continue;
+        }
+
+        let moved_ctx = analyze(&moved_root);
+        let moved_errors = checker::borrow_check(&mut moved_ctx.clone());
+        // NOTE Only what's actually done is explained, not what would be done after the move
+        let (_, moved_cost) = plan(
+            &moved_errors,
+            &moved_root,
+            &moved_ctx,
+            costs,
+            rearrangements - 1,
+            &mut Explanations::default(),
+        );
+
+        // NOTE Ties go to moving statements, since it keeps the pointers plain references
+        let moved_cost = costs.rearrange.saturating_add(moved_cost);
+        let picked = moved_cost <= cost && moved_cost != UNFIXABLE;
+        weighed.push((*error, moved_cost, picked));
+        if picked {
+            rearranged = Some((*error, moved_cost));
+            break;
+        }
+        tried.push(moved_root);
+    }
+
+    explanations.merge(value_explanations, rearranged.is_some());
+    weighed.into_iter().for_each(|(error, moved_cost, picked)| {
+        explanations.weighed(
+            rearrangement(error),
+            &[error],
+            &[Fix::Rearrange(error.clone())],
+            moved_cost,
+            picked,
+        )
+    });
+    match rearranged {
+        Some((error, moved_cost)) => (vec![Fix::Rearrange(error.clone())], moved_cost),
+        None => (fixes, cost),
+    }
+}
+
+fn solve_value(
+    value_id: &str,
+    errors: &[&BorrowError],
+    root: &Node,
+    ctx: &AnalysisContext,
+    costs: &CostModel,
+    explanations: &mut Explanations,
+) -> Vec<Fix> {
+    let candidates: Vec<Vec<Vec<Fix>>> = errors
+        .iter()
+        .map(|error| candidates(error, root, ctx, explanations))
+        .collect();
+    // NOTE A pointer is declared as a borrow of its value, which can't be turned into a clone of
+    // a shared one
+    if ctx.get_var(value_id).is_ptr() || !ctx.shareable(value_id) {
+        let separate = consistent(candidates, costs);
+        explanations.weighed("separate", errors, &separate, costs.total(&separate), true);
+        return separate;
+    }
+
+    // NOTE Sharing fixes every overlap, but the errors it can't fix still need raw pointers
+    let cell = ctx.cell_valid(value_id);
+    let shared_fix = match cell {
+        true => Fix::Cell(value_id.to_string()),
+        false => Fix::Rc(value_id.to_string()),
+    };
+    let shared_candidates: Vec<Vec<Vec<Fix>>> = errors
+        .iter()
+        .zip(candidates.iter())
+        .map(|(error, candidates)| match shared_covers(error, cell) {
+            true => vec![vec![]],
+            false => candidates
+                .iter()
+                .filter(|candidate| candidate.iter().all(|fix| matches!(fix, Fix::Raw(_))))
+                .cloned()
+                .collect(),
+        })
+        .collect();
+
+    let separate = consistent(candidates, costs);
+    let mut shared = consistent(shared_candidates, costs);
+    shared.push(shared_fix);
+
+    let shared_picked = costs.total(&shared) < costs.total(&separate);
+    explanations.weighed(
+        "shared",
+        errors,
+        &shared,
+        costs.total(&shared),
+        shared_picked,
+    );
+    explanations.weighed(
+        "separate",
+        errors,
+        &separate,
+        costs.total(&separate),
+        !shared_picked,
+    );
+    match shared_picked {
+        true => shared,
+        false => separate,
+    }
+}
+
+/// The errors that need fixing, leaving out the ones that turn out to be fine
+pub fn unfixed<'a>(
+    errors: &'a [BorrowError],
+    root: &Node,
+    ctx: &AnalysisContext,
+) -> Vec<&'a BorrowError> {
+    errors
+        .iter()
+        .filter(|error| {
+            !candidates(error, root, ctx, &mut Explanations::default()).contains(&vec![])
+        })
+        .collect()
+}
+
+/// The ways each error can be fixed on its own, an empty one meaning it doesn't need fixing
+fn candidates(
+    error: &BorrowError,
+    root: &Node,
+    ctx: &AnalysisContext,
+    explanations: &mut Explanations,
+) -> Vec<Vec<Fix>> {
+    let mut candidates = vec![];
+    match error {
+        BorrowError::MutMutOverlap {
+            first_ptr_id,
+            second_ptr_id,
+            value_id,
+        }
+        | BorrowError::MutConstOverlap {
+            mut_ptr_id: first_ptr_id,
+            const_ptr_id: second_ptr_id,
+            value_id,
+        } => {
+            match reborrow_source(first_ptr_id, second_ptr_id, value_id, root, ctx) {
+                Some((ptr_id, from_ptr_id)) => candidates.push(vec![Fix::Reborrow {
+                    ptr_id,
+                    from_ptr_id,
+                    value_id: value_id.clone(),
+                }]),
+                None => explanations.failed("reborrow_source", error),
+            }
+            // NOTE The owner of a heap allocation stays a `Box`, only its aliases can be raw
+            candidates.push(
+                [first_ptr_id, second_ptr_id]
+                    .into_iter()
+                    .filter(|ptr_id| ctx.heap_owner(value_id) != Some(ptr_id.as_str()))
+                    .map(|ptr_id| Fix::Raw(ptr_id.clone()))
+                    .collect(),
+            );
+        }
+        BorrowError::MutMutSameLine {
+            first_ptr_id,
+            second_ptr_id,
+            value_id: _,
+        }
+        | BorrowError::MutConstSameLine {
+            mut_ptr_id: first_ptr_id,
+            const_ptr_id: second_ptr_id,
+            value_id: _,
+        }
+        | BorrowError::PlaceOverlap {
+            outer_ptr_id: first_ptr_id,
+            inner_ptr_id: second_ptr_id,
+            value_id: _,
+        } => candidates.push(vec![
+            Fix::Raw(first_ptr_id.clone()),
+            Fix::Raw(second_ptr_id.clone()),
+        ]),
+        // NOTE Borrows of the value within the pointer's borrow are overlaps between the
+        // pointers, which are their own errors
+        BorrowError::ValueMutOverlap { ptr_id, value_id }
+            if value_lines_within(value_id, ptr_id, ctx, false).is_empty() =>
+        {
+            candidates.push(vec![])
+        }
+        BorrowError::ValueConstOverlap { ptr_id, value_id }
+            if value_lines_within(value_id, ptr_id, ctx, true).is_empty() =>
+        {
+            candidates.push(vec![])
+        }
+        BorrowError::ValueMutOverlap {
+            ptr_id,
+            value_id: _,
+        }
+        | BorrowError::ValueConstOverlap {
+            ptr_id,
+            value_id: _,
+        } => candidates.push(vec![Fix::Raw(ptr_id.clone())]),
+        BorrowError::ValueMutSameLine { ptr_id, value_id } => {
+            match clone_before_borrow(value_id, ptr_id, &mut root.clone(), &mut ctx.clone()) {
+                true => candidates.push(vec![Fix::Clone {
+                    ptr_id: ptr_id.clone(),
+                    value_id: value_id.clone(),
+                }]),
+                false => explanations.failed("clone_before_borrow", error),
+            }
+            candidates.push(vec![Fix::Raw(ptr_id.clone())]);
+        }
+        // NOTE This must be rside, so it's fine i think (not actually an error)
+        BorrowError::ValueConstSameLine {
+            ptr_id: _,
+            value_id: _,
+        } => candidates.push(vec![]),
+    }
+
+    candidates.retain(|candidate| candidate.iter().all(|fix| emittable(fix, ctx)));
+    candidates
+}
+
+/// Whether the converter can translate the fix
+fn emittable(fix: &Fix, ctx: &AnalysisContext) -> bool {
+    match fix {
+        Fix::Raw(ptr_id) => ctx.raw_valid(ptr_id),
+        Fix::Cell(value_id) | Fix::Rc(value_id) => ctx.shareable(value_id),
+        _ => true,
+    }
+}
+
+/// The adjuster function that moves statements for the error
+fn rearrangement(error: &BorrowError) -> &'static str {
+    match error {
+        BorrowError::MutMutOverlap { .. } | BorrowError::MutConstOverlap { .. } => {
+            "line_rearrangement_ptr_overlap"
+        }
+        BorrowError::ValueMutOverlap { .. } | BorrowError::ValueConstOverlap { .. } => {
+            "line_rearrangement_value_ptr_overlap"
+        }
+        _ => "rearrange",
+    }
+}
+
+/// A `Cell` can be read and written on the same line it's borrowed, but an `Rc<RefCell>` would
+/// panic, and a `RefCell` can't be shared between a struct and one of its fields
+fn shared_covers(error: &BorrowError, cell: bool) -> bool {
+    match error {
+        BorrowError::PlaceOverlap { .. } => false,
+        BorrowError::MutMutSameLine { .. }
+        | BorrowError::MutConstSameLine { .. }
+        | BorrowError::ValueMutSameLine { .. } => cell,
+        _ => true,
+    }
+}
+
+/// Picks the cheapest candidate for each error, leaving out reborrows through pointers that end up
+/// raw
+fn consistent(mut candidates: Vec<Vec<Vec<Fix>>>, costs: &CostModel) -> Vec<Fix> {
+    loop {
+        let picked = cheapest(&candidates, costs);
+        let raw = raw_ptrs(&picked);
+        if !picked.iter().any(|fix| reborrows_raw(fix, &raw)) {
+            return picked;
+        }
+        candidates.iter_mut().for_each(|candidates| {
+            candidates.retain(|candidate| !candidate.iter().any(|fix| reborrows_raw(fix, &raw)))
+        });
+    }
+}
+
+/// Picks a candidate for each error so that the fixes cost the least in total, counting a fix
+/// picked for several errors once
+///
+/// # Important
+/// Every combination of candidates is tried, unless there are more than `MAX_COMBINATIONS`, then
+/// they're picked greedily instead, which can miss a fix that would have taken care of a later
+/// error too
+pub fn cheapest(candidates: &[Vec<Vec<Fix>>], costs: &CostModel) -> Vec<Fix> {
+    let combinations = candidates.iter().try_fold(1usize, |n, candidates| {
+        n.checked_mul(candidates.len().max(1))
+    });
+    match combinations.is_some_and(|n| n <= MAX_COMBINATIONS) {
+        true => {
+            let mut best = None;
+            search(candidates, costs, &mut vec![], &mut best);
+            best.map(|(picked, _)| picked).unwrap_or_default()
+        }
+        false => greedy(candidates, costs),
+    }
+}
+
+/// Tries every candidate for the first error with each way of fixing the rest, skipping the ones
+/// that already cost more than the best found
+fn search(
+    candidates: &[Vec<Vec<Fix>>],
+    costs: &CostModel,
+    picked: &mut Vec<Fix>,
+    best: &mut Option<(Vec<Fix>, usize)>,
+) {
+    let cost = costs.total(picked);
+    if best
+        .as_ref()
+        .is_some_and(|(_, best_cost)| cost >= *best_cost)
+    {
+        return;
+    }
+    let Some((first, rest)) = candidates.split_first() else {
+        *best = Some((picked.clone(), cost));
+        return;
+    };
+    if first.is_empty() {
+        return search(rest, costs, picked, best);
+    }
+
+    first.iter().for_each(|candidate| {
+        let len = picked.len();
+        candidate.iter().for_each(|fix| {
+            if !picked.contains(fix) {
+                picked.push(fix.clone());
+            }
+        });
+        search(rest, costs, picked, best);
+        picked.truncate(len);
+    });
+}
+
+/// Goes through the errors in order, picking the candidate that adds the least to the cost of the
+/// fixes picked so far
+fn greedy(candidates: &[Vec<Vec<Fix>>], costs: &CostModel) -> Vec<Fix> {
+    let mut picked: Vec<Fix> = vec![];
+    candidates.iter().for_each(|candidates| {
+        let added = |candidate: &&Vec<Fix>| -> usize {
+            candidate.iter().map(|fix| costs.added(fix, &picked)).sum()
+        };
+        if let Some(candidate) = candidates.iter().min_by_key(added).cloned() {
+            candidate.into_iter().for_each(|fix| {
+                if !picked.contains(&fix) {
+                    picked.push(fix);
+                }
+            });
+        }
+    });
+    picked
+}
+
+fn raw_ptrs(fixes: &[Fix]) -> Vec<String> {
+    fixes
+        .iter()
+        .filter_map(|fix| match fix {
+            Fix::Raw(ptr_id) => Some(ptr_id.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn reborrows_raw(fix: &Fix, raw: &[String]) -> bool {
+    matches!(
+        fix,
+        Fix::Reborrow { ptr_id, from_ptr_id, .. } if raw.contains(ptr_id) || raw.contains(from_ptr_id)
+    )
+}
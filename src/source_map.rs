@@ -0,0 +1,74 @@
+// NOTE Statements are tagged with the C line they came from while they're converted, as in
+// `\u{1}12\u{1}let x: i32 = 1;`, and the tags are taken out once the whole program is generated
+const MARKER: char = '\u{1}';
+
+/// Tags converted code with the C line it was translated from
+///
+/// # Important
+/// Nodes sonder makes up itself are on line 0, and aren't tagged
+pub fn mark(line: usize, code: &str) -> String {
+    match line {
+        0 => code.to_string(),
+        line => format!("{MARKER}{line}{MARKER}{code}"),
+    }
+}
+
+/// The C line every line of the generated rust came from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    // NOTE Indexed by the rust line - 1, closing braces and the like don't come from any
+    pub c_lines: Vec<Option<usize>>,
+}
+
+impl SourceMap {
+    /// Takes the tags out of the generated rust, a line tagged more than once is from the first
+    pub fn strip(marked: &str) -> (String, SourceMap) {
+        let mut c_lines = vec![];
+        let rust = marked
+            .split('\n')
+            .map(|line| {
+                let mut parts = line.split(MARKER);
+                let mut rust_line = parts.next().unwrap_or_default().to_string();
+                let mut c_line = None;
+                while let (Some(tag), Some(code)) = (parts.next(), parts.next()) {
+                    c_line = c_line.or(tag.parse().ok());
+                    rust_line.push_str(code);
+                }
+                c_lines.push(c_line);
+                rust_line
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        (rust, SourceMap { c_lines })
+    }
+
+    /// Rust lines start at 1, like in `rustc` errors
+    pub fn c_line(&self, rust_line: usize) -> Option<usize> {
+        self.c_lines
+            .get(rust_line.checked_sub(1)?)
+            .copied()
+            .flatten()
+    }
+
+    /// Ends every line that came from C with a `// c: test.c:12` comment
+    pub fn comment(&self, rust: &str, c_file: &str) -> String {
+        rust.split('\n')
+            .zip(self.c_lines.iter())
+            .map(|(line, c_line)| match c_line {
+                Some(c_line) => format!("{line} // c: {c_file}:{c_line}"),
+                None => line.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// The source map file, a `rust_line c_file:c_line` line for every rust line from C
+    pub fn to_file(&self, c_file: &str) -> String {
+        self.c_lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c_line)| Some(format!("{} {c_file}:{}\n", i + 1, (*c_line)?)))
+            .collect()
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+};
+
+use crate::{
+    annotater::{AnnotatedNode, AnnotatedNodeT},
+    data_model::ReferenceType,
+    source_map::SourceMap,
+};
+
+// NOTE Structs, globals and everything else outside of a function
+const TOP_LEVEL: &str = "<top level>";
+
+/// What a translation falls back on instead of plain references and values, for each function
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Summary {
+    // NOTE Ordered by name, so the summary comes out the same every time
+    pub functions: BTreeMap<String, FunctionSummary>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FunctionSummary {
+    // Each with the C line it's on
+    pub raw_ptrs: Vec<(String, usize)>,
+    pub unsafe_blocks: Vec<usize>,
+    // The C lines of the statements inside the blocks
+    pub unsafe_lines: Vec<usize>,
+    pub rcs: Vec<(String, usize)>,
+    // `Rc` clones, and copies sonder takes of values before they're borrowed
+    pub clones: Vec<(String, usize)>,
+    // Every C line the function is translated from
+    pub lines: Vec<usize>,
+}
+
+impl FunctionSummary {
+    /// The share of the function's C lines translated without raw pointers or `unsafe`, in percent
+    pub fn safety_score(&self) -> usize {
+        let unsafe_lines = self
+            .lines
+            .iter()
+            .filter(|line| {
+                self.unsafe_blocks.contains(line)
+                    || self.unsafe_lines.contains(line)
+                    || self.raw_ptrs.iter().any(|(_, raw_line)| raw_line == *line)
+            })
+            .count();
+        match self.lines.len() {
+            0 => 100,
+            n => 100 * (n - unsafe_lines) / n,
+        }
+    }
+
+    fn merge(&mut self, other: &FunctionSummary) {
+        self.raw_ptrs.extend(other.raw_ptrs.iter().cloned());
+        self.unsafe_blocks.extend(other.unsafe_blocks.iter());
+        self.unsafe_lines.extend(other.unsafe_lines.iter());
+        self.rcs.extend(other.rcs.iter().cloned());
+        self.clones.extend(other.clones.iter().cloned());
+        self.lines.extend(other.lines.iter());
+    }
+
+    fn counts(&self) -> String {
+        format!(
+            "raw pointers {}, unsafe blocks {}, Rc<RefCell> {}, clones {}, safety {}%",
+            self.raw_ptrs.len(),
+            self.unsafe_blocks.len(),
+            self.rcs.len(),
+            self.clones.len(),
+            self.safety_score()
+        )
+    }
+}
+
+/// The C lines of each function, from its declaration to its last statement
+fn function_spans(root: &AnnotatedNode, spans: &mut Vec<(String, RangeInclusive<usize>)>) {
+    match &root.token {
+        AnnotatedNodeT::FunctionDeclaration { id, .. } => {
+            spans.push((id.clone(), root.line..=last_line(root)))
+        }
+        _ => root
+            .children
+            .iter()
+            .for_each(|child| function_spans(child, spans)),
+    }
+}
+
+/// The raw pointer fields of each struct
+fn struct_raw_fields(root: &AnnotatedNode, raw_fields: &mut HashMap<String, Vec<String>>) {
+    match &root.token {
+        AnnotatedNodeT::StructDefinition {
+            struct_id,
+            field_definitions,
+            ..
+        } => {
+            let raw: Vec<String> = field_definitions
+                .iter()
+                .filter(|field| field.ptr_type.first().is_some_and(ReferenceType::is_raw))
+                .map(|field| field.id.clone())
+                .collect();
+            raw_fields.insert(struct_id.clone(), raw);
+        }
+        _ => root
+            .children
+            .iter()
+            .for_each(|child| struct_raw_fields(child, raw_fields)),
+    }
+}
+
+fn nested_lines(root: &AnnotatedNode, lines: &mut Vec<usize>) {
+    root.nested().into_iter().for_each(|node| {
+        lines.push(node.line);
+        nested_lines(node, lines);
+    });
+}
+
+fn last_line(root: &AnnotatedNode) -> usize {
+    root.children
+        .iter()
+        .map(last_line)
+        .fold(root.line, usize::max)
+}
+
+impl Summary {
+    /// Raw pointers, `Rc`s, clones and `unsafe` blocks come from the annotated nodes, each given to
+    /// the function it's in, and the lines of each function from the generated rust, which is
+    /// traced back to C through the source map
+    pub fn new(annotated_ast: &AnnotatedNode, rust: &str, source_map: &SourceMap) -> Summary {
+        let mut summary = Summary::default();
+        let mut raw_fields = HashMap::new();
+        struct_raw_fields(annotated_ast, &mut raw_fields);
+        summary.nodes(annotated_ast, TOP_LEVEL, &raw_fields);
+
+        // NOTE The rust is traced back to C lines, and each function spans its own
+        let mut spans = vec![];
+        function_spans(annotated_ast, &mut spans);
+
+        (1..=rust.split('\n').count())
+            .filter_map(|line| source_map.c_line(line))
+            .for_each(|c_line| {
+                let function = spans
+                    .iter()
+                    .find(|(_, span)| span.contains(&c_line))
+                    .map_or(TOP_LEVEL, |(id, _)| id.as_str());
+                let function_summary = summary.functions.entry(function.to_string()).or_default();
+                if !function_summary.lines.contains(&c_line) {
+                    function_summary.lines.push(c_line);
+                }
+            });
+
+        summary
+    }
+
+    fn nodes(
+        &mut self,
+        root: &AnnotatedNode,
+        function: &str,
+        raw_fields: &HashMap<String, Vec<String>>,
+    ) {
+        let function_summary = self.functions.entry(function.to_string()).or_default();
+        let line = root.line;
+        match &root.token {
+            AnnotatedNodeT::PtrDeclaration { id, ref_type, .. } => match ref_type.first() {
+                Some(ReferenceType::MutPtr | ReferenceType::ConstPtr) => {
+                    function_summary.raw_ptrs.push((id.clone(), line))
+                }
+                Some(ReferenceType::RcRefClone) => function_summary.clones.push((id.clone(), line)),
+                _ => {}
+            },
+            AnnotatedNodeT::Declaration { id, rc: true, .. } => {
+                function_summary.rcs.push((id.clone(), line))
+            }
+            AnnotatedNodeT::Declaration {
+                id, clone: true, ..
+            } => function_summary.clones.push((id.clone(), line)),
+            AnnotatedNodeT::StructDeclaration {
+                var_id,
+                struct_id,
+                rc,
+                ..
+            } => {
+                if *rc {
+                    function_summary.rcs.push((var_id.clone(), line));
+                }
+                // NOTE A raw field is raw in every struct declared with it
+                raw_fields
+                    .get(struct_id)
+                    .into_iter()
+                    .flatten()
+                    .for_each(|field_id| {
+                        function_summary
+                            .raw_ptrs
+                            .push((format!("{var_id}.{field_id}"), line))
+                    });
+            }
+            _ => {}
+        }
+        if root.is_unsafe() {
+            function_summary.unsafe_blocks.push(line);
+        }
+        // NOTE Every statement in a block is unsafe, not just the line it opens on
+        if let AnnotatedNodeT::Unsafe { block: true, .. } = root.token {
+            let mut lines = vec![];
+            nested_lines(root, &mut lines);
+            function_summary.unsafe_lines.extend(lines);
+        }
+
+        let function = match &root.token {
+            AnnotatedNodeT::FunctionDeclaration { id, .. } => id,
+            _ => function,
+        };
+        root.nested()
+            .into_iter()
+            .for_each(|node| self.nodes(node, function, raw_fields));
+    }
+
+    /// The first raw pointer the translation has, for `--deny-unsafe`
+    pub fn first_raw_ptr(&self) -> Option<(String, usize)> {
+        self.functions
+            .values()
+            .flat_map(|function_summary| function_summary.raw_ptrs.iter())
+            .min_by_key(|(_, line)| *line)
+            .cloned()
+    }
+
+    /// The whole file's counts, then each function's, with where in the C each one comes from
+    pub fn report(&self, c_file: &str) -> String {
+        let mut file = FunctionSummary::default();
+        self.functions
+            .values()
+            .for_each(|function_summary| file.merge(function_summary));
+
+        let functions: String =
+            self.functions
+                .iter()
+                .filter(|(function, function_summary)| {
+                    *function != TOP_LEVEL || !function_summary.lines.is_empty()
+                })
+                .map(|(function, function_summary)| {
+                    let at = |line: &usize| format!("{c_file}:{line}");
+                    let items: String =
+                        function_summary
+                            .raw_ptrs
+                            .iter()
+                            .map(|(id, line)| format!("    raw pointer `{id}` at {}\n", at(line)))
+                            .chain(
+                                function_summary
+                                    .unsafe_blocks
+                                    .iter()
+                                    .map(|line| format!("    unsafe block at {}\n", at(line))),
+                            )
+                            .chain(function_summary.rcs.iter().map(|(id, line)| {
+                                format!("    Rc<RefCell> `{id}` at {}\n", at(line))
+                            }))
+                            .chain(
+                                function_summary.clones.iter().map(|(id, line)| {
+                                    format!("    clone `{id}` at {}\n", at(line))
+                                }),
+                            )
+                            .collect();
+                    format!("  {function}: {}\n{items}", function_summary.counts())
+                })
+                .collect();
+
+        format!("{c_file}: {}\n{functions}", file.counts())
+    }
+}
@@ -1,32 +1,33 @@
-use std::{fs, process::Command};
+#![allow(clippy::needless_doctest_main)]
 
-use crate::{convert_to_rust_code, parse_c};
+use std::{
+    env,
+    fmt::Display,
+    fs,
+    io::Write,
+    panic,
+    process::{Command, Output, Stdio},
+};
+
+use crate::{
+    convert_to_rust_code,
+    error::{ErrType, RhErr},
+    generator::Program,
+    lexer, parse_c, parser,
+    solver::{cheapest, CostModel, Fix},
+    verify, Options,
+};
 
 #[test]
 fn basic_assignment() {
-    validate(
-        "int main() {
-            int n = 0;
-            n = 2;
-        }",
-        "basic_assignment",
-    )
+    validate("basic_assignment")
 }
 
 /// Valid use of pointers as if they were Rust references
 /// Translates one-to-one
 #[test]
 fn three_mut_layered() {
-    validate(
-        "int main() {
-            int n = 0;
-            int* g = &n;
-            int* p = &n;
-            int** m = &p;
-            **m = 5;
-        }",
-        "three_mut_layered",
-    );
+    validate("three_mut_layered");
 }
 
 /// ```rust
@@ -39,15 +40,7 @@ fn three_mut_layered() {
 /// ```
 #[test]
 fn value_mut_ptr_overlap() {
-    validate(
-        "int main() {
-            int t = 0;
-            int* g = &t;
-            t = 1;
-            *g = 2;
-        }",
-        "value_mut_ptr_overlap",
-    );
+    validate("value_mut_ptr_overlap");
 }
 
 /// This test should be solvable by rearragement
@@ -64,15 +57,7 @@ fn value_mut_ptr_overlap() {
 /// }
 #[test]
 fn value_const_ptr_overlap() {
-    validate(
-        "int main() {
-            int t = 0;
-            int* g = &t;
-            t = 1;
-            int h = *g;
-        }",
-        "value_const_ptr_overlap",
-    );
+    validate("value_const_ptr_overlap");
 }
 
 // #[test]
@@ -98,14 +83,19 @@ fn value_const_ptr_overlap() {
 /// WARNING THIS TEST PROBABLY NEEDS RAW PTRS
 #[test]
 fn deref_value_assignment() {
-    validate(
-        "void main() {
-            int k = 3;
-            int* y = &k;
-            *y = k + 6;
-        }",
-        "deref_value_assignment",
-    );
+    validate("deref_value_assignment");
+}
+
+/// `k` is read before it's borrowed, into a clone
+#[test]
+fn clone_before_borrow() {
+    validate("clone_before_borrow");
+}
+
+/// `k` changes through `y` before it's read, so a clone would be stale
+#[test]
+fn clone_before_borrow_written_through_ptr() {
+    validate("clone_before_borrow_written_through_ptr");
 }
 
 /// ```rust
@@ -118,62 +108,347 @@ fn deref_value_assignment() {
 /// }
 #[test]
 fn const_mut_ptr_overlap() {
-    validate(
-        "int main() {
-            int n = 0;
-            int* g = &n;
-            int* m = &n;
-            int t = *g;
-            *m = 4;
-        }",
-        "const_mut_ptr_overlap",
-    );
+    validate("const_mut_ptr_overlap");
 }
 
 // For this case, we must move the const ptr down
 #[test]
 fn mut_const_ptr_overlap() {
-    validate(
-        "int main() {
-            int n = 0;
-            int* m = &n;
-            int* g = &n;
-            *m = 4;
-            int t = *g;
-        }",
-        "mut_const_ptr_overlap",
-    );
+    validate("mut_const_ptr_overlap");
+}
+
+/// Both assignments to `t` are moved in front of `g`'s borrow
+#[test]
+fn rearrange_multiple_statements() {
+    validate("rearrange_multiple_statements");
+}
+
+/// `t = x` can't be moved in front of `x = 5`, which it reads
+#[test]
+fn rearrange_blocked_by_dependence() {
+    validate("rearrange_blocked_by_dependence");
+}
+
+#[test]
+fn cell_shared_value() {
+    validate("cell_shared_value");
+}
+
+/// The pointer to `m` carries the reference to `n` further, so `n` can't be a `Cell`
+#[test]
+fn cell_escaping_reference() {
+    validate("cell_escaping_reference");
 }
 
 #[test]
 fn mut_const_ptr_multi_overlap() {
-    validate(
-        "int main() {
-            int n = 0;
-            int* m = &n;
-            int* g = &n;
-            int j = *g;
-            *m = 4;
-            int t = *g;
-        }",
-        "mut_const_ptr_multi_overlap",
+    validate("mut_const_ptr_multi_overlap");
+}
+
+/// Each pointer is done before the next one is borrowed, once `*a` and `*b` are moved up
+#[test]
+fn mut_mut_ptr_multi_overlap() {
+    validate("mut_mut_ptr_multi_overlap");
+}
+
+/// With moving statements made expensive, sharing `t` is cheaper
+#[test]
+fn mut_mut_ptr_multi_overlap_shared() {
+    validate_with(
+        "mut_mut_ptr_multi_overlap_shared",
+        &Options {
+            costs: CostModel {
+                rearrange: 100,
+                ..CostModel::default()
+            },
+            ..Options::default()
+        },
     );
 }
 
+/// Reborrowing is the cheapest fix for the first error on its own, but the raw pointers it's
+/// picked over fix the other two errors as well
 #[test]
-fn value_const_ptr_multi_overlap() {
-    validate(
-        "int main() {
-            int t = 0;
-            int* g = &t;
-            t = 3;
-            t = 1;
-            int h = *g;
-        }",
-        "value_const_ptr_multi_overlap",
+fn cheapest_fix_combination() {
+    let raw = |id: &str| Fix::Raw(id.to_string());
+    let reborrow = Fix::Reborrow {
+        ptr_id: "g".to_string(),
+        from_ptr_id: "m".to_string(),
+        value_id: "n".to_string(),
+    };
+    let candidates = vec![
+        vec![vec![reborrow.clone()], vec![raw("p"), raw("q")]],
+        vec![vec![raw("p")]],
+        vec![vec![raw("q")]],
+    ];
+    assert_eq!(
+        cheapest(&candidates, &CostModel::default()),
+        vec![raw("p"), raw("q")]
+    );
+
+    // NOTE With too many combinations to try, the first error gets its own cheapest fix
+    let mut candidates = candidates;
+    candidates.extend((0..12).map(|_| vec![vec![raw("p")], vec![raw("q")]]));
+    assert_eq!(
+        cheapest(&candidates, &CostModel::default()),
+        vec![reborrow, raw("p"), raw("q")]
+    );
+}
+
+/// `g` is done before `m` is used again, so it's taken through `m` as `&*m`
+#[test]
+fn reborrow_overlapping_borrow() {
+    validate("reborrow_overlapping_borrow");
+}
+
+/// A `Cell` can be read and written on the same line, where an `Rc<RefCell>` would panic
+#[test]
+fn cell_same_line_overlap() {
+    validate("cell_same_line_overlap");
+}
+
+/// Each borrow is done before the next one is taken, and everything printed has to match the C
+#[test]
+fn sequential_borrows_output() {
+    validate("sequential_borrows_output");
+}
+
+/// `*g` is used again by the next iteration, after `t` changes, which only `rustc` catches
+#[test]
+fn verify_loop_borrow() {
+    validate_with(
+        "verify_loop_borrow",
+        &Options {
+            verify: true,
+            ..Options::default()
+        },
+    );
+}
+
+/// A missing `main` is an error `rustc` doesn't give a line for, so it isn't parsed, but it still
+/// fails the check
+#[test]
+fn verify_unparsed_failure() {
+    let message = verify::rustc_errors("").expect_err("rustc should fail");
+    assert!(message.contains("main"), "{message}");
+    assert_eq!(verify::rustc_errors("fn main() {}"), Ok(vec![]));
+}
+
+/// `*p` in the return value is a use of `p`
+#[test]
+fn deref_in_return() {
+    validate("deref_in_return");
+}
+
+/// `a` is an argument, so it's moved into its `Cell` at the start of the function
+#[test]
+fn cell_shared_argument() {
+    validate("cell_shared_argument");
+}
+
+/// The struct itself is shared, its fields are read and written through the `RefCell`
+#[test]
+fn rc_shared_struct() {
+    validate("rc_shared_struct");
+}
+
+/// `*p` is read before it's written, the read's borrow would otherwise still be held by the write
+#[test]
+fn rc_read_in_write() {
+    validate("rc_read_in_write");
+}
+
+/// Moving `p.x`'s print in front of `q`'s borrow would print it first
+#[test]
+fn rearrange_keeps_print_order() {
+    validate("rearrange_keeps_print_order");
+}
+
+/// Moving `t`'s print in front of `g`'s borrow would print it before the `putchar`
+#[test]
+fn rearrange_keeps_putchar_order() {
+    validate("rearrange_keeps_putchar_order");
+}
+
+/// `int* p = arr;` only borrows `arr`, so `arr[0] = 7` can be moved in front of it
+#[test]
+fn rearrange_array_decay() {
+    validate("rearrange_array_decay");
+}
+
+/// `*m` on the right of a field assignment is a use of `m`
+#[test]
+fn struct_field_deref_assignment() {
+    validate("struct_field_deref_assignment");
+}
+
+/// The statements moved in front of `g`'s borrow keep their C lines in the comments and the map
+#[test]
+fn line_comments() {
+    let map_path = format!("{TRANSLATED}/line_comments.map");
+    fs::create_dir_all(TRANSLATED).expect("dir failed");
+    validate_with(
+        "line_comments",
+        &Options {
+            source_map: Some(map_path.clone()),
+            line_comments: true,
+            ..Options::default()
+        },
+    );
+
+    let rust_code = fs::read_to_string(format!("{CASES}/line_comments.rs")).unwrap();
+    let commented: String = rust_code
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let (_, c_line) = line.split_once(" // c: ")?;
+            Some(format!("{} {c_line}\n", i + 1))
+        })
+        .collect();
+    assert_eq!(fs::read_to_string(map_path).unwrap(), commented);
+}
+
+/// `n` is shared, `p` and `x` are raw and `u`'s statements are moved, each with the errors and
+/// the fixes weighed that led there
+#[test]
+fn explain_choices() {
+    let report_path = format!("{TRANSLATED}/explain_choices.txt");
+    let json_path = format!("{TRANSLATED}/explain_choices.json");
+    fs::create_dir_all(TRANSLATED).expect("dir failed");
+    validate_with(
+        "explain_choices",
+        &Options {
+            explain: Some(report_path.clone()),
+            explain_json: Some(json_path.clone()),
+            ..Options::default()
+        },
+    );
+
+    snapshot(
+        "explain_choices.txt",
+        &fs::read_to_string(report_path).unwrap(),
+    );
+    snapshot(
+        "explain_choices.json",
+        &fs::read_to_string(json_path).unwrap(),
+    );
+}
+
+/// The report has the types the translation declares, `[i32; 3]` for `arr` and slices for the
+/// pointers into it
+#[test]
+fn explain_array_choices() {
+    let report_path = format!("{TRANSLATED}/explain_array_choices.txt");
+    fs::create_dir_all(TRANSLATED).expect("dir failed");
+    validate_with(
+        "explain_array_choices",
+        &Options {
+            explain: Some(report_path.clone()),
+            ..Options::default()
+        },
+    );
+
+    snapshot(
+        "explain_array_choices.txt",
+        &fs::read_to_string(report_path).unwrap(),
+    );
+}
+
+/// The writes through `p` and `x` share one `unsafe` block, the read in `y`'s initializer and the
+/// loop condition are `unsafe` on their own, and the loop body has a block of its own
+#[test]
+fn unsafe_blocks() {
+    validate("unsafe_blocks");
+}
+
+/// `h.p` is made raw, so the field is a `*mut i32`, and the print only has its reads in `unsafe`
+#[test]
+fn unsafe_struct_field() {
+    validate("unsafe_struct_field");
+}
+
+/// `overlapping` has the raw pointers and `unsafe` blocks, `shared` the `Rc` and its clone, and
+/// `main` a copy of `k`
+#[test]
+fn unsafe_summary() {
+    let summary_path = format!("{TRANSLATED}/unsafe_summary.txt");
+    fs::create_dir_all(TRANSLATED).expect("dir failed");
+    validate_with(
+        "unsafe_summary",
+        &Options {
+            summary: Some(summary_path.clone()),
+            ..Options::default()
+        },
+    );
+
+    snapshot(
+        "unsafe_summary.txt",
+        &fs::read_to_string(summary_path).unwrap(),
+    );
+}
+
+#[test]
+fn deny_unsafe() {
+    let err = validate_err_with(
+        "unsafe_summary",
+        &Options {
+            deny_unsafe: true,
+            ..Options::default()
+        },
+    );
+    assert!(matches!(err.err, ErrType::Unsafe(id) if id == "p"));
+    assert_eq!(err.line, 14);
+}
+
+/// `h.p` is a raw field, counted where `h` is declared, and the print's `unsafe {` is only text
+#[test]
+fn unsafe_summary_fields() {
+    let summary_path = format!("{TRANSLATED}/unsafe_summary_fields.txt");
+    fs::create_dir_all(TRANSLATED).expect("dir failed");
+    validate_with(
+        "unsafe_summary_fields",
+        &Options {
+            summary: Some(summary_path.clone()),
+            ..Options::default()
+        },
+    );
+
+    snapshot(
+        "unsafe_summary_fields.txt",
+        &fs::read_to_string(summary_path).unwrap(),
+    );
+}
+
+#[test]
+fn deny_unsafe_field() {
+    let err = validate_err_with(
+        "unsafe_summary_fields",
+        &Options {
+            deny_unsafe: true,
+            ..Options::default()
+        },
+    );
+    assert!(matches!(err.err, ErrType::Unsafe(id) if id == "h.p"));
+    assert_eq!(err.line, 6);
+}
+
+/// The checker doesn't follow `q` through `p`, so `rustc` has `n` shared and `q` made raw
+#[test]
+fn verify_ptr_to_ptr() {
+    validate_with(
+        "verify_ptr_to_ptr",
+        &Options {
+            verify: true,
+            ..Options::default()
+        },
     );
 }
 
+#[test]
+fn value_const_ptr_multi_overlap() {
+    validate("value_const_ptr_multi_overlap");
+}
+
 /// ```rust
 /// fn main() {
 ///     let n: i32  = 0;
@@ -185,28 +460,12 @@ fn value_const_ptr_multi_overlap() {
 // }
 #[test]
 fn simple_multi_ref() {
-    validate(
-        "int main() {
-            int n = 0;
-            int* g = &n;
-            int* b = &n;
-            int k = *g;
-            int y = 9;
-            *b = y;
-        }",
-        "simple_multi_ref",
-    );
+    validate("simple_multi_ref");
 }
 
 #[test]
 fn unused_init_value() {
-    validate(
-        "int main() {
-            int n = 0;
-            n = 7;
-        }",
-        "unused_init_value",
-    );
+    validate("unused_init_value");
 }
 
 /// This is actually an interesting case
@@ -219,133 +478,42 @@ fn unused_init_value() {
 /// This sets off the cloning system
 #[test]
 fn crazy_multi_ref() {
-    validate(
-        "int main() {
-            int n = 0;
-            int* g = &n;
-            int* k = &n;
-            int** h = &g;
-            int p = 3;
-            *h = &p;
-        }",
-        "crazy_multi_ref",
-    );
+    validate("crazy_multi_ref");
 }
 
 #[test]
 fn struct_basic() {
-    validate(
-        "struct test {
-            int m;
-            int j;
-        };",
-        "struct_basic",
-    );
+    validate("struct_basic");
 }
 
 #[test]
 fn struct_init() {
-    validate(
-        "struct Test {
-            int m;
-            int j;
-        };
-
-        int main() {
-            struct Test my_test = { 0, 2 };
-        }",
-        "struct_init",
-    );
+    validate("struct_init");
 }
 
 #[test]
 fn struct_field_assignment() {
-    validate(
-        "struct Test {
-            int m;
-            int j;
-        };
-
-        int main() {
-            struct Test my_test = { 0, 2 };
-            my_test.m = 1;
-        }",
-        "struct_field_assignment",
-    );
+    validate("struct_field_assignment");
 }
 
 #[test]
 fn struct_with_ptr() {
-    validate(
-        "struct Point {
-            int* ptr;
-            int c;
-        };
-
-        int main() {
-            int t = 4;
-            struct Point l = { &t, 5 };
-            *l.ptr = 5;
-        }",
-        "struct_with_ptr",
-    );
+    validate("struct_with_ptr");
 }
 
 #[test]
 fn struct_with_ptr_two() {
-    validate(
-        "struct Point {
-            int* ptr;
-            int* ptr2;
-            int c;
-        };
-
-        int main() {
-            int t = 4;
-            int g = 8;
-            struct Point l = { &t, &g, 5 };
-            *l.ptr = 5;
-            *l.ptr2 = 9;
-        }",
-        "struct_with_ptr_two",
-    );
+    validate("struct_with_ptr_two");
 }
 
 #[test]
 fn struct_field_ptr_assignment() {
-    validate(
-        "struct Point {
-            int* ptr;
-        };
-
-        int main() {
-            int t = 4;
-            struct Point l = { &t };
-            *l.ptr = 5;
-        }",
-        "struct_field_ptr_assignment",
-    );
+    validate("struct_field_ptr_assignment");
 }
 
 #[test]
 fn struct_with_ptr_multi() {
-    validate(
-        "struct Point {
-            int* ptr;
-            int** ptr2;
-            int c;
-        };
-
-        int main() {
-            int t = 4;
-            int g = 8;
-            int* h = &g;
-            struct Point l = { &t, &h, 5 };
-            *l.ptr = 5;
-            **l.ptr2 = 9;
-        }",
-        "struct_with_ptr_multi",
-    );
+    validate("struct_with_ptr_multi");
 }
 
 // #[test]
@@ -368,93 +536,687 @@ fn struct_with_ptr_multi() {
 
 #[test]
 fn basic_loop() {
-    validate(
-        "int main() {
-            int i = 0;
-            while (i == 0) {
-                i++;
-            }
-        }",
-        "basic_loop",
-    );
+    validate("basic_loop");
 }
 
 #[test]
 fn ptr_loop() {
-    validate(
-        "int main() {
-            int i = 0;
-            while (i == 0) {
-                int* k = &i;
-                *k = 1;
-            }
-        }",
-        "ptr_loop",
-    );
+    validate("ptr_loop");
 }
 
 #[test]
 fn function_call() {
-    validate(
-        "int main() {
-            test(1, 2);
-        }
-        void test(int a, int b) {
-            int k = a + b;
-        }",
-        "function_call",
-    );
+    validate("function_call");
 }
 
 #[test]
 fn assignment_function_call() {
-    validate(
-        "int main() {
-            int t = add(1, 2);
-        }
-        int add(int a, int b) {
-            int k = a + b;
-            return(k);
-        }",
-        "assignment_function_call",
-    );
+    validate("assignment_function_call");
 }
 
 #[test]
 fn recursive_function() {
-    validate(
-        "int main() {
-            int i = 0;
-            int g = count_four(i);
-        }
-        int count_four(int i) {
-            if (i == 3) {
-                return 4;
-            }
-            return(count_four(i + 1));
-        }",
-        "recursive_function",
-    )
+    validate("recursive_function")
+}
+
+/// ```rust
+/// fn main() -> () {
+///     let mut p: Box<i32> = Box::new(0);
+///     *p = 5;
+///     let _k: i32 = *p + 1;
+///     drop(p);
+/// }
+/// ```
+#[test]
+fn heap_box() {
+    validate("heap_box");
+}
+
+#[test]
+fn heap_vec() {
+    validate("heap_vec");
+}
+
+/// `*p = 4` is used while `q` still borrows the allocation from `p`, so `q` is raw
+#[test]
+fn heap_alias() {
+    validate("heap_alias");
+}
+
+/// `q` is done before `p` is used again, so it's a `&mut` reborrowed from `p`, which has to be `mut`
+#[test]
+fn heap_alias_mut() {
+    validate("heap_alias_mut");
+}
+
+#[test]
+fn heap_struct() {
+    validate("heap_struct");
+}
+
+/// The items are zeroed as floats, and `sizeof` takes any type
+#[test]
+fn heap_doubles() {
+    validate("heap_doubles");
+}
+
+#[test]
+fn heap_unsized() {
+    let err = validate_err("heap_unsized");
+    assert!(matches!(err.err, ErrType::UnsizedAllocation));
+    assert_eq!(err.line, 3);
+}
+
+#[test]
+fn heap_use_after_free() {
+    let err = validate_err("heap_use_after_free");
+    assert!(matches!(err.err, ErrType::UseAfterFree(id) if id == "p"));
+    assert_eq!(err.line, 4);
+}
+
+#[test]
+fn heap_alias_use_after_free() {
+    let err = validate_err("heap_alias_use_after_free");
+    assert!(matches!(err.err, ErrType::UseAfterFree(id) if id == "g"));
+}
+
+#[test]
+fn heap_double_free() {
+    let err = validate_err("heap_double_free");
+    assert!(matches!(err.err, ErrType::DoubleFree(id) if id == "p"));
+    assert_eq!(err.line, 4);
+}
+
+#[test]
+fn array_index() {
+    validate("array_index");
+}
+
+#[test]
+fn array_argument() {
+    validate("array_argument");
+}
+
+#[test]
+fn ptr_arithmetic() {
+    validate("ptr_arithmetic");
+}
+
+/// `q = p + 2` borrows `arr` through `p`, so `arr[0] = 7` is moved in front of both of them
+#[test]
+fn ptr_derived_borrow() {
+    validate("ptr_derived_borrow");
+}
+
+/// Writing through `q = p + 1` needs `p` to be a `&mut` too
+#[test]
+fn ptr_derived_write() {
+    validate("ptr_derived_write");
+}
+
+/// `p++` only moves `p` along `arr`, so `arr[0] = 7` can still be moved in front of `p`
+#[test]
+fn ptr_increment_rearranged() {
+    validate("ptr_increment_rearranged");
+}
+
+#[test]
+fn array_initializers() {
+    validate("array_initializers");
+}
+
+/// C converts between `int`, `char`, `float` and `double` implicitly, rust needs an `as`
+#[test]
+fn number_casts() {
+    validate("number_casts");
+}
+
+/// `char` is signed in C, so `'z' + 10` wraps to -124 when it's stored back, read into an `int`
+/// and printed
+#[test]
+fn char_promotion() {
+    validate("char_promotion");
+}
+
+/// The items left out are zeros of the array's type, and ints in the list become floats
+#[test]
+fn array_float_initializers() {
+    validate("array_float_initializers");
+}
+
+#[test]
+fn array_too_many_items() {
+    let (tokens, line_numbers) =
+        lexer::string_to_tokens(read_case("array_too_many_items")).unwrap();
+    let err = parser::program(tokens, line_numbers, true).expect_err("Should have been rejected");
+    assert!(matches!(err.err, ErrType::TooManyItems));
+    assert_eq!(err.line, 2);
+}
+
+#[test]
+fn array_multi_dimensional() {
+    validate("array_multi_dimensional");
+}
+
+/// `arr` can't be shared and `p` can't be a raw pointer into it, so the overlap is reported
+#[test]
+fn array_unfixable_overlap() {
+    let err = validate_err("array_unfixable_overlap");
+    assert!(matches!(err.err, ErrType::UnfixedBorrows(errors) if errors.len() == 2));
+    assert_eq!(err.line, 4);
+}
+
+#[test]
+fn array_item_address() {
+    validate("array_item_address");
+}
+
+#[test]
+fn string_literals() {
+    validate("string_literals");
+}
+
+#[test]
+fn string_nul_terminated() {
+    validate("string_nul_terminated");
+}
+
+#[test]
+fn string_buffers() {
+    validate("string_buffers");
+}
+
+/// C sets every byte of each `int` to 1, so it can't be a `fill`
+#[test]
+fn memset_nonzero() {
+    let err = validate_err("memset_nonzero");
+    assert!(matches!(err.err, ErrType::NonZeroFill(id) if id == "nums"));
+    assert_eq!(err.line, 5);
+}
+
+#[test]
+fn preprocessor_line_at_end() {
+    let (tokens, _) = lexer::string_to_tokens("int main() {\n}\n#define N 1").unwrap();
+    assert!(!tokens.is_empty());
+}
+
+#[test]
+fn printf_basic() {
+    validate("printf_basic");
+}
+
+#[test]
+fn printf_buffer() {
+    validate("printf_buffer");
+}
+
+#[test]
+fn printf_invalid_format() {
+    let err = validate_err("printf_invalid_format");
+    assert!(matches!(err.err, ErrType::InvalidFormat(_)));
+
+    let err = validate_err("printf_invalid_format_2");
+    assert!(matches!(err.err, ErrType::InvalidFormat(_)));
+}
+
+#[test]
+fn assert_conditions() {
+    validate("assert_conditions");
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn asm_statements() {
+    validate("asm_statements");
+}
+
+#[test]
+fn globals_immutable() {
+    validate("globals_immutable");
+}
+
+#[test]
+fn globals_mutable() {
+    validate("globals_mutable");
+}
+
+#[test]
+fn globals_char() {
+    validate("globals_char");
+}
+
+#[test]
+fn globals_pointed_to() {
+    validate("globals_pointed_to");
+}
+
+/// Enums only compared, assigned and passed around become Rust enums
+#[test]
+fn enums_as_rust_enums() {
+    validate("enums_as_rust_enums");
+}
+
+/// Enums used as integers become `const`s
+#[test]
+fn enums_as_consts() {
+    validate("enums_as_consts");
+}
+
+#[test]
+fn typedef_names() {
+    validate("typedef_names");
+}
+
+/// Reading a union member reinterprets the bytes, which needs `unsafe`
+#[test]
+fn unions() {
+    validate("unions");
+}
+
+/// The tag is always written right before the member it selects, so the union is a Rust enum
+#[test]
+fn tagged_unions() {
+    validate("tagged_unions");
+}
+
+/// The member is written before the tag, so the union stays a `union`
+#[test]
+fn tagged_unions_fallback() {
+    validate("tagged_unions_fallback");
+}
+
+#[test]
+fn struct_ptr_fields() {
+    validate("struct_ptr_fields");
+}
+
+#[test]
+fn heap_struct_ptr_fields() {
+    validate("heap_struct_ptr_fields");
+}
+
+/// Borrows of different fields don't overlap
+#[test]
+fn disjoint_field_borrows() {
+    validate("disjoint_field_borrows");
+}
+
+#[test]
+fn nested_struct_fields() {
+    validate("nested_struct_fields");
+}
+
+#[test]
+fn struct_arrays() {
+    validate("struct_arrays");
+}
+
+/// A borrow of the whole struct overlaps a borrow of one of its fields
+#[test]
+fn overlapping_place_borrows() {
+    validate("overlapping_place_borrows");
+}
+
+/// `&'a mut &'a mut i32` would keep `h` borrowed for as long as the `i32` is
+#[test]
+fn struct_field_lifetimes() {
+    validate("struct_field_lifetimes");
+}
+
+#[test]
+fn nested_struct_lifetimes() {
+    validate("nested_struct_lifetimes");
+}
+
+#[test]
+fn recursive_boxed_list() {
+    validate("recursive_boxed_list");
+}
+
+#[test]
+fn recursive_shared_list() {
+    validate("recursive_shared_list");
+}
+
+#[test]
+fn recursive_doubly_linked() {
+    validate("recursive_doubly_linked");
+}
+
+#[test]
+fn recursive_ring_arena() {
+    validate("recursive_ring_arena");
+}
+
+fn validate(test_name: &str) {
+    validate_with(test_name, &Options::default())
+}
+
+/// Runs the C and its translation, which have to print the same thing and exit the same way
+fn validate_with(test_name: &str, options: &Options) {
+    let rust_code = validate_compiles(test_name, options);
+    let c_code = read_case(test_name);
+    // NOTE Without a `main` there's nothing to run
+    if !c_code.contains("main(") {
+        return;
+    }
+
+    let rust_run = Run::of(
+        Command::new(format!("{TRANSLATED}/bin/{test_name}_test"))
+            .output()
+            .expect("Running the translation failed"),
+    );
+    let mut c_run = run_c(&c_code, test_name);
+    // NOTE A `void main` exits with whatever was left in the return register
+    if c_code.contains("void main(") {
+        c_run.code = rust_run.code;
+    }
+
+    if rust_run != c_run {
+        panic!(
+            "The translation behaves differently\nC: {c_run:?}\nRust: {rust_run:?}\n\n{}",
+            side_by_side(&c_code, &rust_code)
+        );
+    }
 }
 
-fn validate(c_code: &str, test_name: &str) {
-    let ast = parse_c(c_code.to_string());
-    let rust_code = convert_to_rust_code(ast);
+/// Only compiles the translation, for C that can't be built on its own
+fn validate_compiles(test_name: &str, options: &Options) -> String {
+    let ast = parse_c(read_case(test_name));
+    let rust_code = convert_to_rust_code(ast, options).unwrap_or_else(|err| panic!("{err}"));
+    snapshot(&format!("{test_name}.rs"), &rust_code);
+
+    if !compile(test_name, &rust_code) {
+        panic!("Compilation Failed");
+    }
+    println!("Test passed!");
+
+    rust_code
+}
 
-    fs::create_dir_all("./translated/bin").expect("dir failed");
-    let file_name = format!("./translated/{test_name}_test.rs");
+/// Builds the translation with `rustc` into `{TRANSLATED}/bin`
+fn compile(test_name: &str, rust_code: &str) -> bool {
+    fs::create_dir_all(format!("{TRANSLATED}/bin")).expect("dir failed");
+    let file_name = format!("{TRANSLATED}/{test_name}_test.rs");
     fs::write(file_name.clone(), rust_code).expect("writing code to file failed");
 
     match Command::new("rustc")
         .arg(file_name)
         .arg("--out-dir")
-        .arg("./translated/bin")
+        .arg(format!("{TRANSLATED}/bin"))
         .spawn()
         .expect("Rust compilation failed to start")
         .wait()
     {
-        Ok(o) if o.success() => println!("Test passed!"),
-        Ok(_) => panic!("Compilation Failed"),
+        Ok(o) => o.success(),
         Err(err) => panic!("Rustc Panicked, {err}"),
+    }
+}
+
+// NOTE Each case is `tests/cases/{test_name}.c`, with its expected translation next to it
+const CASES: &str = "./tests/cases";
+// NOTE Where the translations and both programs are built, they're only kept to look at
+const TRANSLATED: &str = "./target/translated";
+// NOTE Set to write the current translations as the expected ones, instead of comparing them
+const BLESS: &str = "SONDER_BLESS";
+
+fn read_case(test_name: &str) -> String {
+    let file_name = format!("{CASES}/{test_name}.c");
+    fs::read_to_string(&file_name).unwrap_or_else(|err| panic!("Reading {file_name} failed, {err}"))
+}
+
+/// Compares the translation with the expected one, so that any change to the output is seen
+fn snapshot(file_name: &str, output: &str) {
+    let file_name = format!("{CASES}/{file_name}");
+    if env::var_os(BLESS).is_some() {
+        fs::write(&file_name, output).expect("writing the expected output failed");
+        return;
+    }
+
+    let expected = fs::read_to_string(&file_name).unwrap_or_else(|_| {
+        panic!("No expected output in {file_name}, run the tests with {BLESS}=1 to write it")
+    });
+    if expected != output {
+        panic!(
+            "The output changed, run the tests with {BLESS}=1 if that's intended\n\n{}",
+            unified_diff(&expected, output)
+        );
+    }
+}
+
+/// The lines that differ between the expected and the actual translation, with some context
+fn unified_diff(expected: &str, actual: &str) -> String {
+    const CONTEXT: usize = 3;
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // NOTE The longest common subsequence of the lines from `i` and `j` on
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = match old[i] == new[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    // (tag, old line, new line, text)
+    let mut edits: Vec<(char, usize, usize, &str)> = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            edits.push((' ', i, j, old[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            edits.push(('-', i, j, old[i]));
+            i += 1;
+        } else {
+            edits.push(('+', i, j, new[j]));
+            j += 1;
+        }
+    }
+
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    edits
+        .iter()
+        .enumerate()
+        .filter(|(_, (tag, ..))| *tag != ' ')
+        .for_each(|(k, _)| {
+            let (start, end) = (
+                k.saturating_sub(CONTEXT),
+                (k + CONTEXT + 1).min(edits.len()),
+            );
+            match hunks.last_mut() {
+                Some(last) if start <= last.1 => last.1 = end,
+                _ => hunks.push((start, end)),
+            }
+        });
+
+    let mut diff = String::from("--- expected\n+++ actual\n");
+    hunks.iter().for_each(|(start, end)| {
+        let hunk = &edits[*start..*end];
+        let old_len = hunk.iter().filter(|(tag, ..)| *tag != '+').count();
+        let new_len = hunk.iter().filter(|(tag, ..)| *tag != '-').count();
+        diff += &format!(
+            "@@ -{},{old_len} +{},{new_len} @@\n",
+            hunk[0].1 + 1,
+            hunk[0].2 + 1
+        );
+        hunk.iter()
+            .for_each(|(tag, _, _, line)| diff += &format!("{tag}{line}\n"));
+    });
+    diff
+}
+
+/// What running a program did, which has to be the same for the C and the translation
+#[derive(Debug, PartialEq)]
+struct Run {
+    code: Option<i32>,
+    stdout: String,
+}
+
+impl Run {
+    fn of(output: Output) -> Self {
+        Run {
+            code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        }
+    }
+}
+
+// NOTE The test programs call the standard library without including it
+const C_PRELUDE: &str = "#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+";
+
+/// Compiles the C with the local `cc` and runs it
+fn run_c(c_code: &str, test_name: &str) -> Run {
+    let binary = format!("{TRANSLATED}/bin/{test_name}_c");
+    let mut cc = Command::new("cc")
+        .args(["-w", "-x", "c", "-", "-o", &binary])
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("C compilation failed to start, is cc installed?");
+    cc.stdin
+        .take()
+        .unwrap()
+        .write_all(format!("{C_PRELUDE}{c_code}").as_bytes())
+        .expect("Writing the C to cc failed");
+    match cc.wait() {
+        Ok(o) if o.success() => {}
+        Ok(_) => panic!("C Compilation Failed"),
+        Err(err) => panic!("cc Panicked, {err}"),
+    };
+
+    Run::of(Command::new(binary).output().expect("Running the C failed"))
+}
+
+/// The C and its translation next to each other, to see where they went apart
+fn side_by_side(c_code: &str, rust_code: &str) -> String {
+    let c_lines: Vec<&str> = c_code.lines().map(str::trim_end).collect();
+    let rust_lines: Vec<&str> = rust_code.lines().collect();
+    let width = c_lines.iter().map(|line| line.len()).max().unwrap_or(0);
+
+    (0..c_lines.len().max(rust_lines.len()))
+        .map(|i| {
+            let c_line = c_lines.get(i).copied().unwrap_or_default();
+            let rust_line = rust_lines.get(i).copied().unwrap_or_default();
+            format!("{c_line:width$} | {rust_line}")
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Translations the analysis must refuse
+fn validate_err(test_name: &str) -> RhErr {
+    validate_err_with(test_name, &Options::default())
+}
+
+fn validate_err_with(test_name: &str, options: &Options) -> RhErr {
+    let ast = parse_c(read_case(test_name));
+    convert_to_rust_code(ast, options).expect_err("Translation should have been rejected")
+}
+
+// NOTE The first seed and how many programs to try, `SONDER_FUZZ_SEED=7 SONDER_FUZZ_CASES=1000`
+const FUZZ_SEED: &str = "SONDER_FUZZ_SEED";
+const FUZZ_CASES: &str = "SONDER_FUZZ_CASES";
+
+/// How a generated program went wrong
+enum Failure {
+    Panicked(String),
+    Rejected(String),
+    Uncompiled,
+    Behaved { c_run: Run, rust_run: Run },
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Panicked(message) => write!(f, "sonder panicked, {message}"),
+            Failure::Rejected(message) => write!(f, "the translation was rejected, {message}"),
+            Failure::Uncompiled => write!(f, "the translation doesn't compile"),
+            Failure::Behaved { c_run, rust_run } => write!(
+                f,
+                "the translation behaves differently\nC: {c_run:?}\nRust: {rust_run:?}"
+            ),
+        }
+    }
+}
+
+impl Failure {
+    fn same_kind(&self, other: &Failure) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+/// Translates, compiles and runs a generated program next to the C, checking the translation with
+/// `rustc` if `verify` is set
+fn check_generated(program: &Program, test_name: &str, verify: bool) -> Result<(), Failure> {
+    let c_code = program.to_c();
+    let options = Options {
+        verify,
+        ..Options::default()
+    };
+    let translation =
+        panic::catch_unwind(|| convert_to_rust_code(parse_c(c_code.clone()), &options));
+    let rust_code = match translation {
+        Ok(Ok(rust_code)) => rust_code,
+        Ok(Err(err)) => return Err(Failure::Rejected(err.to_string())),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default();
+            return Err(Failure::Panicked(message));
+        }
+    };
+
+    if !compile(test_name, &rust_code) {
+        return Err(Failure::Uncompiled);
+    }
+    let rust_run = Run::of(
+        Command::new(format!("{TRANSLATED}/bin/{test_name}_test"))
+            .output()
+            .expect("Running the translation failed"),
+    );
+    let c_run = run_c(&c_code, test_name);
+    match rust_run == c_run {
+        true => Ok(()),
+        false => Err(Failure::Behaved { c_run, rust_run }),
+    }
+}
+
+/// Random programs in the supported subset translate without panicking, compile, and behave like
+/// the C, a failing one is shrunk to the smallest program that still fails the same way
+#[test]
+fn fuzz_generated_programs() {
+    let env_number = |name: &str, default: u64| -> u64 {
+        env::var(name)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
     };
+    let first_seed = env_number(FUZZ_SEED, 0);
+
+    for seed in first_seed..first_seed + env_number(FUZZ_CASES, 32) {
+        let test_name = format!("fuzz_{seed}");
+        let program = Program::generate(seed);
+        // NOTE The checker's own translation has to compile, `--verify` only covers for it
+        for verify in [false, true] {
+            if let Err(failure) = check_generated(&program, &test_name, verify) {
+                let shrunk = program.shrink(|smaller| {
+                    check_generated(smaller, &test_name, verify)
+                        .is_err_and(|err| err.same_kind(&failure))
+                });
+                panic!(
+                    "Seed {seed} failed with verify {verify}, {failure}\n\nShrunk to:\n{}",
+                    shrunk.to_c()
+                );
+            }
+        }
+    }
 }
@@ -1,12 +1,17 @@
 use crate::error::{ErrType as ET, RhErr};
-use crate::lexer::{LineNumHandler, Token};
-use std::ops::Range;
+use crate::lexer::{CType, LineNumHandler, Token};
+use std::{collections::HashMap, ops::Range};
 
 pub struct TokenHandler {
     pub tokens: Vec<Token>,
     pub curr_token: usize,
     // Index is line number, range is start and end of line
     pub token_lines: Vec<Range<usize>>,
+    // NOTE Typedef names decide whether `Foo * x` is a declaration, so they're resolved while
+    // parsing
+    pub typedefs: HashMap<String, CType>,
+    // Enumerator -> enum id (None for anonymous enums)
+    pub enum_constants: HashMap<String, Option<String>>,
 }
 
 #[allow(dead_code)]
@@ -25,12 +30,15 @@ impl TokenHandler {
                         false => line_tracker.token_lines[line_number + 1],
                     };
 
+                    // NOTE Lines before the first token (`#include`s) start at 0
                     Range {
-                        start: *start - 1,
-                        end: end - 1,
+                        start: start.saturating_sub(1),
+                        end: end.saturating_sub(1),
                     }
                 })
                 .collect(),
+            typedefs: HashMap::new(),
+            enum_constants: HashMap::new(),
         }
     }
 
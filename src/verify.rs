@@ -0,0 +1,119 @@
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+// NOTE Every check gets its own output directory, since tests translate in parallel
+static CHECKS: AtomicUsize = AtomicUsize::new(0);
+
+// NOTE Moves and borrows, the errors a different pointer type can fix
+const BORROW_ERRORS: [&str; 11] = [
+    "E0382", "E0384", "E0499", "E0502", "E0503", "E0505", "E0506", "E0507", "E0596", "E0597",
+    "E0716",
+];
+
+/// An error `rustc` reports on the translation, `<anon>:7:2: error[E0506]: cannot assign to ...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RustcError {
+    pub code: Option<String>,
+    // Of the generated rust
+    pub line: usize,
+    pub message: String,
+}
+
+impl RustcError {
+    fn parse(line: &str) -> Option<Self> {
+        let rest = line.strip_prefix("<anon>:")?;
+        let (rust_line, rest) = rest.split_once(':')?;
+        let (_column, rest) = rest.split_once(": ")?;
+        let rest = rest.strip_prefix("error")?;
+        let (code, message) = match rest.strip_prefix('[') {
+            Some(rest) => {
+                let (code, message) = rest.split_once("]: ")?;
+                (Some(code.to_string()), message)
+            }
+            None => (None, rest.strip_prefix(": ")?),
+        };
+
+        Some(RustcError {
+            code,
+            line: rust_line.parse().ok()?,
+            message: message.to_string(),
+        })
+    }
+
+    /// Borrow checking errors, as opposed to ones sonder can't fix by changing pointer types
+    pub fn is_borrow_error(&self) -> bool {
+        self.code
+            .as_deref()
+            .is_some_and(|code| BORROW_ERRORS.contains(&code))
+    }
+
+    /// A binding borrowed mutably without being declared `mut`, which no pointer type fixes
+    pub fn is_immutable_binding(&self) -> bool {
+        self.code.as_deref() == Some("E0596") && self.message.contains("not declared as mutable")
+    }
+
+    /// The variables the message names, `*g` and `s.x` name `g` and `s`
+    pub fn named_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .message
+            .split('`')
+            .skip(1)
+            .step_by(2)
+            .filter_map(|quoted| {
+                let id: String = quoted
+                    .trim_start_matches(['*', '&'])
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect();
+                (!id.is_empty()).then_some(id)
+            })
+            .collect();
+        ids.dedup();
+        ids
+    }
+}
+
+/// Type and borrow checks the translation with the local `rustc`, without building it
+///
+/// # Important
+/// `rustc` failing without an error that can be parsed (an ICE, a linker or file error) is
+/// returned as its output, since there's nothing to trace back to the C
+pub fn rustc_errors(rust_code: &str) -> Result<Vec<RustcError>, String> {
+    let out_dir = env::temp_dir().join(format!(
+        "sonder_verify_{}_{}",
+        std::process::id(),
+        CHECKS.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let mut rustc = Command::new("rustc")
+        .args(["-", "--emit=metadata", "--crate-name", "sonder_verify"])
+        .args(["--error-format=short", "-A", "warnings", "--out-dir"])
+        .arg(&out_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Rust verification failed to start, is rustc installed?");
+    rustc
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(rust_code.as_bytes())
+        .expect("Writing the translation to rustc failed");
+    let output = rustc.wait_with_output().expect("Rustc Panicked");
+    let _ = fs::remove_dir_all(&out_dir);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let errors: Vec<RustcError> = stderr.lines().filter_map(RustcError::parse).collect();
+    match output.status.success() || !errors.is_empty() {
+        true => Ok(errors),
+        false => Err(match stderr.trim() {
+            "" => format!("rustc exited with {}", output.status),
+            stderr => stderr.to_string(),
+        }),
+    }
+}
@@ -0,0 +1,14 @@
+void fill(int dst[], int n) {
+    dst[0] = n;
+    dst[1] = n + 1;
+}
+
+int first(int src[]) {
+    return src[0];
+}
+
+int main() {
+    int arr[8];
+    fill(arr, 8);
+    int s = first(arr);
+}
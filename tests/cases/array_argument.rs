@@ -0,0 +1,12 @@
+fn fill(dst: &mut [i32], n: i32) -> () {
+	dst[0] = n;
+	dst[1] = n + 1;
+}
+fn first(src: &[i32]) -> i32 {
+	return(src[0]);
+}
+fn main() -> () {
+	let mut arr: [i32; 8] = [0; 8];
+	fill(&mut arr, 8);
+	let _s: i32 = first(&arr);
+}
\ No newline at end of file
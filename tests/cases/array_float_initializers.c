@@ -0,0 +1,7 @@
+int main() {
+    double d[3] = {1.5};
+    float grid[2][2] = {{2, 0.5}};
+    char c[4] = {'a'};
+    d[2] = d[0] + d[1];
+    printf("%f %f %f %f %d\n", d[0], d[1], d[2], grid[1][1], c[3]);
+}
@@ -0,0 +1,7 @@
+fn main() -> () {
+	let mut d: [f64; 3] = [1.5, 0.0, 0.0];
+	let grid: [[f32; 2]; 2] = [[(2) as f32, 0.5], [0.0; 2]];
+	let c: [u8; 4] = [97, 0, 0, 0];
+	d[2] = d[0] + d[1];
+	print!("{:.6} {:.6} {:.6} {:.6} {}\n", d[0], d[1], d[2], grid[1][1], (c[3]) as i8 as i32);
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+int main() {
+    int arr[4] = {1, 2};
+    int i = 1;
+    arr[i] = 5;
+    arr[2] += arr[0];
+    arr[i + 2]++;
+    int k = arr[3];
+}
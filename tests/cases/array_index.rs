@@ -0,0 +1,8 @@
+fn main() -> () {
+	let mut arr: [i32; 4] = [1, 2, 0, 0];
+	let i: i32 = 1;
+	arr[i as usize] = 5;
+	arr[2] += arr[0];
+	arr[(i + 2) as usize] += 1;
+	let _k: i32 = arr[3];
+}
\ No newline at end of file
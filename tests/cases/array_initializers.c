@@ -0,0 +1,5 @@
+int main() {
+    int inferred[] = {1, 2, 3};
+    int designated[6] = {1, [3] = 5, 6};
+    int k = inferred[2] + designated[4];
+}
@@ -0,0 +1,5 @@
+fn main() -> () {
+	let inferred: [i32; 3] = [1, 2, 3];
+	let designated: [i32; 6] = [1, 0, 0, 5, 6, 0];
+	let _k: i32 = inferred[2] + designated[4];
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+int main() {
+    int arr[4] = {1, 2, 3, 4};
+    int* p = &arr[1];
+    *p = 9;
+    int k = *(p + 1);
+    int grid[2][2];
+    int* row = &grid[1][0];
+    int j = *row;
+}
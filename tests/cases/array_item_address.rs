@@ -0,0 +1,9 @@
+fn main() -> () {
+	let mut arr: [i32; 4] = [1, 2, 3, 4];
+	let p: &mut [i32]  = &mut arr[1..];
+	p[0] = 9;
+	let _k: i32 = p[1];
+	let grid: [[i32; 2]; 2] = [[0; 2]; 2];
+	let row: &[i32] = &grid[1][0..];
+	let _j: i32 = row[0];
+}
\ No newline at end of file
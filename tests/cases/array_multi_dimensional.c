@@ -0,0 +1,8 @@
+int main() {
+    int grid[3][4] = {{1, 2}, [2] = {0, 0, 7}};
+    int empty[2][2];
+    int i = 1;
+    grid[i][3] = 4;
+    empty[0][1] += grid[2][2];
+    int k = grid[i][3];
+}
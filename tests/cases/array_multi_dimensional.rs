@@ -0,0 +1,8 @@
+fn main() -> () {
+	let mut grid: [[i32; 4]; 3] = [[1, 2, 0, 0], [0; 4], [0, 0, 7, 0]];
+	let mut empty: [[i32; 2]; 2] = [[0; 2]; 2];
+	let i: i32 = 1;
+	grid[i as usize][3] = 4;
+	empty[0][1] += grid[2][2];
+	let _k: i32 = grid[i as usize][3];
+}
\ No newline at end of file
@@ -0,0 +1,4 @@
+int main() {
+    int a[2] = {1, 2, 3};
+    printf("%d\n", a[0]);
+}
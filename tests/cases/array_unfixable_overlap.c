@@ -0,0 +1,11 @@
+#include <stdio.h>
+int main() {
+    int arr[3] = {1, 2, 3};
+    int* p = arr;
+    arr[0] = 7;
+    p[1] = 5;
+    arr[2] = 9;
+    p[0] = 4;
+    printf("%d %d %d\n", arr[0], arr[1], arr[2]);
+    return 0;
+}
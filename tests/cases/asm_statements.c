@@ -0,0 +1,14 @@
+#include <stdio.h>
+
+int main() {
+    int x = 1;
+    int* p = &x;
+    *p = 2;
+    asm("nop");
+    asm("addl $1, %0" : "+r"(x));
+    int y = 0;
+    asm("movl %1, %0" : "=r"(y) : "r"(x));
+    asm("movl %1, %%eax\n\taddl %%eax, %0" : "+r"(y) : "r"(x) : "eax", "cc");
+    *p = x + y;
+    printf("%d %d\n", x, y);
+}
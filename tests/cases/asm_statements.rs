@@ -0,0 +1,18 @@
+fn main() -> () {
+	let mut x: i32 = 1;
+	let p: *mut i32 = &mut x as *mut i32;
+	// SAFETY: `p` points to `x`, and `x` is still in scope
+	unsafe {
+		*p = 2;
+	}
+	unsafe { core::arch::asm!("nop", options(att_syntax)); }
+	unsafe { core::arch::asm!("addl $1, {0:e}", inout(reg) x, options(att_syntax)); }
+	let mut y: i32 = 0;
+	unsafe { core::arch::asm!("movl {1:e}, {0:e}", out(reg) y, in(reg) x, options(att_syntax)); }
+	unsafe { core::arch::asm!("movl {1:e}, %eax\n\taddl %eax, {0:e}", inout(reg) y, in(reg) x, out("eax") _, options(att_syntax)); }
+	// SAFETY: `p` points to `x`, and `x` is still in scope
+	unsafe {
+		*p = x + y;
+	}
+	print!("{} {}\n", x, y);
+}
\ No newline at end of file
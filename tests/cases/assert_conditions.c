@@ -0,0 +1,10 @@
+#include <assert.h>
+
+int main() {
+    int n = 3;
+    int m = 0;
+    assert(n);
+    assert(n == 3);
+    assert(m != n);
+    assert(n && m == 0 || m);
+}
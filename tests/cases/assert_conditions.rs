@@ -0,0 +1,8 @@
+fn main() -> () {
+	let n: i32 = 3;
+	let m: i32 = 0;
+	assert!((n) != 0);
+	assert!(n == 3);
+	assert!(m != n);
+	assert!(((n) != 0 && m == 0) || (m) != 0);
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+int main() {
+    int t = add(1, 2);
+}
+int add(int a, int b) {
+    int k = a + b;
+    return(k);
+}
@@ -1,7 +1,7 @@
 fn main() -> () {
-let _t: i32 = add(1, 2);
+	let _t: i32 = add(1, 2);
 }
 fn add(a: i32, b: i32) -> i32 {
-let k: i32 = a + b;
+	let k: i32 = a + b;
 	return(k);
 }
\ No newline at end of file
@@ -0,0 +1,6 @@
+int main() {
+    int i = 0;
+    while (i == 0) {
+        i++;
+    }
+}
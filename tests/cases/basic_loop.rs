@@ -1,5 +1,5 @@
 fn main() -> () {
-let mut i: i32 = 0;
+	let mut i: i32 = 0;
 	while i == 0 {
 		i += 1;
 	}
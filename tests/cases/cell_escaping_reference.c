@@ -0,0 +1,10 @@
+int main() {
+    int n = 0;
+    int* m = &n;
+    int** pm = &m;
+    int* g = &n;
+    int j = *g;
+    *m = 4;
+    int t = *g;
+    printf("%d %d\n", j, t);
+}
@@ -0,0 +1,11 @@
+use std::{cell::RefCell, rc::Rc};
+fn main() -> () {
+	let n: Rc<RefCell<i32>> = Rc::new(RefCell::new(0));
+	let m: Rc<RefCell<i32>> = n.clone();
+	let pm: &Rc<RefCell<i32>> = &m;
+	let g: Rc<RefCell<i32>> = n.clone();
+	let j: i32 = *g.borrow();
+	*m.borrow_mut() = 4;
+	let t: i32 = *g.borrow();
+	print!("{} {}\n", j, t);
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+int main() {
+    int t = 1;
+    int* a = &t;
+    int* b = &t;
+    *a = *b + 1;
+    printf("%d\n", t);
+}
@@ -0,0 +1,8 @@
+use std::cell::Cell;
+fn main() -> () {
+	let t: Cell<i32> = Cell::new(1);
+	let a: &Cell<i32> = &t;
+	let b: &Cell<i32> = &t;
+	a.set(b.get() + 1);
+	print!("{}\n", t.get());
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+int add_five(int a) {
+    int* p = &a;
+    a = *p + 5;
+    return *p;
+}
+
+int main() {
+    printf("%d\n", add_five(2));
+}
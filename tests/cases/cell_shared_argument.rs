@@ -0,0 +1,10 @@
+use std::cell::Cell;
+fn add_five(a: i32) -> i32 {
+	let a: Cell<i32> = Cell::new(a);
+	let p: &Cell<i32> = &a;
+	a.set(p.get() + 5);
+	return(p.get());
+}
+fn main() -> () {
+	print!("{}\n", add_five(2));
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+int main() {
+    int n = 0;
+    int* m = &n;
+    int* g = &n;
+    int j = *g;
+    *m = 4;
+    *m += 2;
+    int t = *g;
+    n = n + 1;
+    printf("%d %d %d\n", j, t, n);
+}
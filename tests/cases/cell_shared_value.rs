@@ -0,0 +1,12 @@
+use std::cell::Cell;
+fn main() -> () {
+	let n: Cell<i32> = Cell::new(0);
+	let m: &Cell<i32> = &n;
+	let g: &Cell<i32> = &n;
+	let j: i32 = g.get();
+	m.set(4);
+	m.set(m.get() + (2));
+	let t: i32 = g.get();
+	n.set(n.get() + 1);
+	print!("{} {} {}\n", j, t, n.get());
+}
\ No newline at end of file
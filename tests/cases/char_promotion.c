@@ -0,0 +1,11 @@
+int main() {
+    char c = 'z';
+    c = c + 10;
+    int k = c;
+    double d = c;
+    int below = 0;
+    if (c != k) {
+        below = 1;
+    }
+    printf("%d %d %f %d %x %u\n", k, c, d, below, c, c);
+}
@@ -0,0 +1,11 @@
+fn main() -> () {
+	let mut c: u8 = 122;
+	c = (((c) as i8) as i32 + 10) as u8;
+	let k: i32 = ((c) as i8) as i32;
+	let d: f64 = ((c) as i8) as f64;
+	let mut below: i32 = 0;
+	if ((c) as i8) as i32 != k {
+		below = 1;
+	}
+	print!("{} {} {:.6} {} {:x} {}\n", k, (c) as i8 as i32, d, below, (c) as i8 as i32, (c) as i8 as u32);
+}
\ No newline at end of file
@@ -0,0 +1,6 @@
+int main() {
+    int k = 3;
+    int* y = &k;
+    *y = k + 6;
+    printf("%d\n", k);
+}
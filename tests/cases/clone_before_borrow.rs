@@ -0,0 +1,7 @@
+fn main() -> () {
+	let mut k: i32 = 3;
+	let k_clone: i32 = k;
+	let y: &mut i32  = &mut k;
+	*y = k_clone + 6;
+	print!("{}\n", k);
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+int main() {
+    int k = 3;
+    int* y = &k;
+    *y = 5;
+    *y = k + 6;
+    printf("%d\n", k);
+}
@@ -0,0 +1,8 @@
+use std::cell::Cell;
+fn main() -> () {
+	let k: Cell<i32> = Cell::new(3);
+	let y: &Cell<i32> = &k;
+	y.set(5);
+	y.set(k.get() + 6);
+	print!("{}\n", k.get());
+}
\ No newline at end of file
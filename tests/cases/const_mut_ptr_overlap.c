@@ -0,0 +1,7 @@
+int main() {
+    int n = 0;
+    int* g = &n;
+    int* m = &n;
+    int t = *g;
+    *m = 4;
+}
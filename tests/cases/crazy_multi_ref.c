@@ -0,0 +1,8 @@
+int main() {
+    int n = 0;
+    int* g = &n;
+    int* k = &n;
+    int** h = &g;
+    int p = 3;
+    *h = &p;
+}
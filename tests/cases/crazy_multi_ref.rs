@@ -1,5 +1,5 @@
 fn main() -> () {
-let mut n: i32 = 0;
+	let mut n: i32 = 0;
 	let k: &i32 = &n;
 	let mut g: &mut i32  = &mut n;
 	let h: &mut &mut i32   = &mut g;
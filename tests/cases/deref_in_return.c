@@ -0,0 +1,8 @@
+int next(int a) {
+    int* p = &a;
+    return *p + 1;
+}
+
+int main() {
+    printf("%d\n", next(4));
+}
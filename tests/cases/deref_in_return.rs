@@ -0,0 +1,7 @@
+fn next(a: i32) -> i32 {
+	let p: &i32 = &a;
+	return(*p + 1);
+}
+fn main() -> () {
+	print!("{}\n", next(4));
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+void main() {
+    int k = 3;
+    int* y = &k;
+    *y = k + 6;
+}
@@ -0,0 +1,6 @@
+fn main() -> () {
+	let mut k: i32 = 3;
+	let k_clone: i32 = k;
+	let y: &mut i32  = &mut k;
+	*y = k_clone + 6;
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+struct Point { int x; int y; };
+
+int main() {
+    struct Point s = {1, 2};
+    int* a = &s.x;
+    int* b = &s.y;
+    *a = 3;
+    *b = 4;
+    *a += 1;
+    printf("%d %d\n", s.x, s.y);
+}
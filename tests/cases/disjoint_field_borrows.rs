@@ -0,0 +1,13 @@
+struct Point {
+	x: i32,
+	y: i32,
+}
+fn main() -> () {
+	let mut s = Point {x: 1,y: 2,};
+	let a: &mut i32  = &mut s.x;
+	let b: &mut i32  = &mut s.y;
+	*a = 3;
+	*b = 4;
+	*a += 1;
+	print!("{} {}\n", s.x, s.y);
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+enum Level { LOW = 1, HIGH = 10 };
+enum { SCALE = 8 };
+
+int main() {
+    enum Level l = LOW;
+    int total = l + HIGH;
+    printf("%d\n", total * SCALE);
+}
@@ -0,0 +1,10 @@
+#[allow(non_camel_case_types)]
+type Level = i32;
+const LOW: Level = 1;
+const HIGH: Level = 10;
+const SCALE: i32 = 8;
+fn main() -> () {
+	let l: Level = LOW;
+	let total: i32 = l + HIGH;
+	print!("{}\n", total * SCALE);
+}
\ No newline at end of file
@@ -0,0 +1,19 @@
+enum Color { RED, GREEN = 4, BLUE };
+typedef enum { NORTH, SOUTH } Direction;
+
+enum Color next(enum Color c) {
+    if (c == RED) {
+        return GREEN;
+    }
+    return BLUE;
+}
+
+int main() {
+    enum Color c = RED;
+    Direction d = NORTH;
+    c = next(c);
+    if (d != SOUTH) {
+        d = SOUTH;
+    }
+    assert(c == GREEN);
+}
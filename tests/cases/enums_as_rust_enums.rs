@@ -0,0 +1,30 @@
+#[derive(Clone, Copy, PartialEq)]
+#[allow(non_camel_case_types)]
+#[repr(i32)]
+enum Color {
+	RED = 0,
+	GREEN = 4,
+	BLUE = 5,
+}
+#[derive(Clone, Copy, PartialEq)]
+#[allow(non_camel_case_types)]
+#[repr(i32)]
+enum Direction {
+	NORTH = 0,
+	SOUTH = 1,
+}
+fn next(mut c: Color) -> Color {
+	if c == Color::RED {
+		return(Color::GREEN);
+	}
+	return(Color::BLUE);
+}
+fn main() -> () {
+	let mut c: Color = Color::RED;
+	let mut d: Direction = Direction::NORTH;
+	c = next(c);
+	if d != Direction::SOUTH {
+		d = Direction::SOUTH;
+	}
+	assert!(c == Color::GREEN);
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+int main() {
+    int arr[3] = {1, 2, 3};
+    int* p = arr;
+    int* q = p + 1;
+    int n = 0;
+    int* g = &n;
+    int j = *g;
+    n = 4;
+    int t = *g;
+    printf("%d %d %d %d\n", *q, arr[0], j, t);
+}
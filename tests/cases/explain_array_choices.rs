@@ -0,0 +1,12 @@
+use std::cell::Cell;
+fn main() -> () {
+	let arr: [i32; 3] = [1, 2, 3];
+	let p: &[i32] = &arr;
+	let q: &[i32] = &p[1..];
+	let n: Cell<i32> = Cell::new(0);
+	let g: &Cell<i32> = &n;
+	let j: i32 = g.get();
+	n.set(4);
+	let t: i32 = g.get();
+	print!("{} {} {} {}\n", q[0], arr[0], j, t);
+}
\ No newline at end of file
@@ -0,0 +1,21 @@
+struct Point { int x; int y; };
+
+int main() {
+    struct Point s = {1, 2};
+    struct Point* p = &s;
+    int* x = &s.x;
+    p->y = 3;
+    *x = 4;
+    p->y += 1;
+    int n = 0;
+    int* g = &n;
+    int j = *g;
+    n = 4;
+    int t = *g;
+    int u = 1;
+    int* q = &u;
+    u = 2;
+    int v = u;
+    *q = 3;
+    printf("%d %d %d %d %d\n", s.x, s.y, j, t, v);
+}
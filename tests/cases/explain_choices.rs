@@ -0,0 +1,27 @@
+use std::cell::Cell;
+struct Point {
+	x: i32,
+	y: i32,
+}
+fn main() -> () {
+	let mut s = Point {x: 1,y: 2,};
+	let p: *mut Point = &mut s as *mut Point;
+	let x: *mut i32 = &mut s.x as *mut i32;
+	// SAFETY: `p` points to `s` and `x` points to `s.x`, and `s` is still in scope
+	unsafe {
+		(*p).y = 3;
+		*x = 4;
+		(*p).y += 1;
+	}
+	let n: Cell<i32> = Cell::new(0);
+	let g: &Cell<i32> = &n;
+	let j: i32 = g.get();
+	n.set(4);
+	let t: i32 = g.get();
+	let mut u: i32 = 1;
+	u = 2;
+	let v: i32 = u;
+	let q: &mut i32  = &mut u;
+	*q = 3;
+	print!("{} {} {} {} {}\n", s.x, s.y, j, t, v);
+}
\ No newline at end of file
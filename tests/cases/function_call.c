@@ -0,0 +1,6 @@
+int main() {
+    test(1, 2);
+}
+void test(int a, int b) {
+    int k = a + b;
+}
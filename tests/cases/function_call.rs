@@ -1,6 +1,6 @@
 fn main() -> () {
-test(1, 2)
+	test(1, 2);
 }
 fn test(a: i32, b: i32) -> () {
-let _k: i32 = a + b;
+	let _k: i32 = a + b;
 }
\ No newline at end of file
@@ -0,0 +1,17 @@
+#include <stdio.h>
+
+char grade = 'A';
+int offset = 2;
+
+void promote(int steps) {
+    grade += steps;
+}
+
+int main() {
+    promote(offset);
+    grade -= 1;
+    int code = grade;
+    grade = code + offset;
+    grade |= 32;
+    printf("%c %d\n", grade, code);
+}
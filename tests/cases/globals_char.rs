@@ -0,0 +1,15 @@
+#[allow(non_upper_case_globals)]
+static grade: std::sync::atomic::AtomicU8 = std::sync::atomic::AtomicU8::new(65);
+#[allow(non_upper_case_globals)]
+const offset: i32 = 2;
+fn promote(steps: i32) -> () {
+	grade.fetch_add((steps) as u8, std::sync::atomic::Ordering::SeqCst);
+}
+fn main() -> () {
+	promote(offset);
+	grade.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+	let code: i32 = ((grade.load(std::sync::atomic::Ordering::SeqCst)) as i8) as i32;
+	grade.store((code + offset) as u8, std::sync::atomic::Ordering::SeqCst);
+	grade.fetch_or(32, std::sync::atomic::Ordering::SeqCst);
+	print!("{} {}\n", (grade.load(std::sync::atomic::Ordering::SeqCst)) as u8 as char, code);
+}
\ No newline at end of file
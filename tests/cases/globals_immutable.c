@@ -0,0 +1,8 @@
+int limit = 10;
+int table[3] = {1, 2, 3};
+double ratio;
+
+int main() {
+    int i = table[1] + limit;
+    printf("%d %f\n", i, ratio);
+}
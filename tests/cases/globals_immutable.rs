@@ -0,0 +1,10 @@
+#[allow(non_upper_case_globals)]
+const limit: i32 = 10;
+#[allow(non_upper_case_globals)]
+static table: [i32; 3] = [1, 2, 3];
+#[allow(non_upper_case_globals)]
+const ratio: f64 = 0.0;
+fn main() -> () {
+	let i: i32 = table[1] + limit;
+	print!("{} {:.6}\n", i, ratio);
+}
\ No newline at end of file
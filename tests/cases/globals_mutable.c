@@ -0,0 +1,17 @@
+int counter;
+double scale = 1.5;
+int history[4];
+
+void bump(int amount) {
+    static int calls = 0;
+    calls += 1;
+    counter += amount;
+    history[calls] = counter;
+}
+
+int main() {
+    bump(2);
+    bump(3);
+    scale = scale * 2.0;
+    printf("%d %d %f\n", counter, history[2], scale);
+}
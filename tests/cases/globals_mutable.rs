@@ -0,0 +1,23 @@
+#[allow(non_upper_case_globals)]
+static counter: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(0);
+thread_local! {
+	#[allow(non_upper_case_globals)]
+	static scale: std::cell::Cell<f64> = std::cell::Cell::new(1.5);
+}
+thread_local! {
+	#[allow(non_upper_case_globals)]
+	static history: std::cell::RefCell<[i32; 4]> = std::cell::RefCell::new([0; 4]);
+}
+fn bump(amount: i32) -> () {
+	#[allow(non_upper_case_globals)]
+	static calls: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(0);
+	calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+	counter.fetch_add(amount, std::sync::atomic::Ordering::SeqCst);
+	history.with(|cell| cell.borrow_mut()[(calls.load(std::sync::atomic::Ordering::SeqCst)) as usize] = counter.load(std::sync::atomic::Ordering::SeqCst));
+}
+fn main() -> () {
+	bump(2);
+	bump(3);
+	scale.with(|cell| cell.set(scale.with(|cell| cell.get()) * 2.0));
+	print!("{} {} {:.6}\n", counter.load(std::sync::atomic::Ordering::SeqCst), history.with(|cell| cell.borrow()[2]), scale.with(|cell| cell.get()));
+}
\ No newline at end of file
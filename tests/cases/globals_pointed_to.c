@@ -0,0 +1,10 @@
+extern int shared;
+
+int main() {
+    int* p = &shared;
+    *p = 5;
+    shared += 1;
+    printf("%d\n", shared);
+}
+
+int shared = 4;
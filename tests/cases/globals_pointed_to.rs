@@ -0,0 +1,11 @@
+fn main() -> () {
+	let p: *mut i32 = std::ptr::addr_of_mut!(shared);
+	// SAFETY: `p` points to `shared`, and `shared` is a global that lives as long as the program
+	unsafe {
+		*p = 5;
+	}
+	unsafe { shared += 1; }
+	print!("{}\n", unsafe { shared });
+}
+#[allow(non_upper_case_globals)]
+static mut shared: i32 = 4;
\ No newline at end of file
@@ -0,0 +1,9 @@
+int main() {
+    int* p = malloc(sizeof(int));
+    int* q = p;
+    *q = 3;
+    *p = 4;
+    *q = 5;
+    printf("%d\n", *p);
+    free(p);
+}
@@ -0,0 +1,15 @@
+fn main() -> () {
+	let mut p: Box<i32> = Box::new(0);
+	let q: *mut i32 = &mut *p as *mut i32;
+	// SAFETY: `q` points to the allocation owned by `p`, and it isn't freed yet
+	unsafe {
+		*q = 3;
+	}
+	*p = 4;
+	// SAFETY: `q` points to the allocation owned by `p`, and it isn't freed yet
+	unsafe {
+		*q = 5;
+	}
+	print!("{}\n", *p);
+	drop(p);
+}
\ No newline at end of file
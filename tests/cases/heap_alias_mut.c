@@ -0,0 +1,7 @@
+int main() {
+    int* p = malloc(sizeof(int));
+    int* q = p;
+    *q = 3;
+    printf("%d\n", *p);
+    free(p);
+}
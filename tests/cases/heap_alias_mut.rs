@@ -0,0 +1,7 @@
+fn main() -> () {
+	let mut p: Box<i32> = Box::new(0);
+	let q: &mut i32  = &mut *p;
+	*q = 3;
+	print!("{}\n", *p);
+	drop(p);
+}
\ No newline at end of file
@@ -0,0 +1,6 @@
+int main() {
+    int* p = malloc(sizeof(int));
+    int* g = p;
+    free(p);
+    int k = *g;
+}
@@ -0,0 +1,6 @@
+int main() {
+    int* p = malloc(sizeof(int));
+    *p = 5;
+    int k = *p + 1;
+    free(p);
+}
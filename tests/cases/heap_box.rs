@@ -0,0 +1,6 @@
+fn main() -> () {
+	let mut p: Box<i32> = Box::new(0);
+	*p = 5;
+	let _k: i32 = *p + 1;
+	drop(p);
+}
\ No newline at end of file
@@ -0,0 +1,10 @@
+int main() {
+    int n = 3;
+    double* d = malloc(n * sizeof(double));
+    d[1] = 2.5;
+    double* one = malloc(sizeof(double));
+    *one = d[1] + d[0];
+    printf("%f %f %d %d\n", d[1], *one, sizeof(float), sizeof(char*));
+    free(d);
+    free(one);
+}
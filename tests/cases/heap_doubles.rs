@@ -0,0 +1,10 @@
+fn main() -> () {
+	let n: i32 = 3;
+	let mut d: Vec<f64> = vec![0.0; (n) as usize];
+	d[1] = 2.5;
+	let mut one: Box<f64> = Box::new(0.0);
+	*one = d[1] + d[0];
+	print!("{:.6} {:.6} {} {}\n", d[1], *one, std::mem::size_of::<f32>() as i32, std::mem::size_of::<*const u8>() as i32);
+	drop(d);
+	drop(one);
+}
\ No newline at end of file
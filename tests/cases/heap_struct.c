@@ -0,0 +1,9 @@
+struct Point {
+    int x;
+    int y;
+};
+
+int main() {
+    struct Point* p = malloc(sizeof(struct Point));
+    free(p);
+}
@@ -0,0 +1,9 @@
+#[derive(Default)]
+struct Point {
+	x: i32,
+	y: i32,
+}
+fn main() -> () {
+	let p: Box<Point> = Box::new(Default::default());
+	drop(p);
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+struct Point { int x; int y; };
+
+int main() {
+    struct Point* p = malloc(sizeof(struct Point));
+    p->x = 3;
+    p->y = p->x * 2;
+    printf("%d %d\n", p->x, p->y);
+    free(p);
+}
@@ -0,0 +1,12 @@
+#[derive(Default)]
+struct Point {
+	x: i32,
+	y: i32,
+}
+fn main() -> () {
+	let mut p: Box<Point> = Box::new(Default::default());
+	p.x = 3;
+	p.y = p.x * 2;
+	print!("{} {}\n", p.x, p.y);
+	drop(p);
+}
\ No newline at end of file
@@ -0,0 +1,6 @@
+int main() {
+    int n = 1;
+    int* p = malloc(4);
+    *p = n;
+    free(p);
+}
@@ -0,0 +1,5 @@
+int main() {
+    int* p = malloc(sizeof(int));
+    free(p);
+    *p = 4;
+}
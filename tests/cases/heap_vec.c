@@ -0,0 +1,9 @@
+int main() {
+    int n = 4;
+    int* arr = calloc(n, sizeof(int));
+    *arr = 3;
+    int* other = malloc(n * sizeof(int));
+    int k = *arr;
+    free(arr);
+    free(other);
+}
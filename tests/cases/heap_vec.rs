@@ -0,0 +1,9 @@
+fn main() -> () {
+	let n: i32 = 4;
+	let mut arr: Vec<i32> = vec![0; (n) as usize];
+	arr[0] = 3;
+	let other: Vec<i32> = vec![0; (n) as usize];
+	let _k: i32 = arr[0];
+	drop(arr);
+	drop(other);
+}
\ No newline at end of file
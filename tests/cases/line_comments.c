@@ -0,0 +1,12 @@
+int main() {
+    int t = 0;
+    int x = 1;
+    int* g = &t;
+    x = 5;
+    t = 3;
+    int h = *g;
+    while (x != 0) {
+        x = x - 1;
+    }
+    printf("%d %d\n", h, x);
+}
@@ -0,0 +1,12 @@
+fn main() -> () { // c: test.c:1
+	let mut t: i32 = 0; // c: test.c:2
+	let mut x: i32 = 1; // c: test.c:3
+	t = 3; // c: test.c:6
+	let g: &i32 = &t; // c: test.c:4
+	x = 5; // c: test.c:5
+	let h: i32 = *g; // c: test.c:7
+	while x != 0 { // c: test.c:8
+		x = x - 1; // c: test.c:9
+	}
+	print!("{} {}\n", h, x); // c: test.c:11
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+#include <string.h>
+
+int main() {
+    int nums[4];
+    memset(nums, 1, sizeof(int) * 4);
+    printf("%d\n", nums[0]);
+}
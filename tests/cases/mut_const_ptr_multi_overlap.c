@@ -0,0 +1,8 @@
+int main() {
+    int n = 0;
+    int* m = &n;
+    int* g = &n;
+    int j = *g;
+    *m = 4;
+    int t = *g;
+}
@@ -0,0 +1,9 @@
+use std::cell::Cell;
+fn main() -> () {
+	let n: Cell<i32> = Cell::new(0);
+	let m: &Cell<i32> = &n;
+	let g: &Cell<i32> = &n;
+	let _j: i32 = g.get();
+	m.set(4);
+	let _t: i32 = g.get();
+}
\ No newline at end of file
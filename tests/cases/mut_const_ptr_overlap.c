@@ -0,0 +1,7 @@
+int main() {
+    int n = 0;
+    int* m = &n;
+    int* g = &n;
+    *m = 4;
+    int t = *g;
+}
@@ -1,7 +1,7 @@
 fn main() -> () {
-let mut n: i32 = 0;
-	let g: &i32 = &n;
-	let _t: i32 = *g;
+	let mut n: i32 = 0;
 	let m: &mut i32  = &mut n;
 	*m = 4;
+	let g: &i32 = &n;
+	let _t: i32 = *g;
 }
\ No newline at end of file
@@ -0,0 +1,10 @@
+fn main() -> () {
+	let mut t: i32 = 0;
+	let a: &mut i32  = &mut t;
+	*a = 1;
+	let b: &mut i32  = &mut t;
+	*b = *b + 2;
+	let c: &mut i32  = &mut t;
+	*c = *c + 3;
+	print!("{}\n", t);
+}
\ No newline at end of file
@@ -0,0 +1,10 @@
+int main() {
+    int t = 0;
+    int* a = &t;
+    int* b = &t;
+    int* c = &t;
+    *a = 1;
+    *b = *b + 2;
+    *c = *c + 3;
+    printf("%d\n", t);
+}
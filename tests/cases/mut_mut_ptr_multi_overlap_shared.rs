@@ -0,0 +1,11 @@
+use std::cell::Cell;
+fn main() -> () {
+	let t: Cell<i32> = Cell::new(0);
+	let a: &Cell<i32> = &t;
+	let b: &Cell<i32> = &t;
+	let c: &Cell<i32> = &t;
+	a.set(1);
+	b.set(b.get() + 2);
+	c.set(c.get() + 3);
+	print!("{}\n", t.get());
+}
\ No newline at end of file
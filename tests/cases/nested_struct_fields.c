@@ -0,0 +1,11 @@
+struct Inner { int a; int b; };
+struct Outer { struct Inner inner; int n; };
+
+int main() {
+    struct Outer o;
+    o.inner.a = 1;
+    o.n = 2;
+    int* b = &o.inner.b;
+    *b = o.inner.a + o.n;
+    printf("%d %d\n", o.inner.a, o.inner.b);
+}
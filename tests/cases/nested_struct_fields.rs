@@ -0,0 +1,18 @@
+#[derive(Default)]
+struct Inner {
+	a: i32,
+	b: i32,
+}
+#[derive(Default)]
+struct Outer {
+	inner: Inner,
+	n: i32,
+}
+fn main() -> () {
+	let mut o = Outer::default();
+	o.inner.a = 1;
+	o.n = 2;
+	let b: &mut i32  = &mut o.inner.b;
+	*b = o.inner.a + o.n;
+	print!("{} {}\n", o.inner.a, o.inner.b);
+}
\ No newline at end of file
@@ -0,0 +1,14 @@
+struct Ref { int* p; };
+struct Holder { struct Ref r; int* q; int n; };
+
+int read(struct Holder h) {
+    return h.n;
+}
+
+int main() {
+    int t = 4;
+    int u = 5;
+    struct Ref r = { &t };
+    struct Holder h = { r, &u, 6 };
+    printf("%d\n", read(h));
+}
@@ -0,0 +1,18 @@
+struct Ref<'a> {
+	p: &'a i32,
+}
+struct Holder<'a, 'b> {
+	r: Ref<'a>,
+	q: &'b i32,
+	n: i32,
+}
+fn read(h: Holder<'_, '_>) -> i32 {
+	return(h.n);
+}
+fn main() -> () {
+	let t: i32 = 4;
+	let u: i32 = 5;
+	let r = Ref {p: &t,};
+	let h = Holder {r: r,q: &u,n: 6,};
+	print!("{}\n", read(h));
+}
\ No newline at end of file
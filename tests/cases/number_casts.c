@@ -0,0 +1,25 @@
+int truncated(double x) {
+    return x;
+}
+
+int main() {
+    double r = 2.75;
+    float f = 1.5;
+    char c = 'a';
+    int k = r;
+    int j = 3;
+    j = r * 2;
+    double d = j + f;
+    double e = 7;
+    k += r;
+    d -= k;
+    c = c + 1;
+    int m = c + j;
+    char back = m;
+    int low = c - 100;
+    int cut = truncated(j);
+    if (r != 2) {
+        e = e / 4;
+    }
+    printf("%d %d %f %f %d %d %d %d %d\n", k, j, d, e, c, m, back, low, cut);
+}
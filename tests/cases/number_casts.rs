@@ -0,0 +1,24 @@
+fn truncated(x: f64) -> i32 {
+	return((x) as i32);
+}
+fn main() -> () {
+	let r: f64 = 2.75;
+	let f: f32 = 1.5;
+	let mut c: u8 = 97;
+	let mut k: i32 = (r) as i32;
+	let mut j: i32 = 3;
+	j = (r * (2) as f64) as i32;
+	let mut d: f64 = ((j) as f32 + f) as f64;
+	let mut e: f64 = (7) as f64;
+	k = ((k) as f64 + r) as i32;
+	d -= (k) as f64;
+	c = (((c) as i8) as i32 + 1) as u8;
+	let m: i32 = ((c) as i8) as i32 + j;
+	let back: u8 = (m) as u8;
+	let low: i32 = ((c) as i8) as i32 - 100;
+	let cut: i32 = truncated((j) as f64);
+	if r != (2) as f64 {
+		e = e / (4) as f64;
+	}
+	print!("{} {} {:.6} {:.6} {} {} {} {} {}\n", k, j, d, e, (c) as i8 as i32, m, (back) as i8 as i32, low, cut);
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+struct Point { int x; int y; };
+
+int main() {
+    struct Point s = {1, 2};
+    struct Point* p = &s;
+    int* x = &s.x;
+    p->y = 3;
+    *x = 4;
+    p->y += 1;
+    printf("%d %d\n", s.x, s.y);
+}
@@ -0,0 +1,13 @@
+#include <stdio.h>
+
+int main() {
+    int n = 42;
+    char c = 'a';
+    double ratio = 2.5;
+    const char* name = "sonder";
+    printf("%d %5d %-4d| %05d %x %X %o %u\n", n, n, n, n, n, n, n, n);
+    printf("%c %s %10s %5.2f %f %% {braces}\n", c, name, name, ratio, ratio);
+    fprintf(stderr, "error: %d\n", n);
+    puts(name);
+    putchar(c);
+}
@@ -0,0 +1,11 @@
+fn main() -> () {
+	let n: i32 = 42;
+	let c: u8 = 97;
+	let ratio: f64 = 2.5;
+	let name: &[u8] = b"sonder";
+	print!("{} {:>5} {:<4}| {:05} {:x} {:X} {:o} {}\n", n, n, n, n, n, n, n, (n) as u32);
+	print!("{} {} {:>10} {:>5.2} {:.6} % {{braces}}\n", (c) as u8 as char, String::from_utf8_lossy(name), String::from_utf8_lossy(name), ratio, ratio);
+	eprint!("error: {}\n", n);
+	print!("{}\n", String::from_utf8_lossy(name));
+	print!("{}", (c) as u8 as char);
+}
\ No newline at end of file
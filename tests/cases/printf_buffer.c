@@ -0,0 +1,9 @@
+int main() {
+    char buf[32];
+    char small[4];
+    int n = 7;
+    sprintf(buf, "n = %d", n);
+    snprintf(small, 4, "%d%d", n, n);
+    snprintf(small, 0, "%d", n);
+    printf("%s %s\n", buf, small);
+}
@@ -0,0 +1,9 @@
+fn main() -> () {
+	let mut buf: [u8; 32] = [0; 32];
+	let mut small: [u8; 4] = [0; 4];
+	let n: i32 = 7;
+	{ use std::io::Write; let end = buf.len() - 1; let mut w = &mut buf[..end]; write!(w, "n = {}", n).unwrap(); let len = end - w.len(); buf[len] = 0; }
+	{ use std::io::Write; if let Some(end) = usize::checked_sub(4, 1).map(|end| end.min(small.len() - 1)) { let mut w = &mut small[..end]; let _ = write!(w, "{}{}", n, n); let len = end - w.len(); small[len] = 0; } }
+	{ use std::io::Write; if let Some(end) = usize::checked_sub(0, 1).map(|end| end.min(small.len() - 1)) { let mut w = &mut small[..end]; let _ = write!(w, "{}", n); let len = end - w.len(); small[len] = 0; } }
+	print!("{} {}\n", String::from_utf8_lossy(std::ffi::CStr::from_bytes_until_nul(&buf).unwrap().to_bytes()), String::from_utf8_lossy(std::ffi::CStr::from_bytes_until_nul(&small).unwrap().to_bytes()));
+}
\ No newline at end of file
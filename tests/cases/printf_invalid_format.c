@@ -0,0 +1,4 @@
+int main() {
+    int n = 1;
+    printf("%s\n", n);
+}
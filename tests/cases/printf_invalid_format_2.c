@@ -0,0 +1,4 @@
+int main() {
+    double d = 1.5;
+    printf("%e %d\n", d);
+}
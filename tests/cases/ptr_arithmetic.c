@@ -0,0 +1,9 @@
+int main() {
+    int arr[4] = {1, 2, 3, 4};
+    int* p = arr;
+    int k = *(p + 1);
+    *(p + 2) = 7;
+    p++;
+    int* q = p + 2;
+    int j = *q;
+}
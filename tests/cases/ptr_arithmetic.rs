@@ -0,0 +1,9 @@
+fn main() -> () {
+	let mut arr: [i32; 4] = [1, 2, 3, 4];
+	let mut p: &mut [i32]  = &mut arr;
+	let _k: i32 = p[1];
+	p[2] = 7;
+	p = std::mem::take(&mut p).split_at_mut(1).1;
+	let q: &[i32] = &p[2..];
+	let _j: i32 = q[0];
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+int main() {
+    int arr[5] = {1, 2, 3, 4, 5};
+    int* p = arr;
+    int* q = p + 2;
+    arr[0] = 7;
+    printf("%d %d\n", *q, arr[0]);
+}
@@ -0,0 +1,7 @@
+fn main() -> () {
+	let mut arr: [i32; 5] = [1, 2, 3, 4, 5];
+	arr[0] = 7;
+	let p: &[i32] = &arr;
+	let q: &[i32] = &p[2..];
+	print!("{} {}\n", q[0], arr[0]);
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+int main() {
+    int arr[5] = {1, 2, 3, 4, 5};
+    int* p = arr;
+    int* q = p + 1;
+    *q = 5;
+    q += 2;
+    *q = 8;
+    printf("%d %d %d\n", arr[0], arr[1], arr[3]);
+}
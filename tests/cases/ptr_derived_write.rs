@@ -0,0 +1,9 @@
+fn main() -> () {
+	let mut arr: [i32; 5] = [1, 2, 3, 4, 5];
+	let p: &mut [i32]  = &mut arr;
+	let mut q: &mut [i32]  = &mut p[1..];
+	q[0] = 5;
+	q = std::mem::take(&mut q).split_at_mut(2).1;
+	q[0] = 8;
+	print!("{} {} {}\n", arr[0], arr[1], arr[3]);
+}
\ No newline at end of file
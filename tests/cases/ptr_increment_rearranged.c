@@ -0,0 +1,9 @@
+int main() {
+    int arr[5] = {1, 2, 3, 4, 5};
+    int* p = arr;
+    p++;
+    arr[0] = 7;
+    int z = *p;
+    p++;
+    printf("%d %d %d\n", z, *p, arr[0]);
+}
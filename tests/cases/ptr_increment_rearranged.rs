@@ -0,0 +1,9 @@
+fn main() -> () {
+	let mut arr: [i32; 5] = [1, 2, 3, 4, 5];
+	arr[0] = 7;
+	let mut p: &[i32] = &arr;
+	p = p.split_at(1).1;
+	let z: i32 = p[0];
+	p = p.split_at(1).1;
+	print!("{} {} {}\n", z, p[0], arr[0]);
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+int main() {
+    int i = 0;
+    while (i == 0) {
+        int* k = &i;
+        *k = 1;
+    }
+}
@@ -1,7 +1,7 @@
 fn main() -> () {
-let mut i: i32 = 0;
+	let mut i: i32 = 0;
 	while i == 0 {
 		let k: &mut i32  = &mut i;
-	*k = 1;
+		*k = 1;
 	}
 }
\ No newline at end of file
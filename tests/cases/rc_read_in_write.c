@@ -0,0 +1,8 @@
+int main() {
+    int n = 4;
+    int* p = &n;
+    int* q = &n;
+    int** r = &q;
+    *p = *p - 2;
+    printf("%d\n", n);
+}
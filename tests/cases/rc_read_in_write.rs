@@ -0,0 +1,9 @@
+use std::{cell::RefCell, rc::Rc};
+fn main() -> () {
+	let n: Rc<RefCell<i32>> = Rc::new(RefCell::new(4));
+	let p: Rc<RefCell<i32>> = n.clone();
+	let q: Rc<RefCell<i32>> = n.clone();
+	let r: &Rc<RefCell<i32>> = &q;
+	{ let value = *p.borrow() - 2; *p.borrow_mut() = value; }
+	print!("{}\n", *n.borrow());
+}
\ No newline at end of file
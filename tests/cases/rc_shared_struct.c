@@ -0,0 +1,12 @@
+struct Point {
+    int x;
+};
+
+int main() {
+    struct Point p = { 5 };
+    struct Point* q = &p;
+    q->x = 8;
+    printf("%d\n", p.x);
+    int x = q->x;
+    printf("%d\n", x);
+}
@@ -0,0 +1,12 @@
+use std::{cell::RefCell, rc::Rc};
+struct Point {
+	x: i32,
+}
+fn main() -> () {
+	let p: Rc<RefCell<Point>> = Rc::new(RefCell::new(Point {x: 5,}));
+	let q: Rc<RefCell<Point>> = p.clone();
+	q.borrow_mut().x = 8;
+	print!("{}\n", p.borrow().x);
+	let x: i32 = q.borrow().x;
+	print!("{}\n", x);
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+int main() {
+    int arr[5] = { 1, 2, 3, 4, 5 };
+    int* p = arr;
+    arr[0] = 7;
+    int z = *(p + 1);
+    printf("%d %d\n", z, arr[0]);
+}
@@ -0,0 +1,9 @@
+int main() {
+    int t = 0;
+    int x = 1;
+    int* g = &t;
+    x = 5;
+    t = x;
+    int h = *g;
+    printf("%d\n", h);
+}
@@ -0,0 +1,10 @@
+use std::cell::Cell;
+fn main() -> () {
+	let t: Cell<i32> = Cell::new(0);
+	let mut x: i32 = 1;
+	let g: &Cell<i32> = &t;
+	x = 5;
+	t.set(x);
+	let h: i32 = g.get();
+	print!("{}\n", h);
+}
\ No newline at end of file
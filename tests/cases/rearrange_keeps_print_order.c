@@ -0,0 +1,11 @@
+struct Point {
+    int x;
+};
+
+int main() {
+    struct Point p = { 3 };
+    struct Point* q = &p;
+    printf("%d\n", 1);
+    printf("%d\n", p.x);
+    q->x = 9;
+}
@@ -0,0 +1,11 @@
+use std::{cell::RefCell, rc::Rc};
+struct Point {
+	x: i32,
+}
+fn main() -> () {
+	let p: Rc<RefCell<Point>> = Rc::new(RefCell::new(Point {x: 3,}));
+	let q: Rc<RefCell<Point>> = p.clone();
+	print!("{}\n", 1);
+	print!("{}\n", p.borrow().x);
+	q.borrow_mut().x = 9;
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+int main() {
+    int t = 0;
+    int* g = &t;
+    putchar('a');
+    printf("%d\n", t);
+    *g = 2;
+}
@@ -0,0 +1,8 @@
+use std::cell::Cell;
+fn main() -> () {
+	let t: Cell<i32> = Cell::new(0);
+	let g: &Cell<i32> = &t;
+	print!("{}", (97) as u8 as char);
+	print!("{}\n", t.get());
+	g.set(2);
+}
\ No newline at end of file
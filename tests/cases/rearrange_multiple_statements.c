@@ -0,0 +1,10 @@
+int main() {
+    int t = 0;
+    int x = 1;
+    int* g = &t;
+    x = 5;
+    t = 3;
+    t = t + 1;
+    int h = *g;
+    printf("%d %d\n", h, x);
+}
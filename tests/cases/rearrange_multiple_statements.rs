@@ -0,0 +1,10 @@
+fn main() -> () {
+	let mut t: i32 = 0;
+	let mut x: i32 = 1;
+	t = 3;
+	t = t + 1;
+	let g: &i32 = &t;
+	x = 5;
+	let h: i32 = *g;
+	print!("{} {}\n", h, x);
+}
\ No newline at end of file
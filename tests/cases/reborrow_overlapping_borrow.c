@@ -0,0 +1,9 @@
+int main() {
+    int n = 0;
+    int* m = &n;
+    *m = 1;
+    int* g = &n;
+    int j = *g;
+    *m = j + 4;
+    printf("%d\n", n);
+}
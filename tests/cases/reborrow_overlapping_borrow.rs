@@ -0,0 +1,9 @@
+fn main() -> () {
+	let mut n: i32 = 0;
+	let m: &mut i32  = &mut n;
+	*m = 1;
+	let g: &i32 = &*m;
+	let j: i32 = *g;
+	*m = j + 4;
+	print!("{}\n", n);
+}
\ No newline at end of file
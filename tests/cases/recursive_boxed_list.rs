@@ -0,0 +1,28 @@
+#[derive(Default)]
+struct Node {
+	v: i32,
+	next: Option<Box<Node>>,
+}
+fn main() -> () {
+	let mut head: Option<Box<Node>> = None;
+	let mut i: i32 = 0;
+	while i != 5 {
+		let mut n: Option<Box<Node>> = Some(Box::new(Node::default()));
+		n.as_mut().unwrap().v = i;
+		n.as_mut().unwrap().next = head.take();
+		head = n.take();
+		i = i + 1;
+	}
+	let mut cur: Option<&mut Node> = head.as_deref_mut();
+	while cur.is_some() {
+		cur.as_mut().unwrap().v = cur.as_ref().unwrap().v * 2;
+		cur = cur.unwrap().next.as_deref_mut();
+	}
+	let mut sum: i32 = 0;
+	let mut it: Option<&Node> = head.as_deref();
+	while it.is_some() {
+		sum = sum + it.unwrap().v;
+		it = it.unwrap().next.as_deref();
+	}
+	print!("{}\n", sum);
+}
\ No newline at end of file
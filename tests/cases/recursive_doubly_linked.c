@@ -0,0 +1,17 @@
+struct DNode {
+    int v;
+    struct DNode* next;
+    struct DNode* prev;
+};
+
+int main() {
+    struct DNode* head = malloc(sizeof(struct DNode));
+    head->v = 1;
+    struct DNode* second = malloc(sizeof(struct DNode));
+    second->v = 2;
+    head->next = second;
+    second->prev = head;
+    struct DNode* back = second->prev;
+    struct DNode* fwd = head->next;
+    printf("%d %d\n", back->v, fwd->v);
+}
@@ -0,0 +1,19 @@
+use std::{cell::RefCell, rc::Rc};
+use std::rc::Weak;
+#[derive(Default)]
+struct DNode {
+	v: i32,
+	next: Option<Rc<RefCell<DNode>>>,
+	prev: Option<Weak<RefCell<DNode>>>,
+}
+fn main() -> () {
+	let head: Option<Rc<RefCell<DNode>>> = Some(Rc::new(RefCell::new(DNode::default())));
+	head.as_ref().unwrap().borrow_mut().v = 1;
+	let second: Option<Rc<RefCell<DNode>>> = Some(Rc::new(RefCell::new(DNode::default())));
+	second.as_ref().unwrap().borrow_mut().v = 2;
+	head.as_ref().unwrap().borrow_mut().next = second.clone();
+	second.as_ref().unwrap().borrow_mut().prev = head.as_ref().map(Rc::downgrade);
+	let back: Option<Rc<RefCell<DNode>>> = second.as_ref().unwrap().borrow().prev.as_ref().and_then(Weak::upgrade);
+	let fwd: Option<Rc<RefCell<DNode>>> = head.as_ref().unwrap().borrow().next.clone();
+	print!("{} {}\n", back.as_ref().unwrap().borrow().v, fwd.as_ref().unwrap().borrow().v);
+}
\ No newline at end of file
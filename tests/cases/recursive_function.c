@@ -0,0 +1,10 @@
+int main() {
+    int i = 0;
+    int g = count_four(i);
+}
+int count_four(int i) {
+    if (i == 3) {
+        return 4;
+    }
+    return(count_four(i + 1));
+}
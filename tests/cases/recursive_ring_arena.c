@@ -0,0 +1,21 @@
+struct Node {
+    int v;
+    struct Node* next;
+};
+
+int main() {
+    struct Node* a = malloc(sizeof(struct Node));
+    struct Node* b = malloc(sizeof(struct Node));
+    a->v = 1;
+    b->v = 2;
+    a->next = b;
+    b->next = a;
+    int sum = 0;
+    struct Node* cur = a->next;
+    while (cur != a) {
+        sum = sum + cur->v;
+        cur = cur->next;
+    }
+    sum = sum + a->v;
+    printf("%d\n", sum);
+}
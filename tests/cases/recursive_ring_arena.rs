@@ -0,0 +1,22 @@
+#[derive(Default)]
+struct Node {
+	v: i32,
+	next: Option<usize>,
+}
+fn main() -> () {
+	let mut node_arena: Vec<Node> = Vec::new();
+	let a: Option<usize> = { node_arena.push(Node::default()); Some(node_arena.len() - 1) };
+	let b: Option<usize> = { node_arena.push(Node::default()); Some(node_arena.len() - 1) };
+	node_arena[a.unwrap()].v = 1;
+	node_arena[b.unwrap()].v = 2;
+	node_arena[a.unwrap()].next = b;
+	node_arena[b.unwrap()].next = a;
+	let mut sum: i32 = 0;
+	let mut cur: Option<usize> = node_arena[a.unwrap()].next;
+	while cur != a {
+		sum = sum + node_arena[cur.unwrap()].v;
+		cur = node_arena[cur.unwrap()].next;
+	}
+	sum = sum + node_arena[a.unwrap()].v;
+	print!("{}\n", sum);
+}
\ No newline at end of file
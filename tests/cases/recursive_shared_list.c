@@ -0,0 +1,33 @@
+struct Node {
+    int v;
+    struct Node* next;
+};
+
+int main() {
+    struct Node* head = NULL;
+    int i = 0;
+    while (i != 5) {
+        struct Node* n = malloc(sizeof(struct Node));
+        n->v = i;
+        n->next = head;
+        head = n;
+        i = i + 1;
+    }
+    struct Node* cur = head;
+    while (cur != NULL) {
+        cur->v = cur->v * 2;
+        cur = cur->next;
+    }
+    int sum = 0;
+    struct Node* it = head;
+    while (it != NULL) {
+        sum = sum + it->v;
+        it = it->next;
+    }
+    printf("%d\n", sum);
+    while (head != NULL) {
+        struct Node* tmp = head;
+        head = head->next;
+        free(tmp);
+    }
+}
@@ -0,0 +1,34 @@
+use std::{cell::RefCell, rc::Rc};
+#[derive(Default)]
+struct Node {
+	v: i32,
+	next: Option<Rc<RefCell<Node>>>,
+}
+fn main() -> () {
+	let mut head: Option<Rc<RefCell<Node>>> = None;
+	let mut i: i32 = 0;
+	while i != 5 {
+		let n: Option<Rc<RefCell<Node>>> = Some(Rc::new(RefCell::new(Node::default())));
+		n.as_ref().unwrap().borrow_mut().v = i;
+		n.as_ref().unwrap().borrow_mut().next = head.clone();
+		head = n.clone();
+		i = i + 1;
+	}
+	let mut cur: Option<Rc<RefCell<Node>>> = head.clone();
+	while cur.is_some() {
+		{ let value = cur.as_ref().unwrap().borrow().v * 2; cur.as_ref().unwrap().borrow_mut().v = value; }
+		cur = Rc::clone(cur.as_ref().unwrap()).borrow().next.clone();
+	}
+	let mut sum: i32 = 0;
+	let mut it: Option<Rc<RefCell<Node>>> = head.clone();
+	while it.is_some() {
+		sum = sum + it.as_ref().unwrap().borrow().v;
+		it = Rc::clone(it.as_ref().unwrap()).borrow().next.clone();
+	}
+	print!("{}\n", sum);
+	while head.is_some() {
+		let mut tmp: Option<Rc<RefCell<Node>>> = head.clone();
+		head = Rc::clone(head.as_ref().unwrap()).borrow().next.clone();
+		tmp = None;
+	}
+}
\ No newline at end of file
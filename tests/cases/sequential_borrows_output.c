@@ -0,0 +1,15 @@
+int main() {
+    int total = 0;
+    int step = 2;
+    int* t = &total;
+    int i = 0;
+    while (i != 4) {
+        *t = *t + step;
+        i = i + 1;
+    }
+    int* s = &step;
+    *s = *s * 10;
+    int* u = &total;
+    *u = *u + step;
+    printf("%d %d %d\n", total, step, i);
+}
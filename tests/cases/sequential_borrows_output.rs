@@ -0,0 +1,15 @@
+fn main() -> () {
+	let mut total: i32 = 0;
+	let mut step: i32 = 2;
+	let t: &mut i32  = &mut total;
+	let mut i: i32 = 0;
+	while i != 4 {
+		*t = *t + step;
+		i = i + 1;
+	}
+	let s: &mut i32  = &mut step;
+	*s = *s * 10;
+	let u: &mut i32  = &mut total;
+	*u = *u + step;
+	print!("{} {} {}\n", total, step, i);
+}
\ No newline at end of file
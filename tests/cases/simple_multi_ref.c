@@ -0,0 +1,8 @@
+int main() {
+    int n = 0;
+    int* g = &n;
+    int* b = &n;
+    int k = *g;
+    int y = 9;
+    *b = y;
+}
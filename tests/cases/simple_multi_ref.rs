@@ -1,5 +1,5 @@
 fn main() -> () {
-let mut n: i32 = 0;
+	let mut n: i32 = 0;
 	let g: &i32 = &n;
 	let _k: i32 = *g;
 	let b: &mut i32  = &mut n;
@@ -0,0 +1,14 @@
+#include <string.h>
+
+int main() {
+    char buf[16];
+    char init[] = "hi";
+    char padded[8] = "hey";
+    strcpy(buf, init);
+    strncpy(padded, "yo", 4);
+    int n = strlen(buf) + strlen(padded);
+    int nums[4] = {1, 2, 3, 4};
+    int copy[4];
+    memcpy(copy, nums, 2 * sizeof(int));
+    memset(nums, 0, sizeof(int) * 4);
+}
@@ -0,0 +1,12 @@
+fn main() -> () {
+	let mut buf: [u8; 16] = [0; 16];
+	let init: [u8; 3] = *b"hi\0";
+	let mut padded: [u8; 8] = *b"hey\0\0\0\0\0";
+	{ let src = std::ffi::CStr::from_bytes_until_nul(&init).unwrap().to_bytes(); buf[..src.len()].copy_from_slice(src); buf[src.len()] = 0; };
+	{ let src = &b"yo"[..]; let n = 4; let len = src.len().min(n); padded[..len].copy_from_slice(&src[..len]); padded[len..n].fill(0); };
+	let _n: i32 = std::ffi::CStr::from_bytes_until_nul(&buf).unwrap().to_bytes().len() as i32 + std::ffi::CStr::from_bytes_until_nul(&padded).unwrap().to_bytes().len() as i32;
+	let mut nums: [i32; 4] = [1, 2, 3, 4];
+	let mut copy: [i32; 4] = [0; 4];
+	copy[..(2 * std::mem::size_of::<i32>() as i32) as usize / std::mem::size_of::<i32>()].copy_from_slice(&nums[..(2 * std::mem::size_of::<i32>() as i32) as usize / std::mem::size_of::<i32>()]);
+	nums[..(std::mem::size_of::<i32>() as i32 * 4) as usize / std::mem::size_of::<i32>()].fill((0) as i32);
+}
\ No newline at end of file
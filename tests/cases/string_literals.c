@@ -0,0 +1,9 @@
+#include <string.h>
+
+int main() {
+    const char* greeting = "hello";
+    const char* name = "sonder";
+    int n = strlen(greeting);
+    int same = strcmp(greeting, name);
+    int other = strcmp(name, "sonder");
+}
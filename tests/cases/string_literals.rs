@@ -0,0 +1,7 @@
+fn main() -> () {
+	let greeting: &[u8] = b"hello";
+	let name: &[u8] = b"sonder";
+	let _n: i32 = greeting.len() as i32;
+	let _same: i32 = greeting.cmp(name) as i32;
+	let _other: i32 = name.cmp(&b"sonder"[..]) as i32;
+}
\ No newline at end of file
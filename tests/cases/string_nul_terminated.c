@@ -0,0 +1,6 @@
+int main() {
+    const char* s = "abc";
+    char first = s[0];
+    char last = *(s + 3);
+    int n = strlen(s);
+}
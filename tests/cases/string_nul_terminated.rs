@@ -0,0 +1,6 @@
+fn main() -> () {
+	let s: &[u8] = b"abc\0";
+	let _first: u8 = s[0];
+	let _last: u8 = s[3];
+	let _n: i32 = std::ffi::CStr::from_bytes_until_nul(&s).unwrap().to_bytes().len() as i32;
+}
\ No newline at end of file
@@ -0,0 +1,12 @@
+struct Point { int x; int y; };
+
+int main() {
+    struct Point pts[3];
+    int i = 0;
+    while (i != 3) {
+        pts[i].x = i;
+        pts[i].y = pts[i].x * 2;
+        i += 1;
+    }
+    printf("%d\n", pts[2].y);
+}
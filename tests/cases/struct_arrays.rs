@@ -0,0 +1,15 @@
+#[derive(Default)]
+struct Point {
+	x: i32,
+	y: i32,
+}
+fn main() -> () {
+	let mut pts: [Point; 3] = Default::default();
+	let mut i: i32 = 0;
+	while i != 3 {
+		pts[i as usize].x = i;
+		pts[i as usize].y = pts[i as usize].x * 2;
+		i += 1;
+	}
+	print!("{}\n", pts[2].y);
+}
\ No newline at end of file
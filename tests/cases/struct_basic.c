@@ -0,0 +1,4 @@
+struct test {
+    int m;
+    int j;
+};
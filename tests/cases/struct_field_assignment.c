@@ -0,0 +1,9 @@
+struct Test {
+    int m;
+    int j;
+};
+
+int main() {
+    struct Test my_test = { 0, 2 };
+    my_test.m = 1;
+}
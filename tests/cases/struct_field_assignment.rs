@@ -3,6 +3,6 @@ struct Test {
 	j: i32,
 }
 fn main() -> () {
-let mut my_test = Test {m: 0,j: 2,};
+	let mut my_test = Test {m: 0,j: 2,};
 	my_test.m = 1;
 }
\ No newline at end of file
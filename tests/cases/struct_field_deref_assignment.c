@@ -0,0 +1,12 @@
+struct Point {
+    int x;
+    int y;
+};
+
+int main() {
+    struct Point p = { 4, 2 };
+    int n = 1;
+    int* m = &n;
+    p.y = *m;
+    printf("%d\n", p.y);
+}
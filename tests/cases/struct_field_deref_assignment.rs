@@ -0,0 +1,11 @@
+struct Point {
+	x: i32,
+	y: i32,
+}
+fn main() -> () {
+	let mut p = Point {x: 4,y: 2,};
+	let n: i32 = 1;
+	let m: &i32 = &n;
+	p.y = *m;
+	print!("{}\n", p.y);
+}
\ No newline at end of file
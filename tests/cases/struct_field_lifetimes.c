@@ -0,0 +1,15 @@
+struct Pair {
+    int* p;
+    int** pp;
+};
+
+int main() {
+    int t = 4;
+    int g = 8;
+    int* h = &g;
+    struct Pair l = { &t, &h };
+    **l.pp = 9;
+    *l.p = 5;
+    *h = 10;
+    printf("%d %d\n", t, g);
+}
@@ -0,0 +1,14 @@
+struct Pair<'a, 'b, 'c> {
+	p: &'a mut i32,
+	pp: &'b mut &'c mut i32,
+}
+fn main() -> () {
+	let mut t: i32 = 4;
+	let mut g: i32 = 8;
+	let mut h: &mut i32  = &mut g;
+	let l = Pair {p: &mut t,pp: &mut h,};
+	**l.pp = 9;
+	*l.p = 5;
+	*h = 10;
+	print!("{} {}\n", t, g);
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+struct Point {
+    int* ptr;
+};
+
+int main() {
+    int t = 4;
+    struct Point l = { &t };
+    *l.ptr = 5;
+}
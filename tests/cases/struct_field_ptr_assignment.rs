@@ -2,7 +2,7 @@ struct Point<'a> {
 	ptr: &'a mut i32,
 }
 fn main() -> () {
-let mut t: i32 = 4;
+	let mut t: i32 = 4;
 	let l = Point {ptr: &mut t,};
 	*l.ptr = 5;
 }
\ No newline at end of file
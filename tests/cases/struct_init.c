@@ -0,0 +1,8 @@
+struct Test {
+    int m;
+    int j;
+};
+
+int main() {
+    struct Test my_test = { 0, 2 };
+}
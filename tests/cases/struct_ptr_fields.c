@@ -0,0 +1,10 @@
+struct Point { int x; int y; };
+
+int main() {
+    struct Point s = {1, 2};
+    struct Point* p = &s;
+    p->x = 5;
+    (*p).y += p->x;
+    int sum = p->x + (*p).y;
+    printf("%d\n", sum);
+}
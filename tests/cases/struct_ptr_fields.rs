@@ -0,0 +1,12 @@
+struct Point {
+	x: i32,
+	y: i32,
+}
+fn main() -> () {
+	let mut s = Point {x: 1,y: 2,};
+	let p: &mut Point  = &mut s;
+	p.x = 5;
+	p.y += p.x;
+	let sum: i32 = p.x + p.y;
+	print!("{}\n", sum);
+}
\ No newline at end of file
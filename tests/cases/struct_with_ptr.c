@@ -0,0 +1,10 @@
+struct Point {
+    int* ptr;
+    int c;
+};
+
+int main() {
+    int t = 4;
+    struct Point l = { &t, 5 };
+    *l.ptr = 5;
+}
@@ -0,0 +1,14 @@
+struct Point {
+    int* ptr;
+    int** ptr2;
+    int c;
+};
+
+int main() {
+    int t = 4;
+    int g = 8;
+    int* h = &g;
+    struct Point l = { &t, &h, 5 };
+    *l.ptr = 5;
+    **l.ptr2 = 9;
+}
@@ -1,10 +1,10 @@
-struct Point<'a> {
+struct Point<'a, 'b, 'c> {
 	ptr: &'a mut i32,
-	ptr2: &'a mut &'a mut i32,
+	ptr2: &'b mut &'c mut i32,
 	c: i32,
 }
 fn main() -> () {
-let mut t: i32 = 4;
+	let mut t: i32 = 4;
 	let mut g: i32 = 8;
 	let mut h: &mut i32  = &mut g;
 	let l = Point {ptr: &mut t,ptr2: &mut h,c: 5,};
@@ -0,0 +1,13 @@
+struct Point {
+    int* ptr;
+    int* ptr2;
+    int c;
+};
+
+int main() {
+    int t = 4;
+    int g = 8;
+    struct Point l = { &t, &g, 5 };
+    *l.ptr = 5;
+    *l.ptr2 = 9;
+}
@@ -0,0 +1,14 @@
+enum Kind { CIRCLE, SQUARE };
+union Dims { double radius; int side; };
+typedef struct { enum Kind kind; union Dims dims; } Shape;
+
+int main() {
+    Shape s;
+    s.kind = SQUARE;
+    s.dims.side = 3;
+    if (s.kind == SQUARE) {
+        printf("%d\n", s.dims.side * s.dims.side);
+    }
+    s.kind = CIRCLE;
+    s.dims.radius = 1.5;
+}
@@ -0,0 +1,33 @@
+#[derive(Clone, Copy, PartialEq)]
+#[allow(non_camel_case_types)]
+#[repr(i32)]
+enum Kind {
+	CIRCLE = 0,
+	SQUARE = 1,
+}
+#[derive(Clone, Copy)]
+#[allow(non_camel_case_types)]
+enum Dims {
+	CIRCLE(f64),
+	SQUARE(i32),
+}
+
+impl Dims {
+	fn kind(&self) -> Kind {
+		match self {
+			Dims::CIRCLE(_) => Kind::CIRCLE,
+			Dims::SQUARE(_) => Kind::SQUARE,
+		}
+	}
+}
+struct Shape {
+	dims: Dims,
+}
+fn main() -> () {
+	let mut s = Shape { dims: Dims::CIRCLE(0.0) };
+	s.dims = Dims::SQUARE(3);
+	if s.dims.kind() == Kind::SQUARE {
+		print!("{}\n", match s.dims { Dims::SQUARE(value) => value, _ => unreachable!() } * match s.dims { Dims::SQUARE(value) => value, _ => unreachable!() });
+	}
+	s.dims = Dims::CIRCLE(1.5);
+}
\ No newline at end of file
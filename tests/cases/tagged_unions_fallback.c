@@ -0,0 +1,10 @@
+enum Kind { INT, FLOAT };
+union Num { int i; float f; };
+struct Number { enum Kind kind; union Num num; };
+
+int main() {
+    struct Number n;
+    n.num.i = 2;
+    n.kind = INT;
+    printf("%d\n", n.num.i);
+}
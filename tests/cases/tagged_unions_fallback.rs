@@ -0,0 +1,23 @@
+#[derive(Clone, Copy, PartialEq)]
+#[allow(non_camel_case_types)]
+#[repr(i32)]
+enum Kind {
+	INT = 0,
+	FLOAT = 1,
+}
+#[repr(C)]
+#[derive(Clone, Copy)]
+union Num {
+	i: i32,
+	f: f32,
+}
+struct Number {
+	kind: Kind,
+	num: Num,
+}
+fn main() -> () {
+	let mut n: Number = unsafe { std::mem::zeroed() };
+	n.num.i = 2;
+	n.kind = Kind::INT;
+	print!("{}\n", unsafe { n.num.i });
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+int main() {
+    int n = 0;
+    int* g = &n;
+    int* p = &n;
+    int** m = &p;
+    **m = 5;
+}
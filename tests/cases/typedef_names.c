@@ -0,0 +1,8 @@
+typedef int Count;
+typedef Count Total;
+
+int main() {
+    Count n = 2;
+    Total * p = &n;
+    *p = 3;
+}
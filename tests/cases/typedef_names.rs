@@ -0,0 +1,5 @@
+fn main() -> () {
+	let mut n: i32 = 2;
+	let p: &mut i32  = &mut n;
+	*p = 3;
+}
\ No newline at end of file
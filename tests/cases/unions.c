@@ -0,0 +1,8 @@
+typedef union { int i; float f; } Value;
+
+int main() {
+    Value v;
+    v.f = 1.5;
+    v.i += 1;
+    printf("%d\n", v.i);
+}
@@ -0,0 +1,12 @@
+#[repr(C)]
+#[derive(Clone, Copy)]
+union Value {
+	i: i32,
+	f: f32,
+}
+fn main() -> () {
+	let mut v: Value = unsafe { std::mem::zeroed() };
+	v.f = 1.5;
+	unsafe { v.i += 1; }
+	print!("{}\n", unsafe { v.i });
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+struct Point { int x; int y; };
+
+int main() {
+    struct Point s = { 1, 2 };
+    struct Point* p = &s;
+    int* x = &s.x;
+    p->y = 3;
+    *x = 4;
+    p->y += 1;
+    int y = p->y;
+    while (*x != 16) {
+        *x += y;
+        p->y = *x;
+    }
+    printf("%d %d %d\n", s.x, s.y, y);
+}
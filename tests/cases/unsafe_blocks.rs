@@ -0,0 +1,26 @@
+struct Point {
+	x: i32,
+	y: i32,
+}
+fn main() -> () {
+	let mut s = Point {x: 1,y: 2,};
+	let p: *mut Point = &mut s as *mut Point;
+	let x: *mut i32 = &mut s.x as *mut i32;
+	// SAFETY: `p` points to `s` and `x` points to `s.x`, and `s` is still in scope
+	unsafe {
+		(*p).y = 3;
+		*x = 4;
+		(*p).y += 1;
+	}
+	// SAFETY: `p` points to `s`, and `s` is still in scope
+	let y: i32 = unsafe { (*p).y };
+	// SAFETY: `x` points to `s.x`, and `s` is still in scope and also reached through `p`
+	while unsafe { *x != 16 } {
+		// SAFETY: `x` points to `s.x` and `p` points to `s`, and `s` is still in scope
+		unsafe {
+			*x += y;
+			(*p).y = *x;
+		}
+	}
+	print!("{} {} {}\n", s.x, s.y, y);
+}
\ No newline at end of file
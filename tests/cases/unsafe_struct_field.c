@@ -0,0 +1,13 @@
+struct Point { int x; int y; };
+struct H { int* p; };
+
+int main() {
+    struct Point s = { 1, 2 };
+    struct Point* q = &s;
+    struct H h = { &s.x };
+    q->y = 2;
+    *h.p = 3;
+    printf("%d %d\n", q->y, *h.p + 1);
+    q->y += 4;
+    printf("%d\n", s.x + s.y);
+}
@@ -0,0 +1,24 @@
+struct Point {
+	x: i32,
+	y: i32,
+}
+struct H {
+	p: *mut i32,
+}
+fn main() -> () {
+	let mut s = Point {x: 1,y: 2,};
+	let q: *mut Point = &mut s as *mut Point;
+	let h = H {p: &mut s.x as *mut i32,};
+	// SAFETY: `q` points to `s` and `h.p` points to `s.x`, and `s` is still in scope
+	unsafe {
+		(*q).y = 2;
+		*h.p = 3;
+	}
+	// SAFETY: `q` points to `s` and `h.p` points to `s.x`, and `s` is still in scope
+	print!("{} {}\n", unsafe { (*q).y }, unsafe { *h.p + 1 });
+	// SAFETY: `q` points to `s`, and `s` is still in scope
+	unsafe {
+		(*q).y += 4;
+	}
+	print!("{}\n", s.x + s.y);
+}
\ No newline at end of file
@@ -0,0 +1,34 @@
+struct Point { int x; int y; };
+
+int shared() {
+    struct Point a = { 5, 6 };
+    struct Point* b = &a;
+    b->x = 8;
+    printf("%d\n", a.x);
+    int c = b->x;
+    return c;
+}
+
+int overlapping() {
+    struct Point s = { 1, 2 };
+    struct Point* p = &s;
+    int* x = &s.x;
+    p->y = 3;
+    *x = 4;
+    p->y += 1;
+    if (*x != 4) {
+        p->y = 5;
+    }
+    int z = *x + p->y;
+    while (z != 10) {
+        z = z + 1;
+    }
+    return s.x + s.y + z;
+}
+
+int main() {
+    int k = 3;
+    int* y = &k;
+    *y = k + 6;
+    printf("%d %d %d\n", k, shared(), overlapping());
+}
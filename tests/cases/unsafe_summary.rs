@@ -0,0 +1,44 @@
+use std::{cell::RefCell, rc::Rc};
+struct Point {
+	x: i32,
+	y: i32,
+}
+fn shared() -> i32 {
+	let a: Rc<RefCell<Point>> = Rc::new(RefCell::new(Point {x: 5,y: 6,}));
+	let b: Rc<RefCell<Point>> = a.clone();
+	b.borrow_mut().x = 8;
+	print!("{}\n", a.borrow().x);
+	let c: i32 = b.borrow().x;
+	return(c);
+}
+fn overlapping() -> i32 {
+	let mut s = Point {x: 1,y: 2,};
+	let p: *mut Point = &mut s as *mut Point;
+	let x: *mut i32 = &mut s.x as *mut i32;
+	// SAFETY: `p` points to `s` and `x` points to `s.x`, and `s` is still in scope
+	unsafe {
+		(*p).y = 3;
+		*x = 4;
+		(*p).y += 1;
+	}
+	// SAFETY: `x` points to `s.x`, and `s` is still in scope and also reached through `p`
+	if unsafe { *x != 4 } {
+		// SAFETY: `p` points to `s`, and `s` is still in scope
+		unsafe {
+			(*p).y = 5;
+		}
+	}
+	// SAFETY: `x` points to `s.x` and `p` points to `s`, and `s` is still in scope
+	let mut z: i32 = unsafe { *x + (*p).y };
+	while z != 10 {
+		z = z + 1;
+	}
+	return(s.x + s.y + z);
+}
+fn main() -> () {
+	let mut k: i32 = 3;
+	let k_clone: i32 = k;
+	let y: &mut i32  = &mut k;
+	*y = k_clone + 6;
+	print!("{} {} {}\n", k, shared(), overlapping());
+}
\ No newline at end of file
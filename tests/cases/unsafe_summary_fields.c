@@ -0,0 +1,12 @@
+struct Point { int x; int y; };
+struct H { int* p; };
+
+int main() {
+    struct Point s = { 1, 2 };
+    struct H h = { &s.x };
+    struct Point* q = &s;
+    *h.p = 3;
+    q->y = 2;
+    *h.p += 4;
+    printf("unsafe { %d }\n", s.x + s.y);
+}
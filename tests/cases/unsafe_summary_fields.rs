@@ -0,0 +1,19 @@
+struct Point {
+	x: i32,
+	y: i32,
+}
+struct H {
+	p: *mut i32,
+}
+fn main() -> () {
+	let mut s = Point {x: 1,y: 2,};
+	let h = H {p: &mut s.x as *mut i32,};
+	let q: *mut Point = &mut s as *mut Point;
+	// SAFETY: `h.p` points to `s.x` and `q` points to `s`, and `s` is still in scope
+	unsafe {
+		*h.p = 3;
+		(*q).y = 2;
+		*h.p += 4;
+	}
+	print!("unsafe {{ {} }}\n", s.x + s.y);
+}
\ No newline at end of file
@@ -0,0 +1,4 @@
+int main() {
+    int n = 0;
+    n = 7;
+}
@@ -1,4 +1,4 @@
 fn main() -> () {
-let mut n: i32 = 0;
+	let mut n: i32 = 0;
 	n = 7;
 }
\ No newline at end of file
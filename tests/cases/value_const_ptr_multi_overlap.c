@@ -0,0 +1,7 @@
+int main() {
+    int t = 0;
+    int* g = &t;
+    t = 3;
+    t = 1;
+    int h = *g;
+}
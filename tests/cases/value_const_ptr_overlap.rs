@@ -1,5 +1,5 @@
 fn main() -> () {
-let mut t: i32 = 0;
+	let mut t: i32 = 0;
 	t = 1;
 	let g: &i32 = &t;
 	let _h: i32 = *g;
@@ -0,0 +1,6 @@
+int main() {
+    int t = 0;
+    int* g = &t;
+    t = 1;
+    *g = 2;
+}
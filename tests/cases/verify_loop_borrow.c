@@ -0,0 +1,11 @@
+int main() {
+    int t = 0;
+    int* g = &t;
+    int i = 0;
+    while (i != 3) {
+        *g = *g + 1;
+        t = t + 1;
+        i = i + 1;
+    }
+    printf("%d\n", t);
+}
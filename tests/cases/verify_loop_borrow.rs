@@ -0,0 +1,12 @@
+use std::cell::Cell;
+fn main() -> () {
+	let t: Cell<i32> = Cell::new(0);
+	let g: &Cell<i32> = &t;
+	let mut i: i32 = 0;
+	while i != 3 {
+		g.set(g.get() + 1);
+		t.set(t.get() + 1);
+		i = i + 1;
+	}
+	print!("{}\n", t.get());
+}
\ No newline at end of file
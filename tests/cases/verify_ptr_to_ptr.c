@@ -0,0 +1,14 @@
+struct Point {
+    int x;
+    int y;
+};
+
+int main() {
+    int n = 1;
+    int* p = &n;
+    int** q = &p;
+    **q = 1;
+    *p = 7;
+    struct Point s = { n, **q };
+    printf("%d %d\n", s.x, s.y);
+}
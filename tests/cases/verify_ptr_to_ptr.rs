@@ -0,0 +1,18 @@
+use std::{cell::RefCell, rc::Rc};
+struct Point {
+	x: i32,
+	y: i32,
+}
+fn main() -> () {
+	let n: Rc<RefCell<i32>> = Rc::new(RefCell::new(1));
+	let mut p: Rc<RefCell<i32>> = n.clone();
+	let q: *mut Rc<RefCell<i32>> = &mut p as *mut Rc<RefCell<i32>>;
+	// SAFETY: `q` points to `p`, and `p` is still in scope
+	unsafe {
+		*(*q).borrow_mut() = 1;
+	}
+	*p.borrow_mut() = 7;
+	// SAFETY: `q` points to `p`, and `p` is still in scope
+	let s = Point {x: *n.borrow(),y: unsafe { *(*q).borrow() },};
+	print!("{} {}\n", s.x, s.y);
+}
\ No newline at end of file